@@ -115,41 +115,43 @@ impl AutoStructField
 
     fn read_expr(&self, struct_name: &Ident) -> proc_macro2::TokenStream
     {
+        let ident = &self.ident;
+        let field_path = quote! {
+            concat!(stringify!(#struct_name), ".", stringify!(#ident))
+        };
         match &self.kind {
             AutoStructFieldKind::PadAlign(aligned) => quote! {
                 {
                     let __curr_len__ = __reader__.len();
-                    __reader__.read(reader_writer::pad_bytes_count(
+                    __reader__.try_read(reader_writer::pad_bytes_count(
                         #aligned,
                         __start_len__ - __curr_len__
-                    ))
+                    )).map_err(|e| e.in_field(#field_path))?
                 }
             },
-            AutoStructFieldKind::Derivable(_, init) => quote!(__reader__.read(#init)),
-            AutoStructFieldKind::IteratorDerivable(_, init) => quote!(__reader__.read(#init)),
+            AutoStructFieldKind::Derivable(_, init) |
+            AutoStructFieldKind::IteratorDerivable(_, init) |
+            AutoStructFieldKind::Simple(init) => quote! {
+                __reader__.try_read(#init).map_err(|e| e.in_field(#field_path))?
+            },
             AutoStructFieldKind::Expected(expected, init) => {
-                let ident = &self.ident;
                 let ty = &self.ty;
-                let file_line = quote_spanned! { self.ident.span() =>
-                    file!(), line!()
-                };
                 quote! {
                     {
-                        let __tmp__ = __reader__.read(#init);
+                        let __field_start__ = __reader__.clone();
+                        let __tmp__ = __reader__.try_read(#init)
+                            .map_err(|e| e.in_field(#field_path))?;
                         let expected: #ty = #expected;
-
-                        // reader_writer::expect!(
-                        assert_eq!(
-                            expected, __tmp__,
-                            "While deserializing {}, {}:{}",
-                            stringify!(#struct_name::#ident), #file_line
-                        );
+                        if expected != __tmp__ {
+                            return Err(reader_writer::ReadError::unexpected_value(
+                                &__field_start__, &expected, &__tmp__
+                            ).in_field(#field_path));
+                        }
                         __tmp__
                     }
                 }
             },
             AutoStructFieldKind::Literal(expr) => quote!(#expr),
-            AutoStructFieldKind::Simple(init) => quote!(__reader__.read(#init)),
         }
     }
 
@@ -188,6 +190,36 @@ impl AutoStructField
     }
 }
 
+// serde can't be taught to serialize `CStr`s (an alias for `Cow<ffi::CStr>`) as strings, so
+// fields of those types are pointed at helper modules in reader_writer instead
+fn serde_with_module(ty: &Type) -> Option<&'static str>
+{
+    fn last_segment(ty: &Type) -> Option<&syn::PathSegment>
+    {
+        match ty {
+            Type::Path(path) => path.path.segments.last(),
+            _ => None,
+        }
+    }
+
+    let segment = last_segment(ty)?;
+    if segment.ident == "CStr" {
+        return Some("reader_writer::serde_impls::cstr")
+    }
+    if segment.ident != "Option" {
+        return None
+    }
+    let inner = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.first()?,
+        _ => return None,
+    };
+    match inner {
+        syn::GenericArgument::Type(inner) if last_segment(inner)?.ident == "CStr"
+            => Some("reader_writer::serde_impls::option_cstr"),
+        _ => None,
+    }
+}
+
 #[derive(Clone)]
 struct RawAutoStructAttr
 {
@@ -222,6 +254,7 @@ struct DeriveOptions
     readable: bool,
     writable: bool,
     fixed_size: bool,
+    serde: bool,
 }
 
 impl Parse for DeriveOptions
@@ -232,6 +265,7 @@ impl Parse for DeriveOptions
             readable: false,
             writable: false,
             fixed_size: false,
+            serde: false,
         };
         let idents = Punctuated::<Ident, Token![,]>::parse_terminated(input)?;
         for ident in idents {
@@ -250,6 +284,11 @@ impl Parse for DeriveOptions
                     err(ident.span(), format!("Duplicate '{}'", ident))?;
                 }
                 options.fixed_size = true;
+            } else if ident == "Serde" {
+                if options.serde {
+                    err(ident.span(), format!("Duplicate '{}'", ident))?;
+                }
+                options.serde = true;
             } else {
                 err(ident.span(), format!("Unknown option '{}'", ident))?;
             }
@@ -440,16 +479,16 @@ impl AutoStructDecl
                 #where_clause
             {
                 type Args = #args_ty;
-                fn read_from(
+                fn try_read_from(
                     __reader__: &mut reader_writer::Reader<#reader_lifetime>,
                     #args_pat: Self::Args
-                ) -> Self
+                ) -> reader_writer::ReadResult<Self>
                 {
                     #offset_let
                     #(let #idents: #tys = #read_exprs;)*
-                    #name {
+                    Ok(#name {
                         #(#storage_idents,)*
-                    }
+                    })
                 }
 
                 #size_fn
@@ -509,7 +548,23 @@ impl AutoStructDecl
             proc_macro2::TokenStream::new()
         };
 
-        let struct_tokens = self.struct_.into_token_stream();
+        // The serde impls are only generated when the crate using auto_struct enables its "serde"
+        // feature. Only fields with storage exist in the struct, so derived counts and padding
+        // are skipped automatically and recomputed when the struct is written.
+        let mut struct_ = self.struct_;
+        if options.serde {
+            struct_.attrs.push(parse_quote! {
+                #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+            });
+            for field in struct_.fields.iter_mut() {
+                if let Some(module) = serde_with_module(&field.ty) {
+                    field.attrs.push(parse_quote! {
+                        #[cfg_attr(feature = "serde", serde(with = #module))]
+                    });
+                }
+            }
+        }
+        let struct_tokens = struct_.into_token_stream();
         quote! {
             #struct_tokens
             #readable_tokens
@@ -83,3 +83,27 @@ fn test_padding()
     let padding_test: PaddingTest = reader.read(());
     assert_eq!(padding_test.size(), 36);
 }
+
+#[test]
+fn test_read_errors()
+{
+    use reader_writer::{ReadErrorKind, Reader};
+
+    let mut data = [0xFFu8; 16];
+    data[1] = 0x12;
+    let err = Reader::new(&data[..]).try_read::<SizeTest>(8).err().unwrap();
+    assert_eq!(err.offset, 0);
+    assert_eq!(err.path, vec!["SizeTest.x".to_string()]);
+    assert!(matches!(err.kind, ReadErrorKind::UnexpectedValue { .. }));
+
+    let data = [0xFFu8; 8];
+    let err = Reader::new(&data[..]).try_read::<SizeTest>(8).err().unwrap();
+    assert_eq!(err.offset, 2);
+    assert_eq!(err.path, vec!["SizeTest.data".to_string()]);
+    assert_eq!(err.kind, ReadErrorKind::UnexpectedEof { needed: 8, remaining: 6 });
+
+    let data = [0xFFu8; 9];
+    let err = Reader::new(&data[..]).try_read::<FixedSizeTest>(()).err().unwrap();
+    assert_eq!(err.offset, 8);
+    assert_eq!(err.path, vec!["FixedSizeTest.y".to_string()]);
+}
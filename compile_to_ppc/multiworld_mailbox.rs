@@ -0,0 +1,66 @@
+mod _multiworld_mailbox {
+    // "RPMWMBOX"
+    pub(crate) const MULTIWORLD_MAILBOX_MAGIC: [u32; 2] = [0x52504d57, 0x4d424f58];
+    pub(crate) const MULTIWORLD_QUEUE_LEN: usize = 32;
+
+    // How many foreign item ids the game holds on to while the outbox is full
+    pub(crate) const MULTIWORLD_OUTBOX_BACKLOG_LEN: usize = 64;
+
+    // Words needed to pad the game's and the client's halves of the mailbox out to whole 32 byte
+    // cache lines
+    pub(crate) const MULTIWORLD_GAME_PADDING: usize = (8 - (MULTIWORLD_QUEUE_LEN + 7) % 8) % 8;
+    pub(crate) const MULTIWORLD_CLIENT_PADDING: usize = (8 - (2 * MULTIWORLD_QUEUE_LEN + 2) % 8) % 8;
+
+    // Shared between the REL and a multiworld client running on the PC. The client finds it by
+    // searching MEM1 for the magic followed by the mailbox's own address. Every field is a big
+    // endian u32. The fields each side writes are on cache lines of their own, and the game
+    // only touches the mailbox through its uncached address, so neither side can overwrite the
+    // other's writes with stale data.
+    #[derive(Copy, Clone)]
+    #[repr(C, align(32))]
+    pub(crate) struct MultiworldMailbox
+    {
+        pub magic: [u32; 2],
+        pub addr: u32,
+
+        // Written by the game. Ids of the foreign items picked up so far, the i-th one is at
+        // `outbox[i % MULTIWORLD_QUEUE_LEN]`. An entry isn't reused until the client has read
+        // it; until then the game keeps any new ids to itself.
+        pub outbox_count: u32,
+        pub outbox: [u32; MULTIWORLD_QUEUE_LEN],
+
+        // Written by the game. How many of the inbox's items have been given to the player, and
+        // how many of them had been the last time the game was saved. Loading a save goes back
+        // to `inbox_saved`, so the items after it are given again.
+        pub inbox_processed: u32,
+        pub inbox_saved: u32,
+
+        // Written by the game. How many foreign items were lost because neither the outbox nor
+        // the game's own backlog had room for them.
+        pub outbox_dropped: u32,
+        pub game_padding: [u32; MULTIWORLD_GAME_PADDING],
+
+        // Written by the client. How many of the outbox's ids it has read.
+        pub outbox_read: u32,
+
+        // Written by the client. Items sent to this player, laid out like the outbox. Entries
+        // after `inbox_saved` are kept until a save covers them.
+        pub inbox_count: u32,
+        pub inbox: [MultiworldItem; MULTIWORLD_QUEUE_LEN],
+        pub client_padding: [u32; MULTIWORLD_CLIENT_PADDING],
+    }
+
+    #[derive(Copy, Clone)]
+    #[repr(C)]
+    pub(crate) struct MultiworldItem
+    {
+        // A CPlayerState::EItemType
+        pub item: u32,
+        // Used as both the capacity and the amount given
+        pub amount: u32,
+    }
+}
+pub(crate) use self::_multiworld_mailbox::{
+    MultiworldItem, MultiworldMailbox, MULTIWORLD_CLIENT_PADDING, MULTIWORLD_GAME_PADDING,
+    MULTIWORLD_MAILBOX_MAGIC, MULTIWORLD_OUTBOX_BACKLOG_LEN, MULTIWORLD_QUEUE_LEN,
+};
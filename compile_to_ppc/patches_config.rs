@@ -1,11 +1,83 @@
 mod _rel_config {
     use serde::{Serialize, Deserialize};
+
+    pub(crate) const PROGRESSIVE_ITEM_CHAIN_COUNT: usize = 8;
+    pub(crate) const PROGRESSIVE_ITEM_CHAIN_LEN: usize = 8;
+
+    // Set in a pickup's capacity to mark it as an item for another multiworld player. The rest
+    // of the capacity is the id reported to the multiworld client.
+    pub(crate) const FOREIGN_ITEM_FLAG: u32 = 0x40000000;
+
+    // One starting state for a new save and one for after the frigate
+    pub(crate) const STARTING_STATE_COUNT: usize = 2;
+
+    // The unused CPlayerState::EItemTypes (UnknownItem1 and UnknownItem2) a patched spawn point
+    // gives one of to mark that the player has just started, one per starting state. The REL
+    // takes the item away again and gives the rest of that starting state instead.
+    pub(crate) const STARTING_STATE_MARKER_ITEMS: [u32; STARTING_STATE_COUNT] = [25, 27];
+
+    // The part of what the player starts with that a spawn point can't hold
+    #[derive(Serialize, Deserialize, Clone, Copy)]
+    #[repr(C)]
+    pub(crate) struct StartingState
+    {
+        // Bit i is set if the player starts with the artifact whose CPlayerState::EItemType is
+        // 29 + i
+        pub artifacts: u32,
+
+        // The CPlayerState::EPlayerVisor and EBeamId to select and the energy to set the
+        // player's health to. 0xFFFFFFFF leaves them as they are.
+        pub visor: u32,
+        pub beam: u32,
+        pub energy: u32,
+    }
+
     #[derive(Serialize, Deserialize)]
     #[repr(C)]
     pub(crate) struct RelConfig
     {
         pub quickplay_mlvl: u32,
         pub quickplay_mrea: u32,
+
+        // Addresses of the calls CScriptPickup::Touch makes to give the item and of the
+        // CPlayerState methods they call. These come from the patcher because not every
+        // version's symbol table has them. The calls are zero if there are no progressive items
+        // and multiworld is disabled, and the methods are also zero if neither starting state
+        // needs the REL.
+        pub initialize_power_up_call: u32,
+        pub incr_pickup_call: u32,
+        pub initialize_power_up_fn: u32,
+        pub incr_pickup_fn: u32,
+
+        // Address of the call in CStateManager::Update to the CPlayerState method it calls every
+        // frame, which is where items received from other multiworld players and the starting
+        // states are given out, and of that method. Zero if multiworld is disabled and neither
+        // starting state needs the REL.
+        pub update_static_interference_call: u32,
+        pub update_static_interference_fn: u32,
+
+        // Addresses of the calls that write the CPlayerState to a save and read it back from
+        // one, and of the functions they call. The multiworld mailbox uses them to only forget
+        // about received items once they've been saved. Zero if multiworld is disabled.
+        pub save_player_state_call: u32,
+        pub save_player_state_fn: u32,
+        pub load_player_state_call: u32,
+        pub load_player_state_fn: u32,
+
+        // Addresses of DCFlushRange and ICInvalidateRange, used to make patched calls visible
+        // to the CPU. Zero if none of the calls above are patched.
+        pub dc_flush_range_fn: u32,
+        pub ic_invalidate_range_fn: u32,
+
+        // Indexed like STARTING_STATE_MARKER_ITEMS
+        pub starting_states: [StartingState; STARTING_STATE_COUNT],
+
+        // Each chain is a list of item types that are given out in order whenever any item in
+        // the chain is picked up. Unused entries are 0xFFFFFFFF.
+        pub progressive_items: [[u32; PROGRESSIVE_ITEM_CHAIN_LEN]; PROGRESSIVE_ITEM_CHAIN_COUNT],
     }
 }
-pub(crate) use self::_rel_config::RelConfig;
+pub(crate) use self::_rel_config::{
+    RelConfig, StartingState, FOREIGN_ITEM_FLAG, PROGRESSIVE_ITEM_CHAIN_COUNT,
+    PROGRESSIVE_ITEM_CHAIN_LEN, STARTING_STATE_COUNT, STARTING_STATE_MARKER_ITEMS,
+};
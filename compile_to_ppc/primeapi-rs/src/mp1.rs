@@ -64,8 +64,16 @@ impl CPlayerState
     pub unsafe fn get_item_capacity(this: *const CPlayerState, type_: i32) -> u32
     { }
 
-    #[cpp_method(CPlayerState::IncrPickup(CPlayerState::EItemType, u32))]
-    pub unsafe fn incr_pickup(this: *const CPlayerState, type_: i32, amount: u32)
+    #[cpp_method(CPlayerState::HasPowerUp(CPlayerState::EItemType) const)]
+    pub unsafe fn has_power_up(this: *const CPlayerState, type_: i32) -> bool
+    { }
+
+    #[cpp_method(CPlayerState::IncrPickUp(CPlayerState::EItemType, int))]
+    pub unsafe fn incr_pickup(this: *mut CPlayerState, type_: i32, amount: i32)
+    { }
+
+    #[cpp_method(CPlayerState::InitializePowerUp(CPlayerState::EItemType, int))]
+    pub unsafe fn initialize_power_up(this: *mut CPlayerState, type_: i32, capacity: i32)
     { }
 
     #[cpp_method(CPlayerState::DecrPickup(CPlayerState::EItemType, u32) const)]
@@ -112,10 +112,21 @@ struct IpcResponse
 }
 
 
-extern "C"
+type CacheFn = unsafe extern "C" fn(*const u8, u32);
+
+// DCFlushRange and ICInvalidateRange. The PAL symbol table doesn't have them, so their addresses
+// come from the patcher like the hooked functions' do. Anything else sharing memory with
+// something outside the CPU, like the multiworld mailbox, uses these too.
+pub(crate) unsafe fn dc_flush_range(start: *const u8, len: u32)
+{
+    let f: CacheFn = mem::transmute(crate::REL_CONFIG.dc_flush_range_fn as usize);
+    f(start, len)
+}
+
+pub(crate) unsafe fn ic_invalidate_range(start: *const u8, len: u32)
 {
-    fn DCFlushRange(start: *const u8, len: u32);
-    fn DCInvalidateRange(start: *const u8, len: u32);
+    let f: CacheFn = mem::transmute(crate::REL_CONFIG.ic_invalidate_range_fn as usize);
+    f(start, len)
 }
 
 // NOTE This clears the cached vs uncached bit too
@@ -139,7 +150,7 @@ fn uncached_to_cached_addr<T>(p: *const T) -> *const T
     (p as u32& 0xBFFFFFFF) as *const T
 }
 
-fn cached_to_uncached_addr<T>(p: *const T) -> *const T
+pub(crate) fn cached_to_uncached_addr<T>(p: *const T) -> *const T
 {
     (p as u32 | 0xC0000000) as *const T
 }
@@ -234,7 +245,7 @@ unsafe fn ipc_send_msg(msg_ptr: *mut IpcMessage)
     let null = ptr::null_mut();
     let ipc_reg: &AtomicPtr<IpcMessage> = &*(0xD3026900 as *const _);
     let msg_ptr = virtual_to_real_addr(msg_ptr) as *mut IpcMessage;
-    while ipc_reg.compare_and_swap(null, msg_ptr, Ordering::Relaxed) != null { }
+    while ipc_reg.compare_exchange(null, msg_ptr, Ordering::Relaxed, Ordering::Relaxed).is_err() { }
 }
 
 unsafe fn ipc_wait_msg(msg_ptr: *const IpcMessage)
@@ -251,8 +262,8 @@ pub(crate) fn ios_open_sync(filepath: &[u8], mode: u32) -> i32
     let mut aligned_filepath = AlignedCStr([0; 64]);
     aligned_filepath.0[..filepath.len()].copy_from_slice(filepath);
     unsafe {
-        DCFlushRange(aligned_filepath.0.as_ptr(), filepath.len() as u32);
-        crate::printf(b"ios_open filepath: %s\n\0".as_ptr(), aligned_filepath.0.as_ptr());
+        dc_flush_range(aligned_filepath.0.as_ptr(), filepath.len() as u32);
+        primeapi::printf(b"ios_open filepath: %s\n\0".as_ptr(), aligned_filepath.0.as_ptr());
     }
 
 
@@ -275,14 +286,14 @@ pub(crate) fn ios_open_sync(filepath: &[u8], mode: u32) -> i32
     };
 
     unsafe {
-        DCFlushRange(&msg as *const _ as *const u8, core::mem::size_of::<IpcMessage>() as u32);
+        dc_flush_range(&msg as *const _ as *const u8, core::mem::size_of::<IpcMessage>() as u32);
         let msg_ptr = &mut msg as *mut _;
-        crate::printf(b"Sending msg_ptr: %p\n\0".as_ptr(), msg_ptr);
+        primeapi::printf(b"Sending msg_ptr: %p\n\0".as_ptr(), msg_ptr);
         ipc_send_msg(msg_ptr);
         ipc_wait_msg(msg_ptr);
     }
     unsafe {
-        crate::printf(b"msg_ptr.cmd: %d\n\0".as_ptr(), msg.cmd);
+        primeapi::printf(b"msg_ptr.cmd: %d\n\0".as_ptr(), msg.cmd);
     }
 
     msg.result
@@ -9,16 +9,38 @@ use primeapi::alignment_utils::Aligned32;
 use primeapi::dol_sdk::dvd::DVDFileInfo;
 use primeapi::mp1::{
     CArchitectureQueue, CGameState, CGuiFrame, CGuiTextSupport, CGuiTextPane, CGuiWidget,
-    CMainFlow, CStringTable, CWorldState,
+    CMainFlow, CPlayerState, CStateManager, CStringTable, CWorldState,
 };
 use primeapi::rstl::WString;
 
-use core::mem::MaybeUninit;
+use core::ffi::c_void;
+use core::mem::{self, MaybeUninit};
+use core::ptr::{self, addr_of, addr_of_mut};
+
+mod ipc;
 
 include!("../../patches_config.rs");
+include!("../../multiworld_mailbox.rs");
 static mut REL_CONFIG: RelConfig = RelConfig {
     quickplay_mlvl: 0xFFFFFFFF,
     quickplay_mrea: 0xFFFFFFFF,
+    initialize_power_up_call: 0,
+    incr_pickup_call: 0,
+    initialize_power_up_fn: 0,
+    incr_pickup_fn: 0,
+    update_static_interference_call: 0,
+    update_static_interference_fn: 0,
+    save_player_state_call: 0,
+    save_player_state_fn: 0,
+    load_player_state_call: 0,
+    load_player_state_fn: 0,
+    dc_flush_range_fn: 0,
+    ic_invalidate_range_fn: 0,
+    starting_states: [
+        StartingState { artifacts: 0, visor: 0xFFFFFFFF, beam: 0xFFFFFFFF, energy: 0xFFFFFFFF };
+        STARTING_STATE_COUNT
+    ],
+    progressive_items: [[0xFFFFFFFF; PROGRESSIVE_ITEM_CHAIN_LEN]; PROGRESSIVE_ITEM_CHAIN_COUNT],
 };
 
 #[prolog_fn]
@@ -41,6 +63,48 @@ unsafe extern "C" fn setup_global_state()
             .unwrap().0;
     }
 
+    if REL_CONFIG.initialize_power_up_call != 0 {
+        redirect_call(
+            REL_CONFIG.initialize_power_up_call,
+            REL_CONFIG.initialize_power_up_fn,
+            pickup_initialize_power_up as *const (),
+        );
+        redirect_call(
+            REL_CONFIG.incr_pickup_call,
+            REL_CONFIG.incr_pickup_fn,
+            pickup_incr_pickup as *const (),
+        );
+    }
+
+    if REL_CONFIG.save_player_state_call != 0 {
+        // Nothing has written the mailbox through the cache yet, but make sure none of it is
+        // sitting there before switching over to the uncached address for good
+        ipc::dc_flush_range(
+            addr_of!(MULTIWORLD_MAILBOX) as *const u8,
+            mem::size_of::<MultiworldMailbox>() as u32,
+        );
+        let mailbox = multiworld_mailbox();
+        ptr::write_volatile(addr_of_mut!((*mailbox).addr), addr_of!(MULTIWORLD_MAILBOX) as u32);
+        ptr::write_volatile(addr_of_mut!((*mailbox).magic), MULTIWORLD_MAILBOX_MAGIC);
+        redirect_call(
+            REL_CONFIG.save_player_state_call,
+            REL_CONFIG.save_player_state_fn,
+            multiworld_save_player_state as *const (),
+        );
+        redirect_call(
+            REL_CONFIG.load_player_state_call,
+            REL_CONFIG.load_player_state_fn,
+            multiworld_load_player_state as *const (),
+        );
+    }
+
+    if REL_CONFIG.update_static_interference_call != 0 {
+        redirect_call(
+            REL_CONFIG.update_static_interference_call,
+            REL_CONFIG.update_static_interference_fn,
+            player_state_update_static_interference as *const (),
+        );
+    }
 }
 
 
@@ -86,3 +150,283 @@ unsafe extern "C" fn quickplay_hook_advance_game_state(
     }
     CMainFlow::advance_game_state(flow, q)
 }
+
+// Point the bl at `call_addr` at `new_fn` instead of `orig_fn`. The addresses aren't linked like
+// the patch_fns above because the PAL symbol table lacks most of them. The patcher only hands
+// out calls it found in the function making them, and anything that isn't a bl to `orig_fn` is
+// left alone.
+unsafe fn redirect_call(call_addr: u32, orig_fn: u32, new_fn: *const ())
+{
+    let instr_ptr = call_addr as *mut u32;
+    let instr = ptr::read(instr_ptr);
+    if instr & 0xfc000003 != 0x48000001 {
+        // Not a bl
+        return;
+    }
+
+    // Sign-extend the 24 bit displacement
+    let rel_addr = (((instr & 0x03fffffc) << 6) as i32) >> 6;
+    if call_addr.wrapping_add(rel_addr as u32) != orig_fn {
+        return;
+    }
+    let rel_addr = (new_fn as u32).wrapping_sub(call_addr);
+    ptr::write(instr_ptr, 0x48000001 | (rel_addr & 0x03fffffc));
+
+    // Write the new instruction out of the data cache and drop any stale copy of it from the
+    // instruction cache, otherwise the CPU may keep running the original call
+    ipc::dc_flush_range(instr_ptr as *const u8, 4);
+    ipc::ic_invalidate_range(instr_ptr as *const u8, 4);
+}
+
+type PowerUpFn = unsafe extern "C" fn(*mut CPlayerState, i32, i32);
+
+// CScriptPickup::Touch initializes and then increments its item, so what was decided for the
+// first call is remembered here and reused for the second one: the item Touch asked for and
+// the item to give instead, or None for a foreign item that isn't given at all. The first call
+// clears it, so nothing carries over from one Touch to the next.
+static mut PENDING_PICKUP: Option<(i32, Option<i32>)> = None;
+
+// The first item of the item's progressive chain the player doesn't have yet, or the last one
+// if they have all of them
+unsafe fn next_progressive_item(player_state: *mut CPlayerState, item: i32) -> i32
+{
+    let chains = REL_CONFIG.progressive_items;
+    let chain = chains.iter()
+        .find(|chain| chain.contains(&(item as u32)));
+    let chain = if let Some(chain) = chain {
+        chain
+    } else {
+        return item;
+    };
+
+    let chain_items = chain.iter()
+        .take_while(|i| **i != 0xFFFFFFFF)
+        .map(|i| *i as i32);
+    chain_items.clone()
+        .find(|i| !CPlayerState::has_power_up(player_state, *i))
+        .or_else(|| chain_items.last())
+        .unwrap_or(item)
+}
+
+unsafe extern "C" fn pickup_initialize_power_up(
+    player_state: *mut CPlayerState,
+    item: i32,
+    capacity: i32,
+)
+{
+    // This is the first call Touch makes, so it starts from a clean slate
+    PENDING_PICKUP = None;
+    let new_item = if capacity as u32 & FOREIGN_ITEM_FLAG != 0 {
+        multiworld_send_item(capacity as u32 & !FOREIGN_ITEM_FLAG);
+        None
+    } else {
+        Some(next_progressive_item(player_state, item))
+    };
+    PENDING_PICKUP = Some((item, new_item));
+
+    if let Some(item) = new_item {
+        let orig_fn: PowerUpFn = core::mem::transmute(REL_CONFIG.initialize_power_up_fn as usize);
+        orig_fn(player_state, item, capacity)
+    }
+}
+
+unsafe extern "C" fn pickup_incr_pickup(
+    player_state: *mut CPlayerState,
+    item: i32,
+    amount: i32,
+)
+{
+    let pending = PENDING_PICKUP;
+    PENDING_PICKUP = None;
+    let new_item = match pending {
+        Some((orig_item, new_item)) if orig_item == item => new_item,
+        _ => Some(next_progressive_item(player_state, item)),
+    };
+    if let Some(item) = new_item {
+        let orig_fn: PowerUpFn = core::mem::transmute(REL_CONFIG.incr_pickup_fn as usize);
+        orig_fn(player_state, item, amount)
+    }
+}
+
+static mut MULTIWORLD_MAILBOX: MultiworldMailbox = MultiworldMailbox {
+    magic: [0; 2],
+    addr: 0,
+    outbox_count: 0,
+    outbox: [0; MULTIWORLD_QUEUE_LEN],
+    inbox_processed: 0,
+    inbox_saved: 0,
+    outbox_dropped: 0,
+    game_padding: [0; MULTIWORLD_GAME_PADDING],
+    outbox_read: 0,
+    inbox_count: 0,
+    inbox: [MultiworldItem { item: 0, amount: 0 }; MULTIWORLD_QUEUE_LEN],
+    client_padding: [0; MULTIWORLD_CLIENT_PADDING],
+};
+
+// Foreign items picked up while the outbox was full, oldest first
+static mut MULTIWORLD_OUTBOX_BACKLOG: [u32; MULTIWORLD_OUTBOX_BACKLOG_LEN]
+    = [0; MULTIWORLD_OUTBOX_BACKLOG_LEN];
+static mut MULTIWORLD_OUTBOX_BACKLOG_COUNT: usize = 0;
+
+// The client reads and writes the mailbox behind the CPU's back, so it's only ever accessed
+// through its uncached address
+unsafe fn multiworld_mailbox() -> *mut MultiworldMailbox
+{
+    ipc::cached_to_uncached_addr(addr_of!(MULTIWORLD_MAILBOX)) as *mut MultiworldMailbox
+}
+
+// Move as many of the backlog's ids into the outbox as the client has made room for
+unsafe fn multiworld_flush_outbox()
+{
+    let mailbox = multiworld_mailbox();
+    let backlog = &mut *addr_of_mut!(MULTIWORLD_OUTBOX_BACKLOG);
+    let backlog_count = MULTIWORLD_OUTBOX_BACKLOG_COUNT;
+    let read = ptr::read_volatile(addr_of!((*mailbox).outbox_read));
+    let mut count = ptr::read_volatile(addr_of!((*mailbox).outbox_count));
+    let mut sent = 0;
+    while sent < backlog_count && (count.wrapping_sub(read) as usize) < MULTIWORLD_QUEUE_LEN {
+        let slot = count as usize % MULTIWORLD_QUEUE_LEN;
+        ptr::write_volatile(addr_of_mut!((*mailbox).outbox[slot]), backlog[sent]);
+        count = count.wrapping_add(1);
+        sent += 1;
+    }
+    ptr::write_volatile(addr_of_mut!((*mailbox).outbox_count), count);
+    backlog.copy_within(sent..backlog_count, 0);
+    MULTIWORLD_OUTBOX_BACKLOG_COUNT = backlog_count - sent;
+}
+
+unsafe fn multiworld_send_item(location_id: u32)
+{
+    if MULTIWORLD_OUTBOX_BACKLOG_COUNT == MULTIWORLD_OUTBOX_BACKLOG_LEN {
+        let mailbox = multiworld_mailbox();
+        let dropped = ptr::read_volatile(addr_of!((*mailbox).outbox_dropped));
+        ptr::write_volatile(addr_of_mut!((*mailbox).outbox_dropped), dropped.wrapping_add(1));
+        return;
+    }
+    let backlog = &mut *addr_of_mut!(MULTIWORLD_OUTBOX_BACKLOG);
+    backlog[MULTIWORLD_OUTBOX_BACKLOG_COUNT] = location_id;
+    MULTIWORLD_OUTBOX_BACKLOG_COUNT += 1;
+    multiworld_flush_outbox();
+}
+
+// Called every frame in place of CPlayerState::UpdateStaticInterference
+unsafe extern "C" fn player_state_update_static_interference(
+    player_state: *mut CPlayerState,
+    state_mgr: *mut CStateManager,
+    dt: *const f32,
+)
+{
+    apply_starting_states(player_state);
+    if REL_CONFIG.save_player_state_call != 0 {
+        multiworld_receive_items(player_state);
+    }
+
+    let orig_fn: unsafe extern "C" fn(*mut CPlayerState, *mut CStateManager, *const f32)
+        = core::mem::transmute(REL_CONFIG.update_static_interference_fn as usize);
+    orig_fn(player_state, state_mgr, dt)
+}
+
+// Offsets of CPlayerState's fields, following URDE
+const PLAYER_STATE_CURRENT_BEAM: usize = 0x8;
+const PLAYER_STATE_HEALTH: usize = 0xc;
+const PLAYER_STATE_CURRENT_VISOR: usize = 0x14;
+const PLAYER_STATE_TRANSITIONING_VISOR: usize = 0x18;
+
+// Swaps the marker item a patched spawn point gave for the rest of its starting state. The
+// spawn point also refills the player's health, so the energy set here sticks.
+unsafe fn apply_starting_states(player_state: *mut CPlayerState)
+{
+    let starting_states = REL_CONFIG.starting_states;
+    for (&marker, state) in STARTING_STATE_MARKER_ITEMS.iter().zip(starting_states.iter()) {
+        let marker = marker as i32;
+        if !CPlayerState::has_power_up(player_state, marker) {
+            continue;
+        }
+
+        let initialize_power_up: PowerUpFn = core::mem::transmute(
+            REL_CONFIG.initialize_power_up_fn as usize
+        );
+        let incr_pickup: PowerUpFn = core::mem::transmute(REL_CONFIG.incr_pickup_fn as usize);
+
+        // Taking the marker away makes this happen only once and keeps it out of saves
+        let capacity = CPlayerState::get_item_capacity(player_state, marker);
+        initialize_power_up(player_state, marker, -(capacity as i32));
+
+        for i in 0..12 {
+            if state.artifacts & (1 << i) != 0 {
+                initialize_power_up(player_state, 29 + i, 1);
+                incr_pickup(player_state, 29 + i, 1);
+            }
+        }
+
+        let fields = player_state as *mut u8;
+        if state.beam != 0xFFFFFFFF {
+            ptr::write(fields.add(PLAYER_STATE_CURRENT_BEAM) as *mut u32, state.beam);
+        }
+        if state.visor != 0xFFFFFFFF {
+            ptr::write(fields.add(PLAYER_STATE_CURRENT_VISOR) as *mut u32, state.visor);
+            ptr::write(fields.add(PLAYER_STATE_TRANSITIONING_VISOR) as *mut u32, state.visor);
+        }
+        if state.energy != 0xFFFFFFFF {
+            ptr::write(fields.add(PLAYER_STATE_HEALTH) as *mut f32, state.energy as f32);
+        }
+    }
+}
+
+// Gives the player the items other players sent them since the last frame
+unsafe fn multiworld_receive_items(player_state: *mut CPlayerState)
+{
+    multiworld_flush_outbox();
+
+    let mailbox = multiworld_mailbox();
+    let inbox_count = ptr::read_volatile(addr_of!((*mailbox).inbox_count));
+    let mut processed = ptr::read_volatile(addr_of!((*mailbox).inbox_processed));
+    while processed != inbox_count {
+        let slot = processed as usize % MULTIWORLD_QUEUE_LEN;
+        let received = ptr::read_volatile(addr_of!((*mailbox).inbox[slot]));
+        let item = next_progressive_item(player_state, received.item as i32);
+
+        let initialize_power_up: PowerUpFn = core::mem::transmute(
+            REL_CONFIG.initialize_power_up_fn as usize
+        );
+        let incr_pickup: PowerUpFn = core::mem::transmute(REL_CONFIG.incr_pickup_fn as usize);
+        initialize_power_up(player_state, item, received.amount as i32);
+        incr_pickup(player_state, item, received.amount as i32);
+
+        processed = processed.wrapping_add(1);
+        ptr::write_volatile(addr_of_mut!((*mailbox).inbox_processed), processed);
+    }
+}
+
+// Called in place of CPlayerState::PutTo when the game state is written out to be saved. The
+// items given so far are part of the save from here on, so the client can forget about them.
+unsafe extern "C" fn multiworld_save_player_state(
+    player_state: *mut CPlayerState,
+    stream: *mut c_void,
+)
+{
+    let mailbox = multiworld_mailbox();
+    let processed = ptr::read_volatile(addr_of!((*mailbox).inbox_processed));
+    ptr::write_volatile(addr_of_mut!((*mailbox).inbox_saved), processed);
+
+    let orig_fn: unsafe extern "C" fn(*mut CPlayerState, *mut c_void)
+        = core::mem::transmute(REL_CONFIG.save_player_state_fn as usize);
+    orig_fn(player_state, stream)
+}
+
+// Called in place of the CPlayerState constructor when a save is loaded. The player no longer
+// has the items given after the save was made, so they're given again.
+unsafe extern "C" fn multiworld_load_player_state(
+    player_state: *mut CPlayerState,
+    stream: *mut c_void,
+) -> *mut CPlayerState
+{
+    let orig_fn: unsafe extern "C" fn(*mut CPlayerState, *mut c_void) -> *mut CPlayerState
+        = core::mem::transmute(REL_CONFIG.load_player_state_fn as usize);
+    let res = orig_fn(player_state, stream);
+
+    let mailbox = multiworld_mailbox();
+    let saved = ptr::read_volatile(addr_of!((*mailbox).inbox_saved));
+    ptr::write_volatile(addr_of_mut!((*mailbox).inbox_processed), saved);
+    res
+}
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+// Generates the SCLY object definitions used by the structs crate from the PrimeWorldEditor
+// style templates in templates/MP1. See the comment at the top of Game.xml for their format.
+
+#[derive(Clone, Copy, PartialEq)]
+enum DeclKind
+{
+    Struct,
+    Value(u32),
+    Group,
+    Object(u8),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section
+{
+    Structs,
+    Objects,
+}
+
+struct Field
+{
+    name: String,
+    ty: String,
+    optional: bool,
+    continued: bool,
+}
+
+struct Decl
+{
+    name: String,
+    game_name: String,
+    kind: DeclKind,
+    section: Section,
+    fields: Vec<Field>,
+}
+
+fn rust_keyword(name: &str) -> bool
+{
+    matches!(name, "type" | "move" | "match" | "loop" | "ref" | "box" | "use" | "mod" | "self")
+}
+
+// "Xray Cmdl" -> "xray_cmdl"
+fn field_name(name: &str) -> String
+{
+    let mut s = name.split_whitespace()
+        .map(|w| w.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if rust_keyword(&s) {
+        s.push('_');
+    }
+    s
+}
+
+fn attr_flag(node: roxmltree::Node, attr: &str) -> bool
+{
+    match node.attribute(attr) {
+        None | Some("false") => false,
+        Some("true") => true,
+        Some(v) => panic!("Invalid value `{}` for `{}`", v, attr),
+    }
+}
+
+struct TemplateLoader<'a>
+{
+    dir: &'a Path,
+    decls: Vec<Decl>,
+    loaded: HashMap<String, usize>,
+}
+
+impl<'a> TemplateLoader<'a>
+{
+    fn read(&self, template: &str) -> String
+    {
+        let path = self.dir.join(template);
+        println!("cargo:rerun-if-changed={}", path.display());
+        fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e))
+    }
+
+    // The name of the type a template defines is the name of its file
+    fn template_name(template: &str) -> String
+    {
+        Path::new(template).file_stem().unwrap().to_str().unwrap().to_string()
+    }
+
+    fn section(template: &str) -> Section
+    {
+        if template.starts_with("Structs/") {
+            Section::Structs
+        } else {
+            Section::Objects
+        }
+    }
+
+    fn properties(&mut self, template: &str, root: roxmltree::Node) -> Vec<Field>
+    {
+        let props = root.children()
+            .find(|n| n.has_tag_name("properties"))
+            .unwrap_or_else(|| panic!("{}: missing <properties>", template));
+        let mut fields = vec![];
+        for node in props.children().filter(|n| n.is_element()) {
+            let name = node.attribute("name")
+                .unwrap_or_else(|| panic!("{}: property without a name", template));
+            let ty = match node.tag_name().name() {
+                "property" => {
+                    let ty = node.attribute("type")
+                        .unwrap_or_else(|| panic!("{}: `{}` has no type", template, name));
+                    let ty = match ty {
+                        "byte" => "u8",
+                        "long" => "u32",
+                        "float" => "f32",
+                        "vector2f" => "vec2",
+                        "vector3f" => "vec3",
+                        "color" => "color",
+                        "string" => "string",
+                        "bytelist" => "flags",
+                        "unknown" => "rest",
+                        _ => panic!("{}: `{}` has unknown type `{}`", template, name, ty),
+                    };
+                    match node.attribute("count") {
+                        Some(count) => format!("{}[{}]", ty, count),
+                        None => ty.to_string(),
+                    }
+                },
+                "struct" => {
+                    let struct_template = node.attribute("template")
+                        .unwrap_or_else(|| panic!("{}: `{}` has no template", template, name));
+                    self.load_struct(struct_template)
+                },
+                tag => panic!("{}: unexpected <{}>", template, tag),
+            };
+            fields.push(Field {
+                name: field_name(name),
+                ty,
+                optional: attr_flag(node, "optional"),
+                continued: attr_flag(node, "continued"),
+            });
+        }
+        fields
+    }
+
+    fn load_struct(&mut self, template: &str) -> String
+    {
+        let name = Self::template_name(template);
+        if self.loaded.contains_key(&name) {
+            return name
+        }
+
+        let text = self.read(template);
+        let doc = roxmltree::Document::parse(&text)
+            .unwrap_or_else(|e| panic!("{}: {}", template, e));
+        let root = doc.root_element();
+        assert!(root.has_tag_name("struct"), "{}: expected <struct>", template);
+        let kind = match root.attribute("type") {
+            Some("multi") => DeclKind::Struct,
+            Some("single") => DeclKind::Value(root.attribute("props").map_or(1, |n| n.parse().unwrap())),
+            Some("inline") => DeclKind::Group,
+            _ => panic!("{}: unknown struct type", template),
+        };
+        let fields = self.properties(template, root);
+        self.loaded.insert(name.clone(), self.decls.len());
+        self.decls.push(Decl {
+            name: name.clone(),
+            game_name: name.clone(),
+            kind,
+            section: Self::section(template),
+            fields,
+        });
+        name
+    }
+
+    fn load_object(&mut self, object: roxmltree::Node)
+    {
+        let game_name = object.attribute("name").expect("Object without a name");
+        let otype = object.attribute("ID")
+            .and_then(|id| u8::from_str_radix(id.trim_start_matches("0x"), 16).ok())
+            .unwrap_or_else(|| panic!("{}: missing or invalid ID", game_name));
+
+        // Objects without a template aren't parsed; they're only listed so that their names are
+        // known
+        let (name, fields) = match object.attribute("template") {
+            Some(template) => {
+                let text = self.read(template);
+                let doc = roxmltree::Document::parse(&text)
+                    .unwrap_or_else(|e| panic!("{}: {}", template, e));
+                let root = doc.root_element();
+                assert!(root.has_tag_name("ScriptTemplate"), "{}: expected <ScriptTemplate>",
+                        template);
+                (Self::template_name(template), self.properties(template, root))
+            },
+            None => (game_name.to_string(), vec![]),
+        };
+        self.decls.push(Decl {
+            name,
+            game_name: game_name.to_string(),
+            kind: DeclKind::Object(otype),
+            section: Section::Objects,
+            fields,
+        });
+    }
+}
+
+fn load_templates(dir: &Path) -> Vec<Decl>
+{
+    let mut loader = TemplateLoader { dir, decls: vec![], loaded: HashMap::new() };
+    let text = loader.read("Game.xml");
+    let doc = roxmltree::Document::parse(&text).unwrap_or_else(|e| panic!("Game.xml: {}", e));
+    let objects = doc.root_element().children()
+        .find(|n| n.has_tag_name("objects"))
+        .expect("Game.xml: missing <objects>");
+    for object in objects.children().filter(|n| n.has_tag_name("object")) {
+        loader.load_object(object);
+    }
+    loader.decls
+}
+
+struct Generator<'a>
+{
+    decls: HashMap<&'a str, &'a Decl>,
+}
+
+impl<'a> Generator<'a>
+{
+    fn decl(&self, name: &str) -> &'a Decl
+    {
+        self.decls.get(name).unwrap_or_else(|| panic!("Unknown property type `{}`", name))
+    }
+
+    fn array_len(ty: &str) -> Option<(&str, usize)>
+    {
+        let (elem, len) = ty.strip_suffix(']')?.split_once('[')?;
+        Some((elem, len.parse().unwrap()))
+    }
+
+    fn is_builtin(ty: &str) -> bool
+    {
+        match ty {
+            "u8" | "u32" | "f32" | "vec2" | "vec3" | "color" | "string" | "flags" | "rest" => true,
+            _ => Self::array_len(ty).is_some(),
+        }
+    }
+
+    fn needs_lifetime(&self, ty: &str) -> bool
+    {
+        match ty {
+            "string" | "flags" | "rest" => true,
+            _ if Self::is_builtin(ty) => false,
+            _ => self.decl(ty).fields.iter().any(|f| self.needs_lifetime(&f.ty)),
+        }
+    }
+
+    fn is_fixed_size(&self, ty: &str) -> bool
+    {
+        match ty {
+            "string" | "flags" | "rest" => false,
+            _ if Self::is_builtin(ty) => true,
+            _ => self.decl(ty).fields.iter().all(|f| !f.optional && self.is_fixed_size(&f.ty)),
+        }
+    }
+
+    // How many properties a field of the given type counts as
+    fn prop_count(&self, field: &Field) -> u32
+    {
+        if field.continued {
+            return 0
+        }
+        if let Some((_, len)) = Self::array_len(&field.ty) {
+            return len as u32
+        }
+        if Self::is_builtin(&field.ty) {
+            return 1
+        }
+        let decl = self.decl(&field.ty);
+        match decl.kind {
+            DeclKind::Group => decl.fields.iter().map(|f| self.prop_count(f)).sum(),
+            DeclKind::Value(n) => n,
+            DeclKind::Struct => 1,
+            DeclKind::Object(_) => panic!("Objects can't be used as properties"),
+        }
+    }
+
+    fn rust_type(&self, ty: &str) -> String
+    {
+        match ty {
+            "u8" | "u32" | "f32" => ty.to_string(),
+            "vec2" => "GenericArray<f32, U2>".to_string(),
+            "vec3" => "GenericArray<f32, U3>".to_string(),
+            "color" => "GenericArray<f32, U4>".to_string(),
+            "string" => "CStr<'r>".to_string(),
+            "flags" => "LazyArray<'r, u8>".to_string(),
+            "rest" => "UnknownProperties<'r>".to_string(),
+            _ => match Self::array_len(ty) {
+                Some((elem, len)) => format!("GenericArray<{}, U{}>", self.rust_type(elem), len),
+                None if self.needs_lifetime(ty) => format!("{}<'r>", ty),
+                None => ty.to_string(),
+            },
+        }
+    }
+
+    fn write_decl(&self, out: &mut String, decl: &Decl)
+    {
+        let is_object = matches!(decl.kind, DeclKind::Object(_));
+        let has_count = matches!(decl.kind, DeclKind::Struct | DeclKind::Object(_));
+        let needs_lifetime = decl.fields.iter().any(|f| self.needs_lifetime(&f.ty));
+        // Every object has a lifetime so that SclyProperty can refer to them all the same way
+        let lifetime = if needs_lifetime || is_object { "<'r>" } else { "" };
+        let fixed_size = !is_object && self.is_fixed_size(&decl.name);
+
+        writeln!(out, "#[auto_struct(Readable, Writable{}, Serde)]",
+                 if fixed_size { ", FixedSize" } else { "" }).unwrap();
+        writeln!(out, "#[derive(Debug, Clone)]").unwrap();
+        writeln!(out, "pub struct {}{}", decl.name, lifetime).unwrap();
+        writeln!(out, "{{").unwrap();
+
+        let required: u32 = decl.fields.iter()
+            .filter(|f| !f.optional)
+            .map(|f| self.prop_count(f))
+            .sum();
+        if has_count {
+            if decl.fields.iter().any(|f| f.ty == "rest") {
+                writeln!(out, "    pub prop_count: u32,").unwrap();
+            } else if decl.fields.iter().any(|f| f.optional) {
+                let mut derive = required.to_string();
+                for f in decl.fields.iter().filter(|f| f.optional) {
+                    write!(derive, " + {}.is_some() as u32 * {}", f.name, self.prop_count(f))
+                        .unwrap();
+                }
+                writeln!(out, "    #[auto_struct(derive = {})]", derive).unwrap();
+                writeln!(out, "    prop_count: u32,").unwrap();
+            } else {
+                writeln!(out, "    #[auto_struct(expect = {})]", required).unwrap();
+                writeln!(out, "    prop_count: u32,").unwrap();
+            }
+        }
+
+        let mut count_so_far = required;
+        for field in &decl.fields {
+            if field.optional {
+                if !has_count {
+                    panic!("{}.{}: only structs and objects can have optional properties",
+                           decl.name, field.name);
+                }
+                count_so_far += self.prop_count(field);
+                writeln!(out,
+                    "    #[auto_struct(init = if prop_count >= {} {{ Some(()) }} else {{ None }})]",
+                    count_so_far,
+                ).unwrap();
+                writeln!(out, "    pub {}: Option<{}>,", field.name, self.rust_type(&field.ty))
+                    .unwrap();
+            } else if field.ty == "flags" {
+                writeln!(out, "    #[auto_struct(derive = {}.len() as u32)]", field.name).unwrap();
+                writeln!(out, "    {}_count: u32,", field.name).unwrap();
+                writeln!(out, "    #[auto_struct(init = ({}_count as usize, ()))]", field.name)
+                    .unwrap();
+                writeln!(out, "    pub {}: {},", field.name, self.rust_type(&field.ty)).unwrap();
+            } else {
+                writeln!(out, "    pub {}: {},", field.name, self.rust_type(&field.ty)).unwrap();
+            }
+        }
+        if is_object && !needs_lifetime {
+            writeln!(out, "    #[auto_struct(literal = PhantomData)]").unwrap();
+            writeln!(out, "    _lifetime: PhantomData<&'r ()>,").unwrap();
+        }
+        writeln!(out, "}}\n").unwrap();
+
+        if let DeclKind::Object(otype) = decl.kind {
+            writeln!(out, "impl<'r> SclyPropertyData for {}<'r>", decl.name).unwrap();
+            writeln!(out, "{{").unwrap();
+            writeln!(out, "    const OBJECT_TYPE: u8 = 0x{:02X};", otype).unwrap();
+            writeln!(out, "}}\n").unwrap();
+        }
+    }
+}
+
+fn snake_case(name: &str) -> String
+{
+    let chars: Vec<char> = name.chars().collect();
+    let mut s = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev_lower = chars[i - 1].is_ascii_lowercase() || chars[i - 1].is_ascii_digit();
+            let next_lower = chars.get(i + 1).is_some_and(|c| c.is_ascii_lowercase());
+            if prev_lower || (chars[i - 1].is_ascii_uppercase() && next_lower) {
+                s.push('_');
+            }
+        }
+        s.push(c.to_ascii_lowercase());
+    }
+    s
+}
+
+const HEADER: &str = "\
+use auto_struct_macros::auto_struct;
+
+#[allow(unused_imports)]
+use reader_writer::{CStr, LazyArray};
+#[allow(unused_imports)]
+use reader_writer::typenum::*;
+#[allow(unused_imports)]
+use reader_writer::generic_array::GenericArray;
+#[allow(unused_imports)]
+use std::marker::PhantomData;
+
+";
+
+// Writes scly_structs.rs, scly_props.rs and scly_property.rs to out_dir, along with
+// scly_macros.rs, which defines the macros the structs crate includes them with
+pub fn generate(templates_dir: &Path, out_dir: &Path)
+{
+    let decls = load_templates(templates_dir);
+    let gen = Generator {
+        decls: decls.iter().map(|d| (&d.name[..], d)).collect(),
+    };
+
+    let mut structs = HEADER.to_string();
+    let mut objects = HEADER.to_string();
+    objects.push_str("use crate::SclyPropertyData;\n#[allow(unused_imports)]\nuse self::structs::*;\n\n");
+    let mut variants = String::new();
+    let mut names = String::new();
+    let mut name_arms = String::new();
+    let mut unnamed = vec![];
+    for decl in &decls {
+        let is_typed = !decl.fields.is_empty();
+        if let DeclKind::Object(otype) = decl.kind {
+            writeln!(names, "        0x{:02X} => \"{}\",", otype, decl.game_name).unwrap();
+            if is_typed {
+                let snake = snake_case(&decl.name);
+                writeln!(variants, "    {}, is_{1}, as_{1}, as_{1}_mut,", decl.name, snake).unwrap();
+            }
+            match decl.fields.first() {
+                Some(f) if f.name == "name" && f.ty == "string" => writeln!(
+                    name_arms, "            SclyProperty::{}(inst) => Some(&inst.name),", decl.name
+                ).unwrap(),
+                Some(_) => unnamed.push(format!("0x{:02X}", otype)),
+                None => (),
+            }
+        }
+        if !is_typed {
+            continue
+        }
+        match decl.section {
+            Section::Structs => gen.write_decl(&mut structs, decl),
+            Section::Objects => gen.write_decl(&mut objects, decl),
+        }
+    }
+
+    let mut scly = format!("build_scly_property!(\n{});\n\n", variants);
+    writeln!(scly, "// The names the game uses for each of its object types, including the ones without a typed").unwrap();
+    writeln!(scly, "// definition here").unwrap();
+    writeln!(scly, "pub fn scly_object_type_name(object_type: u8) -> Option<&'static str>").unwrap();
+    writeln!(scly, "{{\n    Some(match object_type {{\n{}        _ => return None,\n    }})\n}}", names)
+        .unwrap();
+
+
+    writeln!(scly, "\nconst UNNAMED_OBJECT_TYPES: &[u8] = &[{}];\n", unnamed.join(", ")).unwrap();
+    writeln!(scly, "impl<'r> SclyProperty<'r>\n{{").unwrap();
+    writeln!(scly, "    // The object's instance name, if it has one").unwrap();
+    writeln!(scly, "    pub fn name(&self) -> Option<&std::ffi::CStr>\n    {{").unwrap();
+    writeln!(scly, "        match self {{").unwrap();
+    writeln!(scly, "            SclyProperty::Unknown {{ object_type, data }} =>").unwrap();
+    writeln!(scly, "                read_instance_name(*object_type, data.as_bytes()),").unwrap();
+    scly.push_str(&name_arms);
+    if !unnamed.is_empty() {
+        writeln!(scly, "            _ => None,").unwrap();
+    }
+    writeln!(scly, "        }}\n    }}\n}}").unwrap();
+
+    let mut macros = String::new();
+    for (macro_name, file_name, contents) in &[
+        ("scly_structs", "scly_structs.rs", structs),
+        ("scly_props", "scly_props.rs", objects),
+        ("scly_property", "scly_property.rs", scly),
+    ] {
+        let path = out_dir.join(file_name);
+        fs::write(&path, contents).unwrap();
+        writeln!(macros, "#[macro_export]").unwrap();
+        writeln!(macros, "macro_rules! {} {{", macro_name).unwrap();
+        writeln!(macros, "    () => {{ include!({:?}); }}", path.to_str().unwrap()).unwrap();
+        writeln!(macros, "}}\n").unwrap();
+    }
+    fs::write(out_dir.join("scly_macros.rs"), macros).unwrap();
+}
@@ -0,0 +1,281 @@
+// The PC side of multiworld. A `MultiworldClient` finds the mailbox the REL sets up in the
+// game's memory, reports the foreign items the player picks up, and delivers the items other
+// players send them.
+
+use byteorder::{BigEndian, ByteOrder};
+
+use std::collections::VecDeque;
+
+include!("../../compile_to_ppc/multiworld_mailbox.rs");
+
+pub mod memory;
+pub mod mock;
+
+pub use crate::memory::{serve_game_memory, GameMemory, SocketGameMemory};
+pub use crate::mock::MockGame;
+
+pub(crate) const MEM1_START: u32 = 0x80000000;
+pub(crate) const MEM1_SIZE: u32 = 0x01800000;
+const SEARCH_CHUNK_SIZE: u32 = 0x10000;
+
+const OUTBOX_OFFSET: usize = 16;
+const INBOX_PROCESSED_OFFSET: usize = OUTBOX_OFFSET + 4 * MULTIWORLD_QUEUE_LEN;
+const INBOX_SAVED_OFFSET: usize = INBOX_PROCESSED_OFFSET + 4;
+const OUTBOX_DROPPED_OFFSET: usize = INBOX_SAVED_OFFSET + 4;
+const OUTBOX_READ_OFFSET: usize = OUTBOX_DROPPED_OFFSET + 4 + 4 * MULTIWORLD_GAME_PADDING;
+const INBOX_COUNT_OFFSET: usize = OUTBOX_READ_OFFSET + 4;
+const INBOX_OFFSET: usize = INBOX_COUNT_OFFSET + 4;
+const MAILBOX_SIZE: usize = INBOX_OFFSET + 8 * MULTIWORLD_QUEUE_LEN + 4 * MULTIWORLD_CLIENT_PADDING;
+
+impl MultiworldMailbox
+{
+    pub(crate) fn from_bytes(bytes: &[u8]) -> MultiworldMailbox
+    {
+        assert_eq!(MAILBOX_SIZE, std::mem::size_of::<MultiworldMailbox>());
+        let u32_at = |offset: usize| BigEndian::read_u32(&bytes[offset..]);
+        let mut mailbox = MultiworldMailbox {
+            magic: [u32_at(0), u32_at(4)],
+            addr: u32_at(8),
+            outbox_count: u32_at(12),
+            outbox: [0; MULTIWORLD_QUEUE_LEN],
+            inbox_processed: u32_at(INBOX_PROCESSED_OFFSET),
+            inbox_saved: u32_at(INBOX_SAVED_OFFSET),
+            outbox_dropped: u32_at(OUTBOX_DROPPED_OFFSET),
+            game_padding: [0; MULTIWORLD_GAME_PADDING],
+            outbox_read: u32_at(OUTBOX_READ_OFFSET),
+            inbox_count: u32_at(INBOX_COUNT_OFFSET),
+            inbox: [MultiworldItem { item: 0, amount: 0 }; MULTIWORLD_QUEUE_LEN],
+            client_padding: [0; MULTIWORLD_CLIENT_PADDING],
+        };
+        for i in 0..MULTIWORLD_QUEUE_LEN {
+            mailbox.outbox[i] = u32_at(OUTBOX_OFFSET + 4 * i);
+            mailbox.inbox[i] = MultiworldItem {
+                item: u32_at(INBOX_OFFSET + 8 * i),
+                amount: u32_at(INBOX_OFFSET + 8 * i + 4),
+            };
+        }
+        mailbox
+    }
+
+    pub(crate) fn to_bytes(self) -> Vec<u8>
+    {
+        let mut words = vec![self.magic[0], self.magic[1], self.addr, self.outbox_count];
+        words.extend(self.outbox.iter());
+        words.extend(&[self.inbox_processed, self.inbox_saved, self.outbox_dropped]);
+        words.extend(self.game_padding.iter());
+        words.extend(&[self.outbox_read, self.inbox_count]);
+        words.extend(self.inbox.iter().flat_map(|received| vec![received.item, received.amount]));
+        words.extend(self.client_padding.iter());
+
+        let mut bytes = vec![0; MAILBOX_SIZE];
+        BigEndian::write_u32_into(&words, &mut bytes);
+        bytes
+    }
+
+    fn is_valid(&self, addr: u32) -> bool
+    {
+        self.magic == MULTIWORLD_MAILBOX_MAGIC && self.addr == addr
+    }
+
+    // The items in the inbox that haven't been saved yet, oldest first
+    fn unsaved_items(&self) -> VecDeque<MultiworldItem>
+    {
+        (self.inbox_saved..self.inbox_count)
+            .map(|i| self.inbox[i as usize % MULTIWORLD_QUEUE_LEN])
+            .collect()
+    }
+}
+
+// Search MEM1 for the mailbox
+fn find_mailbox<M: GameMemory>(memory: &mut M) -> Result<(u32, MultiworldMailbox), String>
+{
+    let magic = MULTIWORLD_MAILBOX_MAGIC.iter()
+        .flat_map(|word| word.to_be_bytes().to_vec())
+        .collect::<Vec<_>>();
+    let mem1_end = MEM1_START + MEM1_SIZE;
+    for chunk_addr in (MEM1_START..mem1_end).step_by(SEARCH_CHUNK_SIZE as usize) {
+        // Read a bit past the end of the chunk so a mailbox starting near its end is complete,
+        // but never past the end of MEM1
+        let len = (SEARCH_CHUNK_SIZE as usize + MAILBOX_SIZE).min((mem1_end - chunk_addr) as usize);
+        let mut chunk = vec![0; len];
+        memory.read(chunk_addr, &mut chunk)?;
+        // The mailbox is made of u32s, so it's 4 byte aligned
+        for offset in (0..SEARCH_CHUNK_SIZE as usize).step_by(4) {
+            if offset + MAILBOX_SIZE > chunk.len() {
+                break;
+            }
+            if chunk[offset..offset + magic.len()] != magic[..] {
+                continue;
+            }
+            let addr = chunk_addr + offset as u32;
+            let mailbox = MultiworldMailbox::from_bytes(&chunk[offset..offset + MAILBOX_SIZE]);
+            if mailbox.is_valid(addr) {
+                return Ok((addr, mailbox))
+            }
+        }
+    }
+    Err("Couldn't find the multiworld mailbox in the game's memory".to_string())
+}
+
+pub struct MultiworldClient<M>
+{
+    memory: M,
+    mailbox_addr: u32,
+    // How many of the outbox's entries have already been returned by `poll`
+    outbox_read: u32,
+    // How many of the foreign items the game lost have already been reported
+    outbox_dropped: u32,
+    // The mailbox's `inbox_saved` when it was last read, and the items written to the inbox
+    // after it. These are sent again if the game is reset before it saves them.
+    inbox_saved: u32,
+    unsaved: VecDeque<MultiworldItem>,
+    // Items waiting for room in the inbox
+    unsent: VecDeque<MultiworldItem>,
+}
+
+impl<M: GameMemory> MultiworldClient<M>
+{
+    // Search the game's memory for the mailbox. This fails if the game hasn't loaded the REL
+    // yet or was patched without multiworld enabled.
+    pub fn connect(mut memory: M) -> Result<Self, String>
+    {
+        let (mailbox_addr, mailbox) = find_mailbox(&mut memory)?;
+
+        // The outbox's read position is kept in the mailbox, so a client connecting to a game
+        // another client was connected to carries on where that one stopped
+        Ok(MultiworldClient {
+            memory,
+            mailbox_addr,
+            outbox_read: mailbox.outbox_read,
+            outbox_dropped: 0,
+            inbox_saved: mailbox.inbox_saved,
+            unsaved: mailbox.unsaved_items(),
+            unsent: VecDeque::new(),
+        })
+    }
+
+    // Find the mailbox again after the game was reset, e.g. rebooted. Items the game hadn't
+    // saved before the reset are sent again.
+    pub fn reconnect(&mut self) -> Result<(), String>
+    {
+        let (mailbox_addr, mailbox) = find_mailbox(&mut self.memory)?;
+        let expected_inbox_count = self.inbox_saved.wrapping_add(self.unsaved.len() as u32);
+        if mailbox_addr != self.mailbox_addr || mailbox.inbox_count < expected_inbox_count {
+            for received in self.unsaved.drain(..).rev() {
+                self.unsent.push_front(received);
+            }
+        }
+        self.mailbox_addr = mailbox_addr;
+        self.outbox_read = mailbox.outbox_read;
+        self.outbox_dropped = 0;
+        self.inbox_saved = mailbox.inbox_saved;
+        self.unsaved = mailbox.unsaved_items();
+        self.flush()
+    }
+
+    pub fn mailbox_addr(&self) -> u32
+    {
+        self.mailbox_addr
+    }
+
+    pub fn memory_mut(&mut self) -> &mut M
+    {
+        &mut self.memory
+    }
+
+    fn read_mailbox(&mut self) -> Result<MultiworldMailbox, String>
+    {
+        let mut bytes = vec![0; MAILBOX_SIZE];
+        self.memory.read(self.mailbox_addr, &mut bytes)?;
+        let mailbox = MultiworldMailbox::from_bytes(&bytes);
+        let reset = !mailbox.is_valid(self.mailbox_addr) ||
+            mailbox.outbox_count < self.outbox_read ||
+            mailbox.inbox_saved < self.inbox_saved ||
+            mailbox.outbox_dropped < self.outbox_dropped;
+        if reset {
+            Err("The multiworld mailbox was reset, the game needs to be reconnected to".to_string())?
+        }
+        Ok(mailbox)
+    }
+
+    // Queue an item for the player. `item` is a CPlayerState::EItemType and `amount` is used as
+    // both the capacity and the amount given.
+    pub fn send_item(&mut self, item: u32, amount: u32) -> Result<(), String>
+    {
+        self.unsent.push_back(MultiworldItem { item, amount });
+        self.flush()
+    }
+
+    // The number of items sent that haven't fit into the inbox yet
+    pub fn unsent_count(&self) -> usize
+    {
+        self.unsent.len()
+    }
+
+    // The number of items in the inbox that the game hasn't saved yet
+    pub fn unsaved_count(&self) -> usize
+    {
+        self.unsaved.len()
+    }
+
+    fn flush(&mut self) -> Result<(), String>
+    {
+        let mailbox = self.read_mailbox()?;
+
+        // Items the game has saved can't be lost anymore
+        let newly_saved = mailbox.inbox_saved.wrapping_sub(self.inbox_saved) as usize;
+        self.unsaved.drain(..newly_saved.min(self.unsaved.len()));
+        self.inbox_saved = mailbox.inbox_saved;
+
+        // Entries after `inbox_saved` may be given again if a save is loaded, so they can't be
+        // reused until then
+        let mut inbox_count = mailbox.inbox_count;
+        let queued = inbox_count.wrapping_sub(mailbox.inbox_saved) as usize;
+        let free = MULTIWORLD_QUEUE_LEN.saturating_sub(queued);
+        if free == 0 || self.unsent.is_empty() {
+            return Ok(());
+        }
+
+        for received in self.unsent.drain(..free.min(self.unsent.len())) {
+            let slot = inbox_count as usize % MULTIWORLD_QUEUE_LEN;
+            let mut bytes = [0; 8];
+            BigEndian::write_u32_into(&[received.item, received.amount], &mut bytes);
+            self.memory.write(self.mailbox_addr + (INBOX_OFFSET + 8 * slot) as u32, &bytes)?;
+            self.unsaved.push_back(received);
+            inbox_count = inbox_count.wrapping_add(1);
+        }
+        // Only publish the new entries once they've all been written
+        self.memory.write(
+            self.mailbox_addr + INBOX_COUNT_OFFSET as u32,
+            &inbox_count.to_be_bytes(),
+        )
+    }
+
+    // Deliver any queued items and return the ids of the foreign items picked up since the last
+    // call. The game doesn't reuse an outbox entry until it has been read here, but it can only
+    // hold on to so many ids itself; if it had to drop some, that's reported as an error once.
+    pub fn poll(&mut self) -> Result<Vec<u32>, String>
+    {
+        self.flush()?;
+
+        let mailbox = self.read_mailbox()?;
+        if mailbox.outbox_dropped != self.outbox_dropped {
+            let dropped = mailbox.outbox_dropped - self.outbox_dropped;
+            self.outbox_dropped = mailbox.outbox_dropped;
+            Err(format!(
+                "The game picked up {} foreign items while its outbox was full, they were lost",
+                dropped,
+            ))?
+        }
+
+        let ids = (self.outbox_read..mailbox.outbox_count)
+            .map(|i| mailbox.outbox[i as usize % MULTIWORLD_QUEUE_LEN])
+            .collect();
+        self.outbox_read = mailbox.outbox_count;
+        self.memory.write(
+            self.mailbox_addr + OUTBOX_READ_OFFSET as u32,
+            &self.outbox_read.to_be_bytes(),
+        )?;
+        Ok(ids)
+    }
+}
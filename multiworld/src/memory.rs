@@ -0,0 +1,153 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+// Access to the game's memory, e.g. through an emulator's memory watch. Addresses are the
+// game's virtual addresses.
+pub trait GameMemory
+{
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), String>;
+    fn write(&mut self, addr: u32, data: &[u8]) -> Result<(), String>;
+}
+
+impl<M: GameMemory + ?Sized> GameMemory for &mut M
+{
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), String>
+    {
+        (**self).read(addr, buf)
+    }
+
+    fn write(&mut self, addr: u32, data: &[u8]) -> Result<(), String>
+    {
+        (**self).write(addr, data)
+    }
+}
+
+// The socket protocol is a series of requests, each answered before the next is sent:
+//   read:  [0u8][addr: u32][len: u32]             -> [0u8][data: len bytes]
+//   write: [1u8][addr: u32][len: u32][data: len bytes] -> [0u8]
+// Every integer is big endian. A request that fails is answered with
+// [1u8][len: u32][message: len bytes of UTF-8] instead.
+// Nothing the client does needs more than MEM1 at once, so anything bigger is refused rather
+// than allocated
+const MAX_REQUEST_LEN: u32 = crate::MEM1_SIZE;
+
+const REQUEST_READ: u8 = 0;
+const REQUEST_WRITE: u8 = 1;
+const RESPONSE_OK: u8 = 0;
+const RESPONSE_ERR: u8 = 1;
+
+// A `GameMemory` on the other end of a socket, e.g. an emulator script or another process
+// serving a `GameMemory` with `serve_game_memory`
+pub struct SocketGameMemory
+{
+    stream: TcpStream,
+}
+
+impl SocketGameMemory
+{
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, String>
+    {
+        let stream = TcpStream::connect(addr)
+            .and_then(|stream| stream.set_nodelay(true).map(|()| stream))
+            .map_err(|e| format!("Failed to connect to the game: {}", e))?;
+        Ok(SocketGameMemory { stream })
+    }
+
+    fn request(&mut self, kind: u8, addr: u32, len: usize, data: &[u8])
+        -> io::Result<Result<(), String>>
+    {
+        let mut request = vec![kind];
+        request.write_u32::<BigEndian>(addr)?;
+        request.write_u32::<BigEndian>(len as u32)?;
+        request.extend_from_slice(data);
+        self.stream.write_all(&request)?;
+
+        match self.stream.read_u8()? {
+            RESPONSE_OK => Ok(Ok(())),
+            _ => {
+                let len = self.stream.read_u32::<BigEndian>()?;
+                let mut message = vec![0; len as usize];
+                self.stream.read_exact(&mut message)?;
+                Ok(Err(String::from_utf8_lossy(&message).into_owned()))
+            },
+        }
+    }
+}
+
+impl GameMemory for SocketGameMemory
+{
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), String>
+    {
+        self.request(REQUEST_READ, addr, buf.len(), &[])
+            .and_then(|res| {
+                if res.is_ok() {
+                    self.stream.read_exact(buf)?;
+                }
+                Ok(res)
+            })
+            .map_err(|e| format!("Failed to read the game's memory: {}", e))?
+    }
+
+    fn write(&mut self, addr: u32, data: &[u8]) -> Result<(), String>
+    {
+        self.request(REQUEST_WRITE, addr, data.len(), data)
+            .map_err(|e| format!("Failed to write the game's memory: {}", e))?
+    }
+}
+
+// Answer `SocketGameMemory` requests from `stream` with `memory` until the other end
+// disconnects
+pub fn serve_game_memory<S, M>(mut stream: S, memory: &mut M) -> Result<(), String>
+    where S: Read + Write,
+          M: GameMemory + ?Sized,
+{
+    let mut serve = || -> io::Result<()> {
+        loop {
+            let kind = match stream.read_u8() {
+                Ok(kind) => kind,
+                Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            let addr = stream.read_u32::<BigEndian>()?;
+            let len = stream.read_u32::<BigEndian>()?;
+            if len > MAX_REQUEST_LEN {
+                // The request's data can't be skipped without reading it, so there's no way to
+                // carry on after this
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("request for {} bytes is larger than the {} allowed", len, MAX_REQUEST_LEN),
+                ))?
+            }
+
+            let mut data = vec![0; len as usize];
+            let res = match kind {
+                REQUEST_READ => memory.read(addr, &mut data),
+                REQUEST_WRITE => {
+                    stream.read_exact(&mut data)?;
+                    memory.write(addr, &data)
+                },
+                _ => Err(format!("Unknown request {}", kind)),
+            };
+
+            let mut response = vec![];
+            match res {
+                Ok(()) => {
+                    response.push(RESPONSE_OK);
+                    if kind == REQUEST_READ {
+                        response.extend_from_slice(&data);
+                    }
+                },
+                Err(message) => {
+                    response.push(RESPONSE_ERR);
+                    response.write_u32::<BigEndian>(message.len() as u32)?;
+                    response.extend_from_slice(message.as_bytes());
+                },
+            }
+            stream.write_all(&response)?;
+            stream.flush()?;
+        }
+    };
+    serve().map_err(|e| format!("Failed to serve the game's memory: {}", e))
+}
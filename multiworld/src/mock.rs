@@ -0,0 +1,148 @@
+use crate::{
+    GameMemory, MultiworldItem, MultiworldMailbox, MAILBOX_SIZE, MULTIWORLD_CLIENT_PADDING,
+    MULTIWORLD_GAME_PADDING, MULTIWORLD_MAILBOX_MAGIC, MULTIWORLD_OUTBOX_BACKLOG_LEN,
+    MULTIWORLD_QUEUE_LEN,
+};
+
+use std::collections::HashMap;
+
+// Stands in for a game running the REL, for testing clients without an emulator. Picking up
+// foreign items, the per frame update, saving and loading do what the REL's hooks do; all
+// other memory reads as zero.
+pub struct MockGame
+{
+    mailbox_addr: u32,
+    mailbox: Vec<u8>,
+    backlog: Vec<u32>,
+    inventory: HashMap<u32, u32>,
+    saved_inventory: HashMap<u32, u32>,
+}
+
+impl MockGame
+{
+    pub fn new(mailbox_addr: u32) -> MockGame
+    {
+        assert_eq!(mailbox_addr % 4, 0);
+        let mailbox = MultiworldMailbox {
+            magic: MULTIWORLD_MAILBOX_MAGIC,
+            addr: mailbox_addr,
+            outbox_count: 0,
+            outbox: [0; MULTIWORLD_QUEUE_LEN],
+            inbox_processed: 0,
+            inbox_saved: 0,
+            outbox_dropped: 0,
+            game_padding: [0; MULTIWORLD_GAME_PADDING],
+            outbox_read: 0,
+            inbox_count: 0,
+            inbox: [MultiworldItem { item: 0, amount: 0 }; MULTIWORLD_QUEUE_LEN],
+            client_padding: [0; MULTIWORLD_CLIENT_PADDING],
+        };
+        MockGame {
+            mailbox_addr,
+            mailbox: mailbox.to_bytes(),
+            backlog: vec![],
+            inventory: HashMap::new(),
+            saved_inventory: HashMap::new(),
+        }
+    }
+
+    fn with_mailbox<T>(&mut self, f: impl FnOnce(&mut MultiworldMailbox) -> T) -> T
+    {
+        let mut mailbox = MultiworldMailbox::from_bytes(&self.mailbox);
+        let res = f(&mut mailbox);
+        self.mailbox = mailbox.to_bytes();
+        res
+    }
+
+    // Move as much of the backlog into the outbox as the client has made room for
+    fn flush_outbox(&mut self)
+    {
+        let mut backlog = std::mem::take(&mut self.backlog);
+        self.with_mailbox(|mailbox| {
+            let free = MULTIWORLD_QUEUE_LEN
+                .saturating_sub(mailbox.outbox_count.wrapping_sub(mailbox.outbox_read) as usize);
+            for id in backlog.drain(..free.min(backlog.len())) {
+                mailbox.outbox[mailbox.outbox_count as usize % MULTIWORLD_QUEUE_LEN] = id;
+                mailbox.outbox_count = mailbox.outbox_count.wrapping_add(1);
+            }
+        });
+        self.backlog = backlog;
+    }
+
+    pub fn pick_up_foreign_item(&mut self, id: u32)
+    {
+        if self.backlog.len() == MULTIWORLD_OUTBOX_BACKLOG_LEN {
+            self.with_mailbox(|mailbox| mailbox.outbox_dropped += 1);
+        } else {
+            self.backlog.push(id);
+        }
+        self.flush_outbox();
+    }
+
+    // Give the player the items that have arrived in the inbox
+    pub fn update(&mut self)
+    {
+        self.flush_outbox();
+        let received = self.with_mailbox(|mailbox| {
+            let mut received = vec![];
+            while mailbox.inbox_processed != mailbox.inbox_count {
+                received.push(mailbox.inbox[mailbox.inbox_processed as usize % MULTIWORLD_QUEUE_LEN]);
+                mailbox.inbox_processed = mailbox.inbox_processed.wrapping_add(1);
+            }
+            received
+        });
+        for item in received {
+            *self.inventory.entry(item.item).or_insert(0) += item.amount;
+        }
+    }
+
+    pub fn item_amount(&self, item: u32) -> u32
+    {
+        self.inventory.get(&item).cloned().unwrap_or(0)
+    }
+
+    pub fn save(&mut self)
+    {
+        self.saved_inventory = self.inventory.clone();
+        self.with_mailbox(|mailbox| mailbox.inbox_saved = mailbox.inbox_processed);
+    }
+
+    // Load the last save without rebooting
+    pub fn load_save(&mut self)
+    {
+        self.inventory = self.saved_inventory.clone();
+        self.with_mailbox(|mailbox| mailbox.inbox_processed = mailbox.inbox_saved);
+    }
+
+    // Reboot the game and load the last save. The mailbox starts over from scratch.
+    pub fn reset(&mut self)
+    {
+        let saved_inventory = self.saved_inventory.clone();
+        *self = MockGame::new(self.mailbox_addr);
+        self.inventory = saved_inventory.clone();
+        self.saved_inventory = saved_inventory;
+    }
+}
+
+impl GameMemory for MockGame
+{
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), String>
+    {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            let offset = (addr as u64 + i as u64).wrapping_sub(self.mailbox_addr as u64) as usize;
+            *byte = self.mailbox.get(offset).cloned().unwrap_or(0);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, addr: u32, data: &[u8]) -> Result<(), String>
+    {
+        let end = addr as u64 + data.len() as u64;
+        if addr < self.mailbox_addr || end > self.mailbox_addr as u64 + MAILBOX_SIZE as u64 {
+            Err(format!("Write of {} bytes to {:#x} is outside the mailbox", data.len(), addr))?
+        }
+        let offset = (addr - self.mailbox_addr) as usize;
+        self.mailbox[offset..offset + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+}
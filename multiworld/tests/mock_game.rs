@@ -0,0 +1,243 @@
+use multiworld::{serve_game_memory, GameMemory, MockGame, MultiworldClient, SocketGameMemory};
+
+use std::io::{self, Cursor, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+const MISSILE: u32 = 4;
+const ENERGY_TANK: u32 = 24;
+
+#[test]
+fn test_relay_between_players()
+{
+    let mut game_a = MockGame::new(0x803F1230);
+    let mut game_b = MockGame::new(0x80500000);
+    let mut client_a = MultiworldClient::connect(&mut game_a).unwrap();
+    assert_eq!(client_a.mailbox_addr(), 0x803F1230);
+    let mut client_b = MultiworldClient::connect(&mut game_b).unwrap();
+
+    // Player A finds player B's energy tank and missile expansion
+    client_a.memory_mut().pick_up_foreign_item(7);
+    client_a.memory_mut().pick_up_foreign_item(12);
+    assert_eq!(client_a.poll().unwrap(), vec![7, 12]);
+    assert_eq!(client_a.poll().unwrap(), Vec::<u32>::new());
+
+    client_b.send_item(ENERGY_TANK, 1).unwrap();
+    client_b.send_item(MISSILE, 5).unwrap();
+    client_b.memory_mut().update();
+    assert_eq!(client_b.memory_mut().item_amount(ENERGY_TANK), 1);
+    assert_eq!(client_b.memory_mut().item_amount(MISSILE), 5);
+}
+
+#[test]
+fn test_inbox_overflow()
+{
+    let mut game = MockGame::new(0x80400000);
+    let mut client = MultiworldClient::connect(&mut game).unwrap();
+
+    // More items than fit in the inbox arrive before the game gets to run
+    for _ in 0..40 {
+        client.send_item(MISSILE, 5).unwrap();
+    }
+    assert!(client.unsent_count() > 0);
+    while client.unsent_count() > 0 {
+        client.memory_mut().update();
+        client.memory_mut().save();
+        client.poll().unwrap();
+    }
+    client.memory_mut().update();
+    assert_eq!(client.memory_mut().item_amount(MISSILE), 200);
+}
+
+#[test]
+fn test_reconnect_and_reset()
+{
+    let mut game = MockGame::new(0x80400000);
+    game.pick_up_foreign_item(1);
+    game.pick_up_foreign_item(2);
+
+    // A client connecting later still sees what is left in the outbox
+    let mut client = MultiworldClient::connect(&mut game).unwrap();
+    assert_eq!(client.poll().unwrap(), vec![1, 2]);
+
+    client.memory_mut().reset();
+    assert!(client.poll().is_err());
+
+    let mut game = MockGame::new(0x80400000);
+    for id in 0..20 {
+        game.pick_up_foreign_item(id);
+    }
+    let mut client = MultiworldClient::connect(&mut game).unwrap();
+    assert_eq!(client.poll().unwrap(), (0..20).collect::<Vec<_>>());
+
+    // A new client carries on from where the last one stopped reading
+    game.pick_up_foreign_item(20);
+    let mut client = MultiworldClient::connect(&mut game).unwrap();
+    assert_eq!(client.poll().unwrap(), vec![20]);
+}
+
+#[test]
+fn test_outbox_back_pressure()
+{
+    let mut game = MockGame::new(0x80400000);
+    for id in 0..40 {
+        game.pick_up_foreign_item(id);
+    }
+    let mut client = MultiworldClient::connect(&mut game).unwrap();
+
+    // The outbox only has room for the first ids, the rest wait in the game until it's read
+    let ids = client.poll().unwrap();
+    assert!(ids.len() < 40);
+    client.memory_mut().update();
+    let mut all_ids = ids;
+    all_ids.extend(client.poll().unwrap());
+    assert_eq!(all_ids, (0..40).collect::<Vec<_>>());
+
+    // Once the game can't hold on to any more, the ids it had to drop are reported
+    for id in 0..200 {
+        client.memory_mut().pick_up_foreign_item(id);
+    }
+    assert!(client.poll().is_err());
+    let mut count = 0;
+    loop {
+        client.memory_mut().update();
+        let ids = client.poll().unwrap();
+        if ids.is_empty() {
+            break;
+        }
+        count += ids.len();
+    }
+    assert!(count > 0 && count < 200);
+}
+
+#[test]
+fn test_unsaved_items_are_given_again()
+{
+    let mut game = MockGame::new(0x80400000);
+    let mut client = MultiworldClient::connect(&mut game).unwrap();
+
+    client.send_item(MISSILE, 5).unwrap();
+    client.memory_mut().update();
+    client.memory_mut().save();
+    client.send_item(ENERGY_TANK, 1).unwrap();
+    client.memory_mut().update();
+    client.poll().unwrap();
+    assert_eq!(client.unsaved_count(), 1);
+
+    // Loading the save takes the energy tank away, and the game gives it again
+    client.memory_mut().load_save();
+    assert_eq!(client.memory_mut().item_amount(ENERGY_TANK), 0);
+    client.memory_mut().update();
+    assert_eq!(client.memory_mut().item_amount(ENERGY_TANK), 1);
+
+    // So does rebooting, after which the client sends it again
+    client.memory_mut().reset();
+    assert!(client.poll().is_err());
+    client.reconnect().unwrap();
+    client.memory_mut().update();
+    assert_eq!(client.memory_mut().item_amount(MISSILE), 5);
+    assert_eq!(client.memory_mut().item_amount(ENERGY_TANK), 1);
+
+    // Once it's saved it's safe
+    client.memory_mut().save();
+    client.poll().unwrap();
+    assert_eq!(client.unsaved_count(), 0);
+    client.memory_mut().reset();
+    client.reconnect().unwrap();
+    client.memory_mut().update();
+    assert_eq!(client.memory_mut().item_amount(ENERGY_TANK), 1);
+}
+
+// Memory without a mailbox in it
+struct BlankMemory;
+
+impl GameMemory for BlankMemory
+{
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), String>
+    {
+        if addr < 0x80000000 || addr as u64 + buf.len() as u64 > 0x81800000 {
+            Err(format!("Read of {} bytes from {:#x} is outside MEM1", buf.len(), addr))?
+        }
+        buf.iter_mut().for_each(|byte| *byte = 0);
+        Ok(())
+    }
+
+    fn write(&mut self, addr: u32, _data: &[u8]) -> Result<(), String>
+    {
+        Err(format!("Unexpected write to {:#x}", addr))
+    }
+}
+
+#[test]
+fn test_no_mailbox()
+{
+    // The search stays inside MEM1, so it fails because there's no mailbox rather than because
+    // of a bad read
+    let err = MultiworldClient::connect(BlankMemory).err().unwrap();
+    assert!(err.starts_with("Couldn't find"), "{}", err);
+}
+
+#[test]
+fn test_socket()
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let mut game = MockGame::new(0x80400000);
+        game.pick_up_foreign_item(3);
+        let (stream, _) = listener.accept().unwrap();
+        serve_game_memory(stream, &mut game).unwrap();
+        game.update();
+        game.item_amount(ENERGY_TANK)
+    });
+
+    {
+        let mut client = MultiworldClient::connect(SocketGameMemory::connect(addr).unwrap()).unwrap();
+        assert_eq!(client.mailbox_addr(), 0x80400000);
+        assert_eq!(client.poll().unwrap(), vec![3]);
+        client.send_item(ENERGY_TANK, 1).unwrap();
+
+        // Errors from the other end make it back to the client
+        assert!(client.memory_mut().write(0x80000000, &[0]).is_err());
+    }
+    assert_eq!(server.join().unwrap(), 1);
+}
+
+// A stream that reads from a buffer and writes into another
+struct BufferStream
+{
+    input: Cursor<Vec<u8>>,
+    output: Vec<u8>,
+}
+
+impl Read for BufferStream
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>
+    {
+        self.input.read(buf)
+    }
+}
+
+impl Write for BufferStream
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+    {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()>
+    {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_serve_rejects_huge_requests()
+{
+    // A read of 4GB
+    let request = vec![0, 0x80, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF];
+    let mut stream = BufferStream { input: Cursor::new(request), output: vec![] };
+    let mut game = MockGame::new(0x80400000);
+    assert!(serve_game_memory(&mut stream, &mut game).is_err());
+    assert!(stream.output.is_empty());
+}
@@ -4,7 +4,7 @@ use std::slice::Iter as SliceIter;
 use std::slice::IterMut as SliceIterMut;
 
 use crate::lcow::LCow;
-use crate::reader::{Reader, Readable};
+use crate::reader::{Reader, Readable, ReadResult};
 use crate::writer::Writable;
 use crate::read_only_array::{RoArray, RoArrayIter};
 use crate::derivable_array_proxy::DerivableFromIterator;
@@ -14,13 +14,13 @@ impl<'r, T> Readable<'r> for Vec<T>
           T::Args: Clone,
 {
     type Args = (usize, T::Args);
-    fn read_from(reader: &mut Reader<'r>, (len, args): Self::Args) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (len, args): Self::Args) -> ReadResult<Self>
     {
         let mut res = Vec::with_capacity(len);
-        for _ in 0..len {
-            res.push(reader.read(args.clone()));
+        for i in 0..len {
+            res.push(reader.try_read(args.clone()).map_err(|e| e.in_field(format!("[{}]", i)))?);
         };
-        res
+        Ok(res)
     }
 
     fn size(&self) -> usize
@@ -124,10 +124,10 @@ impl<'r, T> Readable<'r> for LazyArray<'r, T>
 {
     type Args = (usize, T::Args);
 
-    fn read_from(reader: &mut Reader<'r>, args: Self::Args) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, args: Self::Args) -> ReadResult<Self>
     {
-        let array = RoArray::read_from(reader, args);
-        LazyArray::Borrowed(array)
+        let array = RoArray::try_read_from(reader, args)?;
+        Ok(LazyArray::Borrowed(array))
     }
 
     fn size(&self) -> usize
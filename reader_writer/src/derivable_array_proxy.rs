@@ -5,7 +5,7 @@ use std::{
 };
 
 use crate::{
-    reader::{Reader, Readable},
+    reader::{Reader, Readable, ReadError, ReadResult},
     writer::Writable,
 };
 
@@ -35,9 +35,9 @@ impl<'r, I, T> Readable<'r> for Dap<I, T>
           T: Readable<'r>,
 {
     type Args = ();
-    fn read_from(_: &mut Reader<'r>, (): ()) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<Self>
     {
-        panic!("Dap should not ever be read.")
+        Err(ReadError::invalid_value(reader, "Dap should not ever be read."))
     }
 
     fn size(&self) -> usize
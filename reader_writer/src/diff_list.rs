@@ -8,7 +8,7 @@ use std::{
 };
 
 use crate::{
-    reader::{Reader, Readable},
+    reader::{Reader, Readable, ReadResult},
     writer::Writable,
     lcow::LCow,
 };
@@ -327,13 +327,13 @@ impl<'r, A> Readable<'r> for DiffList<A>
           <A::Cursor as DiffListSourceCursor>::Item: Readable<'r>,
 {
     type Args = A;
-    fn read_from(reader: &mut Reader<'r>, args: A) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, args: A) -> ReadResult<Self>
     {
         let res = DiffList {
             list: Vec::from_iter(once(DiffListElem::Array(args))),
         };
-        reader.advance(res.size());
-        res
+        reader.try_advance(res.size())?;
+        Ok(res)
     }
 
     fn size(&self) -> usize
@@ -480,6 +480,24 @@ mod test
         }
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_diff_list_serde()
+    {
+        let junk = &[0u8; 1024][..];
+        let source = Source(&[1, 2, 3]);
+        let mut diff_list: super::DiffList<Source> = crate::Reader::new(junk).read(source);
+        diff_list.cursor().insert_before(std::iter::once(0));
+
+        let json = serde_json::to_string(&diff_list).unwrap();
+        assert_eq!(json, "[0,1,2,3]");
+        let owned: super::DiffList<Source> = serde_json::from_str(&json).unwrap();
+        let v = owned.iter()
+            .map(|i| i.into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![0, 1, 2, 3]);
+    }
+
     #[test]
     fn test_diff_list_unmodified_iter()
     {
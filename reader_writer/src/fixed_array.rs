@@ -3,7 +3,7 @@ use std::io;
 use crate::{
     derivable_array_proxy::DerivableFromIterator,
     generic_array::{GenericArray, ArrayLength},
-    reader::{Reader, Readable},
+    reader::{Reader, Readable, ReadResult},
     writer::Writable,
 };
 
@@ -17,13 +17,13 @@ impl<'r, T, N> Readable<'r> for FixedArray<T, N>
     type Args = T::Args;
 
 
-    fn read_from(reader: &mut Reader<'r>, args: Self::Args) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, args: Self::Args) -> ReadResult<Self>
     {
-        let array = {
-            let iter = (0..N::to_usize()).map(|_| reader.read(args.clone()));
-            GenericArray::from_exact_iter(iter).unwrap()
-        };
-        array
+        let mut elems = Vec::with_capacity(N::to_usize());
+        for i in 0..N::to_usize() {
+            elems.push(reader.try_read(args.clone()).map_err(|e| e.in_field(format!("[{}]", i)))?);
+        }
+        Ok(GenericArray::from_exact_iter(elems).unwrap())
     }
 
     fn size(&self) -> usize
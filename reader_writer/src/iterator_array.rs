@@ -6,7 +6,7 @@ use std::{
 
 use crate::{
     lcow::LCow,
-    reader::{Reader, Readable},
+    reader::{Reader, Readable, ReadResult},
     writer::Writable,
 };
 
@@ -42,19 +42,30 @@ impl<'r, T, I> IteratorArray<'r, T, I>
     }
 
     pub fn as_mut_vec(&mut self) -> &mut Vec<T>
+    {
+        match self.try_as_mut_vec() {
+            Ok(vec) => vec,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    // Like `as_mut_vec`, but an element that fails to parse is returned as an error and the
+    // array is left borrowed
+    pub fn try_as_mut_vec(&mut self) -> ReadResult<&mut Vec<T>>
     {
         *self = match *self {
-            IteratorArray::Borrowed(ref mut reader, ref mut iter) => {
+            IteratorArray::Borrowed(ref reader, ref iter) => {
+                let mut reader = reader.clone();
                 let mut vec = Vec::with_capacity(iter.len());
-                while let Some(arg) = iter.next() {
-                    vec.push(reader.read(arg));
+                for (n, arg) in iter.clone().enumerate() {
+                    vec.push(reader.try_read(arg).map_err(|e| e.in_field(format!("[{}]", n)))?);
                 };
                 IteratorArray::Owned(vec)
             },
-            IteratorArray::Owned(ref mut vec) => return vec,
+            IteratorArray::Owned(ref mut vec) => return Ok(vec),
         };
         match *self {
-            IteratorArray::Owned(ref mut vec) => vec,
+            IteratorArray::Owned(ref mut vec) => Ok(vec),
             IteratorArray::Borrowed(_, _) => unreachable!(),
         }
     }
@@ -65,11 +76,17 @@ impl<'r, T, I> Readable<'r> for IteratorArray<'r, T, I>
           I: Iterator<Item=T::Args> + ExactSizeIterator + Clone
 {
     type Args = I;
-    fn read_from(reader: &mut Reader<'r>, i: I) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, i: I) -> ReadResult<Self>
     {
-        let res = IteratorArray::Borrowed(reader.clone(), i);
-        reader.advance(res.size());
-        res
+        let res = IteratorArray::Borrowed(reader.clone(), i.clone());
+        if let Some(fixed_size) = T::fixed_size() {
+            reader.try_advance(fixed_size * i.len())?;
+        } else {
+            for (n, args) in i.enumerate() {
+                T::try_read_from(reader, args).map_err(|e| e.in_field(format!("[{}]", n)))?;
+            }
+        }
+        Ok(res)
     }
 
     fn size(&self) -> usize
@@ -167,3 +184,30 @@ impl<'r, T, I> From<Vec<T>> for IteratorArray<'r, T, I>
     }
 }
 
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{IteratorArray, Reader};
+    #[test]
+    fn test_try_as_mut_vec()
+    {
+        let data = [0, 0, 0, 1, 0, 0];
+        let args = vec![(), ()];
+        let mut array: IteratorArray<u32, _> = IteratorArray::Borrowed(
+            Reader::new(&data),
+            args.into_iter(),
+        );
+        let err = array.try_as_mut_vec().unwrap_err();
+        assert!(err.to_string().contains("[1]"), "{}", err);
+        assert_eq!(array.len(), 2);
+
+        let data = [0, 0, 0, 1, 0, 0, 0, 2];
+        let mut array: IteratorArray<u32, _> = IteratorArray::Borrowed(
+            Reader::new(&data),
+            vec![(), ()].into_iter(),
+        );
+        array.try_as_mut_vec().unwrap().push(3);
+        assert_eq!(array.iter().map(|i| *i).collect::<Vec<_>>(), [1, 2, 3]);
+    }
+}
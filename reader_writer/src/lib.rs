@@ -1,6 +1,8 @@
 // Rexport these crates to make syncing version numbers less of a pain
 pub use byteorder;
 pub use generic_array;
+#[cfg(feature = "serde")]
+pub use serde;
 
 pub mod reader;
 pub mod writer;
@@ -21,11 +23,14 @@ pub mod padding;
 
 pub mod utf16_string;
 
+#[cfg(feature = "serde")]
+pub mod serde_impls;
+
 
 pub use crate::{
     generic_array::typenum,
 
-    reader::{Reader, Readable},
+    reader::{Reader, Readable, ReadError, ReadErrorKind, ReadResult},
     writer::Writable,
 
     primitive_types::{FourCC, CStr, CStrConversionExtension},
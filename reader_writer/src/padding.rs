@@ -2,7 +2,7 @@
 use std::io;
 use crate::{
     read_only_array::RoArray,
-    reader::{Readable, Reader},
+    reader::{Readable, Reader, ReadResult},
     writer::Writable,
 };
 
@@ -39,10 +39,10 @@ pub struct PaddingBlackhole(pub usize);
 impl<'r> Readable<'r> for PaddingBlackhole
 {
     type Args = usize;
-    fn read_from(reader: &mut Reader<'r>, i: Self::Args) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, i: Self::Args) -> ReadResult<Self>
     {
-        reader.advance(i);
-        PaddingBlackhole(i)
+        reader.try_advance(i)?;
+        Ok(PaddingBlackhole(i))
     }
 
     fn size(&self) -> usize
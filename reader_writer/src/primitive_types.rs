@@ -9,7 +9,7 @@ use std::marker::PhantomData;
 
 use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
 
-use crate::reader::{Readable, Reader};
+use crate::reader::{Readable, Reader, ReadError, ReadResult};
 use crate::writer::Writable;
 
 macro_rules! define_arith_readable {
@@ -18,10 +18,10 @@ macro_rules! define_arith_readable {
             impl<'r> Readable<'r> for $T
             {
                 type Args = ();
-                fn read_from(reader: &mut Reader<'r>, (): ()) -> $T
+                fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<$T>
                 {
-                    let res = reader.$rf::<BigEndian>();
-                    res.unwrap()
+                    reader.check_len(mem::size_of::<$T>())?;
+                    Ok(reader.$rf::<BigEndian>().unwrap())
                 }
 
                 fn fixed_size() -> Option<usize>
@@ -47,10 +47,10 @@ macro_rules! define_byte_readable {
             impl<'r> Readable<'r> for $T
             {
                 type Args = ();
-                fn read_from(reader: &mut Reader<'r>, (): ()) -> $T
+                fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<$T>
                 {
-                    let res = reader.$rf();
-                    res.unwrap()
+                    reader.check_len(mem::size_of::<$T>())?;
+                    Ok(reader.$rf().unwrap())
                 }
 
                 fn fixed_size() -> Option<usize>
@@ -110,12 +110,12 @@ impl FourCC
 impl<'r> Readable<'r> for FourCC
 {
     type Args = ();
-    fn read_from(reader: &mut Reader<'r>, (): ()) -> FourCC
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<FourCC>
     {
         // TODO: Verify ordering
-        let res = [reader.read(()), reader.read(()),
-                   reader.read(()), reader.read(())];
-        FourCC::from_bytes(&res)
+        let res = [reader.try_read(())?, reader.try_read(())?,
+                   reader.try_read(())?, reader.try_read(())?];
+        Ok(FourCC::from_bytes(&res))
     }
 
     fn fixed_size() -> Option<usize>
@@ -164,13 +164,13 @@ impl<'r, T> Readable<'r> for Option<T>
     where T: Readable<'r>
 {
     type Args = Option<T::Args>;
-    fn read_from(reader: &mut Reader<'r>, args: Self::Args) -> Option<T>
+    fn try_read_from(reader: &mut Reader<'r>, args: Self::Args) -> ReadResult<Option<T>>
     {
         if let Some(args) = args {
-            let res = reader.read(args);
-            Some(res)
+            let res = reader.try_read(args)?;
+            Ok(Some(res))
         } else {
-            None
+            Ok(None)
         }
     }
 
@@ -197,9 +197,9 @@ impl<'r, T> Readable<'r> for Box<T>
     where T: Readable<'r>
 {
     type Args = T::Args;
-    fn read_from(reader: &mut Reader<'r>, args: T::Args) -> Box<T>
+    fn try_read_from(reader: &mut Reader<'r>, args: T::Args) -> ReadResult<Box<T>>
     {
-        Box::new(reader.read(args))
+        Ok(Box::new(reader.try_read(args)?))
     }
 
     fn size(&self) -> usize
@@ -226,9 +226,9 @@ impl<T> Writable for Box<T>
 impl<'r, T> Readable<'r> for PhantomData<T>
 {
     type Args = ();
-    fn read_from(_reader: &mut Reader<'r>, (): ()) -> Self
+    fn try_read_from(_reader: &mut Reader<'r>, (): ()) -> ReadResult<Self>
     {
-        PhantomData
+        Ok(PhantomData)
     }
 
     fn fixed_size() -> Option<usize>
@@ -249,14 +249,15 @@ pub type CStr<'r> = Cow<'r, ffi::CStr>;
 impl<'r> Readable<'r> for CStr<'r>
 {
     type Args = ();
-    fn read_from(reader: &mut Reader<'r>, (): ()) -> CStr<'r>
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<CStr<'r>>
     {
+        let len = reader.iter().position(|&i| i == b'\0')
+            .ok_or_else(|| ReadError::invalid_value(reader, "Unterminated string"))? + 1;
         // XXX A possible optimization would be to use from_bytes_with_nul_unchecked here
-        let buf = &(*reader)[0..(reader.iter().position(|&i| i == b'\0').unwrap() + 1)];
+        let buf = &(*reader)[0..len];
         let cstr = Cow::Borrowed(ffi::CStr::from_bytes_with_nul(buf).unwrap());
-        let len = cstr.size();
         reader.advance(len);
-        cstr
+        Ok(cstr)
     }
 
     fn size(&self) -> usize
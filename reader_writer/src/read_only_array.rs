@@ -1,11 +1,11 @@
-
 use std::{
     fmt,
     io,
+    rc::Rc,
 };
 
 use crate::{
-    reader::{Reader, Readable},
+    reader::{Reader, Readable, ReadResult},
     writer::Writable,
     derivable_array_proxy::DerivableFromIterator,
 };
@@ -16,9 +16,57 @@ pub struct RoArray<'r, T>
     where T: Readable<'r>,
           T::Args: Clone,
 {
-    t_args: T::Args,
     length: usize,
-    data_start: Reader<'r>,
+    data: RoArrayData<'r, T>,
+}
+
+// Arrays are normally read lazily out of a buffer. Arrays built from a `Vec` (e.g. when
+// deserializing) own their elements instead; `clone` is captured while `T: Clone` is known so
+// that iterating over them doesn't need that bound everywhere else.
+enum RoArrayData<'r, T>
+    where T: Readable<'r>,
+          T::Args: Clone,
+{
+    Borrowed {
+        t_args: T::Args,
+        data_start: Reader<'r>,
+    },
+    Owned {
+        elems: Rc<[T]>,
+        clone: fn(&T) -> T,
+    },
+}
+
+impl<'r, T> Clone for RoArrayData<'r, T>
+    where T: Readable<'r>,
+          T::Args: Clone,
+{
+    fn clone(&self) -> Self
+    {
+        match self {
+            RoArrayData::Borrowed { t_args, data_start } => RoArrayData::Borrowed {
+                t_args: t_args.clone(),
+                data_start: data_start.clone(),
+            },
+            RoArrayData::Owned { elems, clone } => RoArrayData::Owned {
+                elems: elems.clone(),
+                clone: *clone,
+            },
+        }
+    }
+}
+
+impl<'r, T> fmt::Debug for RoArrayData<'r, T>
+    where T: Readable<'r>,
+          T::Args: Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            RoArrayData::Borrowed { data_start, .. } => write!(f, "Borrowed({:?})", data_start),
+            RoArrayData::Owned { elems, .. } => write!(f, "Owned({} elements)", elems.len()),
+        }
+    }
 }
 
 
@@ -34,9 +82,8 @@ impl<'r, T> RoArray<'r, T>
     pub fn iter(&self) -> RoArrayIter<'r, T>
     {
         RoArrayIter {
-            t_args: self.t_args.clone(),
             length: self.length,
-            data_start: self.data_start.clone(),
+            data: self.data.clone(),
         }
     }
 
@@ -48,34 +95,87 @@ impl<'r, T> RoArray<'r, T>
         let right_len = self.length - at;
         // Shorten self to the new length
         self.length = at;
-        // self is now the new length, so calculate its new size
-        let new_size = T::fixed_size()
-            .map(|i| i * self.length)
-            .unwrap_or_else(|| self.iter().fold(0, |s, i| s + i.size()));
-
-        let res = RoArray {
-            t_args: self.t_args.clone(),
-            length: right_len,
-            data_start: self.data_start.offset(new_size),
+        let data = match &mut self.data {
+            RoArrayData::Borrowed { t_args, data_start } => {
+                // self is now the new length, so calculate its new size
+                let new_size = T::fixed_size()
+                    .map(|i| i * at)
+                    .unwrap_or_else(|| {
+                        let mut reader = data_start.clone();
+                        (0..at).fold(0, |s, _| s + reader.read::<T>(t_args.clone()).size())
+                    });
+                let right = RoArrayData::Borrowed {
+                    t_args: t_args.clone(),
+                    data_start: data_start.offset(new_size),
+                };
+                data_start.truncate(new_size);
+                right
+            },
+            RoArrayData::Owned { elems, clone } => {
+                let right = RoArrayData::Owned {
+                    elems: elems[at..].iter().map(*clone).collect(),
+                    clone: *clone,
+                };
+                *elems = elems[..at].iter().map(*clone).collect();
+                right
+            },
         };
-        self.data_start.truncate(new_size);
-        res
+        RoArray {
+            length: right_len,
+            data,
+        }
     }
 
     pub fn get(&self, at: usize) -> Option<T>
     {
-        let fixed_size = T::fixed_size().expect(
-                "Array::get should only be called for Ts that are fixed size.");
         if at >= self.length {
-            None
-        } else {
-            Some(self.data_start.offset(at * fixed_size).read(self.t_args.clone()))
+            return None
+        }
+        match &self.data {
+            RoArrayData::Borrowed { t_args, data_start } => {
+                let fixed_size = T::fixed_size().expect(
+                        "Array::get should only be called for Ts that are fixed size.");
+                Some(data_start.offset(at * fixed_size).read(t_args.clone()))
+            },
+            RoArrayData::Owned { elems, clone } => Some(clone(&elems[at])),
+        }
+    }
+
+    // The buffer the array was read from. Arrays that weren't read from a buffer don't have one.
+    pub fn data_start(&self) -> Option<Reader<'r>>
+    {
+        match &self.data {
+            RoArrayData::Borrowed { data_start, .. } => Some(data_start.clone()),
+            RoArrayData::Owned { .. } => None,
+        }
+    }
+}
+
+impl<'r> RoArray<'r, u8>
+{
+    // The array's contents, wherever they're stored
+    pub fn as_bytes(&self) -> &[u8]
+    {
+        match &self.data {
+            RoArrayData::Borrowed { data_start, .. } => &data_start[..],
+            RoArrayData::Owned { elems, .. } => elems,
         }
     }
+}
 
-    pub fn data_start(&self) -> Reader<'r>
+impl<'r, T> From<Vec<T>> for RoArray<'r, T>
+    where T: Readable<'r> + Clone,
+          T::Args: Clone,
+{
+    fn from(elems: Vec<T>) -> RoArray<'r, T>
     {
-        self.data_start.clone()
+        RoArray {
+            length: elems.len(),
+            data: RoArrayData::Owned {
+                elems: elems.into(),
+                clone: T::clone,
+            },
+        }
     }
 }
 
@@ -86,30 +186,37 @@ impl<'r, T> Readable<'r> for RoArray<'r, T>
     type Args = (usize, T::Args);
 
     // TODO: It would be cool to cache the size in the reader's length field.
-    fn read_from(reader: &mut Reader<'r>, (length, args): Self::Args) -> Self
-    {
-        let size = T::fixed_size()
-            .map(|i| i * length)
-            .unwrap_or_else(|| {
-                let iter = RoArrayIter::<T> {
-                    t_args: args.clone(),
-                    length: length,
-                    data_start: reader.clone(),
-                };
-                iter.fold(0, |s, i| s + i.size())
-            });
+    // Elements of fixed size types are read lazily, so only the array's bounds are checked here.
+    fn try_read_from(reader: &mut Reader<'r>, (length, args): Self::Args) -> ReadResult<Self>
+    {
+        let size = if let Some(fixed_size) = T::fixed_size() {
+            fixed_size * length
+        } else {
+            let mut tmp_reader = reader.clone();
+            for i in 0..length {
+                T::try_read_from(&mut tmp_reader, args.clone())
+                    .map_err(|e| e.in_field(format!("[{}]", i)))?;
+            }
+            reader.len() - tmp_reader.len()
+        };
+        reader.check_len(size)?;
         let array = RoArray {
-            t_args: args,
             length: length,
-            data_start: reader.truncated(size),
+            data: RoArrayData::Borrowed {
+                t_args: args,
+                data_start: reader.truncated(size),
+            },
         };
         reader.advance(size);
-        array
+        Ok(array)
     }
 
     fn size(&self) -> usize
     {
-        self.data_start.len()
+        match &self.data {
+            RoArrayData::Borrowed { data_start, .. } => data_start.len(),
+            RoArrayData::Owned { elems, .. } => elems.iter().map(|elem| elem.size()).sum(),
+        }
     }
 }
 
@@ -129,9 +236,8 @@ pub struct RoArrayIter<'r, T>
     where T: Readable<'r>,
           T::Args: Clone,
 {
-    data_start: Reader<'r>,
     length: usize,
-    t_args: T::Args,
+    data: RoArrayData<'r, T>,
 }
 
 impl<'r, T> Iterator for RoArrayIter<'r, T>
@@ -145,7 +251,12 @@ impl<'r, T> Iterator for RoArrayIter<'r, T>
             None
         } else {
             self.length -= 1;
-            Some(self.data_start.read::<T>(self.t_args.clone()))
+            match &mut self.data {
+                RoArrayData::Borrowed { t_args, data_start } =>
+                    Some(data_start.read::<T>(t_args.clone())),
+                RoArrayData::Owned { elems, clone } =>
+                    Some(clone(&elems[elems.len() - self.length - 1])),
+            }
         }
     }
 
@@ -172,11 +283,22 @@ impl<'r, T> Writable for RoArray<'r, T>
 {
     fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
     {
-        // TODO: Could this be done more efficently by using the length component of
-        //       the reader?
-        let len = self.size();
-        writer.write_all(&(*self.data_start)[0..len])?;
-        Ok(len as u64)
+        match &self.data {
+            // TODO: Could this be done more efficently by using the length component of
+            //       the reader?
+            RoArrayData::Borrowed { data_start, .. } => {
+                let len = data_start.len();
+                writer.write_all(&(*data_start)[0..len])?;
+                Ok(len as u64)
+            },
+            RoArrayData::Owned { elems, .. } => {
+                let mut s = 0;
+                for elem in elems.iter() {
+                    s += elem.write_to(writer)?;
+                }
+                Ok(s)
+            },
+        }
     }
 }
 
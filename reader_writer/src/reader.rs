@@ -1,5 +1,5 @@
 use std::{
-    io,
+    error, fmt, io,
     fmt::{Debug, Formatter, Error},
     ops::{Deref, DerefMut},
 };
@@ -7,7 +7,7 @@ use std::{
 use crate::writer::Writable;
 
 #[derive(Clone)]
-pub struct Reader<'r>(&'r [u8]);
+pub struct Reader<'r>(&'r [u8], usize);
 
 
 impl<'r> Deref for Reader<'r>
@@ -31,7 +31,7 @@ impl<'r> Debug for Reader<'r>
 {
     fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error>
     {
-        let ptr = self.0.as_ptr() as *const _ as usize;
+        let ptr = self.0.as_ptr() as usize;
         formatter.write_fmt(format_args!("Reader(0x{:x})", ptr))
     }
 }
@@ -40,12 +40,12 @@ impl <'r> Reader<'r>
 {
     pub fn new(data: &'r [u8]) -> Reader<'r>
     {
-        Reader(data)
+        Reader(data, data.as_ptr() as usize)
     }
 
     pub fn dummy() -> Reader<'r>
     {
-        Reader(&[])
+        Reader::new(&[])
     }
 
     pub fn read<T>(&mut self, args: T::Args) -> T
@@ -54,33 +54,140 @@ impl <'r> Reader<'r>
         T::read_from(self, args)
     }
 
+    pub fn try_read<T>(&mut self, args: T::Args) -> ReadResult<T>
+        where T : Readable<'r>
+    {
+        T::try_read_from(self, args)
+    }
+
+    // The number of bytes between the start of the buffer passed to `Reader::new` and the
+    // reader's current position
+    pub fn position(&self) -> usize
+    {
+        self.0.as_ptr() as usize - self.1
+    }
+
     pub fn advance(&mut self, len: usize)
     {
         self.0 = self.0.split_at(len).1
     }
 
+    pub fn try_advance(&mut self, len: usize) -> ReadResult<()>
+    {
+        self.check_len(len)?;
+        self.advance(len);
+        Ok(())
+    }
+
+    // Fail with an UnexpectedEof error if fewer than `len` bytes remain
+    pub fn check_len(&self, len: usize) -> ReadResult<()>
+    {
+        if self.0.len() < len {
+            Err(ReadError::new(self, ReadErrorKind::UnexpectedEof {
+                needed: len,
+                remaining: self.0.len(),
+            }))
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn offset(&self, len: usize) -> Reader<'r>
     {
-        Reader(self.0.split_at(len).1)
+        Reader(self.0.split_at(len).1, self.1)
     }
 
     pub fn truncate(&mut self, len: usize)
     {
-        *self = Reader(&self.0[0..len])
+        *self = Reader(&self.0[0..len], self.1)
     }
 
     pub fn truncated(&self, len: usize) -> Reader<'r>
     {
-        Reader(&self.0[0..len])
+        Reader(&self.0[0..len], self.1)
     }
 }
 
+pub type ReadResult<T> = Result<T, ReadError>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReadErrorKind
+{
+    UnexpectedEof { needed: usize, remaining: usize },
+    UnexpectedValue { expected: String, found: String },
+    InvalidValue(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadError
+{
+    // Relative to the start of the buffer the outermost Reader was created from
+    pub offset: usize,
+    // The fields being read when the error occured, outermost first (e.g. "Mlvl.areas", "[3]",
+    // "MlvlArea.name")
+    pub path: Vec<String>,
+    pub kind: ReadErrorKind,
+}
+
+impl ReadError
+{
+    pub fn new(reader: &Reader, kind: ReadErrorKind) -> ReadError
+    {
+        ReadError {
+            offset: reader.position(),
+            path: vec![],
+            kind,
+        }
+    }
+
+    pub fn invalid_value<T: fmt::Display>(reader: &Reader, msg: T) -> ReadError
+    {
+        ReadError::new(reader, ReadErrorKind::InvalidValue(msg.to_string()))
+    }
+
+    pub fn unexpected_value<T: Debug>(reader: &Reader, expected: &T, found: &T) -> ReadError
+    {
+        ReadError::new(reader, ReadErrorKind::UnexpectedValue {
+            expected: format!("{:?}", expected),
+            found: format!("{:?}", found),
+        })
+    }
+
+    // Record that the error happened while reading `field`
+    pub fn in_field<T: Into<String>>(mut self, field: T) -> ReadError
+    {
+        self.path.insert(0, field.into());
+        self
+    }
+}
+
+impl fmt::Display for ReadError
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result
+    {
+        match &self.kind {
+            ReadErrorKind::UnexpectedEof { needed, remaining } =>
+                write!(f, "Unexpected end of data (needed {} bytes, {} remaining)", needed, remaining)?,
+            ReadErrorKind::UnexpectedValue { expected, found } =>
+                write!(f, "Expected {}, found {}", expected, found)?,
+            ReadErrorKind::InvalidValue(msg) => write!(f, "{}", msg)?,
+        }
+        write!(f, " at offset 0x{:x}", self.offset)?;
+        if !self.path.is_empty() {
+            write!(f, " while reading {}", self.path.join(" -> "))?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ReadError { }
+
 impl<'r> Readable<'r> for Reader<'r>
 {
     type Args = ();
-    fn read_from(reader: &mut Reader<'r>, (): ()) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<Self>
     {
-        reader.clone()
+        Ok(reader.clone())
     }
 
     fn fixed_size() -> Option<usize>
@@ -101,7 +208,15 @@ impl<'r> Writable for Reader<'r>
 pub trait Readable<'r> : Sized
 {
     type Args;
-    fn read_from(reader: &mut Reader<'r>, args: Self::Args) -> Self;
+    fn try_read_from(reader: &mut Reader<'r>, args: Self::Args) -> ReadResult<Self>;
+
+    fn read_from(reader: &mut Reader<'r>, args: Self::Args) -> Self
+    {
+        match Self::try_read_from(reader, args) {
+            Ok(res) => res,
+            Err(e) => panic!("{}", e),
+        }
+    }
 
     fn size(&self) -> usize
     {
@@ -0,0 +1,374 @@
+// Serde support for the reader_writer types that can appear inside an auto_struct.
+//
+// Lazily parsed containers are serialized as plain sequences of their elements. On the way back
+// in they become their owned variants. A `Reader` can only ever point into a buffer that outlives
+// `'r`, so it can be serialized but not deserialized; types holding raw data that needs to be
+// deserialized use a `RoArray<u8>` instead.
+
+use std::{
+    borrow::Cow,
+    ffi,
+    fmt,
+};
+
+use serde::{
+    de::{self, Deserialize, Deserializer, Visitor},
+    ser::{Serialize, Serializer, SerializeSeq},
+};
+
+use crate::{
+    array::LazyArray,
+    diff_list::{AsDiffListSourceCursor, DiffList, DiffListSourceCursor},
+    iterator_array::IteratorArray,
+    primitive_types::FourCC,
+    read_only_array::RoArray,
+    reader::{Reader, Readable},
+    uncached::Uncached,
+    utf16_string::LazyUtf16beStr,
+};
+
+fn serialize_iter<S, T, I>(serializer: S, len: usize, iter: I) -> Result<S::Ok, S::Error>
+    where S: Serializer,
+          T: Serialize,
+          I: Iterator,
+          I::Item: std::ops::Deref<Target = T>,
+{
+    let mut seq = serializer.serialize_seq(Some(len))?;
+    for elem in iter {
+        seq.serialize_element(&*elem)?;
+    }
+    seq.end()
+}
+
+impl Serialize for FourCC
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        match std::str::from_utf8(self.as_bytes()) {
+            Ok(s) => serializer.serialize_str(s),
+            Err(_) => serializer.serialize_u32(self.to_u32()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FourCC
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        struct FourCCVisitor;
+        impl<'de> Visitor<'de> for FourCCVisitor
+        {
+            type Value = FourCC;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result
+            {
+                f.write_str("a 4 character string or a u32")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<FourCC, E>
+            {
+                if s.len() != 4 {
+                    Err(E::invalid_length(s.len(), &self))?
+                }
+                let mut bytes = [0u8; 4];
+                bytes.copy_from_slice(s.as_bytes());
+                Ok(FourCC::from_bytes(&bytes))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<FourCC, E>
+            {
+                if v > u32::max_value() as u64 {
+                    Err(E::invalid_value(de::Unexpected::Unsigned(v), &self))?
+                }
+                Ok(FourCC::new(v as u32))
+            }
+        }
+        deserializer.deserialize_any(FourCCVisitor)
+    }
+}
+
+impl<'r> Serialize for Reader<'r>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serializer.serialize_bytes(&self[..])
+    }
+}
+
+impl<'r, T> Serialize for RoArray<'r, T>
+    where T: Readable<'r> + Serialize,
+          T::Args: Clone,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for elem in self.iter() {
+            seq.serialize_element(&elem)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, 'r, T> Deserialize<'de> for RoArray<'r, T>
+    where T: Readable<'r> + Clone + Deserialize<'de>,
+          T::Args: Clone,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        Ok(<Vec<T>>::deserialize(deserializer)?.into())
+    }
+}
+
+impl<'r, T> Serialize for LazyArray<'r, T>
+    where T: Readable<'r> + Serialize,
+          T::Args: Clone,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serialize_iter(serializer, self.len(), self.iter())
+    }
+}
+
+impl<'de, 'r, T> Deserialize<'de> for LazyArray<'r, T>
+    where T: Readable<'r> + Deserialize<'de>,
+          T::Args: Clone,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        Ok(LazyArray::Owned(<Vec<T>>::deserialize(deserializer)?))
+    }
+}
+
+impl<'r, T, I> Serialize for IteratorArray<'r, T, I>
+    where T: Readable<'r> + Serialize,
+          I: Iterator<Item=T::Args> + ExactSizeIterator + Clone,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serialize_iter(serializer, self.len(), self.iter())
+    }
+}
+
+impl<'de, 'r, T, I> Deserialize<'de> for IteratorArray<'r, T, I>
+    where T: Readable<'r> + Deserialize<'de>,
+          I: Iterator<Item=T::Args> + ExactSizeIterator + Clone,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        Ok(IteratorArray::Owned(<Vec<T>>::deserialize(deserializer)?))
+    }
+}
+
+impl<'r, T> Serialize for Uncached<'r, T>
+    where T: Readable<'r> + Serialize,
+          T::Args: Clone,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        (*self.get()).serialize(serializer)
+    }
+}
+
+impl<'de, 'r, T> Deserialize<'de> for Uncached<'r, T>
+    where T: Readable<'r> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        Ok(Uncached::Owned(Box::new(T::deserialize(deserializer)?)))
+    }
+}
+
+impl<A> Serialize for DiffList<A>
+    where A: AsDiffListSourceCursor,
+          <A::Cursor as DiffListSourceCursor>::Item: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serialize_iter(serializer, self.len(), self.iter())
+    }
+}
+
+impl<'de, A> Deserialize<'de> for DiffList<A>
+    where A: AsDiffListSourceCursor,
+          <A::Cursor as DiffListSourceCursor>::Item: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        let elems = <Vec<<A::Cursor as DiffListSourceCursor>::Item>>::deserialize(deserializer)?;
+        Ok(elems.into_iter().collect())
+    }
+}
+
+impl<'r> Serialize for LazyUtf16beStr<'r>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serializer.collect_str(&self.chars().collect::<String>())
+    }
+}
+
+impl<'de, 'r> Deserialize<'de> for LazyUtf16beStr<'r>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        Ok(LazyUtf16beStr::Owned(String::deserialize(deserializer)?))
+    }
+}
+
+// `CStr` is an alias for a std type, so it can't be given its own `Serialize` impl. serde's
+// built-in impl writes the raw bytes; auto_struct fields of type `CStr` and `Option<CStr>` use
+// these modules instead so that they're written as strings whenever they're valid UTF-8.
+pub mod cstr
+{
+    use super::*;
+
+    pub fn serialize<S: Serializer>(s: &Cow<ffi::CStr>, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        match s.to_str() {
+            Ok(s) => serializer.serialize_str(s),
+            Err(_) => serializer.serialize_bytes(s.to_bytes()),
+        }
+    }
+
+    pub fn deserialize<'de, 'r, D: Deserializer<'de>>(deserializer: D)
+        -> Result<Cow<'r, ffi::CStr>, D::Error>
+    {
+        Ok(Cow::Owned(ffi::CString::deserialize(deserializer)?))
+    }
+}
+
+pub mod option_cstr
+{
+    use super::*;
+
+    struct Wrapper<'a, 'r>(&'a Cow<'r, ffi::CStr>);
+    impl<'a, 'r> Serialize for Wrapper<'a, 'r>
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            cstr::serialize(self.0, serializer)
+        }
+    }
+
+    pub fn serialize<S: Serializer>(s: &Option<Cow<ffi::CStr>>, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        s.as_ref().map(Wrapper).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, 'r, D: Deserializer<'de>>(deserializer: D)
+        -> Result<Option<Cow<'r, ffi::CStr>>, D::Error>
+    {
+        Ok(<Option<ffi::CString>>::deserialize(deserializer)?.map(Cow::Owned))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        CStrConversionExtension, FourCC, IteratorArray, LazyArray, Reader, Readable, RoArray,
+        Uncached, Writable,
+        utf16_string::LazyUtf16beStr,
+    };
+
+    fn round_trip<T>(value: &T) -> T
+        where T: Serialize + for<'de> Deserialize<'de>,
+    {
+        serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    fn to_bytes<T: Writable>(value: &T) -> Vec<u8>
+    {
+        let mut bytes = vec![];
+        value.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_ro_array()
+    {
+        let data = [1, 2, 3, 4, 5];
+        let array: RoArray<u8> = Reader::new(&data).read((5, ()));
+        let mut owned = round_trip(&array);
+        assert!(owned.data_start().is_none());
+        assert_eq!(to_bytes(&owned), data);
+        assert_eq!(owned.size(), data.len());
+        assert_eq!(owned.as_bytes(), data);
+        assert_eq!(owned.get(4), Some(5));
+
+        let right = owned.split_off(2);
+        assert_eq!(owned.iter().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(right.iter().collect::<Vec<_>>(), [3, 4, 5]);
+    }
+
+    #[test]
+    fn test_ro_array_with_args()
+    {
+        // The inner arrays' lengths only exist in their args
+        let data = [1, 2, 3, 4, 5, 6];
+        let array: RoArray<RoArray<u8>> = Reader::new(&data).read((2, (3, ())));
+        let owned = round_trip(&array);
+        let lens: Vec<_> = owned.iter().map(|inner| inner.len()).collect();
+        assert_eq!(lens, [3, 3]);
+        assert_eq!(to_bytes(&owned), data);
+    }
+
+    #[test]
+    fn test_lazy_array()
+    {
+        let data = [0, 1, 0, 2, 0, 3];
+        let array: LazyArray<u16> = Reader::new(&data).read((3, ()));
+        let owned = round_trip(&array);
+        assert!(matches!(owned, LazyArray::Owned(_)));
+        assert_eq!(to_bytes(&owned), data);
+    }
+
+    #[test]
+    fn test_iterator_array()
+    {
+        let data = [1, 2, 3, 4, 5, 6];
+        let args = vec![(1, ()), (2, ()), (3, ())].into_iter();
+        let array: IteratorArray<RoArray<u8>, _> = Reader::new(&data).read(args);
+        let owned = round_trip(&array);
+        assert!(matches!(owned, IteratorArray::Owned(_)));
+        let lens: Vec<_> = owned.iter().map(|inner| inner.len()).collect();
+        assert_eq!(lens, [1, 2, 3]);
+        assert_eq!(to_bytes(&owned), data);
+    }
+
+    #[test]
+    fn test_uncached()
+    {
+        let data = [0, 0, 1, 0];
+        let value: Uncached<u32> = Reader::new(&data).read(());
+        let owned = round_trip(&value);
+        assert_eq!(*owned.get(), 0x100);
+        assert_eq!(to_bytes(&owned), data);
+    }
+
+    #[test]
+    fn test_strings()
+    {
+        let fourcc = FourCC::from_bytes(b"SCLY");
+        assert_eq!(serde_json::to_string(&fourcc).unwrap(), "\"SCLY\"");
+        assert_eq!(round_trip(&fourcc), fourcc);
+        let fourcc = FourCC::new(0xFF000000);
+        assert_eq!(round_trip(&fourcc), fourcc);
+
+        let data = [0, b'h', 0, b'i', 0, 0];
+        let s: LazyUtf16beStr = Reader::new(&data).read(());
+        let owned = round_trip(&s);
+        assert!(matches!(owned, LazyUtf16beStr::Owned(_)));
+        assert_eq!(owned, s);
+
+        let s = b"name\0".as_cstr();
+        let mut json = vec![];
+        super::cstr::serialize(&s, &mut serde_json::Serializer::new(&mut json)).unwrap();
+        assert_eq!(json, b"\"name\"");
+        let mut deserializer = serde_json::Deserializer::from_slice(&json);
+        assert_eq!(super::cstr::deserialize(&mut deserializer).unwrap(), s);
+    }
+}
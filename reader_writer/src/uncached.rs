@@ -5,7 +5,7 @@ use std::{
 
 use crate::{
     lcow::LCow,
-    reader::{Reader, Readable},
+    reader::{Reader, Readable, ReadResult},
     writer::Writable,
 };
 
@@ -45,13 +45,13 @@ impl<'r, T> Readable<'r> for Uncached<'r, T>
           T::Args: Clone,
 {
     type Args = T::Args;
-    fn read_from(reader: &mut Reader<'r>, args: Self::Args) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, args: Self::Args) -> ReadResult<Self>
     {
         let start_reader = reader.clone();
-        let _ = <T as Readable>::read_from(reader, args.clone());
+        let _ = <T as Readable>::try_read_from(reader, args.clone())?;
         let size = start_reader.len() - reader.len();
 
-        Uncached::Borrowed(start_reader.truncated(size), args)
+        Ok(Uncached::Borrowed(start_reader.truncated(size), args))
     }
 
     fn size(&self) -> usize
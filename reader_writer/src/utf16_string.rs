@@ -7,7 +7,7 @@ use std::{
 };
 
 use crate::{
-    reader::{Readable, Reader},
+    reader::{Readable, Reader, ReadResult},
     writer::Writable,
 };
 
@@ -25,16 +25,16 @@ impl<'r> Utf16beStr<'r>
 impl<'r> Readable<'r> for Utf16beStr<'r>
 {
     type Args = ();
-    fn read_from(reader: &mut Reader<'r>, (): ()) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<Self>
     {
         let start_reader = reader.clone();
         loop {
-            if reader.read::<u16>(()) == 0 {
+            if reader.try_read::<u16>(())? == 0 {
                 break
             }
         }
         let read_len = start_reader.len() - reader.len();
-        Utf16beStr(start_reader.truncated(read_len))
+        Ok(Utf16beStr(start_reader.truncated(read_len)))
     }
 
     fn size(&self) -> usize
@@ -163,10 +163,10 @@ impl<'r, 'r2> cmp::PartialEq<LazyUtf16beStr<'r2>> for LazyUtf16beStr<'r>
 impl<'r> Readable<'r> for LazyUtf16beStr<'r>
 {
     type Args = ();
-    fn read_from(reader: &mut Reader<'r>, (): ()) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<Self>
     {
-        let s = reader.read(());
-        LazyUtf16beStr::Borrowed(s)
+        let s = reader.try_read(())?;
+        Ok(LazyUtf16beStr::Borrowed(s))
     }
 
     fn size(&self) -> usize
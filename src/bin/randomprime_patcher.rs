@@ -7,9 +7,13 @@ use clap::{
 };
 
 use randomprime::{
-    door_meta::Weights, extract_flaahgra_music_files, parse_layout, patches, reader_writer, structs
+    door_meta::Weights, elevators::ELEVATORS, extract_flaahgra_music_files, layout::Layout, logic,
+    parse_layout, patches, pickup_meta::PickupType, reader_writer, spoiler,
+    starting_items::StartingItems, structs, write_layout,
 };
 
+use rand::{rngs::StdRng, SeedableRng};
+
 use std::{
     collections::HashMap,
     fs::{File, OpenOptions},
@@ -81,6 +85,14 @@ impl structs::ProgressNotifier for ProgressNotifier
         }
         println!("Item randomized game. Skipping item randomizer configuration.");
     }
+
+    fn notify_warning(&mut self, msg: &str)
+    {
+        if self.quiet {
+            return;
+        }
+        println!("Warning: {}", msg);
+    }
 }
 
 fn default_as_false() -> bool {
@@ -113,10 +125,6 @@ fn default_empty_string() -> String {
     "".to_string()
 }
 
-fn default_u64_123456789() -> u64 {
-    123456789
-}
-
 #[derive(Deserialize)]
 struct PatchConfig {
     skip_frigate: bool,
@@ -162,14 +170,29 @@ struct PatchConfig {
 
     #[serde(default = "default_as_false")]
     quickplay: bool,
+
+    #[serde(default = "default_as_false")]
+    shuffle_door_destinations: bool,
+
+    #[serde(default = "default_as_false")]
+    shuffle_elevators: bool,
+
+    #[serde(default = "default_as_false")]
+    patch_hints: bool,
 }
 
 #[derive(Deserialize)]
 struct Config {
     input_iso: String,
     output_iso: String,
+
+    #[serde(default)]
     layout_string: String,
 
+    // Takes the place of the layout string when given
+    #[serde(default)]
+    layout: Option<Layout>,
+
     #[serde(default = "default_as_empty_str_vec")]
     elevator_layout_override: Vec<String>,
 
@@ -196,6 +219,18 @@ struct Config {
 
     #[serde(default = "default_as_empty_add_items_vec")]
     additional_items: Vec<patches::AdditionalItem>,
+
+    #[serde(default)]
+    pickup_overrides: Vec<patches::PickupLocationOverride>,
+
+    #[serde(default)]
+    progressive_items: Vec<patches::ProgressiveItem>,
+
+    #[serde(default)]
+    pickup_models: Vec<patches::PickupModel>,
+
+    #[serde(default)]
+    multiworld: bool,
     
     #[serde(default = "default_empty_string")]
     new_save_spawn_room: String,
@@ -207,16 +242,35 @@ struct Config {
     door_weights: Weights,
     patch_settings: PatchConfig,
     
-    #[serde(default = "default_u64_123456789")]
-    starting_pickups: u64,
+    #[serde(default)]
+    starting_pickups: Option<StartingItems>,
 
-    #[serde(default = "default_u64_123456789")]
-    new_save_starting_items: u64,
+    #[serde(default)]
+    new_save_starting_items: Option<StartingItems>,
 
-    #[serde(default = "default_u64_123456789")]
-    frigate_done_starting_items: u64,
+    #[serde(default)]
+    frigate_done_starting_items: Option<StartingItems>,
     
     excluded_doors: [HashMap<String,Vec<String>>;7],
+
+    #[serde(default)]
+    spoiler_path: Option<String>,
+
+    #[serde(default)]
+    spoiler_key: Option<String>,
+
+    // Place the pickups with the placement logic instead of taking them from the layout
+    #[serde(default)]
+    generate_layout: bool,
+
+    // Item names and how many of each to place when generating. Defaults to the standard 100.
+    #[serde(default)]
+    item_pool: Option<HashMap<String, u32>>,
+
+    // Refuse to patch if the layout can't be completed. Door colors and missile locks are only
+    // checked for the pickups in their own room.
+    #[serde(default)]
+    verify_layout: bool,
 }
 
 #[derive(Deserialize)]
@@ -266,7 +320,9 @@ fn get_config() -> Result<patches::ParsedConfig, String>
             .help("New save files will skip the \"Space Pirate Frigate\" tutorial level"))
         .arg(Arg::with_name("skip hudmenus")
             .long("non-modal-item-messages")
-            .help("Display a non-modal message when an item is is acquired"))
+            .help(concat!("Display a non-modal message when an item is is acquired, except in ",
+                          "Watery Hall, Research Core and the Artifact Temple, where a cutscene ",
+                          "follows the item")))
         .arg(Arg::with_name("nonvaria heat damage")
             .long("nonvaria-heat-damage")
             .help("If the Varia Suit has not been collect, heat damage applies"))
@@ -358,11 +414,33 @@ fn get_config() -> Result<patches::ParsedConfig, String>
         patches::IsoFormat::Iso
     };
 
-    let layout_string = String::from(&config.layout_string);
-    let (pickup_layout, elevator_layout, item_seed) = parse_layout(&layout_string)?;
-
     let seed = config.seed;
 
+    let new_save_starting_items = config.new_save_starting_items.as_ref()
+        .or(config.starting_pickups.as_ref())
+        .ok_or("Either new_save_starting_items or starting_pickups must be specified")?
+        .clone();
+
+    let frigate_done_starting_items = config.frigate_done_starting_items.as_ref()
+        .or(config.starting_pickups.as_ref())
+        .ok_or("Either frigate_done_starting_items or starting_pickups must be specified")?
+        .clone();
+
+    // Generated layouts are filled in once the rest of the config is known, since the logic
+    // needs it
+    let layout_string = if config.generate_layout {
+        None
+    } else {
+        Some(match config.layout {
+            Some(layout) => layout.to_layout_string()?,
+            None => String::from(&config.layout_string),
+        })
+    };
+    let vanilla_elevator_layout: Vec<u8> = ELEVATORS.iter()
+        .map(|elv| elv.default_dest)
+        .chain(Some(ELEVATORS.len() as u8)) // Landing Site
+        .collect();
+
     let artifact_hints = String::from(&config.patch_settings.artifact_hints);
     let artifact_hint_behavior = if artifact_hints == "default" {
         patches::ArtifactHintBehavior::Default
@@ -387,6 +465,11 @@ fn get_config() -> Result<patches::ParsedConfig, String>
     let mut comment_message:String = "Generated with ".to_owned();
     comment_message.push_str(mpdr_version);
 
+    let spoiler_key = match &config.spoiler_key {
+        Some(key) => Some(spoiler::parse_spoiler_key(key)?),
+        None => None,
+    };
+
     let mut banner = Some(ConfigBanner {
         game_name: Some(String::from("Metroid Prime")),
         developer: Some(String::from("^_^")),
@@ -396,38 +479,12 @@ fn get_config() -> Result<patches::ParsedConfig, String>
         description: Some(String::from("Metroid Prime, but probably a cursed seed")),
     });
 
-    let new_save_starting_items = {
-        if config.new_save_starting_items != 123456789 {
-            config.new_save_starting_items
-        }
-        else if config.starting_pickups != 123456789 {
-            config.starting_pickups
-        }
-        else {
-            assert!(false);
-            0
-        }
-    };
-    
-    let frigate_done_starting_items = {
-        if config.frigate_done_starting_items != 123456789 {
-            config.frigate_done_starting_items
-        }
-        else if config.starting_pickups != 123456789 {
-            config.starting_pickups
-        }
-        else {
-            assert!(false);
-            0
-        }
-    };
-
-    Ok(patches::ParsedConfig {
+    let mut parsed_config = patches::ParsedConfig {
         input_iso:input_iso_mmap,
         output_iso:out_iso,
         is_item_randomized: None,
-        pickup_layout, elevator_layout, seed,
-        item_seed,door_weights:config.door_weights,
+        pickup_layout: vec![], elevator_layout: vanilla_elevator_layout.clone(), seed,
+        item_seed: 0,door_weights:config.door_weights,
         excluded_doors:config.excluded_doors,
         patch_map:config.patch_settings.patch_map,
         patch_power_conduits: config.patch_settings.patch_power_conduits,
@@ -444,8 +501,12 @@ fn get_config() -> Result<patches::ParsedConfig, String>
         liquid_volumes: config.liquid_volumes,
         aether_transforms: config.aether_transforms,
         additional_items: config.additional_items,
+        pickup_overrides: config.pickup_overrides,
+        progressive_items: config.progressive_items,
+        pickup_models: config.pickup_models,
+        multiworld: config.multiworld,
         
-        layout_string,
+        layout_string: String::new(),
         elevator_layout_override: config.elevator_layout_override,
         missile_lock_override: config.missile_lock_override,
         new_save_spawn_room: config.new_save_spawn_room,
@@ -466,6 +527,9 @@ fn get_config() -> Result<patches::ParsedConfig, String>
         enable_vault_ledge_door: config.patch_settings.enable_one_way_doors,
         artifact_hint_behavior,
         patch_vertical_to_blue: config.patch_settings.patch_vertical_to_blue,
+        shuffle_door_destinations: config.patch_settings.shuffle_door_destinations,
+        shuffle_elevators: config.patch_settings.shuffle_elevators,
+        patch_hints: config.patch_settings.patch_hints,
         tiny_elvetator_samus: config.patch_settings.tiny_elvetator_samus,
 
         flaahgra_music_files,
@@ -476,6 +540,9 @@ fn get_config() -> Result<patches::ParsedConfig, String>
         comment: comment_message,
         main_menu_message: String::from(mpdr_version),
 
+        spoiler_path: config.spoiler_path,
+        spoiler_key,
+
         quickplay: config.patch_settings.quickplay,
 
         bnr_game_name: banner.as_mut().and_then(|b| b.game_name.take()),
@@ -486,8 +553,44 @@ fn get_config() -> Result<patches::ParsedConfig, String>
         bnr_description: banner.as_mut().and_then(|b| b.description.take()),
 
         pal_override: false,
-    })
+    };
+
+    let explicit_door_types = logic::explicit_door_types(&parsed_config.excluded_doors)?;
+    let layout_string = match layout_string {
+        Some(layout_string) => layout_string,
+        None => {
+            let logic_options = patches::door_logic_options(&parsed_config, &explicit_door_types)
+                .map_err(|e| format!("The layout can't be generated: {}", e))?;
+            let item_pool = match &config.item_pool {
+                Some(item_pool) => logic::parse_item_pool(item_pool)?,
+                None => logic::default_item_pool(),
+            };
+            let mut rng = StdRng::seed_from_u64(seed);
+            let pickups = logic::generate_layout(&mut rng, &item_pool, &logic_options)?;
+            // Only the long form of the layout string can hold the Scan Visor and the pickups
+            // after it
+            let has_scan_visor = pickups.iter().any(|pt| pt.idx() >= PickupType::ScanVisor.idx());
+            let pickup_layout: Vec<u8> = pickups.iter().map(|pt| pt.idx() as u8).collect();
+            write_layout(&pickup_layout, &vanilla_elevator_layout, has_scan_visor)?
+        },
+    };
+    let (pickup_layout, elevator_layout, item_seed) = parse_layout(&layout_string)?;
+    parsed_config.pickup_layout = pickup_layout;
+    parsed_config.elevator_layout = elevator_layout;
+    parsed_config.item_seed = item_seed;
+    parsed_config.layout_string = layout_string;
+
+    if config.verify_layout {
+        let logic_options = patches::door_logic_options(&parsed_config, &explicit_door_types)
+            .map_err(|e| format!("The layout can't be verified: {}", e))?;
+        let pickups: Vec<PickupType> = parsed_config.pickup_layout.iter()
+            .map(|&idx| PickupType::from_idx(idx as usize)
+                .ok_or_else(|| format!("Unknown pickup {} in the layout", idx)))
+            .collect::<Result<_, String>>()?;
+        logic::verify_layout(&pickups, &logic_options)?;
+    }
 
+    Ok(parsed_config)
 }
 
 
@@ -0,0 +1,40 @@
+//! This program prints the encrypted spoiler stored in an ISO patched with a spoiler key.
+//! The location of the ISO and the key (as 64 hex digits) should be provided as command line
+//! arguments. Only uncompressed ISOs are supported.
+
+use randomprime::{memmap, reader_writer::Reader, spoiler, structs, GcDiscLookupExtensions};
+
+use std::{
+    env::args,
+    fs::File,
+    process,
+};
+
+fn main_inner() -> Result<(), String>
+{
+    let iso_path = args().nth(1).ok_or("Usage: randomprime_spoiler <iso> <key>")?;
+    let key = args().nth(2).ok_or("Usage: randomprime_spoiler <iso> <key>")?;
+    let key = spoiler::parse_spoiler_key(&key)?;
+
+    let file = File::open(&iso_path)
+        .map_err(|e| format!("Failed to open {}: {}", iso_path, e))?;
+    let mmap = unsafe { memmap::Mmap::map(&file) }
+        .map_err(|e| format!("Failed to open {}: {}", iso_path, e))?;
+    let gc_disc: structs::GcDisc = Reader::new(&mmap[..]).try_read(())
+        .map_err(|e| format!("Failed to parse the ISO: {}", e))?;
+
+    let encrypted = match gc_disc.find_file(spoiler::SPOILER_FILE_NAME).and_then(|f| f.file()) {
+        Some(structs::FstEntryFile::Unknown(reader)) => &reader[..],
+        _ => Err("The ISO doesn't contain a spoiler")?,
+    };
+    print!("{}", spoiler::decrypt_spoiler(encrypted, &key)?);
+    Ok(())
+}
+
+fn main()
+{
+    if let Err(e) = main_inner() {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
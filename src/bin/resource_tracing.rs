@@ -5,7 +5,7 @@ pub use randomprime::*;
 use randomprime::pickup_meta::{PickupType, ScriptObjectLocation};
 
 use reader_writer::{FourCC, Reader, Writable};
-use structs::{Ancs, Cmdl, Evnt, Pickup, Scan, Resource};
+use structs::{Ancs, Cmdl, Evnt, Part, Pickup, Scan, Resource};
 use generated::{resource_info, ResourceInfo};
 
 use std::{
@@ -117,32 +117,9 @@ impl<'r> ResourceDb<'r>
                 }
             } else if key.fourcc == b"PART".into() {
                 let buf = data.decompress();
-                let buf: &[u8] = &buf;
-                // We're cheating here. We're going to find the sub-string ICTSCNST
-                // and then using the next word as the id of a PART.
-                for i in 0..(buf.len() - 8) {
-                    if &buf[i..(i + 8)] == b"ICTSCNST" {
-                        let id : u32 = Reader::new(&buf[(i + 8)..(i+12)]).read(());
-                        if id != 0 {
-                            extend_deps(id, b"PART");
-                        }
-                        // TODO: IITS and IDTS too?
-                    } else if &buf[i..(i + 4)] == b"TEXR" {
-                        if &buf[(i + 4)..(i + 8)] == b"ATEX" {
-                            let id : u32 = Reader::new(&buf[(i + 12)..(i + 16)]).read(());
-                            if id != 0 {
-                                extend_deps(id, b"TXTR");
-                            }
-                        }
-                    } else if &buf[i..(i + 4)] == b"KSSM" && &buf[(i + 4)..(i + 8)] != b"NONE" {
-
-                        let kssm : structs::Kssm = Reader::new(&buf[(i + 8)..]).read(());
-                        for list in kssm.lists.iter() {
-                            for item in list.items.iter() {
-                                extend_deps(item.part, b"PART");
-                            }
-                        }
-                    }
+                let part: Part = Reader::new(&buf).read(());
+                for (id, fourcc) in part.dependencies() {
+                    extend_deps(id, fourcc.as_bytes());
                 }
             } else if key.fourcc == b"CMDL".into() {
                 let buf = data.decompress();
@@ -3,7 +3,8 @@ use serde::{Serialize, Deserialize};
 
 use crate::{
     patches,
-    door_meta::{Weights}
+    door_meta::{Weights},
+    starting_items::StartingItems,
 };
 
 use std::{
@@ -45,6 +46,8 @@ struct PatchConfig {
     remove_frigidite_lock:  bool,
     remove_mine_security_station_locks: bool,
     lower_mines_backwards: bool,
+    #[serde(default)]
+    patch_hints: bool,
 }
 
 #[derive(Deserialize)]
@@ -60,8 +63,8 @@ struct Config {
     seed: u64,
     door_weights: Weights,
     patch_settings: PatchConfig,
-    new_save_starting_items: u64,
-    frigate_done_starting_items: u64,
+    new_save_starting_items: StartingItems,
+    frigate_done_starting_items: StartingItems,
     excluded_doors: [HashMap<String,Vec<String>>;7],
 }
 
@@ -225,6 +228,11 @@ impl structs::ProgressNotifier for ProgressNotifier
             CbMessage::warning_json("Item randomized game. Skipping item randomizer configuration").as_ptr(),
         );
     }
+
+    fn notify_warning(&mut self, msg: &str)
+    {
+        (self.cb)(self.cb_data, CbMessage::warning_json(msg).as_ptr());
+    }
 }
 
 fn inner(config_json: *const c_char, cb_data: *const (), cb: extern fn(*const (), *const c_char))
@@ -309,6 +317,9 @@ fn inner(config_json: *const c_char, cb_data: *const (), cb: extern fn(*const ()
         auto_enabled_elevators: false,
         quiet: false,
         patch_vertical_to_blue: false,
+        shuffle_door_destinations: false,
+        shuffle_elevators: false,
+        patch_hints: config.patch_settings.patch_hints,
         tiny_elvetator_samus: config.patch_settings.tiny_elvetator_samus,
 
         skip_impact_crater: config.patch_settings.skip_crater,
@@ -323,6 +334,9 @@ fn inner(config_json: *const c_char, cb_data: *const (), cb: extern fn(*const ()
         comment: comment_message,
         main_menu_message: String::from(mpdr_version),
 
+        spoiler_path: None,
+        spoiler_key: None,
+
         quickplay: false,
 
         bnr_game_name: banner.as_mut().and_then(|b| b.game_name.take()),
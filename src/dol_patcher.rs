@@ -139,7 +139,7 @@ impl<'a> DolSegment<'a>
     fn new(seg: &structs::DolSegment<'a>) -> DolSegment<'a>
     {
         let bytes = match &seg.contents {
-            LazyArray::Borrowed(array) => &array.data_start()[..],
+            LazyArray::Borrowed(array) => *array.data_start().unwrap(),
             _ => unreachable!(),
         };
         if seg.load_addr == 0 {
@@ -303,6 +303,22 @@ impl<'a> DolPatcher<'a>
     {
         self.patch(asm.addr(), asm.encoded_bytes().into())
     }
+
+    // Reads a word of the original, unpatched DOL at `addr`
+    pub fn read_u32(&self, addr: u32) -> Result<u32, String>
+    {
+        for seg in self.text_segments.iter().chain(&self.data_segments) {
+            if let DolSegment::PatchedSegment(seg_addr, patcher) = seg {
+                if addr >= *seg_addr && addr + 4 <= *seg_addr + patcher.len() as u32 {
+                    let start = (addr - seg_addr) as usize;
+                    let mut bytes = [0; 4];
+                    bytes.copy_from_slice(&patcher.data[start..start + 4]);
+                    return Ok(u32::from_be_bytes(bytes))
+                }
+            }
+        }
+        Err(format!("Failed to find segment to read at {:x}", addr))
+    }
 }
 
 
@@ -15,6 +15,26 @@ pub struct DoorLocation {
     pub dock_number: Option<u32>,
 }
 
+// Doors in the floor or ceiling, which need the vertical variants of each door type
+pub fn is_vertical_door(room_id: u32, door_index: usize) -> bool
+{
+    (room_id == 0x11BD63B7 && door_index == 0) || // Tower Chamber
+    (room_id == 0x0D72F1F7 && door_index == 1) || // Tower of Light
+    (room_id == 0xFB54A0CB && door_index == 4) || // Hall of the Elders
+    (room_id == 0xE1981EFC && door_index == 0) || // Elder Chamber
+    (room_id == 0x43E4CC25 && door_index == 1) || // Research Lab Hydra
+    (room_id == 0x37BBB33C && door_index == 1) || // Observatory Access
+    (room_id == 0xD8E905DD && door_index == 1) || // Research Core Access
+    (room_id == 0x21B4BFF6 && door_index == 1) || // Research Lab Aether
+    (room_id == 0x3F375ECC && door_index == 2) || // Omega Research
+    (room_id == 0xF517A1EA && door_index == 1) || // Dynamo Access (Careful of Chozo room w/ same name)
+    (room_id == 0x8A97BB54 && door_index == 1) || // Elite Research
+    (room_id == 0xA20201D4                   ) || // Security Access B (both doors)
+    (room_id == 0x956F1552 && door_index == 1) || // Mine Security Station
+    (room_id == 0xC50AF17A && door_index == 2) || // Elite Control
+    (room_id == 0x90709AAC && door_index == 1)    // Ventilation Shaft
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 enum TypeVulnerability {
     Normal = 0x1,
@@ -77,6 +97,7 @@ pub struct Weights {
     pub phazon_mines: [u8;4]
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum World {
     FrigateOrpheon,
     TallonOverworld,
@@ -0,0 +1,400 @@
+use rand::{rngs::StdRng, seq::SliceRandom};
+use reader_writer::{generic_array::GenericArray, typenum::U3};
+
+use crate::{
+    elevators::ELEVATORS,
+    structs::{Area, DockId, MlvlGraph},
+};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// How many layouts to roll before giving up on finding one that keeps everything reachable
+const MAX_SHUFFLE_ATTEMPTS: usize = 1000;
+
+fn reachable(adjacency: &HashMap<usize, Vec<usize>>, start: usize) -> HashSet<usize>
+{
+    let mut seen: HashSet<usize> = [start].iter().cloned().collect();
+    let mut queue: VecDeque<usize> = [start].iter().cloned().collect();
+    while let Some(node) = queue.pop_front() {
+        for &next in adjacency.get(&node).into_iter().flatten() {
+            if seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    seen
+}
+
+fn reachable_from(adjacency: &HashMap<usize, Vec<usize>>, starts: &[usize]) -> HashSet<usize>
+{
+    starts.iter().flat_map(|&start| reachable(adjacency, start)).collect()
+}
+
+// How far apart, in world units, the two sides of a rewired door may end up after aligning
+const DOCK_ALIGN_TOLERANCE: f32 = 0.1;
+
+// Whether the only way between the two sides of `dock`'s connection is through it. The start
+// areas count as a single room, since none of them move. Shuffling only these keeps the rooms
+// joined by rewired doors a tree, which `align_areas` can always line up: rewiring a door that's
+// part of a loop would leave a door closing the loop between two rooms that were each placed
+// from somewhere else.
+fn is_bridge(graph: &MlvlGraph, dock: DockId, start_areas: &[usize]) -> bool
+{
+    let node = |area: usize| if start_areas.contains(&area) { start_areas[0] } else { area };
+    let other = match graph.connections(dock).first() {
+        Some(&other) => other,
+        None => return false,
+    };
+    if node(dock.area) == node(other.area) {
+        return false;
+    }
+
+    let mut adjacency: HashMap<usize, Vec<usize>> = HashMap::new();
+    for area in 0..graph.area_count() {
+        for (from, to) in graph.exits(area) {
+            if from != dock && from != other {
+                adjacency.entry(node(from.area)).or_default().push(node(to.area));
+            }
+        }
+    }
+    !reachable(&adjacency, node(dock.area)).contains(&node(other.area))
+}
+
+// Randomly re-pair the given docks with each other, rewiring which room each of their doors
+// leads to. Only docks that are currently connected to another dock in the set, by a connection
+// that `is_bridge`, take part, so every dock still has exactly one connection afterwards and both
+// sides of each connection are rewired together. Layouts are rerolled until every area that was
+// reachable from one of `start_areas` (where the player can enter the world) before the shuffle
+// is still reachable. Returns the new connections.
+pub fn shuffle_docks(
+    graph: &mut MlvlGraph,
+    rng: &mut StdRng,
+    docks: &[DockId],
+    start_areas: &[usize],
+) -> Result<Vec<(DockId, DockId)>, String>
+{
+    let candidates: HashSet<DockId> = docks.iter().cloned().collect();
+    let mut ends: Vec<DockId> = candidates.iter()
+        .cloned()
+        .filter(|&dock| {
+            let conns = graph.connections(dock);
+            conns.len() == 1 && conns[0].area != dock.area && candidates.contains(&conns[0]) &&
+                is_bridge(graph, dock, start_areas)
+        })
+        .collect();
+    // Sort so that the result only depends on the seed
+    ends.sort();
+    if ends.len() % 2 == 1 {
+        Err(format!("{} docks can't be paired up with each other", ends.len()))?
+    }
+
+    let shuffled: HashSet<DockId> = ends.iter().cloned().collect();
+    let mut fixed_adjacency: HashMap<usize, Vec<usize>> = HashMap::new();
+    for area in 0..graph.area_count() {
+        for (from, to) in graph.exits(area) {
+            if !shuffled.contains(&from) {
+                fixed_adjacency.entry(from.area).or_default().push(to.area);
+            }
+        }
+    }
+    let must_reach: HashSet<usize> = start_areas.iter()
+        .flat_map(|&area| graph.reachable_areas(area))
+        .collect();
+
+    for _ in 0..MAX_SHUFFLE_ATTEMPTS {
+        ends.shuffle(rng);
+        let pairs: Vec<(DockId, DockId)> = ends.chunks(2).map(|c| (c[0], c[1])).collect();
+        if pairs.iter().any(|(a, b)| a.area == b.area) {
+            continue;
+        }
+
+        let mut adjacency = fixed_adjacency.clone();
+        for &(a, b) in &pairs {
+            adjacency.entry(a.area).or_default().push(b.area);
+            adjacency.entry(b.area).or_default().push(a.area);
+        }
+        if !must_reach.is_subset(&reachable_from(&adjacency, start_areas)) {
+            continue;
+        }
+
+        for &(a, b) in &pairs {
+            graph.connect(a, b)?;
+        }
+        return Ok(pairs);
+    }
+    Err(format!("Failed to find a door layout that keeps every room reachable after {} attempts",
+                MAX_SHUFFLE_ATTEMPTS))
+}
+
+// A rotation about the vertical axis followed by a translation. Used to move a room so that a
+// door it has been connected to lines up with the door on the other side.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AreaMove
+{
+    pub angle: f32,
+    pub pivot: [f32; 3],
+    pub offset: [f32; 3],
+}
+
+impl AreaMove
+{
+    pub fn identity() -> AreaMove
+    {
+        AreaMove { angle: 0.0, pivot: [0.0; 3], offset: [0.0; 3] }
+    }
+
+    pub fn is_identity(&self) -> bool
+    {
+        self.angle == 0.0 && self.pivot == self.offset
+    }
+
+    pub fn apply_to_direction(&self, v: [f32; 3]) -> [f32; 3]
+    {
+        let (sin, cos) = self.angle.sin_cos();
+        [cos * v[0] - sin * v[1], sin * v[0] + cos * v[1], v[2]]
+    }
+
+    pub fn apply_to_point(&self, p: [f32; 3]) -> [f32; 3]
+    {
+        let v = self.apply_to_direction([
+            p[0] - self.pivot[0],
+            p[1] - self.pivot[1],
+            p[2] - self.pivot[2],
+        ]);
+        [v[0] + self.offset[0], v[1] + self.offset[1], v[2] + self.offset[2]]
+    }
+
+    // A row-major 3x4 transform, as stored in MLVL and MREA
+    pub fn apply_to_transform(&self, m: &mut [f32])
+    {
+        for col in 0..3 {
+            let v = self.apply_to_direction([m[col], m[4 + col], m[8 + col]]);
+            m[col] = v[0];
+            m[4 + col] = v[1];
+            m[8 + col] = v[2];
+        }
+        let t = self.apply_to_point([m[3], m[7], m[11]]);
+        m[3] = t[0];
+        m[7] = t[1];
+        m[11] = t[2];
+    }
+
+    // A bounding box stored as its minimum corner followed by its maximum corner
+    pub fn apply_to_aabb(&self, aabb: &mut [f32])
+    {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for corner in 0..8 {
+            let p = self.apply_to_point([
+                aabb[if corner & 1 == 0 { 0 } else { 3 }],
+                aabb[if corner & 2 == 0 { 1 } else { 4 }],
+                aabb[if corner & 4 == 0 { 2 } else { 5 }],
+            ]);
+            for i in 0..3 {
+                min[i] = min[i].min(p[i]);
+                max[i] = max[i].max(p[i]);
+            }
+        }
+        aabb[..3].copy_from_slice(&min);
+        aabb[3..6].copy_from_slice(&max);
+    }
+
+    // Move an area's transform, bounding box and dock coordinates in the MLVL. The map is drawn
+    // with the area's transform, so the area's MAPA follows it without being edited.
+    pub fn apply_to_area(&self, area: &mut Area)
+    {
+        self.apply_to_transform(&mut area.area_transform[..]);
+        self.apply_to_aabb(&mut area.area_bounding_box[..]);
+        for dock in area.docks.as_mut_vec().iter_mut() {
+            let coords: Vec<GenericArray<f32, U3>> = dock.dock_coordinates.iter()
+                .map(|c| self.apply_to_point([c[0], c[1], c[2]]).into())
+                .collect();
+            dock.dock_coordinates = coords.into();
+        }
+    }
+}
+
+// The centre of a dock after moving its area and the horizontal direction it faces, away from
+// the middle of the area. None if its coordinates don't describe a doorway.
+fn dock_frame(area: &Area, dock: usize, mv: &AreaMove) -> Option<([f32; 3], [f32; 2])>
+{
+    let coords: Vec<[f32; 3]> = area.docks.get(dock)?.dock_coordinates.iter()
+        .map(|c| mv.apply_to_point([c[0], c[1], c[2]]))
+        .collect();
+    if coords.is_empty() {
+        return None;
+    }
+
+    let n = coords.len() as f32;
+    let center = [
+        coords.iter().map(|c| c[0]).sum::<f32>() / n,
+        coords.iter().map(|c| c[1]).sum::<f32>() / n,
+        coords.iter().map(|c| c[2]).sum::<f32>() / n,
+    ];
+
+    // The doorway's widest horizontal extent runs along the wall it's in
+    let mut edge = [0.0f32, 0.0];
+    for a in &coords {
+        for b in &coords {
+            let e = [b[0] - a[0], b[1] - a[1]];
+            if e[0].hypot(e[1]) > edge[0].hypot(edge[1]) {
+                edge = e;
+            }
+        }
+    }
+    let len = edge[0].hypot(edge[1]);
+    if len < 1e-3 {
+        return None;
+    }
+    let mut normal = [-edge[1] / len, edge[0] / len];
+
+    let aabb = &area.area_bounding_box;
+    let area_center = mv.apply_to_point([
+        (aabb[0] + aabb[3]) / 2.0,
+        (aabb[1] + aabb[4]) / 2.0,
+        (aabb[2] + aabb[5]) / 2.0,
+    ]);
+    if normal[0] * (center[0] - area_center[0]) + normal[1] * (center[1] - area_center[1]) < 0.0 {
+        normal = [-normal[0], -normal[1]];
+    }
+    Some((center, normal))
+}
+
+// Work out where rooms have to be moved so that the doors rewired by `links` line up. Rooms are
+// placed by walking outwards from `start_areas`, which stay where they are: a room entered
+// through a rewired door is turned and moved so that the door meets the one it was entered
+// from, and a room entered through an untouched door moves along with the room it was entered
+// from. Each room is only moved once, so this relies on the rewired doors joining the rooms in
+// a tree, as `shuffle_docks` leaves them; a rewired door whose sides still don't meet afterwards
+// is an error. Returns the rooms that move.
+pub fn align_areas(graph: &MlvlGraph, links: &[(DockId, DockId)], start_areas: &[usize])
+    -> Result<Vec<(usize, AreaMove)>, String>
+{
+    let rewired: HashSet<(DockId, DockId)> = links.iter()
+        .flat_map(|&(a, b)| vec![(a, b), (b, a)])
+        .collect();
+
+    let mut moves: HashMap<usize, AreaMove> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for &area in start_areas {
+        if moves.insert(area, AreaMove::identity()).is_none() {
+            queue.push_back(area);
+        }
+    }
+    while let Some(area) = queue.pop_front() {
+        let area_move = moves[&area];
+        for (from, to) in graph.exits(area) {
+            if moves.contains_key(&to.area) {
+                continue;
+            }
+            let mut to_move = area_move;
+            if rewired.contains(&(from, to)) {
+                let from_frame = dock_frame(&graph.areas()[from.area], from.dock, &area_move);
+                let to_frame = dock_frame(&graph.areas()[to.area], to.dock, &AreaMove::identity());
+                if let (Some((from_center, from_normal)), Some((to_center, to_normal))) =
+                    (from_frame, to_frame)
+                {
+                    // Turn the new room so that its door faces back into the old one
+                    let mut angle = (-from_normal[1]).atan2(-from_normal[0]) -
+                        to_normal[1].atan2(to_normal[0]);
+                    if angle <= -std::f32::consts::PI {
+                        angle += 2.0 * std::f32::consts::PI;
+                    } else if angle > std::f32::consts::PI {
+                        angle -= 2.0 * std::f32::consts::PI;
+                    }
+                    to_move = AreaMove { angle, pivot: to_center, offset: from_center };
+                }
+            }
+            moves.insert(to.area, to_move);
+            queue.push_back(to.area);
+        }
+    }
+
+    for &(a, b) in links {
+        let frame = |dock: DockId| {
+            let area_move = moves.get(&dock.area).cloned().unwrap_or_else(AreaMove::identity);
+            dock_frame(&graph.areas()[dock.area], dock.dock, &area_move)
+                .ok_or_else(|| format!("Dock {} of area {} has no doorway to line up",
+                                       dock.dock, dock.area))
+        };
+        let ((a_center, a_normal), (b_center, b_normal)) = (frame(a)?, frame(b)?);
+        let distance = (0..3).map(|i| (a_center[i] - b_center[i]).powi(2)).sum::<f32>().sqrt();
+        let facing = a_normal[0] * b_normal[0] + a_normal[1] * b_normal[1];
+        if distance > DOCK_ALIGN_TOLERANCE || facing > -0.99 {
+            Err(format!("Dock {} of area {} and dock {} of area {} don't line up",
+                        a.dock, a.area, b.dock, b.area))?
+        }
+    }
+
+    let mut moves: Vec<(usize, AreaMove)> = moves.into_iter()
+        .filter(|(_, mv)| !mv.is_identity())
+        .collect();
+    moves.sort_by_key(|&(area, _)| area);
+    Ok(moves)
+}
+
+// Randomly pair up the elevators so that each one leads to a different world and riding an
+// elevator back always returns to where it was taken from. `base_layout` is the configured
+// elevator layout (the index of each elevator's destination in `ELEVATORS`): elevators it
+// already sends somewhere other than their vanilla destination are kept as they are, as are
+// the Artifact Temple and Impact Crater elevators. Layouts are rerolled until every world can
+// be reached. Returns the new elevator layout.
+pub fn shuffle_elevators(rng: &mut StdRng, base_layout: &[u8]) -> Result<Vec<u8>, String>
+{
+    if base_layout.len() < ELEVATORS.len() {
+        Err(format!("The elevator layout has {} entries, expected {}",
+                    base_layout.len(), ELEVATORS.len()))?
+    }
+    let mut layout: Vec<u8> = base_layout[..ELEVATORS.len()].to_vec();
+    let is_fixed = |i: usize| {
+        layout[i] != ELEVATORS[i].default_dest ||
+            ELEVATORS[i].pak_name == "Metroid7.pak" ||
+            ELEVATORS[ELEVATORS[i].default_dest as usize].pak_name == "Metroid7.pak"
+    };
+    let mut ends: Vec<usize> = (0..ELEVATORS.len()).filter(|&i| !is_fixed(i)).collect();
+    if ends.len() % 2 == 1 {
+        Err(format!("{} elevators can't be paired up with each other", ends.len()))?
+    }
+    if ends.is_empty() {
+        return Ok(layout);
+    }
+
+    // Worlds are identified by the index of the first elevator in them
+    let world = |i: usize| ELEVATORS.iter()
+        .position(|elv| elv.pak_name == ELEVATORS[i].pak_name)
+        .unwrap();
+
+    // Elevators that keep their configured destination still connect worlds
+    let mut fixed_adjacency: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, &dest) in layout.iter().enumerate() {
+        if !ends.contains(&i) && (dest as usize) < ELEVATORS.len() {
+            fixed_adjacency.entry(world(i)).or_default().push(world(dest as usize));
+        }
+    }
+
+    for _ in 0..MAX_SHUFFLE_ATTEMPTS {
+        ends.shuffle(rng);
+        let pairs: Vec<(usize, usize)> = ends.chunks(2).map(|c| (c[0], c[1])).collect();
+        if pairs.iter().any(|&(a, b)| world(a) == world(b)) {
+            continue;
+        }
+
+        let mut adjacency = fixed_adjacency.clone();
+        for &(a, b) in &pairs {
+            adjacency.entry(world(a)).or_default().push(world(b));
+            adjacency.entry(world(b)).or_default().push(world(a));
+        }
+        let worlds: HashSet<usize> = ends.iter().map(|&i| world(i)).collect();
+        if !worlds.is_subset(&reachable(&adjacency, world(ends[0]))) {
+            continue;
+        }
+
+        for (a, b) in pairs {
+            layout[a] = b as u8;
+            layout[b] = a as u8;
+        }
+        return Ok(layout);
+    }
+    Err(format!("Failed to find an elevator layout that connects every world after {} attempts",
+                MAX_SHUFFLE_ATTEMPTS))
+}
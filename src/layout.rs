@@ -0,0 +1,154 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    door_meta::World,
+    elevators::ELEVATORS,
+    pickup_meta::{self, PickupType},
+    parse_layout,
+    write_layout,
+};
+
+use std::collections::BTreeMap;
+
+const LANDING_SITE: &str = "Landing Site";
+
+// A human-readable alternative to the layout string. Pickups are keyed by world and room name
+// (as they appear in `PICKUP_LOCATIONS`), with one entry per pickup location in the room.
+// Elevators are keyed by name and map to the name of the elevator they lead to. The starting
+// room is either the name of an elevator or "Landing Site". `scan_visor` records whether the
+// layout string uses the '!' form, which is required when the Scan Visor or any later pickup is
+// placed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Layout
+{
+    pub pickups: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+    pub elevators: BTreeMap<String, String>,
+    pub starting_room: String,
+    #[serde(default)]
+    pub scan_visor: bool,
+}
+
+fn elevator_name(idx: usize) -> String
+{
+    ELEVATORS[idx].name.replace('\0', " ")
+}
+
+fn elevator_idx(name: &str) -> Result<u8, String>
+{
+    (0..ELEVATORS.len())
+        .find(|&i| elevator_name(i).to_lowercase() == name.to_lowercase())
+        .map(|i| i as u8)
+        .ok_or_else(|| format!("Unknown elevator '{}'", name))
+}
+
+fn world_name(pak_name: &str) -> String
+{
+    World::from_pak(pak_name).unwrap().as_string()
+}
+
+impl Layout
+{
+    pub fn from_indices(pickup_layout: &[u8], elevator_layout: &[u8]) -> Result<Layout, String>
+    {
+        let mut pickup_iter = pickup_layout.iter();
+        let mut pickups = BTreeMap::new();
+        for (pak_name, rooms) in pickup_meta::PICKUP_LOCATIONS.iter() {
+            for room_info in rooms.iter().filter(|room_info| !room_info.pickup_locations.is_empty()) {
+                let mut room_pickups = vec![];
+                for _ in room_info.pickup_locations.iter() {
+                    let idx = *pickup_iter.next()
+                        .ok_or_else(|| "Pickup layout has too few entries".to_string())?;
+                    let pickup_type = PickupType::from_idx(idx as usize)
+                        .ok_or_else(|| format!("Unknown pickup index {}", idx))?;
+                    room_pickups.push(pickup_type.name().to_string());
+                }
+                pickups.entry(world_name(pak_name))
+                    .or_insert_with(BTreeMap::new)
+                    .insert(room_info.name.to_string(), room_pickups);
+            }
+        }
+        if pickup_iter.next().is_some() {
+            Err("Pickup layout has too many entries".to_string())?
+        }
+
+        if elevator_layout.len() != ELEVATORS.len() + 1 {
+            Err(format!("Elevator layout should have exactly {} entries", ELEVATORS.len() + 1))?
+        }
+        let mut elevators = BTreeMap::new();
+        for (i, &dest) in elevator_layout[..ELEVATORS.len()].iter().enumerate() {
+            if dest as usize >= ELEVATORS.len() {
+                Err(format!("Unknown elevator index {}", dest))?
+            }
+            elevators.insert(elevator_name(i), elevator_name(dest as usize));
+        }
+        let starting_room = match elevator_layout[ELEVATORS.len()] as usize {
+            i if i == ELEVATORS.len() => LANDING_SITE.to_string(),
+            i if i < ELEVATORS.len() => elevator_name(i),
+            i => Err(format!("Unknown starting room index {}", i))?,
+        };
+
+        let scan_visor = pickup_layout.iter().any(|&i| i >= 36);
+        Ok(Layout { pickups, elevators, starting_room, scan_visor })
+    }
+
+    // The pickup and elevator indices, in the same form `parse_layout` returns them
+    pub fn to_indices(&self) -> Result<(Vec<u8>, Vec<u8>), String>
+    {
+        let mut pickup_layout = vec![];
+        let mut room_count = 0;
+        for (pak_name, rooms) in pickup_meta::PICKUP_LOCATIONS.iter() {
+            let world = world_name(pak_name);
+            for room_info in rooms.iter().filter(|room_info| !room_info.pickup_locations.is_empty()) {
+                let room_pickups = self.pickups.get(&world)
+                    .and_then(|world_pickups| world_pickups.get(room_info.name))
+                    .ok_or_else(|| format!("Layout is missing {}: {}", world, room_info.name))?;
+                if room_pickups.len() != room_info.pickup_locations.len() {
+                    Err(format!("{}: {} should have exactly {} pickups",
+                                world, room_info.name, room_info.pickup_locations.len()))?
+                }
+                for name in room_pickups {
+                    let pickup_type = PickupType::iter()
+                        .find(|pt| pt.name().to_lowercase() == name.to_lowercase())
+                        .ok_or_else(|| format!("Unknown pickup '{}'", name))?;
+                    pickup_layout.push(pickup_type.idx() as u8);
+                }
+                room_count += 1;
+            }
+        }
+        if self.pickups.values().map(|world_pickups| world_pickups.len()).sum::<usize>() != room_count {
+            Err("Layout has pickups for unknown rooms".to_string())?
+        }
+
+        let mut destinations = vec![None; ELEVATORS.len()];
+        for (name, dest) in self.elevators.iter() {
+            destinations[elevator_idx(name)? as usize] = Some(elevator_idx(dest)?);
+        }
+        let mut elevator_layout = destinations.into_iter()
+            .enumerate()
+            .map(|(i, dest)| dest.ok_or_else(|| format!("Layout is missing {}", elevator_name(i))))
+            .collect::<Result<Vec<_>, _>>()?;
+        elevator_layout.push(if self.starting_room.to_lowercase() == LANDING_SITE.to_lowercase() {
+            ELEVATORS.len() as u8
+        } else {
+            elevator_idx(&self.starting_room)?
+        });
+
+        Ok((pickup_layout, elevator_layout))
+    }
+
+    pub fn from_layout_string(text: &str) -> Result<Layout, String>
+    {
+        let (pickup_layout, elevator_layout, _) = parse_layout(text)?;
+        let mut layout = Layout::from_indices(&pickup_layout, &elevator_layout)?;
+        // The pickups follow the '.' if there is one
+        layout.scan_visor = text.splitn(2, '.').last().unwrap_or("").starts_with('!');
+        Ok(layout)
+    }
+
+    pub fn to_layout_string(&self) -> Result<String, String>
+    {
+        let (pickup_layout, elevator_layout) = self.to_indices()?;
+        let has_scan_visor = self.scan_visor || pickup_layout.iter().any(|&i| i >= 36);
+        write_layout(&pickup_layout, &elevator_layout, has_scan_visor)
+    }
+}
@@ -25,8 +25,14 @@ use std::{
 
 pub mod elevators;
 pub mod mlvl_wrapper;
+pub mod savw_wrapper;
 pub mod pickup_meta;
 pub mod door_meta;
+pub mod door_randomizer;
+pub mod layout;
+pub mod logic;
+pub mod spoiler;
+pub mod starting_items;
 pub mod patcher;
 pub mod patches;
 pub mod c_interface;
@@ -182,13 +188,36 @@ pub fn extract_flaahgra_music_files(iso_path: &str) -> Result<[nod_wrapper::File
     res.map_err(|s: String| format!("Failed to extract Flaahgra music files: {}", s))
 }
 
+const LAYOUT_CHAR_TABLE: [u8; 64] =
+    *b"ABCDEFGHIJKLMNOPQRSTUWVXYZabcdefghijklmnopqrstuwvxyz0123456789-_";
+
+// Reverse the order of the odd bits. Applying this twice gives back the original number.
+fn reverse_layout_odd_bits(sum: BigUint) -> BigUint
+{
+    let mut bits = sum.to_str_radix(2).into_bytes();
+    for i in 0..(bits.len() / 4) {
+        let len = bits.len() - bits.len() % 2;
+        bits.swap(i * 2 + 1, len - i * 2 - 1);
+    }
+    BigUint::parse_bytes(&bits, 2).unwrap()
+}
+
+fn layout_checksum(mut sum: BigUint, checksum_size: usize) -> u8
+{
+    let checksum_bitmask = (1u8 << checksum_size) - 1;
+    let mut checksum = 0;
+    while sum > 0u8.into() {
+        let remainder = (sum.clone() & BigUint::from(checksum_bitmask)).to_u8().unwrap();
+        checksum = (checksum + remainder) & checksum_bitmask;
+        sum >>= checksum_size;
+    }
+    checksum
+}
+
 pub fn parse_layout_chars_to_ints<I>(bytes: &[u8], layout_data_size: usize, checksum_size: usize, is: I)
     -> Result<Vec<u8>, String>
     where I: Iterator<Item = u8> + Clone
 {
-    const LAYOUT_CHAR_TABLE: [u8; 64] =
-        *b"ABCDEFGHIJKLMNOPQRSTUWVXYZabcdefghijklmnopqrstuwvxyz0123456789-_";
-
     let mut sum: BigUint = 0u8.into();
     for c in bytes.iter().rev() {
         if let Some(idx) = LAYOUT_CHAR_TABLE.iter().position(|i| i == c) {
@@ -198,13 +227,7 @@ pub fn parse_layout_chars_to_ints<I>(bytes: &[u8], layout_data_size: usize, chec
         }
     }
 
-    // Reverse the order of the odd bits
-    let mut bits = sum.to_str_radix(2).into_bytes();
-    for i in 0..(bits.len() / 4) {
-        let len = bits.len() - bits.len() % 2;
-        bits.swap(i * 2 + 1, len - i * 2 - 1);
-    }
-    sum = BigUint::parse_bytes(&bits, 2).unwrap();
+    sum = reverse_layout_odd_bits(sum);
 
     // The upper `checksum_size` bits are a checksum, so seperate them from the sum.
     let checksum_bitmask = (1u8 << checksum_size) - 1;
@@ -212,16 +235,7 @@ pub fn parse_layout_chars_to_ints<I>(bytes: &[u8], layout_data_size: usize, chec
     sum -= checksum.clone();
     let checksum = (checksum >> layout_data_size).to_u8().unwrap();
 
-    let mut computed_checksum = 0;
-    {
-        let mut sum = sum.clone();
-        while sum > 0u8.into() {
-            let remainder = (sum.clone() & BigUint::from(checksum_bitmask)).to_u8().unwrap();
-            computed_checksum = (computed_checksum + remainder) & checksum_bitmask;
-            sum >>= checksum_size;
-        }
-    }
-    if checksum != computed_checksum {
+    if checksum != layout_checksum(sum.clone(), checksum_size) {
         return Err("Layout checksum failed.".to_string());
     }
 
@@ -238,6 +252,45 @@ pub fn parse_layout_chars_to_ints<I>(bytes: &[u8], layout_data_size: usize, chec
     Ok(res)
 }
 
+// The inverse of `parse_layout_chars_to_ints`
+pub fn write_layout_ints_to_chars<I>(
+    ints: &[u8],
+    char_count: usize,
+    layout_data_size: usize,
+    checksum_size: usize,
+    is: I,
+) -> Result<Vec<u8>, String>
+    where I: Iterator<Item = u8> + Clone
+{
+    let denums: Vec<u8> = is.collect();
+    if ints.len() != denums.len() {
+        return Err(format!("Layout should have exactly {} entries", denums.len()));
+    }
+
+    let mut sum: BigUint = 0u8.into();
+    for (i, (int, denum)) in ints.iter().zip(denums.iter().rev()).enumerate() {
+        if int >= denum {
+            return Err(format!("Layout entry {} is out of range ({} >= {}).", i, int, denum));
+        }
+        sum = sum * BigUint::from(*denum) + BigUint::from(*int);
+    }
+
+    let checksum = layout_checksum(sum.clone(), checksum_size);
+    sum += BigUint::from(checksum) << layout_data_size;
+    sum = reverse_layout_odd_bits(sum);
+
+    let mut res = vec![];
+    for _ in 0..char_count {
+        let (quotient, remainder) = sum.div_rem(&64u8.into());
+        res.push(LAYOUT_CHAR_TABLE[remainder.to_usize().unwrap()]);
+        sum = quotient;
+    }
+    if sum != 0u8.into() {
+        return Err(format!("Layout does not fit in {} characters.", char_count));
+    }
+    Ok(res)
+}
+
 
 pub fn parse_layout(text: &str) -> Result<(Vec<u8>, Vec<u8>, u64), String>
 {
@@ -289,6 +342,41 @@ pub fn parse_layout(text: &str) -> Result<(Vec<u8>, Vec<u8>, u64), String>
     Ok((pickup_layout, elevator_layout, seed))
 }
 
+// Builds a layout string from pickup and elevator indices, such that `parse_layout` gives them
+// back. The elevator section is always included, even when it's the default one.
+// `has_scan_visor` selects the '!' form of the string, which is needed for pickup indices of 36
+// and above but may also be used without them.
+pub fn write_layout(pickup_layout: &[u8], elevator_layout: &[u8], has_scan_visor: bool)
+    -> Result<String, String>
+{
+    if !has_scan_visor && pickup_layout.iter().any(|&i| i >= 36) {
+        Err("Pickup layouts with the Scan Visor or later pickups need the '!' form".to_string())?
+    }
+
+    let pickup_bytes = write_layout_ints_to_chars(
+            pickup_layout,
+            87,
+            if has_scan_visor { 521 } else { 517 },
+            if has_scan_visor { 1 } else { 5 },
+            iter::repeat(if has_scan_visor { 37u8 } else { 36u8 }).take(100)
+        ).map_err(|err| format!("Writing pickup layout: {}", err))?;
+
+    let elevator_bytes = write_layout_ints_to_chars(
+            elevator_layout,
+            16,
+            91, 5,
+            iter::once(21u8).chain(iter::repeat(20u8).take(20))
+        ).map_err(|err| format!("Writing elevator layout: {}", err))?;
+
+    let mut text = String::from_utf8(elevator_bytes).unwrap();
+    text.push('.');
+    if has_scan_visor {
+        text.push('!');
+    }
+    text.push_str(std::str::from_utf8(&pickup_bytes).unwrap());
+    Ok(text)
+}
+
 
 
 #[derive(Clone, Debug)]
@@ -412,5 +500,55 @@ pub mod custom_asset_ids {
         // has to be at the end //
         SKIP_HUDMEMO_STRG_START,
         SKIP_HUDMEMO_STRG_END = SKIP_HUDMEMO_STRG_START + 38,
+
+        // ids from here on are handed out as needed for pickup overrides //
+        PICKUP_OVERRIDE_ASSETS_START = SKIP_HUDMEMO_STRG_END,
+        PICKUP_OVERRIDE_ASSETS_END = PICKUP_OVERRIDE_ASSETS_START + 0x1000,
+    }
+}
+
+// Hands out the asset ids in `start..end` one at a time, for assets that are created as needed
+// (custom pickup models, their scans and HUD memos, hint text and so on). By default that's the
+// range reserved for pickup overrides in `custom_asset_ids`.
+#[derive(Clone, Debug)]
+pub struct AssetIdAllocator
+{
+    next: u32,
+    end: u32,
+}
+
+impl AssetIdAllocator
+{
+    pub fn new(start: u32, end: u32) -> AssetIdAllocator
+    {
+        AssetIdAllocator { next: start, end }
+    }
+
+    pub fn next_id(&mut self) -> Result<u32, String>
+    {
+        if self.next >= self.end {
+            Err(format!("Ran out of asset ids for new assets (0x{:08X} is the last one)",
+                        self.end.wrapping_sub(1)))?
+        }
+        let id = self.next;
+        self.next += 1;
+        Ok(id)
+    }
+
+    // The id the next call to `next_id` will hand out, if there are any left
+    pub fn peek(&self) -> u32
+    {
+        self.next
+    }
+}
+
+impl Default for AssetIdAllocator
+{
+    fn default() -> AssetIdAllocator
+    {
+        AssetIdAllocator::new(
+            custom_asset_ids::PICKUP_OVERRIDE_ASSETS_START,
+            custom_asset_ids::PICKUP_OVERRIDE_ASSETS_END,
+        )
     }
 }
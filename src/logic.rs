@@ -0,0 +1,1105 @@
+// Item placement logic, ported from the web generator (`web/generator.js` and
+// `web/normal_difficulty.js`). Requirements are kept in disjunctive normal form: a list of
+// alternatives, each of which is a list of pickups and how many of each are needed.
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    door_meta::{DoorType, World},
+    pickup_meta::{self, PickupType, RoomInfo},
+    starting_items::StartingItems,
+};
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+pub type Inventory = HashMap<PickupType, u32>;
+
+fn count(inventory: &Inventory, pickup_type: PickupType) -> u32
+{
+    inventory.get(&pickup_type).cloned().unwrap_or(0)
+}
+
+// Combines the entries for the same pickup, keeping the largest count
+fn merge_conjunct(conjunct: Vec<(PickupType, u32)>) -> Vec<(PickupType, u32)>
+{
+    let mut merged: Vec<(PickupType, u32)> = vec![];
+    for (pickup_type, n) in conjunct {
+        match merged.iter_mut().find(|(pt, _)| *pt == pickup_type) {
+            Some((_, m)) => *m = (*m).max(n),
+            None => merged.push((pickup_type, n)),
+        }
+    }
+    merged
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Requirement(Vec<Vec<(PickupType, u32)>>);
+
+impl Requirement
+{
+    // Satisfied by anything
+    pub fn none() -> Self
+    {
+        Requirement(vec![vec![]])
+    }
+
+    // Never satisfied
+    pub fn impossible() -> Self
+    {
+        Requirement(vec![])
+    }
+
+    pub fn n_of(pickup_type: PickupType, n: u32) -> Self
+    {
+        Requirement(vec![vec![(pickup_type, n)]])
+    }
+
+    pub fn all(reqs: Vec<Requirement>) -> Self
+    {
+        let mut conjuncts = vec![vec![]];
+        for req in reqs {
+            conjuncts = conjuncts.iter()
+                .flat_map(|c: &Vec<_>| req.0.iter().map(move |r| merge_conjunct([&c[..], &r[..]].concat())))
+                .collect();
+        }
+        Requirement(conjuncts)
+    }
+
+    pub fn any(reqs: Vec<Requirement>) -> Self
+    {
+        Requirement(reqs.into_iter().flat_map(|req| req.0).collect())
+    }
+
+    pub fn is_satisfied(&self, inventory: &Inventory) -> bool
+    {
+        self.0.iter().any(|c| c.iter().all(|&(pickup_type, n)| count(inventory, pickup_type) >= n))
+    }
+
+    // Drops the alternatives that need at least as much as another one does and sorts what's
+    // left, so that equivalent requirements compare equal
+    fn simplified(self) -> Self
+    {
+        let key = |c: &Vec<(PickupType, u32)>| {
+            c.iter().map(|&(pt, n)| (pt.idx(), n)).collect::<Vec<_>>()
+        };
+        let mut conjuncts: Vec<_> = self.0.into_iter()
+            .map(|mut c| {
+                c.sort_by_key(|&(pt, _)| pt.idx());
+                c
+            })
+            .collect();
+        conjuncts.sort_by_key(|c| (c.len(), key(c)));
+        conjuncts.dedup();
+        let mut kept: Vec<Vec<(PickupType, u32)>> = vec![];
+        for c in conjuncts {
+            let inventory: Inventory = c.iter().cloned().collect();
+            if !kept.iter().any(|k| k.iter().all(|&(pt, n)| count(&inventory, pt) >= n)) {
+                kept.push(c);
+            }
+        }
+        kept.sort_by_key(key);
+        Requirement(kept)
+    }
+}
+
+impl From<PickupType> for Requirement
+{
+    fn from(pickup_type: PickupType) -> Self
+    {
+        Requirement::n_of(pickup_type, 1)
+    }
+}
+
+impl From<&Requirement> for Requirement
+{
+    fn from(req: &Requirement) -> Self
+    {
+        req.clone()
+    }
+}
+
+macro_rules! all {
+    ($($req:expr),* $(,)?) => {
+        Requirement::all(vec![$(Requirement::from($req)),*])
+    };
+}
+
+macro_rules! any {
+    ($($req:expr),* $(,)?) => {
+        Requirement::any(vec![$(Requirement::from($req)),*])
+    };
+}
+
+#[derive(Clone, Debug)]
+pub struct LocationRequirements
+{
+    // What's needed to reach the location
+    pub required: Requirement,
+    // What's needed to leave again. The pickup at the location counts towards this.
+    pub escape: Requirement,
+}
+
+impl LocationRequirements
+{
+    fn new(required: Requirement) -> Self
+    {
+        LocationRequirements { required, escape: Requirement::none() }
+    }
+
+    fn with_escape(required: Requirement, escape: Requirement) -> Self
+    {
+        LocationRequirements { required, escape }
+    }
+}
+
+// The requirements for each pickup location, in layout order, assuming vanilla elevators and
+// door destinations
+pub fn normal_difficulty() -> Vec<LocationRequirements>
+{
+    use PickupType::*;
+
+    let boost_ball = all![MorphBall, BoostBall];
+    let spider_ball = all![MorphBall, SpiderBall];
+    let morph_ball_bomb = all![MorphBall, MorphBallBomb];
+    let power_bomb = all![MorphBall, PowerBomb];
+    let super_missile = all![ChargeBeam, Missile, SuperMissile];
+
+    let any_suit = any![VariaSuit, GravitySuit, PhazonSuit];
+    let any_power_bombs = any![PowerBomb, PowerBombExpansion];
+    let mbb_or_pb = any![MorphBallBomb, PowerBomb];
+
+    let phendrana_reqs = all![Missile, &morph_ball_bomb, &any_suit];
+    // XXX With BBJ + unmore this doesn't require SJB
+    let backwards_phendrana_reqs = all![Missile, &any_suit, &spider_ball, SpaceJumpBoots,
+                                        WaveBeam];
+    let mines_from_tallon_reqs = all![Missile, &morph_ball_bomb, SpaceJumpBoots, GravitySuit,
+                                      ThermalVisor, WaveBeam, IceBeam];
+    let mines_from_magmoor_reqs = all![Missile, &spider_ball, SpaceJumpBoots, &any_suit, WaveBeam,
+                                       IceBeam, &any_power_bombs];
+
+    vec![
+        // 0: Chozo - - - Main Plaza (Half-Pipe) - - - - - - - - Missile Expansion 1
+        LocationRequirements::new(all![&boost_ball]),
+        // 1: Chozo - - - Main Plaza (Grapple Ledge) - - - - - - Missile Expansion 2
+        LocationRequirements::new(all![Missile, GrappleBeam, &any_suit, &boost_ball,
+                                       &morph_ball_bomb, WaveBeam]),
+        // 2: Chozo - - - Main Plaza (Tree)  - - - - - - - - - - Missile Expansion 3
+        LocationRequirements::new(all![&super_missile]),
+        // 3: Chozo - - - Main Plaza (Locked Door) - - - - - - - Energy Tank 1
+        LocationRequirements::new(all![Missile, &morph_ball_bomb]),
+        // 4: Chozo - - - Ruined Fountain  - - - - - - - - - - - Missile Expansion 4
+        LocationRequirements::new(all![Missile, &spider_ball]),
+        // 5: Chozo - - - Ruined Shrine ("Beetle Battle")  - - - Morph Ball
+        LocationRequirements::with_escape(all![Missile], any![SpaceJumpBoots, MorphBall]),
+        // 6: Chozo - - - Ruined Shrine (Half-Pipe)  - - - - - - Missile Expansion 5
+        LocationRequirements::new(all![Missile, &boost_ball]),
+        // 7: Chozo - - - Ruined Shrine (Lower Tunnel) - - - - - Missile Expansion 6
+        LocationRequirements::new(all![Missile, &mbb_or_pb]),
+        // 8: Chozo - - - Vault  - - - - - - - - - - - - - - - - Missile Expansion 7
+        LocationRequirements::new(all![Missile, &morph_ball_bomb]),
+        // 9: Chozo - - - Training Chamber - - - - - - - - - - - Energy Tank 2
+        LocationRequirements::new(all![Missile, &any_suit, GrappleBeam, WaveBeam, &boost_ball,
+                                       &spider_ball]),
+        //10: Chozo - - - Ruined Nursery - - - - - - - - - - - - Missile Expansion 8
+        LocationRequirements::new(all![&morph_ball_bomb]),
+        //11: Chozo - - - Training Chamber Access  - - - - - - - Missile Expansion 9
+        LocationRequirements::new(all![Missile, &any_suit, GrappleBeam, WaveBeam, MorphBall]),
+        //12: Chozo - - - Magma Pool - - - - - - - - - - - - - - Power Bomb Expansion 1
+        LocationRequirements::new(all![Missile, &any_suit, GrappleBeam, &any_power_bombs]),
+        //13: Chozo - - - Tower of Light - - - - - - - - - - - - Wavebuster
+        // This actually only requires 75 missiles, but 80 is less hard
+        LocationRequirements::new(all![Requirement::n_of(Missile, 80 / 5), &boost_ball,
+                                       &spider_ball, WaveBeam, SpaceJumpBoots]),
+        //14: Chozo - - - Tower Chamber  - - - - - - - - - - - - Artifact of Lifegiver
+        // XXX Requires SJB without GS, but w/ GS doesn't
+        LocationRequirements::new(all![Missile, &boost_ball, &spider_ball, WaveBeam, GravitySuit,
+                                       SpaceJumpBoots]),
+        //15: Chozo - - - Ruined Gallery (Missile Wall)  - - - - Missile Expansion 10
+        LocationRequirements::new(all![Missile]),
+        //16: Chozo - - - Ruined Gallery (Tunnel)  - - - - - - - Missile Expansion 11
+        LocationRequirements::new(all![&morph_ball_bomb]),
+        //17: Chozo - - - Transport Access North - - - - - - - - Energy Tank 3
+        LocationRequirements::new(all![Missile]),
+        //18: Chozo - - - Gathering Hall - - - - - - - - - - - - Missile Expansion 12
+        LocationRequirements::new(all![Missile, &mbb_or_pb, SpaceJumpBoots]),
+        //19: Chozo - - - Hive Totem - - - - - - - - - - - - - - Missile Launcher
+        LocationRequirements::new(all![]),
+        //20: Chozo - - - Sunchamber (Flaahgra)  - - - - - - - - Varia Suit
+        LocationRequirements::new(all![Missile, &morph_ball_bomb]),
+        //21: Chozo - - - Sunchamber (Ghosts)  - - - - - - - - - Artifact of Wild
+        // XXX MBB is very questionable here...
+        //     Its not strickly needed, but it is needed to fight flaahgra
+        LocationRequirements::new(all![&morph_ball_bomb, &spider_ball, &super_missile]),
+        //22: Chozo - - - Watery Hall Access - - - - - - - - - - Missile Expansion 13
+        LocationRequirements::new(all![Missile, MorphBall]),
+        //23: Chozo - - - Watery Hall (Scan Puzzle)  - - - - - - Charge Beam
+        LocationRequirements::new(all![Missile, MorphBall]),
+        //24: Chozo - - - Watery Hall (Underwater) - - - - - - - Missile Expansion 14
+        // NOTE: Does this actually require Space Jump? (Yes, without a dbj + unmorph)
+        LocationRequirements::new(all![Missile, &morph_ball_bomb, GravitySuit, SpaceJumpBoots]),
+        //25: Chozo - - - Dynamo (Lower) - - - - - - - - - - - - Missile Expansion 15
+        LocationRequirements::new(all![Missile, &mbb_or_pb]),
+        //26: Chozo - - - Dynamo (Spider Track)  - - - - - - - - Missile Expansion 16
+        LocationRequirements::new(all![Missile, &mbb_or_pb, &spider_ball]),
+        //27: Chozo - - - Burn Dome (Missile)  - - - - - - - - - Missile Expansion 17
+        LocationRequirements::with_escape(all![Missile, &mbb_or_pb], all![&morph_ball_bomb]),
+        //28: Chozo - - - Burn Dome (I. Drone) - - - - - - - - - Morph Ball Bomb
+        LocationRequirements::with_escape(all![Missile, MorphBall], all![&morph_ball_bomb]),
+        //29: Chozo - - - Furnace (Spider Tracks)  - - - - - - - Missile Expansion 18
+        LocationRequirements::new(all![Missile, &morph_ball_bomb, &any_power_bombs, &boost_ball,
+                                       &spider_ball]),
+        //30: Chozo - - - Furnace (Inside Furnace) - - - - - - - Energy Tank 4
+        LocationRequirements::new(all![Missile, &morph_ball_bomb]),
+        //31: Chozo - - - Hall of the Elders - - - - - - - - - - Energy Tank 5
+        LocationRequirements::new(all![Missile, &morph_ball_bomb, &spider_ball, WaveBeam,
+                                       &boost_ball, IceBeam, SpaceJumpBoots]),
+        //32: Chozo - - - Crossway - - - - - - - - - - - - - - - Missile Expansion 19
+        LocationRequirements::new(all![Missile, &morph_ball_bomb, &spider_ball, WaveBeam,
+                                       &boost_ball]),
+        //33: Chozo - - - Elder Chamber  - - - - - - - - - - - - Artifact of World
+        LocationRequirements::with_escape(
+            all![Missile, &morph_ball_bomb, &spider_ball, WaveBeam, &boost_ball, PlasmaBeam,
+                 SpaceJumpBoots],
+            all![IceBeam],
+        ),
+        //34: Chozo - - - Antechamber  - - - - - - - - - - - - - Ice Beam
+        LocationRequirements::with_escape(
+            all![Missile, &morph_ball_bomb, &spider_ball, WaveBeam, &boost_ball, SpaceJumpBoots],
+            all![IceBeam],
+        ),
+        //35: Phendrana - Phendrana Shorelines (Behind Ice)  - - Missile Expansion 20
+        LocationRequirements::new(all![&phendrana_reqs, PlasmaBeam]),
+        //36: Phendrana - Phendrana Shorelines (Spider Track)  - Missile Expansion 21
+        LocationRequirements::new(all![&phendrana_reqs, SpaceJumpBoots, &spider_ball,
+                                       &super_missile]),
+        //37: Phendrana - Chozo Ice Temple - - - - - - - - - - - Artifact of Sun
+        LocationRequirements::new(all![&phendrana_reqs, SpaceJumpBoots, PlasmaBeam]),
+        //38: Phendrana - Ice Ruins West - - - - - - - - - - - - Power Bomb Expansion 2
+        LocationRequirements::new(all![&phendrana_reqs, PlasmaBeam, SpaceJumpBoots]),
+        //39: Phendrana - Ice Ruins East (Behind Ice)  - - - - - Missile Expansion 22
+        LocationRequirements::new(all![&phendrana_reqs, PlasmaBeam]),
+        //40: Phendrana - Ice Ruins East (Spider Track)  - - - - Missile Expansion 23
+        LocationRequirements::new(all![&phendrana_reqs, &spider_ball]),
+        //41: Phendrana - Chapel of the Elders - - - - - - - - - Wave Beam
+        LocationRequirements::with_escape(all![&phendrana_reqs, SpaceJumpBoots], all![WaveBeam]),
+        //42: Phendrana - Ruined Courtyard - - - - - - - - - - - Energy Tank 6
+        LocationRequirements::new(all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam]),
+        //43: Phendrana - Phendrana Canyon - - - - - - - - - - - Boost Ball
+        // XXX Strictly speaking, you can escape without either of these, but it requires
+        //     jumping on destructable boxes, and thus makes this room a potential hazard
+        LocationRequirements::with_escape(all![&phendrana_reqs], any![&boost_ball, SpaceJumpBoots]),
+        //44: Phendrana - Quarantine Cave  - - - - - - - - - - - Spider Ball
+        LocationRequirements::with_escape(
+            any![
+                all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam, ThermalVisor],
+                // XXX Thermal visor is only required for fighting Thardus. It could be removed
+                all![&backwards_phendrana_reqs, ThermalVisor],
+            ],
+            all![&spider_ball],
+        ),
+        //45: Phendrana - Research Lab Hydra - - - - - - - - - - Missile Expansion 24
+        LocationRequirements::new(any![
+            all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam, ThermalVisor,
+                 &super_missile],
+            // XXX Research Core Thermal Visor
+            all![&backwards_phendrana_reqs, ThermalVisor, IceBeam, &super_missile],
+        ]),
+        //46: Phendrana - Quarantine Monitor - - - - - - - - - - Missile Expansion 25
+        LocationRequirements::with_escape(
+            any![
+                all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam, ThermalVisor,
+                     GrappleBeam],
+                // XXX Thermal visor is only required for fighting Thardus. It could be removed?
+                all![&backwards_phendrana_reqs, ThermalVisor, GrappleBeam],
+            ],
+            all![&spider_ball],
+        ),
+        //47: Phendrana - Observatory  - - - - - - - - - - - - - Super Missile
+        LocationRequirements::new(all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam]),
+        //48: Phendrana - Transport Access - - - - - - - - - - - Energy Tank 7
+        LocationRequirements::new(any![
+            all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam, ThermalVisor,
+                 &spider_ball, PlasmaBeam],
+            // XXX Research Core Thermal Visor
+            all![&backwards_phendrana_reqs, ThermalVisor, IceBeam, PlasmaBeam],
+        ]),
+        //49: Phendrana - Control Tower  - - - - - - - - - - - - Artifact of Elder
+        LocationRequirements::new(any![
+            all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam, PlasmaBeam],
+            // XXX Research Core Thermal Visor
+            all![&backwards_phendrana_reqs, ThermalVisor, IceBeam, PlasmaBeam],
+        ]),
+        //50: Phendrana - Research Core  - - - - - - - - - - - - Thermal Visor
+        LocationRequirements::with_escape(
+            any![
+                all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam],
+                all![&backwards_phendrana_reqs, IceBeam],
+            ],
+            any![ThermalVisor, IceBeam],
+        ),
+        //51: Phendrana - Frost Cave - - - - - - - - - - - - - - Missile Expansion 26
+        // The thermal visor is required to escape either via Research Core or fight Thardus
+        LocationRequirements::with_escape(
+            any![
+                all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam, IceBeam,
+                     GrappleBeam],
+                all![&backwards_phendrana_reqs, IceBeam, GrappleBeam],
+            ],
+            any![ThermalVisor, &backwards_phendrana_reqs],
+        ),
+        //52: Phendrana - Research Lab Aether (Tank) - - - - - - Energy Tank 8
+        LocationRequirements::new(any![
+            all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam],
+            // XXX Research Core Thermal Visor
+            all![&backwards_phendrana_reqs, ThermalVisor, IceBeam],
+        ]),
+        //53: Phendrana - Research Lab Aether (Morph Track)  - - Missile Expansion 27
+        LocationRequirements::new(any![
+            all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam],
+            // XXX Research Core Thermal Visor
+            all![&backwards_phendrana_reqs, ThermalVisor, IceBeam],
+        ]),
+        //54: Phendrana - Gravity Chamber (Underwater) - - - - - Gravity Suit
+        LocationRequirements::with_escape(
+            any![
+                all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam, IceBeam],
+                all![&backwards_phendrana_reqs, IceBeam],
+            ],
+            all![GravitySuit, any![ThermalVisor, &backwards_phendrana_reqs]],
+        ),
+        //55: Phendrana - Gravity Chamber (Grapple Ledge)  - - - Missile Expansion 28
+        // See 51
+        LocationRequirements::with_escape(
+            any![
+                all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam, IceBeam, PlasmaBeam,
+                     GrappleBeam, GravitySuit],
+                all![&backwards_phendrana_reqs, IceBeam, PlasmaBeam, GrappleBeam, GravitySuit],
+            ],
+            any![ThermalVisor, &backwards_phendrana_reqs],
+        ),
+        //56: Phendrana - Storage Cave - - - - - - - - - - - - - Artifact of Spirit
+        // See 51
+        LocationRequirements::with_escape(
+            any![
+                all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam, IceBeam,
+                     ThermalVisor, PlasmaBeam, &power_bomb, GrappleBeam],
+                all![&backwards_phendrana_reqs, IceBeam, PlasmaBeam, &power_bomb, GrappleBeam],
+            ],
+            any![ThermalVisor, &backwards_phendrana_reqs],
+        ),
+        //57: Phendrana - Security Cave  - - - - - - - - - - - - Power Bomb Expansion 3
+        // See 51
+        LocationRequirements::with_escape(
+            any![
+                all![&phendrana_reqs, &boost_ball, SpaceJumpBoots, WaveBeam, IceBeam,
+                     GrappleBeam],
+                all![&backwards_phendrana_reqs, IceBeam, GrappleBeam],
+            ],
+            any![ThermalVisor, &backwards_phendrana_reqs],
+        ),
+        //58: Tallon  - - Landing Site - - - - - - - - - - - - - Missile Expansion 29
+        LocationRequirements::new(all![MorphBall]),
+        //59: Tallon  - - Alcove - - - - - - - - - - - - - - - - Space Jump Boots
+        LocationRequirements::new(any![all![&morph_ball_bomb, &boost_ball], SpaceJumpBoots]),
+        //60: Tallon  - - Frigate Crash Site - - - - - - - - - - Missile Expansion 30
+        LocationRequirements::new(all![Missile, MorphBall, GravitySuit, SpaceJumpBoots]),
+        //61: Tallon  - - Overgrown Cavern - - - - - - - - - - - Missile Expansion 31
+        LocationRequirements::new(all![Missile, &morph_ball_bomb, WaveBeam, &spider_ball,
+                                       &boost_ball, IceBeam, SpaceJumpBoots]),
+        //62: Tallon  - - Root Cave  - - - - - - - - - - - - - - Missile Expansion 32
+        LocationRequirements::new(all![Missile, SpaceJumpBoots, GrappleBeam, XRayVisor]),
+        //63: Tallon  - - Artifact Temple  - - - - - - - - - - - Artifact of Truth
+        LocationRequirements::new(all![Missile]),
+        //64: Tallon  - - Transport Tunnel B - - - - - - - - - - Missile Expansion 33
+        LocationRequirements::new(all![Missile]),
+        //65: Tallon  - - Arbor Chamber  - - - - - - - - - - - - Missile Expansion 34
+        LocationRequirements::new(all![Missile, SpaceJumpBoots, GrappleBeam, XRayVisor,
+                                       PlasmaBeam]),
+        //66: Tallon  - - Cargo Freight Lift to Deck Gamma - - - Energy Tank 9
+        LocationRequirements::new(all![Missile, MorphBall, GravitySuit, ThermalVisor, WaveBeam,
+                                       IceBeam]),
+        //67: Tallon  - - Biohazard Containment  - - - - - - - - Missile Expansion 35
+        LocationRequirements::with_escape(
+            any![
+                all![MorphBall, GravitySuit, ThermalVisor, WaveBeam, IceBeam, SpaceJumpBoots,
+                     &super_missile],
+                // Backwards through Phazon Mines. Requires this to contain the thermal visor.
+                all![Missile, MorphBall, GravitySuit, &spider_ball, SpaceJumpBoots, WaveBeam,
+                     IceBeam, &any_power_bombs, GrappleBeam],
+            ],
+            any![ThermalVisor],
+        ),
+        //68: Tallon  - - Hydro Access Tunnel  - - - - - - - - - Energy Tank 10
+        LocationRequirements::new(any![
+            all![Missile, MorphBall, GravitySuit, ThermalVisor, WaveBeam, IceBeam, SpaceJumpBoots,
+                 &morph_ball_bomb],
+            // Backwards through Phazon Mines
+            // TODO Is the Grapple Beam requirement necessary? A simple l-jump by passes it
+            //      easily.
+            all![Missile, MorphBall, GravitySuit, &spider_ball, SpaceJumpBoots, WaveBeam, IceBeam,
+                 &any_power_bombs, GrappleBeam],
+        ]),
+        //69: Tallon  - - Great Tree Chamber - - - - - - - - - - Missile Expansion 36
+        LocationRequirements::new(any![
+            // From frigate
+            all![Missile, &morph_ball_bomb, WaveBeam, IceBeam, SpaceJumpBoots, XRayVisor,
+                 GravitySuit, ThermalVisor],
+            // From backwards mines
+            all![Missile, &morph_ball_bomb, WaveBeam, IceBeam, SpaceJumpBoots, XRayVisor,
+                 &any_suit, &spider_ball, &any_power_bombs, GrappleBeam],
+            // From chozo
+            all![Missile, &morph_ball_bomb, WaveBeam, IceBeam, SpaceJumpBoots, XRayVisor,
+                 &spider_ball, &boost_ball],
+        ]),
+        //70: Tallon  - - Life Grove Tunnel  - - - - - - - - - - Missile Expansion 37
+        LocationRequirements::new(all![Missile, GravitySuit, WaveBeam, IceBeam, SpaceJumpBoots,
+                                       &morph_ball_bomb, &spider_ball, &boost_ball,
+                                       &any_power_bombs]),
+        //71: Tallon  - - Life Grove (Start) - - - - - - - - - - X-Ray Visor
+        LocationRequirements::new(all![Missile, GravitySuit, WaveBeam, IceBeam, SpaceJumpBoots,
+                                       &morph_ball_bomb, &spider_ball, &boost_ball,
+                                       &any_power_bombs]),
+        //72: Tallon  - - Life Grove (Underwater Spinner)  - - - Artifact of Chozo
+        // XXX Gravity suit: Its not actually required, but could be considered a glitch.
+        LocationRequirements::new(all![Missile, GravitySuit, WaveBeam, IceBeam, SpaceJumpBoots,
+                                       &morph_ball_bomb, &spider_ball, &boost_ball,
+                                       &any_power_bombs]),
+        //73: Mines - - - Main Quarry  - - - - - - - - - - - - - Missile Expansion 38
+        LocationRequirements::new(all![Missile, &any_suit, WaveBeam, IceBeam, SpaceJumpBoots,
+                                       &morph_ball_bomb, &spider_ball, ThermalVisor]),
+        //74: Mines - - - Security Access A  - - - - - - - - - - Missile Expansion 39
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &any_power_bombs, &boost_ball],
+            all![&mines_from_magmoor_reqs, &morph_ball_bomb],
+        ]),
+        //75: Mines - - - Storage Depot B  - - - - - - - - - - - Grapple Beam
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &any_power_bombs],
+            all![&mines_from_magmoor_reqs, GrappleBeam, &morph_ball_bomb],
+        ]),
+        //76: Mines - - - Storage Depot A  - - - - - - - - - - - Flamethrower
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, PlasmaBeam],
+            all![&mines_from_magmoor_reqs, GrappleBeam, &morph_ball_bomb, PlasmaBeam],
+        ]),
+        //77: Mines - - - Elite Research (Phazon Elite)  - - - - Artifact of Warrior
+        // You need to #84 to unlock this fight. So, boost ball is required so one can go down to
+        // it and back up.
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &any_power_bombs, &boost_ball],
+            all![&mines_from_magmoor_reqs, GrappleBeam, &morph_ball_bomb, &boost_ball],
+        ]),
+        //78: Mines - - - Elite Research (Laser) - - - - - - - - Missile Expansion 40
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &boost_ball],
+            all![&mines_from_magmoor_reqs, GrappleBeam, &morph_ball_bomb, &boost_ball],
+        ]),
+        //79: Mines - - - Elite Control Access - - - - - - - - - Missile Expansion 41
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs],
+            all![&mines_from_magmoor_reqs],
+        ]),
+        //80: Mines - - - Ventilation Shaft  - - - - - - - - - - Energy Tank 11
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &boost_ball, &any_power_bombs],
+            all![&mines_from_magmoor_reqs, &boost_ball],
+        ]),
+        //81: Mines - - - Phazon Processing Center - - - - - - - Missile Expansion 42
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &any_power_bombs],
+            all![&mines_from_magmoor_reqs],
+        ]),
+        //82: Mines - - - Processing Center Access - - - - - - - Energy Tank 12
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &any_power_bombs, &boost_ball, PlasmaBeam, XRayVisor,
+                 GrappleBeam],
+            all![&mines_from_magmoor_reqs, &boost_ball, PlasmaBeam, XRayVisor, GrappleBeam],
+        ]),
+        //83: Mines - - - Elite Quarters - - - - - - - - - - - - Phazon Suit
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &any_power_bombs, &boost_ball, PlasmaBeam, XRayVisor],
+            all![&mines_from_magmoor_reqs, &boost_ball, PlasmaBeam, XRayVisor],
+        ]),
+        //84: Mines - - - Central Dynamo - - - - - - - - - - - - Power Bomb
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &boost_ball],
+            all![&mines_from_magmoor_reqs, &boost_ball],
+        ]),
+        //85: Mines - - - Metroid Quarantine B - - - - - - - - - Missile Expansion 43
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &any_power_bombs, &boost_ball, XRayVisor, GrappleBeam,
+                 PlasmaBeam],
+            all![&mines_from_magmoor_reqs, &boost_ball, XRayVisor, GrappleBeam, PlasmaBeam],
+        ]),
+        //86: Mines - - - Metroid Quarantine A - - - - - - - - - Missile Expansion 44
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &any_power_bombs, &boost_ball, XRayVisor],
+            all![&mines_from_magmoor_reqs, &boost_ball, XRayVisor],
+        ]),
+        //87: Mines - - - Fungal Hall B  - - - - - - - - - - - - Missile Expansion 45
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &boost_ball, XRayVisor, PlasmaBeam],
+            all![&mines_from_magmoor_reqs, &boost_ball, XRayVisor, PlasmaBeam],
+        ]),
+        //88: Mines - - - Phazon Mining Tunnel - - - - - - - - - Artifact of Newborn
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &any_power_bombs, &boost_ball, XRayVisor, PlasmaBeam,
+                 GrappleBeam, PhazonSuit],
+            all![&mines_from_magmoor_reqs, &boost_ball, XRayVisor, PlasmaBeam, GrappleBeam,
+                 PhazonSuit],
+        ]),
+        //89: Mines - - - Fungal Hall Access - - - - - - - - - - Missile Expansion 46
+        LocationRequirements::new(any![
+            all![&mines_from_tallon_reqs, &any_power_bombs, &boost_ball, XRayVisor],
+            all![&mines_from_magmoor_reqs, &boost_ball, XRayVisor, PlasmaBeam],
+        ]),
+        //90: Magmoor - - Lava Lake  - - - - - - - - - - - - - - Artifact of Nature
+        LocationRequirements::new(all![Missile, &mbb_or_pb, &any_suit, SpaceJumpBoots]),
+        //91: Magmoor - - Triclops Pit - - - - - - - - - - - - - Missile Expansion 47
+        // TODO Double if x-ray is needed if space jump??
+        LocationRequirements::new(all![Missile, MorphBall, &any_suit,
+                                       any![SpaceJumpBoots, XRayVisor]]),
+        //92: Magmoor - - Storage Cavern - - - - - - - - - - - - Missile Expansion 48
+        LocationRequirements::new(all![Missile, MorphBall, &any_suit]),
+        //93: Magmoor - - Transport Tunnel A - - - - - - - - - - Energy Tank 13
+        LocationRequirements::new(all![Missile, &morph_ball_bomb, &any_suit]),
+        //94: Magmoor - - Warrior Shrine - - - - - - - - - - - - Artifact of Strength
+        LocationRequirements::new(all![Missile, &any_suit, SpaceJumpBoots, &boost_ball]),
+        //95: Magmoor - - Shore Tunnel - - - - - - - - - - - - - Ice Spreader
+        LocationRequirements::new(all![Missile, &any_power_bombs, &any_suit, SpaceJumpBoots]),
+        //96: Magmoor - - Fiery Shores (Morph Track) - - - - - - Missile Expansion 49
+        // TODO This can be done using space jump, but is that too much of a glitch?
+        LocationRequirements::new(all![Missile, &morph_ball_bomb, &any_suit]),
+        //97: Magmoor - - Fiery Shores (Warrior Shrine Tunnel) - Power Bomb Expansion 4
+        LocationRequirements::new(all![Missile, &any_suit, SpaceJumpBoots, &boost_ball,
+                                       &any_power_bombs]),
+        //98: Magmoor - - Plasma Processing  - - - - - - - - - - Plasma Beam
+        LocationRequirements::new(all![Missile, &any_suit, SpaceJumpBoots, &boost_ball,
+                                       &spider_ball, GrappleBeam, WaveBeam, IceBeam]),
+        //99: Magmoor - - Magmoor Workstation  - - - - - - - - - Energy Tank 14
+        LocationRequirements::new(all![Missile, &any_suit, SpaceJumpBoots, &spider_ball, WaveBeam,
+                                       ThermalVisor]),
+    ]
+}
+
+// What it takes to open a door of the given type
+pub fn door_requirement(door_type: DoorType) -> Requirement
+{
+    use PickupType::*;
+    match door_type {
+        DoorType::Blue | DoorType::VerticalBlue |
+        DoorType::PowerOnly | DoorType::VerticalPowerOnly => Requirement::none(),
+        DoorType::Purple | DoorType::VerticalPurple => all![WaveBeam],
+        DoorType::White | DoorType::VerticalWhite => all![IceBeam],
+        DoorType::Red | DoorType::VerticalRed => all![PlasmaBeam],
+        DoorType::PowerBomb | DoorType::VerticalPowerBomb =>
+            all![MorphBall, any![PowerBomb, PowerBombExpansion]],
+        DoorType::Bomb | DoorType::VerticalBomb => all![MorphBall, MorphBallBomb],
+        DoorType::Boost => all![MorphBall, BoostBall],
+        DoorType::Missile | DoorType::VerticalMissile => all![Missile],
+        DoorType::Charge | DoorType::VerticalCharge => all![ChargeBeam],
+        DoorType::Super | DoorType::VerticalSuper => all![ChargeBeam, Missile, SuperMissile],
+        DoorType::Wavebuster | DoorType::VerticalWavebuster =>
+            all![ChargeBeam, Missile, WaveBeam, Wavebuster],
+        DoorType::Icespreader | DoorType::VerticalIcespreader =>
+            all![ChargeBeam, Missile, IceBeam, IceSpreader],
+        DoorType::Flamethrower | DoorType::VerticalFlamethrower =>
+            all![ChargeBeam, Missile, PlasmaBeam, Flamethrower],
+        // Only enemies can open these
+        DoorType::Ai | DoorType::VerticalAi |
+        DoorType::Disabled | DoorType::VerticalDisabled => Requirement::impossible(),
+    }
+}
+
+// The world and room of each pickup location, in layout order
+pub fn location_rooms() -> impl Iterator<Item = (World, &'static RoomInfo)>
+{
+    pickup_meta::PICKUP_LOCATIONS.iter()
+        .flat_map(|(pak_name, rooms)| {
+            let world = World::from_pak(pak_name).unwrap();
+            rooms.iter().flat_map(move |room_info| {
+                room_info.pickup_locations.iter().map(move |_| (world, room_info))
+            })
+        })
+}
+
+fn location_name(idx: usize) -> String
+{
+    location_rooms()
+        .nth(idx)
+        .map(|(world, room_info)| format!("{} - {}", world.as_string(), room_info.name))
+        .unwrap_or_else(|| format!("Location {}", idx))
+}
+
+// The standard 100 items
+pub fn default_item_pool() -> HashMap<PickupType, u32>
+{
+    PickupType::iter()
+        .map(|pickup_type| {
+            let n = match pickup_type {
+                PickupType::Missile => 50,
+                PickupType::EnergyTank => 14,
+                PickupType::PowerBombExpansion => 4,
+                PickupType::Nothing | PickupType::ScanVisor | PickupType::ShinyMissile => 0,
+                _ => 1,
+            };
+            (pickup_type, n)
+        })
+        .collect()
+}
+
+// Maps pickup names to how many of them should be placed
+pub fn parse_item_pool(names: &HashMap<String, u32>) -> Result<HashMap<PickupType, u32>, String>
+{
+    let mut pool = HashMap::new();
+    for (name, &n) in names {
+        let pickup_type = PickupType::iter()
+            .find(|pt| pt.name().to_lowercase() == name.to_lowercase())
+            .ok_or_else(|| format!("Unknown pickup '{}' in the item pool", name))?;
+        *pool.entry(pickup_type).or_insert(0) += n;
+    }
+    Ok(pool)
+}
+
+// What the player starts with, counted in pickups like the requirements are: every 5 missiles
+// are a Missile, and any power bombs are the Power Bomb
+pub fn starting_inventory(starting_items: &StartingItems) -> Inventory
+{
+    PickupType::iter()
+        .filter(|&pt| pt != PickupType::ShinyMissile && pt != PickupType::PowerBombExpansion)
+        .filter_map(|pickup_type| {
+            let amount = starting_items.amount(pickup_type.pickup_data().kind as usize)?;
+            let n = match pickup_type {
+                PickupType::Missile => amount.div_ceil(5),
+                PickupType::PowerBomb => amount.min(1),
+                _ => amount,
+            };
+            if n > 0 {
+                Some((pickup_type, n))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// Door types set explicitly in `excluded_doors`. Doors that are "random" aren't known until the
+// ISO is patched, and "default" doors are left blue.
+pub fn explicit_door_types(excluded_doors: &[HashMap<String, Vec<String>>; 7])
+    -> Result<HashMap<(u32, u32), DoorType>, String>
+{
+    let mut door_types = HashMap::new();
+    for (pak_name, rooms) in pickup_meta::PICKUP_LOCATIONS.iter() {
+        let world = World::from_pak(pak_name).unwrap();
+        for room_info in rooms.iter() {
+            let specs = match excluded_doors[world as usize].get(room_info.name) {
+                Some(specs) => specs,
+                None => continue,
+            };
+            for dock in room_info.door_locations.iter().filter_map(|door| door.dock_number) {
+                let spec = match specs.get(dock as usize) {
+                    Some(spec) if spec != "random" && spec != "default" => spec,
+                    _ => continue,
+                };
+                let door_type = DoorType::from_string(spec.to_string())
+                    .ok_or_else(|| format!("Unknown door type '{}'", spec))?;
+                door_types.insert((room_info.room_id, dock), door_type);
+            }
+        }
+    }
+    Ok(door_types)
+}
+
+// The rooms of every world and how they're connected: by the doors in each room, which are
+// known by their dock numbers, and by passages that don't have a door to open, like elevators
+#[derive(Clone, Debug)]
+pub struct RoomGraph
+{
+    // Where the player is once the pickups can be collected
+    start_room: u32,
+    // The doors and passages out of each room, keyed by room id, along with the room they lead to
+    exits: HashMap<u32, Vec<(Option<u32>, u32)>>,
+}
+
+impl RoomGraph
+{
+    pub fn new(start_room: u32) -> Self
+    {
+        let mut exits = HashMap::new();
+        exits.insert(start_room, vec![]);
+        RoomGraph { start_room, exits }
+    }
+
+    // The door at `dock` in `room` leads to `to_room`. The door on the other side is added
+    // separately, since it has its own color.
+    pub fn add_door(&mut self, room: u32, dock: u32, to_room: u32)
+    {
+        self.exits.entry(room).or_default().push((Some(dock), to_room));
+        self.exits.entry(to_room).or_default();
+    }
+
+    // A way between two rooms in both directions that doesn't need a door opened
+    pub fn add_passage(&mut self, a: u32, b: u32)
+    {
+        self.exits.entry(a).or_default().push((None, b));
+        self.exits.entry(b).or_default().push((None, a));
+    }
+
+    // What it takes to get into each room from the start room, which is any path there that
+    // opens every door on the way and has missiles for every missile locked room on the way.
+    // Rooms that can't be reached at all are left out.
+    fn room_requirements(&self, options: &LogicOptions) -> HashMap<u32, Requirement>
+    {
+        let mut reqs = HashMap::new();
+        reqs.insert(self.start_room, Requirement::none());
+        let mut queue = vec![self.start_room];
+        while let Some(room) = queue.pop() {
+            let room_req = reqs[&room].clone();
+            for &(dock, to_room) in &self.exits[&room] {
+                let door_req = match dock.and_then(|dock| options.door_types.get(&(room, dock))) {
+                    Some(&door_type) => door_requirement(door_type),
+                    None => Requirement::none(),
+                };
+                let mut req = all![&room_req, door_req];
+                if options.missile_locked_rooms.contains(&to_room) {
+                    req = all![req, PickupType::Missile];
+                }
+                let new_req = match reqs.get(&to_room) {
+                    Some(old_req) => any![old_req, req].simplified(),
+                    None => req.simplified(),
+                };
+                if reqs.get(&to_room) != Some(&new_req) {
+                    reqs.insert(to_room, new_req);
+                    queue.push(to_room);
+                }
+            }
+        }
+        reqs
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LogicOptions
+{
+    // The types of the doors that aren't left blue, keyed by room id and dock number
+    pub door_types: HashMap<(u32, u32), DoorType>,
+    // Rooms that still have missile locks
+    pub missile_locked_rooms: HashSet<u32>,
+    pub skip_impact_crater: bool,
+    // What the player has before collecting any pickups
+    pub starting_inventory: Inventory,
+    // How the rooms are connected. Door types and missile locks are checked along the way to
+    // each pickup, so they can't be checked without it.
+    pub room_graph: Option<RoomGraph>,
+}
+
+impl LogicOptions
+{
+    // The requirements of each location with the doors and missile locks on the way there. The
+    // location requirements themselves assume vanilla doors, so with neither a room graph nor
+    // anything changed they're used as they are.
+    pub fn location_requirements(&self) -> Result<Vec<LocationRequirements>, String>
+    {
+        let room_graph = match &self.room_graph {
+            Some(room_graph) => room_graph,
+            None if self.door_types.is_empty() && self.missile_locked_rooms.is_empty() => {
+                return Ok(normal_difficulty())
+            },
+            None => Err("The door colors and missile locks can't be checked without the room graph")?,
+        };
+        let room_reqs = room_graph.room_requirements(self);
+        normal_difficulty().into_iter()
+            .zip(location_rooms())
+            .map(|(loc, (world, room_info))| {
+                if !room_graph.exits.contains_key(&room_info.room_id) {
+                    Err(format!("{} - {} isn't in the room graph", world.as_string(), room_info.name))?
+                }
+                let room_req = room_reqs.get(&room_info.room_id)
+                    .cloned()
+                    .unwrap_or_else(Requirement::impossible);
+                Ok(LocationRequirements {
+                    required: all![loc.required, room_req],
+                    escape: loc.escape,
+                })
+            })
+            .collect()
+    }
+
+    // What's needed to finish the game
+    pub fn victory_requirement(&self) -> Requirement
+    {
+        use PickupType::*;
+        let artifacts = all![
+            ArtifactOfLifegiver, ArtifactOfWild, ArtifactOfWorld, ArtifactOfSun,
+            ArtifactOfElder, ArtifactOfSpirit, ArtifactOfTruth, ArtifactOfChozo,
+            ArtifactOfWarrior, ArtifactOfNewborn, ArtifactOfNature, ArtifactOfStrength,
+        ];
+        if self.skip_impact_crater {
+            all![artifacts, Missile]
+        } else {
+            // Metroid Prime needs every beam and visor, and the Phazon Suit for its last phase
+            all![artifacts, Missile, WaveBeam, IceBeam, PlasmaBeam, XRayVisor, ThermalVisor,
+                 PhazonSuit, SpaceJumpBoots]
+        }
+    }
+}
+
+pub struct Reachability
+{
+    // The starting inventory and everything that can be collected
+    pub inventory: Inventory,
+    // The indices of pickup locations that can't be reached, not counting ones with Nothing
+    pub unreachable_locations: Vec<usize>,
+    pub completable: bool,
+}
+
+impl Reachability
+{
+    // Describes why the layout can't be completed
+    pub fn error_message(&self, layout: &[PickupType]) -> String
+    {
+        let mut msg = "The layout isn't completable".to_string();
+        if !self.unreachable_locations.is_empty() {
+            msg += ". Unreachable pickups:";
+            for &idx in &self.unreachable_locations {
+                msg += &format!("\n    {}: {}", location_name(idx), layout[idx].name());
+            }
+        }
+        msg
+    }
+}
+
+pub fn check_reachability(layout: &[PickupType], options: &LogicOptions)
+    -> Result<Reachability, String>
+{
+    let reqs = options.location_requirements()?;
+    if layout.len() != reqs.len() {
+        Err(format!("The layout should have exactly {} pickups", reqs.len()))?
+    }
+
+    let mut inventory = options.starting_inventory.clone();
+    let mut collected = vec![false; layout.len()];
+    let mut progress = true;
+    while progress {
+        progress = false;
+        for (i, loc) in reqs.iter().enumerate() {
+            if collected[i] || !loc.required.is_satisfied(&inventory) {
+                continue;
+            }
+            let mut new_inventory = inventory.clone();
+            *new_inventory.entry(layout[i]).or_insert(0) += 1;
+            if loc.escape.is_satisfied(&new_inventory) {
+                inventory = new_inventory;
+                collected[i] = true;
+                progress = true;
+            }
+        }
+    }
+
+    let unreachable_locations = (0..layout.len())
+        .filter(|&i| !collected[i] && layout[i] != PickupType::Nothing)
+        .collect();
+    let completable = options.victory_requirement().is_satisfied(&inventory);
+    Ok(Reachability { inventory, unreachable_locations, completable })
+}
+
+pub fn verify_layout(layout: &[PickupType], options: &LogicOptions) -> Result<(), String>
+{
+    let reachability = check_reachability(layout, options)?;
+    if !reachability.completable {
+        Err(reachability.error_message(layout))?
+    }
+    Ok(())
+}
+
+// How many times the random doors are rolled looking for a completable seed
+pub const MAX_DOOR_ROLLS: usize = 100;
+
+// Rolls door colors with `roll` until the layout can be completed with the door types
+// `door_types` gives for them, or errors if no roll in MAX_DOOR_ROLLS works. If the layout can't
+// be completed even with just the doors in `options`, which no roll can fix, the first roll is
+// kept along with a warning saying why.
+pub fn roll_completable_doors<T, R, D>(
+    layout: &[PickupType],
+    options: &LogicOptions,
+    mut roll: R,
+    door_types: D,
+) -> Result<(T, Option<String>), String>
+    where R: FnMut() -> Result<T, String>,
+          D: Fn(&T) -> HashMap<(u32, u32), DoorType>,
+{
+    let first_roll = roll()?;
+    let reachability = check_reachability(layout, options)?;
+    if !reachability.completable {
+        let warning = format!("The door colors weren't checked. {}", reachability.error_message(layout));
+        return Ok((first_roll, Some(warning)));
+    }
+
+    let with_doors = |door_colors: &T| LogicOptions {
+        door_types: door_types(door_colors),
+        ..options.clone()
+    };
+    let first_reachability = check_reachability(layout, &with_doors(&first_roll))?;
+    if first_reachability.completable {
+        return Ok((first_roll, None));
+    }
+    for _ in 1..MAX_DOOR_ROLLS {
+        let door_colors = roll()?;
+        if check_reachability(layout, &with_doors(&door_colors))?.completable {
+            return Ok((door_colors, None));
+        }
+    }
+
+    Err(format!(
+        "No door colors that make the layout completable were found in {} tries. With the first \
+         ones: {}",
+        MAX_DOOR_ROLLS,
+        first_reachability.error_message(layout),
+    ))
+}
+
+// Places the items in the pool so that every location can be reached. This follows the web
+// generator: items that open up the most new locations for the fewest pickups are placed
+// first, in locations that are reachable (and can be escaped with the new item), and whatever
+// is left is placed randomly afterwards. A pool with fewer items than there are locations is
+// padded with Nothing.
+pub fn generate_layout<R>(
+    rng: &mut R,
+    item_pool: &HashMap<PickupType, u32>,
+    options: &LogicOptions,
+) -> Result<Vec<PickupType>, String>
+    where R: Rng
+{
+    let reqs = options.location_requirements()?;
+    let mut pool = item_pool.clone();
+    let pool_size: u32 = pool.values().sum();
+    if pool_size as usize > reqs.len() {
+        Err(format!("The item pool has {} items, but there are only {} locations",
+                    pool_size, reqs.len()))?
+    }
+    *pool.entry(PickupType::Nothing).or_insert(0) += reqs.len() as u32 - pool_size;
+
+    // The starting items count as part of the pool that has already been placed
+    for (&pickup_type, &n) in &options.starting_inventory {
+        *pool.entry(pickup_type).or_insert(0) += n;
+    }
+    let mut placed: Vec<Option<PickupType>> = vec![None; reqs.len()];
+    let mut obtained = options.starting_inventory.clone();
+
+    // Locations that can't be reached even with every item get Nothing
+    let mut unreachable_locations = vec![];
+    for (i, loc) in reqs.iter().enumerate() {
+        if loc.required.is_satisfied(&pool) && loc.escape.is_satisfied(&pool) {
+            unreachable_locations.push(i);
+        } else {
+            placed[i] = Some(PickupType::Nothing);
+            *obtained.entry(PickupType::Nothing).or_insert(0) += 1;
+        }
+    }
+    if count(&obtained, PickupType::Nothing) > count(&pool, PickupType::Nothing) {
+        Err(format!("{} locations can't be reached with the item pool, but it only has room for {} Nothings",
+                    count(&obtained, PickupType::Nothing), count(&pool, PickupType::Nothing)))?
+    }
+
+    let mut reachable_unplaced_locations = vec![];
+    loop {
+        unreachable_locations.retain(|&i| {
+            let loc = &reqs[i];
+            if loc.escape.is_satisfied(&obtained) && loc.required.is_satisfied(&obtained) {
+                reachable_unplaced_locations.push(i);
+                false
+            } else {
+                true
+            }
+        });
+
+        if unreachable_locations.is_empty() {
+            break;
+        }
+
+        // Find the smallest sets of pickups needed to reach one or more additional locations.
+        // Pickups are kept as indices so the choice between them is deterministic.
+        let mut smallest_needed_count = u32::MAX;
+        let mut smallest_needed_items = BTreeSet::new();
+        for &i in &unreachable_locations {
+            let loc = &reqs[i];
+            for required in &loc.required.0 {
+                for escape in &loc.escape.0 {
+                    let conjunct = merge_conjunct([&required[..], &escape[..]].concat());
+                    // Skip the alternatives that need more than the pool has
+                    if conjunct.iter().any(|&(pt, n)| count(&pool, pt) < n) {
+                        continue;
+                    }
+                    let mut needed_count = 0;
+                    let mut needed_items = BTreeSet::new();
+                    for &(pickup_type, n) in &conjunct {
+                        let current = count(&obtained, pickup_type);
+                        if current < n {
+                            needed_count += n - current;
+                            needed_items.insert(pickup_type.idx());
+                        }
+                    }
+                    if needed_count == smallest_needed_count {
+                        smallest_needed_items.extend(needed_items);
+                    } else if needed_count < smallest_needed_count {
+                        smallest_needed_count = needed_count;
+                        smallest_needed_items = needed_items;
+                    }
+                }
+            }
+        }
+
+        if smallest_needed_items.is_empty() ||
+            smallest_needed_count as usize > reachable_unplaced_locations.len() {
+            Err("Not enough locations are reachable to make any other location reachable")?
+        }
+
+        // Place one of the needed pickups. The set it came from will now be (one of) the
+        // smallest and will (potentially) have another of its members placed next iteration.
+        let item_list: Vec<_> = smallest_needed_items.into_iter().collect();
+        let item = PickupType::from_idx(item_list[rng.gen_range(0, item_list.len())]).unwrap();
+        let mut new_obtained = obtained.clone();
+        *new_obtained.entry(item).or_insert(0) += 1;
+
+        // Locations that need the new item to escape are valid places to put it
+        unreachable_locations.retain(|&i| {
+            let loc = &reqs[i];
+            if loc.escape.is_satisfied(&new_obtained) && loc.required.is_satisfied(&obtained) {
+                reachable_unplaced_locations.push(i);
+                false
+            } else {
+                true
+            }
+        });
+
+        if reachable_unplaced_locations.is_empty() {
+            Err("Ran out of reachable locations while placing items")?
+        }
+        let location = reachable_unplaced_locations
+            .remove(rng.gen_range(0, reachable_unplaced_locations.len()));
+        placed[location] = Some(item);
+        obtained = new_obtained;
+    }
+
+    // Every location can be reached, so whatever is left can go anywhere
+    let mut remaining_items = vec![];
+    for pickup_type in PickupType::iter() {
+        let (current, total) = (count(&obtained, pickup_type), count(&pool, pickup_type));
+        if current > total {
+            Err(format!("Placed too many of {}", pickup_type.name()))?
+        }
+        remaining_items.extend(vec![pickup_type; (total - current) as usize]);
+    }
+    if remaining_items.len() != reachable_unplaced_locations.len() {
+        Err("The number of remaining items doesn't match the number of remaining locations")?
+    }
+    remaining_items.shuffle(rng);
+    for (location, item) in reachable_unplaced_locations.into_iter().zip(remaining_items) {
+        placed[location] = Some(item);
+    }
+
+    let layout: Vec<_> = placed.into_iter().collect::<Option<_>>()
+        .ok_or("Not every location had an item placed in it")?;
+    verify_layout(&layout, options)?;
+    Ok(layout)
+}
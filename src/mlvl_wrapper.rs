@@ -1,31 +1,44 @@
 use structs::{
-    Area, AreaLayerFlags, Dependency, MemoryRelayConn, Mlvl, Mrea, SclyLayer, Resource,
-    ResourceSource
+    Area, AreaLayerFlags, Dependency, MemoryRelayConn, Mlvl, MlvlGraph, Mrea, SclyGraph, SclyLayer,
+    SclyObject, Resource, ResourceSource
 };
 use reader_writer::{CStr, DiffListCursor, FourCC, LazyArray};
 
+use crate::savw_wrapper::SavwEditor;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub struct MlvlEditor<'r>
 {
     pub mlvl: Mlvl<'r>,
+    pub savw: SavwEditor,
+    // Object indices handed out by `MlvlArea::new_object_id_from_layer_id`, per area
+    allocated_object_indices: HashMap<usize, HashSet<u32>>,
 }
 
 pub struct MlvlArea<'r, 'mlvl, 'cursor, 'list>
 {
     pub mrea_cursor: &'cursor mut DiffListCursor<'list, ResourceSource<'r>>,
     pub mlvl_area: &'mlvl mut Area<'r>,
+    pub mrea_idx: usize,
     pub layer_flags: &'mlvl mut AreaLayerFlags,
     pub layer_names: &'mlvl mut Vec<CStr<'r>>,
     pub memory_relay_conns: &'mlvl mut LazyArray<'r, MemoryRelayConn>,
+    pub savw: &'mlvl mut SavwEditor,
+    allocated_object_indices: &'mlvl mut HashSet<u32>,
 }
 
 impl<'r> MlvlEditor<'r>
 {
     pub fn new(mlvl: Mlvl<'r>) -> MlvlEditor<'r>
     {
-        MlvlEditor { mlvl }
+        MlvlEditor { mlvl, savw: SavwEditor::new(), allocated_object_indices: HashMap::new() }
+    }
+
+    // The areas of the world and the docks connecting them
+    pub fn graph(&mut self) -> MlvlGraph<'_, 'r>
+    {
+        MlvlGraph::new(&mut self.mlvl)
     }
 
     pub fn get_area<'s, 'cursor, 'list: 'cursor>(
@@ -43,9 +56,12 @@ impl<'r> MlvlEditor<'r>
         MlvlArea {
             mrea_cursor,
             mlvl_area: area,
+            mrea_idx: i,
             layer_flags: self.mlvl.area_layer_flags.as_mut_vec().get_mut(i).unwrap(),
             layer_names: self.mlvl.area_layer_names.mut_names_for_area(i).unwrap(),
             memory_relay_conns: &mut self.mlvl.memory_relay_conns,
+            savw: &mut self.savw,
+            allocated_object_indices: self.allocated_object_indices.entry(i).or_default(),
         }
     }
 }
@@ -62,12 +78,32 @@ impl<'r, 'mlvl, 'cursor, 'list> MlvlArea<'r, 'mlvl, 'cursor, 'list>
         self.mrea_cursor.value().unwrap().kind.as_mrea_mut().unwrap()
     }
 
+    pub fn scly_graph(&mut self) -> SclyGraph<'_, 'r>
+    {
+        SclyGraph::new(self.mrea().scly_section_mut())
+    }
+
+    // Delete objects from the area along with every connection to them, including the memory
+    // relay connections stored in the MLVL, and whatever the SAVW saves for them
+    pub fn remove_scly_objects(&mut self, instance_ids: &HashSet<u32>) -> Vec<SclyObject<'r>>
+    {
+        let removed = self.scly_graph().remove_objects(instance_ids);
+        self.memory_relay_conns.as_mut_vec().retain(|conn| {
+            !instance_ids.contains(&conn.sender_id) && !instance_ids.contains(&conn.target_id)
+        });
+        for obj in &removed {
+            self.savw.remove_object(obj.instance_id);
+        }
+        removed
+    }
+
     pub fn add_layer(&mut self, name: CStr<'r>)
     {
         // Mark this layer as active
         self.layer_flags.flags |= 1 << self.layer_flags.layer_count;
         self.layer_flags.layer_count += 1;
         self.layer_names.push(name);
+        self.savw.add_layer_toggle(self.mrea_idx as u32, self.layer_flags.layer_count - 1);
 
         {
             let deps = self.mlvl_area.dependencies.deps.as_mut_vec();
@@ -78,6 +114,77 @@ impl<'r, 'mlvl, 'cursor, 'list> MlvlArea<'r, 'mlvl, 'cursor, 'list>
         self.mrea().scly_section_mut().layers.as_mut_vec().push(SclyLayer::new());
     }
 
+    pub fn instance_ids(&mut self) -> HashSet<u32>
+    {
+        let mut ids = HashSet::new();
+        for layer in self.mrea().scly_section().layers.iter() {
+            ids.extend(layer.objects.iter().map(|obj| obj.instance_id));
+        }
+        ids
+    }
+
+    // Allocate an instance ID for a new object on the given layer. IDs are laid out the way the
+    // engine expects: the layer in the top 6 bits, the area's index in the MLVL in the next 10
+    // bits and the object's index within the area in the bottom 16 bits. Object indices are
+    // unique within an area regardless of layer, so an index is never reused, whether it belongs
+    // to an existing object or to an ID handed out earlier that hasn't been added yet.
+    pub fn new_object_id_from_layer_id(&mut self, layer_id: usize) -> u32
+    {
+        assert!(layer_id < 64, "Layer {} can't be encoded in an instance ID", layer_id);
+        assert!(layer_id < self.layer_flags.layer_count as usize,
+                "Area {} has no layer {}", self.mrea_idx, layer_id);
+
+        let mut used: HashSet<u32> = self.instance_ids().iter().map(|id| id & 0xFFFF).collect();
+        used.extend(self.allocated_object_indices.iter().cloned());
+
+        // Prefer appending after the highest index in use, but fall back to filling a gap
+        let index = match used.iter().max() {
+            None => 0,
+            Some(&max) if max < 0xFFFF => max + 1,
+            Some(_) => (0..0xFFFF).find(|i| !used.contains(i))
+                .unwrap_or_else(|| panic!("Area {} has no free object indices", self.mrea_idx)),
+        };
+        self.allocated_object_indices.insert(index);
+
+        ((layer_id as u32) << 26) | ((self.mrea_idx as u32) << 16) | index
+    }
+
+    pub fn new_object_id_from_layer_name(&mut self, layer_name: &str) -> u32
+    {
+        let layer_id = self.layer_names.iter()
+            .position(|name| name.to_str() == Ok(layer_name))
+            .unwrap_or_else(|| panic!("Area {} has no layer named {}", self.mrea_idx, layer_name));
+        self.new_object_id_from_layer_id(layer_id)
+    }
+
+    // The layer and object type of every object in the area, keyed by instance ID
+    pub fn object_snapshot(&mut self) -> HashMap<u32, (usize, u8)>
+    {
+        let mut snapshot = HashMap::new();
+        for (layer_idx, layer) in self.mrea().scly_section().layers.iter().enumerate() {
+            for obj in layer.objects.iter() {
+                snapshot.insert(obj.instance_id, (layer_idx, obj.property_data.object_type()));
+            }
+        }
+        snapshot
+    }
+
+    // Register any objects that are new since `snapshot` was taken, or that have moved to a
+    // different layer or been replaced with a different type of object, with the world's SAVW
+    pub fn register_changed_objects(&mut self, snapshot: &HashMap<u32, (usize, u8)>)
+    {
+        let savw = &mut *self.savw;
+        let scly = self.mrea_cursor.value().unwrap().kind.as_mrea_mut().unwrap().scly_section();
+        for (layer_idx, layer) in scly.layers.iter().enumerate() {
+            for obj in layer.objects.iter() {
+                let current = (layer_idx, obj.property_data.object_type());
+                if snapshot.get(&obj.instance_id) != Some(&current) {
+                    savw.add_scly_object(&obj);
+                }
+            }
+        }
+    }
+
     pub fn add_dependencies<I>(&mut self, pickup_resources: &HashMap<(u32, FourCC), Resource<'r>>,
                                layer_num: usize, deps: I)
         where I: Iterator<Item=Dependency>,
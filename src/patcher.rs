@@ -3,10 +3,7 @@ use structs::{FstEntryFile, GcDisc, Resource, ResourceKind};
 
 use crate::mlvl_wrapper::{MlvlArea, MlvlEditor};
 
-use std::{
-    collections::{HashMap, HashSet},
-    ops::RangeFrom,
-};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Debug)]
 struct ResourceKey<'r>
@@ -23,7 +20,7 @@ struct MreaKey<'r>
     room_id: u32,
 }
 
-type SclyPatch<'r, 's> = dyn FnMut(&mut PatcherState, &mut MlvlArea<'r, '_, '_, '_>) -> Result<(), String> + 's;
+type SclyPatch<'r, 's> = dyn FnMut(&mut MlvlArea<'r, '_, '_, '_>) -> Result<(), String> + 's;
 pub struct PrimePatcher<'r, 's>
 {
     file_patches: HashMap<&'s [u8], Box<dyn FnMut(&mut FstEntryFile<'r>) -> Result<(), String> + 's>>,
@@ -32,11 +29,6 @@ pub struct PrimePatcher<'r, 's>
     scly_patches: Vec<(MreaKey<'s>, Vec<Box<SclyPatch<'r, 's>>>)>,
 }
 
-pub struct PatcherState
-{
-    pub fresh_instance_id_range: RangeFrom<u32>,
-}
-
 impl<'r, 's> PrimePatcher<'r, 's>
 {
     pub fn new() -> PrimePatcher<'r, 's>
@@ -72,7 +64,7 @@ impl<'r, 's> PrimePatcher<'r, 's>
     }
 
     pub fn add_scly_patch<F>(&mut self, (pak_name, room_id): (&'s [u8], u32), f: F)
-        where F: FnMut(&mut PatcherState, &mut MlvlArea<'r, '_, '_, '_>) -> Result<(), String> + 's
+        where F: FnMut(&mut MlvlArea<'r, '_, '_, '_>) -> Result<(), String> + 's
     {
         let key = MreaKey { pak_name, room_id, };
         if let Some((_, v)) = self.scly_patches.iter_mut().find(|p| p.0 == key) {
@@ -84,10 +76,6 @@ impl<'r, 's> PrimePatcher<'r, 's>
 
     pub fn run(&mut self, gc_disc: &mut GcDisc<'r>) -> Result<(), String>
     {
-        let mut patcher_state = PatcherState {
-            fresh_instance_id_range: 0xDEADBABE..
-        };
-
         let files_to_patch = self.file_patches.keys()
             .map(|k| *k)
             .chain(self.scly_patches.iter().map(|p| p.0.pak_name))
@@ -99,7 +87,8 @@ impl<'r, 's> PrimePatcher<'r, 's>
 
         for (name, fst_entry) in files {
             if let Some(patch) = self.file_patches.get_mut(&name[..]) {
-                fst_entry.guess_kind();
+                fst_entry.try_guess_kind()
+                    .map_err(|e| format!("Failed to parse {}: {}", String::from_utf8_lossy(&name), e))?;
                 patch(&mut fst_entry.file_mut().unwrap())?
             }
 
@@ -111,7 +100,8 @@ impl<'r, 's> PrimePatcher<'r, 's>
                 continue;
             }
 
-            fst_entry.guess_kind();
+            fst_entry.try_guess_kind()
+                .map_err(|e| format!("Failed to parse {}: {}", String::from_utf8_lossy(&name), e))?;
             let pak = match fst_entry.file_mut().unwrap() {
                 structs::FstEntryFile::Pak(pak) => pak,
                 _ => panic!(),
@@ -133,13 +123,20 @@ impl<'r, 's> PrimePatcher<'r, 's>
 
                 let mlvl = pak.resources.iter()
                     .find(|i| i.fourcc() == reader_writer::FourCC::from_bytes(b"MLVL"))
-                    .unwrap()
-                    .kind.as_mlvl().unwrap().into_owned();
+                    .ok_or_else(|| format!("Failed to find the MLVL in {}",
+                                           String::from_utf8_lossy(&name)))?
+                    .kind.try_as_mlvl()
+                    .map_err(|e| format!("Failed to parse the MLVL in {}: {}",
+                                         String::from_utf8_lossy(&name), e))?
+                    .ok_or_else(|| format!("The MLVL in {} isn't parseable",
+                                           String::from_utf8_lossy(&name)))?
+                    .into_owned();
                 Some(MlvlEditor::new(mlvl))
             } else {
                 None
             };
 
+            let mut savw_editor = None;
             let mut cursor = pak.resources.cursor();
             while cursor.peek().is_some() {
                 let mut cursor = cursor.cursor_advancer();
@@ -149,6 +146,14 @@ impl<'r, 's> PrimePatcher<'r, 's>
                     id: cursor.peek().unwrap().file_id,
                 };
 
+                // Write the MLVL edited by the scly patches back first, so that any patches for
+                // the MLVL itself are applied on top of it rather than being overwritten by it
+                if cursor.peek().unwrap().fourcc() == b"MLVL".into() && mlvl_editor.is_some() {
+                    let MlvlEditor { mlvl, savw, .. } = mlvl_editor.take().unwrap();
+                    savw_editor = Some((mlvl.world_savw, savw));
+                    cursor.value().unwrap().kind = ResourceKind::Mlvl(mlvl);
+                }
+
                 for (patch_key, patch_func) in self.resource_patches.iter_mut() {
                     if *patch_key == res_key {
                         patch_func(cursor.value().unwrap())?;
@@ -160,16 +165,52 @@ impl<'r, 's> PrimePatcher<'r, 's>
                     room_id: cursor.peek().unwrap().file_id,
                 };
                 if let Some((_, patches)) = self.scly_patches.iter_mut().find(|p| p.0 == mrea_key) {
+                    // Parse the MREA up front so a malformed one is an error rather than a panic
+                    // once the patches start editing it
+                    cursor.value().unwrap().kind.try_as_mrea_mut()
+                        .map_err(|e| format!("Failed to parse MREA 0x{:08X}: {}",
+                                             mrea_key.room_id, e))?;
                     let mut mlvl_area = mlvl_editor.as_mut().unwrap().get_area(&mut cursor);
+                    let snapshot = mlvl_area.object_snapshot();
                     for patch in patches.iter_mut() {
-                        patch(&mut patcher_state, &mut mlvl_area)?;
+                        patch(&mut mlvl_area)?;
                     }
+                    mlvl_area.register_changed_objects(&snapshot);
                 }
+            }
 
-                if cursor.peek().unwrap().fourcc() == b"MLVL".into() && mlvl_editor.is_some() {
-                    let mlvl = mlvl_editor.take().unwrap().mlvl;
-                    cursor.value().unwrap().kind = ResourceKind::Mlvl(mlvl);
+            // Now that every patch for this world has been run, make sure the world's SAVW
+            // knows about any relays, layers, doors, and scans that were added.
+            if let Some((savw_id, savw_editor)) = savw_editor {
+                if savw_editor.is_empty() {
+                    continue;
+                }
+                // Scans on new objects go in the same logbook category as their SCAN says
+                let unresolved_scans: HashSet<u32> = savw_editor.unresolved_scans().collect();
+                let scan_categories: HashMap<u32, u32> = pak.resources.iter()
+                    .filter(|res| res.fourcc() == b"SCAN".into() &&
+                                  unresolved_scans.contains(&res.file_id))
+                    .filter_map(|res| {
+                        res.kind.try_as_scan()
+                            .map_err(|e| format!("Failed to parse SCAN 0x{:08X}: {}", res.file_id, e))
+                            .map(|scan| scan.map(|scan| (res.file_id, scan.category)))
+                            .transpose()
+                    })
+                    .collect::<Result<_, String>>()?;
+
+                let mut cursor = pak.resources.cursor();
+                while let Some(res) = cursor.peek() {
+                    if res.fourcc() == b"SAVW".into() && res.file_id == savw_id {
+                        break;
+                    }
+                    cursor.next();
                 }
+                let savw = cursor.value()
+                    .ok_or_else(|| format!("Failed to find SAVW 0x{:08X}", savw_id))?
+                    .kind.try_as_savw_mut()
+                    .map_err(|e| format!("Failed to parse SAVW 0x{:08X}: {}", savw_id, e))?
+                    .ok_or_else(|| format!("SAVW 0x{:08X} isn't parseable", savw_id))?;
+                savw_editor.apply(savw, &scan_categories);
             }
         }
         Ok(())
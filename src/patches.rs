@@ -23,10 +23,16 @@ use crate::{
     memmap,
     mlvl_wrapper,
     pickup_meta::{self, PickupType},
-    door_meta::{DoorType, BlastShieldType, DoorLocation, Weights, World},
+    door_meta::{DoorType, BlastShieldType, DoorLocation, Weights, World, is_vertical_door},
+    door_randomizer,
+    logic,
     reader_writer,
-    patcher::{PatcherState, PrimePatcher},
-    structs,
+    patcher::PrimePatcher,
+    savw_wrapper::SavwEditor,
+    spoiler::{self, Spoiler, SpoilerDoor, SpoilerElevator, SpoilerLocation},
+    starting_items::StartingItems,
+    structs::{self, DockId, MlvlGraph},
+    AssetIdAllocator,
     GcDiscLookupExtensions,
     ResourceData,
 };
@@ -49,7 +55,7 @@ use std::{
     collections::{HashMap, HashSet},
     ffi::CString,
     fmt,
-    fs::File,
+    fs::{self, File},
     io::Write,
     iter,
     mem,
@@ -77,14 +83,69 @@ pub struct AetherTransform{
     scale: Xyz,
 }
 
+// Changes to a single pickup on top of what its `PickupType` normally gives. `amount` sets both
+// the capacity and the current amount given, `max_increase` and `curr_increase` set them
+// individually. `model` is the name of the pickup type (or `PickupModel`) whose model (and scan)
+// to show instead.
+// `foreign_item` turns the pickup into an item for another multiworld player: instead of being
+// given to the player, its id is reported to the multiworld client.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct PickupOverride {
+    amount: Option<u32>,
+    max_increase: Option<u32>,
+    curr_increase: Option<u32>,
+    model: Option<String>,
+    scan_text: Option<String>,
+    hudmemo_text: Option<String>,
+    foreign_item: Option<u32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PickupLocationOverride {
+    room: String,
+    #[serde(default)]
+    index: usize,
+    #[serde(flatten)]
+    overrides: PickupOverride,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct AdditionalItem {
     room: String,
     item_type: String,
     position: Xyz,
+    #[serde(flatten)]
+    overrides: PickupOverride,
+}
+
+// A chain of pickup types that are given out in order. Picking up any item in the chain gives
+// the first one the player doesn't have yet, e.g. "Progressive Suit" with Varia, Gravity and
+// Phazon.
+#[derive(Deserialize, Debug)]
+pub struct ProgressiveItem {
+    name: String,
+    items: Vec<String>,
+}
+
+// A recolored or retextured copy of a pickup's model, shown at a location by naming it as the
+// `model` of a `PickupOverride`. `hue_shift` rotates the hue of every texture of `base`'s model
+// by that many degrees. `textures` are paths to TXTR files replacing the textures of the model's
+// first material set in order, `null` keeps a texture (hue shifted if asked to).
+#[derive(Deserialize, Debug)]
+pub struct PickupModel {
+    name: String,
+    base: String,
+    #[serde(default)]
+    hue_shift: Option<f32>,
+    #[serde(default)]
+    textures: Vec<Option<String>>,
 }
 
 const ARTIFACT_OF_TRUTH_REQ_LAYER: u32 = 24;
+// Indices into the layout of the locations whose hudmemos stay modal even with skip_hudmenus
+// (see update_hudmemo). Every pickup now gets its own index, so these apply to the vanilla
+// rooms they name no matter what item is placed there; additional items come after the layout
+// and are never affected.
 const ALWAYS_MODAL_HUDMENUS: &[usize] = &[23, 50, 63];
 
 
@@ -871,8 +932,12 @@ fn create_shiny_missile_assets<'r>(
         let shiny_missile_anim = ResourceData::new(
             &resources[&resource_info!("Missile_Launcher_ready.ANIM").into()]
         );
-        let mut anim_bytes = shiny_missile_anim.decompress().into_owned();
-        custom_asset_ids::SHINY_MISSILE_EVNT.write_to(&mut std::io::Cursor::new(&mut anim_bytes[8..])).unwrap();
+        let anim_bytes = shiny_missile_anim.decompress().into_owned();
+        let mut anim = Reader::new(&anim_bytes[..]).read::<structs::Anim>(());
+        anim.set_evnt(custom_asset_ids::SHINY_MISSILE_EVNT);
+
+        let mut anim_bytes = vec![];
+        anim.write_to(&mut anim_bytes).unwrap();
         let len = anim_bytes.len();
         anim_bytes.extend(reader_writer::pad_bytes(32, len).iter());
         pickup_meta::build_resource(
@@ -883,6 +948,245 @@ fn create_shiny_missile_assets<'r>(
     [shiny_missile_cmdl, shiny_missile_ancs, shiny_missile_evnt, shiny_missile_anim]
 }
 
+// Rotate the hue of a color by `degrees`, keeping its saturation and brightness
+fn shift_hue(rgb: [u8; 3], degrees: f32) -> [u8; 3]
+{
+    let [r, g, b] = [rgb[0] as f32 / 255., rgb[1] as f32 / 255., rgb[2] as f32 / 255.];
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    if chroma == 0. {
+        return rgb;
+    }
+
+    let hue = if max == r {
+        ((g - b) / chroma).rem_euclid(6.)
+    } else if max == g {
+        (b - r) / chroma + 2.
+    } else {
+        (r - g) / chroma + 4.
+    };
+    let hue = (hue + degrees / 60.).rem_euclid(6.);
+
+    let x = chroma * (1. - ((hue % 2.) - 1.).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+    let to_u8 = |c: f32| ((c + min) * 255.).round().clamp(0., 255.) as u8;
+    [to_u8(r), to_u8(g), to_u8(b)]
+}
+
+// The bytes of a resource that may or may not have been generated by the patcher
+fn pickup_resource_bytes<'a>(res: &'a structs::Resource) -> Cow<'a, [u8]>
+{
+    match &res.kind {
+        structs::ResourceKind::External(bytes, _) => Cow::Borrowed(&bytes[..]),
+        _ => ResourceData::new(res).decompress(),
+    }
+}
+
+fn padded_resource_bytes<T: Writable>(t: &T) -> Vec<u8>
+{
+    let mut bytes = vec![];
+    t.write_to(&mut bytes).unwrap();
+
+    // Ensure the length is a multiple of 32
+    let len = bytes.len();
+    bytes.extend(reader_writer::pad_bytes(32, len).iter());
+    bytes
+}
+
+// A `PickupModel` with its assets generated
+#[derive(Debug)]
+pub struct CustomPickupModel
+{
+    base: PickupType,
+    pickup: structs::Pickup<'static>,
+    dependencies: Vec<(u32, FourCC)>,
+}
+
+impl CustomPickupModel
+{
+    // Also returns the generated assets
+    fn new<'r>(
+        pickup_model: &PickupModel,
+        base: PickupType,
+        pickup_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+        asset_ids: &mut AssetIdAllocator,
+    ) -> Result<(Self, Vec<structs::Resource<'r>>), String>
+    {
+        let find_resource = |id: u32, fourcc: &[u8; 4]| {
+            pickup_resources.get(&(id, fourcc.into()))
+                .ok_or_else(|| format!("Missing {} {:08X} needed by {}",
+                                       FourCC::from_bytes(fourcc), id, pickup_model.name))
+        };
+
+        let base_pickup = base.pickup_data();
+        let is_dependency = |id: u32, fourcc: &[u8; 4]| base.dependencies().contains(&(id, fourcc.into()));
+        let ancs_bytes = if is_dependency(base_pickup.ancs.file_id, b"ANCS") {
+            Some(pickup_resource_bytes(find_resource(base_pickup.ancs.file_id, b"ANCS")?))
+        } else {
+            None
+        };
+        let mut ancs = ancs_bytes.as_ref()
+            .map(|bytes| Reader::new(&bytes[..]).read::<structs::Ancs>(()));
+
+        // The pickup is drawn with its ANCS's character if it has one, so that model is the one
+        // `textures` refers to
+        let mut cmdl_ids = vec![];
+        if let Some(ancs) = &ancs {
+            let char_info = ancs.char_set.char_info.iter()
+                .nth(base_pickup.ancs.node_index as usize)
+                .ok_or_else(|| format!("{}'s ANCS has no character {}",
+                                       base.name(), base_pickup.ancs.node_index))?;
+            cmdl_ids.push(char_info.cmdl);
+        }
+        if is_dependency(base_pickup.cmdl, b"CMDL") && !cmdl_ids.contains(&base_pickup.cmdl) {
+            cmdl_ids.push(base_pickup.cmdl);
+        }
+        if cmdl_ids.is_empty() {
+            Err(format!("{} doesn't have a model to change", base.name()))?
+        }
+        let cmdl_bytes = cmdl_ids.iter()
+            .map(|&id| Ok(pickup_resource_bytes(find_resource(id, b"CMDL")?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let mut cmdls = cmdl_bytes.iter()
+            .map(|bytes| Reader::new(&bytes[..]).read::<structs::Cmdl>(()))
+            .collect::<Vec<_>>();
+
+        let mut new_resources = vec![];
+        let mut new_txtr_ids = HashMap::new();
+
+        let textures = cmdls[0].material_sets.iter().next()
+            .map(|material_set| material_set.texture_ids.iter().map(|id| *id).collect::<Vec<_>>())
+            .unwrap_or_default();
+        if pickup_model.textures.len() > textures.len() {
+            Err(format!("{} has {} textures to replace, but {}'s model only has {}",
+                        pickup_model.name, pickup_model.textures.len(), base.name(), textures.len()))?
+        }
+        for (&old_id, path) in textures.iter().zip(pickup_model.textures.iter()) {
+            let path = match path {
+                Some(path) => path,
+                None => continue,
+            };
+            let bytes = fs::read(path)
+                .map_err(|e| format!("Failed to read texture {}: {}", path, e))?;
+            Reader::new(&bytes[..]).try_read::<structs::Txtr>(())
+                .map_err(|e| format!("{} isn't a valid TXTR: {}", path, e))?;
+            let new_id = asset_ids.next_id()?;
+            new_txtr_ids.insert(old_id, new_id);
+            new_resources.push(pickup_meta::build_resource(
+                new_id,
+                structs::ResourceKind::External(bytes, b"TXTR".into())
+            ));
+        }
+
+        if let Some(degrees) = pickup_model.hue_shift {
+            let texture_ids = cmdls.iter()
+                .flat_map(|cmdl| cmdl.material_sets.iter())
+                .flat_map(|material_set| material_set.texture_ids.iter().map(|id| *id).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            for old_id in texture_ids {
+                if new_txtr_ids.contains_key(&old_id) {
+                    continue;
+                }
+                let bytes = pickup_resource_bytes(find_resource(old_id, b"TXTR")?);
+                let mut txtr = Reader::new(&bytes[..]).read::<structs::Txtr>(());
+                txtr.map_colors(|rgb| shift_hue(rgb, degrees));
+
+                let new_id = asset_ids.next_id()?;
+                new_txtr_ids.insert(old_id, new_id);
+                new_resources.push(pickup_meta::build_resource(
+                    new_id,
+                    structs::ResourceKind::External(padded_resource_bytes(&txtr), b"TXTR".into())
+                ));
+            }
+        }
+
+        if new_txtr_ids.is_empty() {
+            Err(format!("{} doesn't change any of {}'s textures", pickup_model.name, base.name()))?
+        }
+
+        let mut new_cmdl_ids = HashMap::new();
+        for (&old_id, cmdl) in cmdl_ids.iter().zip(cmdls.iter_mut()) {
+            for material_set in cmdl.material_sets.as_mut_vec().iter_mut() {
+                for texture_id in material_set.texture_ids.as_mut_vec().iter_mut() {
+                    *texture_id = new_txtr_ids.get(texture_id).cloned().unwrap_or(*texture_id);
+                }
+            }
+
+            let new_id = asset_ids.next_id()?;
+            new_cmdl_ids.insert(old_id, new_id);
+            new_resources.push(pickup_meta::build_resource(
+                new_id,
+                structs::ResourceKind::External(padded_resource_bytes(cmdl), b"CMDL".into())
+            ));
+        }
+
+        let mut pickup = base_pickup.clone();
+        pickup.cmdl = new_cmdl_ids.get(&pickup.cmdl).cloned().unwrap_or(pickup.cmdl);
+        if let Some(ancs) = &mut ancs {
+            for char_info in ancs.char_set.char_info.as_mut_vec().iter_mut() {
+                char_info.cmdl = new_cmdl_ids.get(&char_info.cmdl).cloned().unwrap_or(char_info.cmdl);
+            }
+
+            pickup.ancs.file_id = asset_ids.next_id()?;
+            new_resources.push(pickup_meta::build_resource(
+                pickup.ancs.file_id,
+                structs::ResourceKind::External(padded_resource_bytes(ancs), b"ANCS".into())
+            ));
+        }
+
+        // The original assets stay as dependencies, as particles and the like may still use them
+        let mut dependencies = base.dependencies().to_vec();
+        dependencies.extend(new_resources.iter().map(|res| (res.file_id, res.fourcc())));
+
+        let model = CustomPickupModel {
+            base,
+            pickup,
+            dependencies,
+        };
+        Ok((model, new_resources))
+    }
+}
+
+fn create_custom_pickup_models(
+    pickup_resources: &mut HashMap<(u32, FourCC), structs::Resource>,
+    config: &ParsedConfig,
+    asset_ids: &mut AssetIdAllocator,
+) -> Result<HashMap<String, CustomPickupModel>, String>
+{
+    let mut models = HashMap::new();
+    for pickup_model in config.pickup_models.iter() {
+        let name = pickup_model.name.to_lowercase();
+        if PickupType::iter().any(|pt| pt.name().to_lowercase() == name) {
+            Err(format!("Pickup model {} has the same name as a pickup", pickup_model.name))?
+        }
+        let base = PickupType::iter()
+            .find(|pt| pt.name().to_lowercase() == pickup_model.base.to_lowercase())
+            .ok_or_else(|| format!("Unknown pickup '{}'", pickup_model.base))?;
+
+        let (model, resources) = CustomPickupModel::new(
+            pickup_model,
+            base,
+            pickup_resources,
+            asset_ids,
+        )?;
+        for res in resources {
+            pickup_resources.insert((res.file_id, res.fourcc()), res);
+        }
+        if models.insert(name, model).is_some() {
+            Err(format!("Pickup model {} is defined more than once", pickup_model.name))?
+        }
+    }
+    Ok(models)
+}
+
 fn create_item_scan_strg_pair<'r>(
     new_scan: u32,
     new_strg: u32,
@@ -957,6 +1261,119 @@ fn post_pickup_relay_template<'r>(instance_id: u32, connections: &'static [struc
     }
 }
 
+// Pickup overrides keyed by room and pickup location index within the room
+pub type PickupOverrides<'a> = HashMap<(u32, usize), ResolvedPickupOverride<'a>>;
+
+// Generate the assets needed by the pickup overrides. Returns the overrides for the randomized
+// pickup locations and the overrides for each additional item.
+pub fn resolve_pickup_overrides<'a>(
+    location_overrides: &[PickupLocationOverride],
+    additional_items: &[AdditionalItem],
+    multiworld: bool,
+    pickup_resources: &mut HashMap<(u32, FourCC), structs::Resource>,
+    custom_models: &'a HashMap<String, CustomPickupModel>,
+    asset_ids: &mut AssetIdAllocator,
+) -> Result<(PickupOverrides<'a>, Vec<ResolvedPickupOverride<'a>>), String>
+{
+    let has_foreign_items = location_overrides.iter()
+        .map(|location_override| &location_override.overrides)
+        .chain(additional_items.iter().map(|item| &item.overrides))
+        .any(|pickup_override| pickup_override.foreign_item.is_some());
+    if has_foreign_items && !multiworld {
+        Err("Foreign items require multiworld to be enabled")?
+    }
+
+    let mut pickup_overrides = HashMap::new();
+    for location_override in location_overrides.iter() {
+        let room = spawn_room_from_string(location_override.room.to_string());
+        let location_count = pickup_meta::PICKUP_LOCATIONS.iter()
+            .flat_map(|(_, rooms)| rooms.iter())
+            .find(|room_info| room_info.room_id == room.mrea)
+            .map(|room_info| room_info.pickup_locations.len())
+            .unwrap_or(0);
+        if location_override.index >= location_count {
+            Err(format!("{} has no pickup location {}", location_override.room, location_override.index))?
+        }
+
+        let resolved = ResolvedPickupOverride::new(
+            &location_override.overrides,
+            pickup_resources,
+            custom_models,
+            asset_ids,
+        )?;
+        if pickup_overrides.insert((room.mrea, location_override.index), resolved).is_some() {
+            Err(format!("Pickup location {} of {} is overridden more than once",
+                        location_override.index, location_override.room))?
+        }
+    }
+
+    let additional_item_overrides = additional_items.iter()
+        .map(|item| ResolvedPickupOverride::new(
+            &item.overrides,
+            pickup_resources,
+            custom_models,
+            asset_ids,
+        ))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((pickup_overrides, additional_item_overrides))
+}
+
+// Parse the progressive item chains into the form the REL expects
+fn progressive_item_chains(config: &ParsedConfig)
+    -> Result<Vec<(&str, Vec<PickupType>)>, String>
+{
+    if config.progressive_items.len() > PROGRESSIVE_ITEM_CHAIN_COUNT {
+        Err(format!("At most {} progressive items are supported", PROGRESSIVE_ITEM_CHAIN_COUNT))?
+    }
+
+    let mut seen = HashSet::new();
+    config.progressive_items.iter()
+        .map(|progressive_item| {
+            if progressive_item.items.is_empty()
+                || progressive_item.items.len() > PROGRESSIVE_ITEM_CHAIN_LEN {
+                Err(format!("{} must have between 1 and {} items",
+                            progressive_item.name, PROGRESSIVE_ITEM_CHAIN_LEN))?
+            }
+            let items = progressive_item.items.iter()
+                .map(|name| {
+                    let pickup_type = PickupType::iter()
+                        .find(|pt| pt.name().to_lowercase() == name.to_lowercase())
+                        .ok_or_else(|| format!("Unknown pickup '{}'", name))?;
+                    if !seen.insert(pickup_type.pickup_data().kind) {
+                        Err(format!("{} is part of more than one progressive item", name))?
+                    }
+                    Ok(pickup_type)
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok((&progressive_item.name[..], items))
+        })
+        .collect()
+}
+
+// Every pickup in a progressive item chain gets a HUD memo naming the chain rather than the
+// item, since the item actually given out is only decided when it is picked up
+fn add_progressive_item_strgs(
+    pickup_resources: &mut HashMap<(u32, FourCC), structs::Resource>,
+    config: &ParsedConfig,
+    asset_ids: &mut AssetIdAllocator,
+) -> Result<HashMap<PickupType, u32>, String>
+{
+    let mut hudmemo_strgs = HashMap::new();
+    for (name, items) in progressive_item_chains(config)? {
+        let id = asset_ids.next_id()?;
+        let strg = pickup_meta::build_resource(
+            id,
+            structs::ResourceKind::Strg(structs::Strg::from_strings(vec![
+                format!("&just=center;{} acquired!\0", name),
+            ])),
+        );
+        pickup_resources.insert((id, b"STRG".into()), strg);
+        hudmemo_strgs.extend(items.into_iter().map(|pt| (pt, id)));
+    }
+    Ok(hudmemo_strgs)
+}
+
 fn add_skip_hudmemos_strgs(pickup_resources: &mut HashMap<(u32, FourCC), structs::Resource>)
 {
     for pt in PickupType::iter() {
@@ -977,7 +1394,63 @@ fn add_skip_hudmemos_strgs(pickup_resources: &mut HashMap<(u32, FourCC), structs
     }
 }
 
-fn build_artifact_temple_totem_scan_strings<R>(pickup_layout: &[PickupType], rng: &mut R)
+// A pickup placed in the seed, either at one of the locations from `PICKUP_LOCATIONS` or at one
+// added through `additional_items`
+#[derive(Clone, Copy, Debug)]
+struct PlacedPickup
+{
+    // The locations from `PICKUP_LOCATIONS` come first, in layout order, followed by the
+    // additional items
+    location_idx: usize,
+    pak_name: &'static str,
+    room_id: u32,
+    // The room's index in the world's MLVL
+    room_idx: u32,
+    room_name: &'static str,
+    pickup_type: PickupType,
+}
+
+fn collect_placed_pickups(pickup_layout: &[PickupType], config: &ParsedConfig)
+    -> Result<Vec<PlacedPickup>, String>
+{
+    let layout_rooms = pickup_meta::PICKUP_LOCATIONS.iter()
+        .flat_map(|(pak_name, rooms)| {
+            rooms.iter().enumerate().flat_map(move |(room_idx, room_info)| {
+                iter::repeat((*pak_name, room_idx as u32, room_info))
+                    .take(room_info.pickup_locations.len())
+            })
+        });
+    let additional_items = config.additional_items.iter()
+        .map(|item| {
+            let room = spawn_room_from_string(item.room.to_string());
+            let room_info = pickup_meta::PICKUP_LOCATIONS.iter()
+                .flat_map(|(_, rooms)| rooms.iter())
+                .find(|room_info| room_info.room_id == room.mrea)
+                .ok_or_else(|| format!(
+                    "Additional items can't be placed in {}, it has no pickup locations",
+                    item.room,
+                ))?;
+            let pickup_type = PickupType::from_string(item.item_type.to_string());
+            Ok(((room.pak_name, room.mrea_idx, room_info), pickup_type))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let placed_pickups = layout_rooms.zip(pickup_layout.iter().cloned())
+        .chain(additional_items)
+        .enumerate()
+        .map(|(location_idx, ((pak_name, room_idx, room_info), pickup_type))| PlacedPickup {
+            location_idx,
+            pak_name,
+            room_id: room_info.room_id,
+            room_idx,
+            room_name: room_info.name,
+            pickup_type,
+        })
+        .collect();
+    Ok(placed_pickups)
+}
+
+fn build_artifact_temple_totem_scan_strings<R>(placed_pickups: &[PlacedPickup], rng: &mut R)
     -> [String; 12]
     where R: Rng
 {
@@ -1018,16 +1491,12 @@ fn build_artifact_temple_totem_scan_strings<R>(pickup_layout: &[PickupType], rng
         String::new(), String::new(), String::new(), String::new(),
     ];
 
-    let names_iter = pickup_meta::PICKUP_LOCATIONS.iter()
-        .flat_map(|i| i.1.iter()) // Flatten out the rooms of the paks
-        .flat_map(|l| iter::repeat((l.room_id, l.name)).take(l.pickup_locations.len()));
-    let iter = pickup_layout.iter()
-        .zip(names_iter)
+    let iter = placed_pickups.iter()
         // ▼▼▼▼ Only yield artifacts ▼▼▼▼
-        .filter(|&(pt, _)| pt.is_artifact());
+        .filter(|placed| placed.pickup_type.is_artifact());
 
-    // Shame there isn't a way to flatten tuples automatically
-    for (pt, (room_id, name)) in iter {
+    for placed in iter {
+        let pt = placed.pickup_type;
         let artifact_id = pt.idx() - PickupType::ArtifactOfLifegiver.idx();
         if scan_text[artifact_id].len() != 0 {
             // If there are multiple of this particular artifact, then we use the first instance
@@ -1038,11 +1507,11 @@ fn build_artifact_temple_totem_scan_strings<R>(pickup_layout: &[PickupType], rng
         // If there are specific messages for this room, choose one, other wise choose a generic
         // message.
         let template = specific_room_templates.iter_mut()
-            .find(|row| row.0 == room_id)
+            .find(|row| row.0 == placed.room_id)
             .and_then(|row| row.1.pop())
             .unwrap_or_else(|| generic_templates_iter.next().unwrap());
         let pickup_name = pt.name();
-        scan_text[artifact_id] = template.replace("{room}", name).replace("{pickup}", pickup_name);
+        scan_text[artifact_id] = template.replace("{room}", placed.room_name).replace("{pickup}", pickup_name);
     }
 
     // Set a default value for any artifacts that we didn't find.
@@ -1078,9 +1547,7 @@ fn patch_morphball_hud(res: &mut structs::Resource)
     -> Result<(), String>
 {
     let frme = res.kind.as_frme_mut().unwrap();
-    let widget = frme.widgets.iter_mut()
-        .find(|widget| widget.name == b"textpane_bombdigits\0".as_cstr())
-        .unwrap();
+    let widget = frme.widget_mut(b"textpane_bombdigits").unwrap();
     // Use the version of Deface18 that has more than just numerical characters for the powerbomb
     // ammo counter
     match &mut widget.kind {
@@ -1117,41 +1584,87 @@ fn patch_mines_savw_for_phazon_suit_scan(res: &mut structs::Resource)
     -> Result<(), String>
 {
     // Add a scan for the Phazon suit.
-    let savw = res.kind.as_savw_mut().unwrap();
-    savw.scan_array.as_mut_vec().push(structs::ScannableObject {
-        scan: custom_asset_ids::PHAZON_SUIT_SCAN,
-        logbook_category: 0,
-    });
+    let mut savw_editor = SavwEditor::new();
+    savw_editor.add_scan(custom_asset_ids::PHAZON_SUIT_SCAN, 0);
+    savw_editor.apply(res.kind.as_savw_mut().unwrap(), &HashMap::new());
     Ok(())
 }
 
+// The model a pickup is shown with
+#[derive(Copy, Clone, Debug)]
+enum PickupModelRef<'a>
+{
+    Vanilla(PickupType),
+    Custom(&'a CustomPickupModel),
+}
+
 #[derive(Copy, Clone, Debug)]
-enum MaybeObfuscatedPickup
+enum MaybeObfuscatedPickup<'a>
 {
     Unobfuscated(PickupType),
     Obfuscated(PickupType),
+    // The pickup, shown with another model and scan
+    Disguised(PickupType, PickupModelRef<'a>),
 }
 
-impl MaybeObfuscatedPickup
+impl<'a> MaybeObfuscatedPickup<'a>
 {
+    fn new(pickup_type: PickupType, model: Option<PickupModelRef<'a>>, obfuscate: bool) -> Self
+    {
+        match model {
+            Some(model) => MaybeObfuscatedPickup::Disguised(pickup_type, model),
+            None if obfuscate => MaybeObfuscatedPickup::Obfuscated(pickup_type),
+            None => MaybeObfuscatedPickup::Unobfuscated(pickup_type),
+        }
+    }
+
     fn orig(&self) -> PickupType
     {
         match self {
             MaybeObfuscatedPickup::Unobfuscated(pt) => *pt,
             MaybeObfuscatedPickup::Obfuscated(pt) => *pt,
+            MaybeObfuscatedPickup::Disguised(pt, _) => *pt,
+        }
+    }
+
+    // The model that is shown
+    fn model(&self) -> PickupModelRef<'a>
+    {
+        match self {
+            MaybeObfuscatedPickup::Unobfuscated(pt) => PickupModelRef::Vanilla(*pt),
+            MaybeObfuscatedPickup::Obfuscated(_) => PickupModelRef::Vanilla(PickupType::Nothing),
+            MaybeObfuscatedPickup::Disguised(_, model) => *model,
+        }
+    }
+
+    fn model_data(&self) -> &'a structs::Pickup<'static>
+    {
+        match self.model() {
+            PickupModelRef::Vanilla(pt) => pt.pickup_data(),
+            PickupModelRef::Custom(model) => &model.pickup,
         }
     }
 
+    // The AABB of the model that is shown. Custom models share theirs with their base.
+    fn model_aabb(&self) -> [f32; 6]
+    {
+        let cmdl = match self.model() {
+            PickupModelRef::Vanilla(pt) => pt.pickup_data().cmdl,
+            PickupModelRef::Custom(model) => model.base.pickup_data().cmdl,
+        };
+        pickup_meta::aabb_for_pickup_cmdl(cmdl).unwrap()
+    }
+
     // fn name(&self) -> &'static str
     // {
     //     self.orig().name()
     // }
 
-    fn dependencies(&self) -> &'static [(u32, FourCC)]
+    fn dependencies(&self) -> &'a [(u32, FourCC)]
     {
-        match self {
-            MaybeObfuscatedPickup::Unobfuscated(pt) => pt.dependencies(),
-            MaybeObfuscatedPickup::Obfuscated(_) => PickupType::Nothing.dependencies(),
+        match self.model() {
+            PickupModelRef::Vanilla(pt) => pt.dependencies(),
+            PickupModelRef::Custom(model) => &model.dependencies,
         }
     }
 
@@ -1170,49 +1683,143 @@ impl MaybeObfuscatedPickup
         self.orig().attainment_audio_file_name()
     }
 
-    pub fn pickup_data<'a>(&self) -> LCow<'a, structs::Pickup<'static>>
+    pub fn pickup_data(&self) -> LCow<'a, structs::Pickup<'static>>
     {
-        match self {
-            MaybeObfuscatedPickup::Unobfuscated(pt) => LCow::Borrowed(pt.pickup_data()),
-            MaybeObfuscatedPickup::Obfuscated(original) => {
-                let original = original.pickup_data();
-                let nothing = PickupType::Nothing.pickup_data();
-
-                LCow::Owned(structs::Pickup {
-                    name: original.name.clone(),
-                    kind: original.kind,
-                    max_increase: original.max_increase,
-                    curr_increase: original.curr_increase,
-                    ..nothing.clone()
-                })
+        let original = self.orig().pickup_data();
+        let model = self.model_data();
+        if std::ptr::eq(original, model) {
+            return LCow::Borrowed(original);
+        }
+
+        LCow::Owned(structs::Pickup {
+            name: original.name.clone(),
+            kind: original.kind,
+            max_increase: original.max_increase,
+            curr_increase: original.curr_increase,
+            ..model.clone()
+        })
+    }
+}
+
+// A `PickupOverride` with its model looked up and its text turned into assets
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResolvedPickupOverride<'a>
+{
+    max_increase: Option<u32>,
+    curr_increase: Option<u32>,
+    model: Option<PickupModelRef<'a>>,
+    scan: Option<(u32, u32)>,
+    hudmemo_strg: Option<u32>,
+}
+
+impl<'a> ResolvedPickupOverride<'a>
+{
+    pub fn new(
+        pickup_override: &PickupOverride,
+        pickup_resources: &mut HashMap<(u32, FourCC), structs::Resource>,
+        custom_models: &'a HashMap<String, CustomPickupModel>,
+        asset_ids: &mut AssetIdAllocator,
+    ) -> Result<Self, String>
+    {
+        let model = match &pickup_override.model {
+            Some(name) => Some(
+                PickupType::iter()
+                    .find(|pt| pt.name().to_lowercase() == name.to_lowercase())
+                    .map(PickupModelRef::Vanilla)
+                    .or_else(|| custom_models.get(&name.to_lowercase()).map(PickupModelRef::Custom))
+                    .ok_or_else(|| format!("Unknown pickup model '{}'", name))?
+            ),
+            None => None,
+        };
+
+        let scan = pickup_override.scan_text.as_ref().map(|text| -> Result<_, String> {
+            let (scan_id, strg_id) = (asset_ids.next_id()?, asset_ids.next_id()?);
+            let [scan, strg] = create_item_scan_strg_pair(scan_id, strg_id, &format!("{}\0", text));
+            pickup_resources.insert((scan_id, b"SCAN".into()), scan);
+            pickup_resources.insert((strg_id, b"STRG".into()), strg);
+            Ok((scan_id, strg_id))
+        }).transpose()?;
+
+        let hudmemo_strg = pickup_override.hudmemo_text.as_ref().map(|text| -> Result<_, String> {
+            let id = asset_ids.next_id()?;
+            let strg = pickup_meta::build_resource(
+                id,
+                structs::ResourceKind::Strg(structs::Strg::from_strings(vec![
+                    format!("&just=center;{}\0", text),
+                ])),
+            );
+            pickup_resources.insert((id, b"STRG".into()), strg);
+            Ok(id)
+        }).transpose()?;
+
+        // The REL recognizes foreign items by the flag in their capacity
+        let (max_increase, curr_increase) = match pickup_override.foreign_item {
+            Some(id) if id & FOREIGN_ITEM_FLAG != 0 => {
+                Err(format!("Foreign item id {} is too large", id))?
             },
+            Some(id) => (Some(FOREIGN_ITEM_FLAG | id), Some(0)),
+            None => (
+                pickup_override.max_increase.or(pickup_override.amount),
+                pickup_override.curr_increase.or(pickup_override.amount),
+            ),
+        };
+
+        Ok(ResolvedPickupOverride {
+            max_increase,
+            curr_increase,
+            model,
+            scan,
+            hudmemo_strg,
+        })
+    }
+
+    pub fn dependencies(&self) -> impl Iterator<Item = structs::Dependency>
+    {
+        let scan_deps = self.scan.into_iter()
+            .flat_map(|(scan, strg)| vec![(scan, b"SCAN".into()), (strg, b"STRG".into())]);
+        let hudmemo_deps = self.hudmemo_strg.into_iter().map(|strg| (strg, b"STRG".into()));
+        scan_deps.chain(hudmemo_deps)
+            .map(|(asset_id, asset_type)| structs::Dependency { asset_id, asset_type })
+    }
+
+    pub fn apply(&self, pickup: &mut structs::Pickup)
+    {
+        if let Some(max_increase) = self.max_increase {
+            pickup.max_increase = max_increase;
+        }
+        if let Some(curr_increase) = self.curr_increase {
+            pickup.curr_increase = curr_increase;
+        }
+        if let Some((scan, _)) = self.scan {
+            pickup.actor_params.scan_params.scan = scan;
         }
     }
 }
 
 fn patch_add_item<'r>(
-    ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
-    pickup_type: PickupType,
+    placed: PlacedPickup,
     pickup_position: Xyz,
+    pickup_override: ResolvedPickupOverride,
     pickup_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
     config: &ParsedConfig,
 ) -> Result<(), String>
 {
     // resolve dependencies
-    let location_idx = 0;
+    let location_idx = placed.location_idx;
 
-    let pickup_type = if config.obfuscate_items {
-        MaybeObfuscatedPickup::Obfuscated(pickup_type)
-    } else {
-        MaybeObfuscatedPickup::Unobfuscated(pickup_type)
-    };
+    let pickup_type = MaybeObfuscatedPickup::new(
+        placed.pickup_type,
+        pickup_override.model,
+        config.obfuscate_items,
+    );
 
     let deps_iter = pickup_type.dependencies().iter()
         .map(|&(file_id, fourcc)| structs::Dependency {
                 asset_id: file_id,
                 asset_type: fourcc,
-            });
+            })
+        .chain(pickup_override.dependencies());
 
     let name = CString::new(format!(
             "Randomizer - Pickup {} ({:?})", location_idx, pickup_type.pickup_data().name)).unwrap();
@@ -1234,7 +1841,7 @@ fn patch_add_item<'r>(
 
     // create pickup
     let mut pickup = structs::SclyObject {
-        instance_id: ps.fresh_instance_id_range.next().unwrap(),
+        instance_id: area.new_object_id_from_layer_id(new_layer_idx),
         connections: vec![].into(),
         property_data: structs::SclyProperty::Pickup(
             structs::Pickup {
@@ -1258,22 +1865,27 @@ fn patch_add_item<'r>(
             }
         )
     };
+    pickup_override.apply(pickup.property_data.as_pickup_mut().unwrap());
 
-    // create hudmemo
-    let hudmemo = structs::SclyObject {
-        instance_id: ps.fresh_instance_id_range.next().unwrap(),
+    // create hudmemo, set up the same way as the ones for the existing pickups
+    let mut hudmemo = structs::SclyObject {
+        instance_id: area.new_object_id_from_layer_id(new_layer_idx),
         connections: vec![].into(),
         property_data: structs::SclyProperty::HudMemo(
             structs::HudMemo {
                 name: b"myhudmemo\0".as_cstr(),
-                first_message_timer: 5.,
+                first_message_timer: 3.,
                 unknown: 1,
-                memo_type: 0, // not a text box
-                strg: pickup_type.skip_hudmemos_strg(),
+                memo_type: 1, // text box
+                strg: pickup_type.hudmemo_strg(),
                 active: 1,
             }
         )
     };
+    update_hudmemo(&mut hudmemo, pickup_type, location_idx, config.skip_hudmenus);
+    if let Some(strg) = pickup_override.hudmemo_strg {
+        hudmemo.property_data.as_hud_memo_mut().unwrap().strg = strg;
+    }
 
     // Display hudmemo when item is picked up
     pickup.connections.as_mut_vec().push(
@@ -1288,7 +1900,7 @@ fn patch_add_item<'r>(
     // This is needed because otherwise the item would re-appear every
     // time the room is loaded
     let special_function = structs::SclyObject {
-        instance_id: ps.fresh_instance_id_range.next().unwrap(),
+        instance_id: area.new_object_id_from_layer_id(0),
         connections: vec![].into(),
         property_data: structs::SclyProperty::SpecialFunction(
             structs::SpecialFunction {
@@ -1323,7 +1935,7 @@ fn patch_add_item<'r>(
 
     // create attainment audio
     let attainment_audio = structs::SclyObject {
-        instance_id: ps.fresh_instance_id_range.next().unwrap(),
+        instance_id: area.new_object_id_from_layer_id(new_layer_idx),
         connections: vec![].into(),
         property_data: structs::SclyProperty::Sound(
             structs::Sound { // copied from main plaza half-pipe
@@ -1364,14 +1976,19 @@ fn patch_add_item<'r>(
         }
     );
 
+    // If this is an artifact, create and push change function
+    let pickup_kind = pickup_type.pickup_data().kind;
+    let artifact_function_id = if pickup_kind >= 29 && pickup_kind <= 40 {
+        Some(area.new_object_id_from_layer_id(new_layer_idx))
+    } else {
+        None
+    };
+
     // update MREA layer with new Objects
     let scly = area.mrea().scly_section_mut();
     let layers = scly.layers.as_mut_vec();
 
-    // If this is an artifact, create and push change function
-    let pickup_kind = pickup_type.pickup_data().kind;
-    if pickup_kind >= 29 && pickup_kind <= 40 {
-        let instance_id = ps.fresh_instance_id_range.next().unwrap();
+    if let Some(instance_id) = artifact_function_id {
         let function = artifact_layer_change_template(instance_id, pickup_kind);
         layers[new_layer_idx].objects.as_mut_vec().push(function);
         pickup.connections.as_mut_vec().push(
@@ -1392,28 +2009,28 @@ fn patch_add_item<'r>(
 }
 
 fn modify_pickups_in_mrea<'r>(
-    ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
-    pickup_type: PickupType,
+    placed: PlacedPickup,
     pickup_location: pickup_meta::PickupLocation,
-    pickup_count: u32,
+    pickup_override: ResolvedPickupOverride,
     pickup_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
     config: &ParsedConfig,
 ) -> Result<(), String>
 {
-    let location_idx = 0;
+    let location_idx = placed.location_idx;
 
-    let pickup_type = if config.obfuscate_items {
-        MaybeObfuscatedPickup::Obfuscated(pickup_type)
-    } else {
-        MaybeObfuscatedPickup::Unobfuscated(pickup_type)
-    };
+    let pickup_type = MaybeObfuscatedPickup::new(
+        placed.pickup_type,
+        pickup_override.model,
+        config.obfuscate_items,
+    );
 
     let deps_iter = pickup_type.dependencies().iter()
         .map(|&(file_id, fourcc)| structs::Dependency {
                 asset_id: file_id,
                 asset_type: fourcc,
-            });
+            })
+        .chain(pickup_override.dependencies());
 
     let name = CString::new(format!(
             "Randomizer - Pickup {} ({:?})", location_idx, pickup_type.pickup_data().name)).unwrap();
@@ -1433,26 +2050,31 @@ fn modify_pickups_in_mrea<'r>(
     let deps_iter = deps_iter.chain(iter::once(hudmemo_dep));
     area.add_dependencies(pickup_resources, new_layer_idx, deps_iter);
 
+    let relay_id = area.new_object_id_from_layer_id(new_layer_idx);
+    let pickup_kind = pickup_type.pickup_data().kind;
+    let artifact_function_id = if pickup_kind >= 29 && pickup_kind <= 40 {
+        Some(area.new_object_id_from_layer_id(new_layer_idx))
+    } else {
+        None
+    };
+
     let scly = area.mrea().scly_section_mut();
     let layers = scly.layers.as_mut_vec();
 
     let mut additional_connections = Vec::new();
 
     // Add a post-pickup relay. This is used to support cutscene-skipping
-    let instance_id = ps.fresh_instance_id_range.next().unwrap();
-    let relay = post_pickup_relay_template(instance_id,
+    let relay = post_pickup_relay_template(relay_id,
                                             pickup_location.post_pickup_relay_connections);
     layers[new_layer_idx].objects.as_mut_vec().push(relay);
     additional_connections.push(structs::Connection {
         state: structs::ConnectionState::ARRIVED,
         message: structs::ConnectionMsg::SET_TO_ZERO,
-        target_object_id: instance_id,
+        target_object_id: relay_id,
     });
 
     // If this is an artifact, insert a layer change function
-    let pickup_kind = pickup_type.pickup_data().kind;
-    if pickup_kind >= 29 && pickup_kind <= 40 {
-        let instance_id = ps.fresh_instance_id_range.next().unwrap();
+    if let Some(instance_id) = artifact_function_id {
         let function = artifact_layer_change_template(instance_id, pickup_kind);
         layers[new_layer_idx].objects.as_mut_vec().push(function);
         additional_connections.push(structs::Connection {
@@ -1465,7 +2087,8 @@ fn modify_pickups_in_mrea<'r>(
     let pickup = layers[pickup_location.location.layer as usize].objects.iter_mut()
         .find(|obj| obj.instance_id ==  pickup_location.location.instance_id)
         .unwrap();
-    update_pickup(pickup, pickup_type, pickup_count);
+    update_pickup(pickup, pickup_type);
+    pickup_override.apply(pickup.property_data.as_pickup_mut().unwrap());
     if additional_connections.len() > 0 {
         pickup.connections.as_mut_vec().extend_from_slice(&additional_connections);
     }
@@ -1474,6 +2097,9 @@ fn modify_pickups_in_mrea<'r>(
         .find(|obj| obj.instance_id ==  pickup_location.hudmemo.instance_id)
         .unwrap();
     update_hudmemo(hudmemo, pickup_type, location_idx, config.skip_hudmenus);
+    if let Some(strg) = pickup_override.hudmemo_strg {
+        hudmemo.property_data.as_hud_memo_mut().unwrap().strg = strg;
+    }
 
     let location = pickup_location.attainment_audio;
     let attainment_audio = layers[location.layer as usize].objects.iter_mut()
@@ -1486,14 +2112,13 @@ fn modify_pickups_in_mrea<'r>(
 fn update_pickup(
     pickup: &mut structs::SclyObject,
     pickup_type: MaybeObfuscatedPickup,
-    pickup_count: u32,
 )
 {
     let pickup = pickup.property_data.as_pickup_mut().unwrap();
     let original_pickup = pickup.clone();
     
     let original_aabb = pickup_meta::aabb_for_pickup_cmdl(original_pickup.cmdl).unwrap();
-    let new_aabb = pickup_meta::aabb_for_pickup_cmdl(pickup_type.pickup_data().cmdl).unwrap();
+    let new_aabb = pickup_type.model_aabb();
     let original_center = calculate_center(original_aabb, original_pickup.rotation,
                                             original_pickup.scale);
     let new_center = calculate_center(new_aabb, pickup_type.pickup_data().rotation,
@@ -1520,12 +2145,6 @@ fn update_pickup(
 
         ..(pickup_type.pickup_data().into_owned())
     };
-
-    if pickup_count != 0xFFFFFFFF
-    {
-        pickup.max_increase  = pickup_count;
-        pickup.curr_increase = pickup_count;
-    }
 }
 
 fn update_hudmemo(
@@ -1610,7 +2229,7 @@ fn make_elevators_patch<'a>(
             continue
         }
 
-        patcher.add_scly_patch((elv.pak_name.as_bytes(), elv.mrea), move |ps, area| {
+        patcher.add_scly_patch((elv.pak_name.as_bytes(), elv.mrea), move |area| {
             let scly = area.mrea().scly_section_mut();
             for layer in scly.layers.iter_mut() {
                 let obj = layer.objects.iter_mut()
@@ -1630,8 +2249,7 @@ fn make_elevators_patch<'a>(
 
             if auto_enabled_elevators {
                 // Auto enable the elevator
-                let layer = &mut scly.layers.as_mut_vec()[0];
-                let mr_id = layer.objects.iter()
+                let mr_id = scly.layers.iter().next().unwrap().objects.iter()
                     .find(|obj| obj.property_data.as_memory_relay()
                         .map(|mr| mr.name == b"Memory Relay - dim scan holo\0".as_cstr())
                         .unwrap_or(false)
@@ -1639,8 +2257,10 @@ fn make_elevators_patch<'a>(
                     .map(|mr| mr.instance_id);
 
                 if let Some(mr_id) = mr_id {
+                    let timer_id = area.new_object_id_from_layer_id(0);
+                    let layer = &mut area.mrea().scly_section_mut().layers.as_mut_vec()[0];
                     layer.objects.as_mut_vec().push(structs::SclyObject {
-                        instance_id: ps.fresh_instance_id_range.next().unwrap(),
+                        instance_id: timer_id,
                         property_data: structs::SclyProperty::Timer(structs::Timer {
                             name: b"Auto enable elevator\0".as_cstr(),
 
@@ -1706,15 +2326,14 @@ fn make_elevators_patch<'a>(
 }
 
 fn patch_landing_site_cutscene_triggers(
-    ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
 ) -> Result<(), String>
 {
     // XXX I'd like to do this some other way than inserting a timer to trigger
     //     the memory relay, but I couldn't figure out how to make the memory
     //     relay default to on/enabled.
+    let timer_id = area.new_object_id_from_layer_id(0);
     let layer = area.mrea().scly_section_mut().layers.iter_mut().next().unwrap();
-    let timer_id = ps.fresh_instance_id_range.next().unwrap();
     for obj in layer.objects.iter_mut() {
         if obj.instance_id == 427 {
             obj.connections.as_mut_vec().push(structs::Connection {
@@ -1765,7 +2384,6 @@ fn patch_landing_site_cutscene_triggers(
 }
 
 fn patch_ending_scene_straight_to_credits(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
 ) -> Result<(), String>
 {
@@ -1818,6 +2436,188 @@ fn calculate_door_type(pak_name: &str, mut rng: &mut StdRng, weights: &Weights)
     }
 }
 
+// The colour of every door that gets patched, keyed by room id and dock number
+pub type DoorColors = HashMap<(u32, u32), DoorType>;
+
+// Picks the colour of every door that gets patched, in the order they're patched, so a seed
+// gets the same colours it always has. Colours are picked before being made vertical.
+fn roll_door_colors(
+    config: &ParsedConfig,
+    door_rng: &mut StdRng,
+    door_partners: &HashMap<(u32, u32), (u32, u32)>,
+) -> Result<DoorColors, String>
+{
+    let mut door_colors = HashMap::new();
+    for (name, rooms) in pickup_meta::PICKUP_LOCATIONS.iter() {
+        let level = World::from_pak(name).unwrap() as usize;
+        if level == 0 && config.skip_frigate {continue;}
+
+        for room_info in rooms.iter() {
+            for dock_number in room_info.door_locations.iter().filter_map(|door| door.dock_number) {
+                let door_specification = &config.excluded_doors[level][room_info.name][dock_number as usize];
+
+                let mut door_type = calculate_door_type(name, door_rng, &config.door_weights); // randomly pick a door color using weights
+
+                // If the doors were shuffled, both sides of a door need to match, so reuse the
+                // color already picked for the door on the other side
+                let door_key = (room_info.room_id, dock_number);
+                let partner_door_type = door_partners.get(&door_key)
+                    .and_then(|partner| door_colors.get(partner))
+                    .cloned();
+
+                if let Some(partner_door_type) = partner_door_type {
+                    door_type = partner_door_type;
+                } else if door_specification != "random" && door_specification != "default" {
+                    door_type = DoorType::from_string(door_specification.to_string())
+                        .ok_or_else(|| format!("Unknown door type '{}'", door_specification))?;
+                }
+
+                if door_specification != "default" || partner_door_type.is_some() {
+                    door_colors.insert(door_key, door_type);
+                }
+            }
+        }
+    }
+    Ok(door_colors)
+}
+
+// The door types the placement logic sees for the given door colors
+fn logic_door_types(config: &ParsedConfig, door_colors: &DoorColors)
+    -> HashMap<(u32, u32), DoorType>
+{
+    door_colors.iter()
+        .filter(|&(&(room_id, dock), _)| {
+            !(config.patch_vertical_to_blue && is_vertical_door(room_id, dock as usize))
+        })
+        .map(|(&key, &door_type)| (key, door_type))
+        .collect()
+}
+
+// A world's MLVL, as it is on the disc
+fn world_mlvl<'r>(gc_disc: &structs::GcDisc<'r>, pak_name: &str) -> Result<structs::Mlvl<'r>, String>
+{
+    let file_entry = gc_disc.find_file(pak_name)
+        .ok_or_else(|| format!("The disc has no {}", pak_name))?;
+    let pak = match *file_entry.file().unwrap() {
+        structs::FstEntryFile::Pak(ref pak) => Cow::Borrowed(pak),
+        structs::FstEntryFile::Unknown(ref reader) => Cow::Owned(reader.clone().try_read(())
+            .map_err(|e| format!("Failed to parse {}: {}", pak_name, e))?),
+        _ => Err(format!("{} isn't a pak", pak_name))?,
+    };
+    let mlvl = pak.resources.iter()
+        .find(|res| res.fourcc() == b"MLVL".into())
+        .ok_or_else(|| format!("{} has no MLVL", pak_name))?
+        .kind.try_as_mlvl()
+        .map_err(|e| format!("Failed to parse the MLVL in {}: {}", pak_name, e))?
+        .ok_or_else(|| format!("The MLVL in {} isn't parseable", pak_name))?
+        .into_owned();
+    Ok(mlvl)
+}
+
+// Every room joined by the doors in the MLVLs' dock data and by the vanilla elevators, starting
+// from the Landing Site
+fn logic_room_graph(gc_disc: &structs::GcDisc) -> Result<logic::RoomGraph, String>
+{
+    let mut room_graph = logic::RoomGraph::new(SpawnRoom::landing_site_spawn_room().mrea);
+    for (pak_name, _) in pickup_meta::PICKUP_LOCATIONS.iter() {
+        let mut mlvl = world_mlvl(gc_disc, pak_name)?;
+        let graph = MlvlGraph::new(&mut mlvl);
+        for area in 0..graph.area_count() {
+            for (from, to) in graph.exits(area) {
+                room_graph.add_door(
+                    graph.areas()[from.area].mrea,
+                    from.dock as u32,
+                    graph.areas()[to.area].mrea,
+                );
+            }
+        }
+    }
+    for elv in ELEVATORS.iter() {
+        if let Some(dest) = ELEVATORS.get(elv.default_dest as usize) {
+            room_graph.add_passage(elv.mrea, dest.mrea);
+        }
+    }
+    Ok(room_graph)
+}
+
+// The settings the placement logic needs to judge a seed with the given door colors, with the
+// room graph read from the input ISO, or why it can't: it doesn't model changed elevators, door
+// destinations or starting rooms, or an already randomized ISO
+pub fn door_logic_options(config: &ParsedConfig, door_colors: &DoorColors)
+    -> Result<logic::LogicOptions, String>
+{
+    let gc_disc: structs::GcDisc = Reader::new(&config.input_iso[..]).try_read(())
+        .map_err(|e| format!("Failed to parse the input ISO: {}", e))?;
+    let vanilla_elevators = ELEVATORS.iter()
+        .map(|elv| elv.default_dest)
+        .chain(Some(ELEVATORS.len() as u8)) // Landing Site
+        .eq(config.elevator_layout.iter().cloned());
+    if config.is_item_randomized.unwrap_or(false) || gc_disc.find_file("randomprime.txt").is_some() {
+        Err("The logic can't check an ISO whose items are already randomized")?
+    }
+    if config.shuffle_elevators || !config.elevator_layout_override.is_empty() ||
+        !vanilla_elevators {
+        Err("The logic only knows about the vanilla elevators")?
+    }
+    if config.shuffle_door_destinations {
+        Err("The logic only knows about the vanilla door destinations")?
+    }
+    if !config.new_save_spawn_room.is_empty() || !config.frigate_done_spawn_room.is_empty() {
+        Err("The logic only knows about the vanilla starting room")?
+    }
+
+    Ok(logic::LogicOptions {
+        door_types: logic_door_types(config, door_colors),
+        missile_locked_rooms: missile_locked_rooms(
+            config.remove_missile_locks,
+            &config.missile_lock_override,
+        ).into_iter().collect(),
+        skip_impact_crater: config.skip_impact_crater,
+        // The pickups are only collected after the frigate
+        starting_inventory: logic::starting_inventory(if config.skip_frigate {
+            &config.new_save_starting_items
+        } else {
+            &config.frigate_done_starting_items
+        }),
+        room_graph: Some(logic_room_graph(&gc_disc)?),
+    })
+}
+
+// Rolls the random doors until the seed can be completed, or errors if no roll works. If it can't
+// be checked, the first roll is used and a warning saying why is returned with it.
+fn roll_completable_door_colors(
+    config: &ParsedConfig,
+    pickup_layout: &[PickupType],
+    door_partners: &HashMap<(u32, u32), (u32, u32)>,
+) -> Result<(DoorColors, Option<String>), String>
+{
+    let mut door_rng = StdRng::seed_from_u64(config.seed);
+    let mut roll = || roll_door_colors(config, &mut door_rng, door_partners);
+
+    // Without random doors every roll is the same
+    let has_random_doors = config.excluded_doors.iter()
+        .flat_map(|rooms| rooms.values())
+        .any(|specs| specs.iter().any(|spec| spec == "random"));
+    if !has_random_doors {
+        return Ok((roll()?, None));
+    }
+
+    let explicit_door_colors = logic::explicit_door_types(&config.excluded_doors)?;
+    let options = match door_logic_options(config, &explicit_door_colors) {
+        Ok(options) => options,
+        Err(e) => {
+            let warning = format!("The random doors weren't checked for completability. {}", e);
+            return Ok((roll()?, Some(warning)));
+        },
+    };
+    logic::roll_completable_doors(
+        pickup_layout,
+        &options,
+        roll,
+        |door_colors| logic_door_types(config, door_colors),
+    )
+}
+
 /*
 {
 
@@ -1828,7 +2628,7 @@ fn calculate_door_type(pak_name: &str, mut rng: &mut StdRng, weights: &Weights)
     // If this is an artifact, create and push change function
     let pickup_kind = pickup_type.pickup_data().kind;
     if pickup_kind >= 29 && pickup_kind <= 40 {
-        let instance_id = ps.fresh_instance_id_range.next().unwrap();
+        let instance_id = area.new_object_id_from_layer_id(new_layer_idx);
         let function = artifact_layer_change_template(instance_id, pickup_kind);
         layers[new_layer_idx].objects.as_mut_vec().push(function);
         pickup.connections.as_mut_vec().push(
@@ -1849,7 +2649,6 @@ fn calculate_door_type(pak_name: &str, mut rng: &mut StdRng, weights: &Weights)
 }
 */
 fn patch_door<'r>(
-    ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     door_loc: DoorLocation,
     door_type: DoorType,
@@ -1880,6 +2679,13 @@ fn patch_door<'r>(
     });
     area.add_dependencies(&door_resources,0,deps_iter);
 
+    // Reserve IDs for the blast shield's objects before borrowing the layers //
+    let mut blast_shield_ids = if blast_shield_type.is_some() && door_loc.door_shield_location.is_some() {
+        (0..4).map(|_| area.new_object_id_from_layer_id(new_layer_idx)).collect()
+    } else {
+        vec![]
+    }.into_iter();
+
     let area_internal_id = area.mlvl_area.internal_id;
     let scly = area.mrea().scly_section_mut();
     let layers = &mut scly.layers.as_mut_vec();
@@ -1954,7 +2760,7 @@ fn patch_door<'r>(
             }
 
             // Create new blast shield actor //
-            let blast_shield_instance_id = ps.fresh_instance_id_range.next().unwrap();
+            let blast_shield_instance_id = blast_shield_ids.next().unwrap();
             let mut blast_shield = structs::SclyObject {
                 instance_id: blast_shield_instance_id,
                 connections: vec![
@@ -2041,7 +2847,7 @@ fn patch_door<'r>(
             // This is needed because otherwise the shield would re-appear every
             // time the room is loaded
             let special_function = structs::SclyObject {
-                instance_id: ps.fresh_instance_id_range.next().unwrap(),
+                instance_id: blast_shield_ids.next().unwrap(),
                 connections: vec![].into(),
                 property_data: structs::SclyProperty::SpecialFunction(Box::new(
                     structs::SpecialFunction {
@@ -2082,7 +2888,7 @@ fn patch_door<'r>(
             
             // Create explosion sfx //
             let sound = structs::SclyObject {
-                instance_id: ps.fresh_instance_id_range.next().unwrap(),
+                instance_id: blast_shield_ids.next().unwrap(),
                 connections: vec![].into(),
                 property_data: structs::SclyProperty::Sound(Box::new(
                     structs::Sound { // copied from main plaza half-pipe
@@ -2125,7 +2931,7 @@ fn patch_door<'r>(
 
             // Create "You did it" Jingle //
             let streamed_audio = structs::SclyObject {
-                instance_id: ps.fresh_instance_id_range.next().unwrap(),
+                instance_id: blast_shield_ids.next().unwrap(),
                 connections: vec![].into(),
                 property_data: structs::SclyProperty::StreamedAudio(Box::new(
                     structs::StreamedAudio {
@@ -2181,19 +2987,136 @@ fn patch_map_door_icon(
     Ok(())
 }
 
-fn fix_artifact_of_truth_requirements(
-    ps: &mut PatcherState,
-    area: &mut mlvl_wrapper::MlvlArea,
-    pickup_layout: &[PickupType],
-) -> Result<(), String>
+// A world whose doors were shuffled: the pak it's in, its MLVL's file ID, the new dock
+// connections and the rooms that were moved to make the rewired doors line up
+struct DockShuffle
 {
-    let truth_req_layer_id = area.layer_flags.layer_count;
-    // assert_eq!(truth_req_layer_id, ARTIFACT_OF_TRUTH_REQ_LAYER);
-
-    // Create a new layer that will be toggled on when the Artifact of Truth is collected
-    area.add_layer(b"Randomizer - Got Artifact 1\0".as_cstr());
+    pak_name: &'static str,
+    mlvl: u32,
+    links: Vec<(DockId, DockId)>,
+    moved_areas: Vec<(u32, usize, door_randomizer::AreaMove)>,
+}
 
-    let at_pickup_kind = pickup_layout[63].pickup_data().kind;
+// Shuffle the doors of every world except the frigate. The player can enter a world through
+// any of its elevators or the rooms they spawn in, so everything reachable from those before
+// the shuffle stays reachable. Returns the changes for each world, along with which door ends
+// up on the other side of each door, keyed by room and dock number.
+fn plan_dock_shuffles(gc_disc: &structs::GcDisc, config: &ParsedConfig, spawn_rooms: &[SpawnRoom])
+    -> Result<(Vec<DockShuffle>, HashMap<(u32, u32), (u32, u32)>), String>
+{
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut shuffles = vec![];
+    let mut door_partners = HashMap::new();
+    for (pak_name, rooms) in pickup_meta::PICKUP_LOCATIONS.iter() {
+        let world = World::from_pak(pak_name).unwrap();
+        if let World::FrigateOrpheon = world {
+            continue;
+        }
+
+        let mut mlvl = world_mlvl(gc_disc, pak_name)?;
+        let mut graph = MlvlGraph::new(&mut mlvl);
+
+        let start_areas: Vec<usize> = ELEVATORS.iter()
+            .filter(|elv| elv.pak_name == *pak_name)
+            .map(|elv| elv.mrea)
+            .chain(spawn_rooms.iter().filter(|room| room.mlvl == world.mlvl()).map(|room| room.mrea))
+            .filter_map(|mrea| graph.area_index_by_mrea(mrea))
+            .collect();
+        if start_areas.is_empty() {
+            Err(format!("{} has no elevator or spawn room to enter it from", pak_name))?
+        }
+
+        // Only horizontal doors are shuffled, since floor and ceiling doors can only lead to
+        // each other
+        let mut docks = vec![];
+        for room_info in rooms.iter() {
+            let area = match graph.area_index_by_mrea(room_info.room_id) {
+                Some(area) => area,
+                None => continue,
+            };
+            for door_location in room_info.door_locations.iter() {
+                if let Some(dock_number) = door_location.dock_number {
+                    if !is_vertical_door(room_info.room_id, dock_number as usize) {
+                        docks.push(DockId::new(area, dock_number as usize));
+                    }
+                }
+            }
+        }
+        let links = door_randomizer::shuffle_docks(&mut graph, &mut rng, &docks, &start_areas)
+            .map_err(|e| format!("{}: {}", pak_name, e))?;
+        let moved_areas = door_randomizer::align_areas(&graph, &links, &start_areas)
+            .map_err(|e| format!("{}: {}", pak_name, e))?
+            .into_iter()
+            .map(|(area, area_move)| (graph.areas()[area].mrea, area, area_move))
+            .collect();
+
+        for area in 0..graph.area_count() {
+            for (from, to) in graph.exits(area) {
+                let from_key = (graph.areas()[from.area].mrea, from.dock as u32);
+                let to_key = (graph.areas()[to.area].mrea, to.dock as u32);
+                door_partners.insert(from_key, to_key);
+            }
+        }
+        shuffles.push(DockShuffle { pak_name, mlvl: world.mlvl(), links, moved_areas });
+    }
+    Ok((shuffles, door_partners))
+}
+
+fn patch_dock_connections(res: &mut structs::Resource, shuffle: &DockShuffle)
+    -> Result<(), String>
+{
+    let mlvl = res.kind.as_mlvl_mut().unwrap();
+    let mut graph = MlvlGraph::new(mlvl);
+    for &(a, b) in &shuffle.links {
+        graph.connect(a, b)?;
+    }
+    let areas = graph.mlvl().areas.as_mut_vec();
+    for (_, area, area_move) in &shuffle.moved_areas {
+        area_move.apply_to_area(&mut areas[*area]);
+    }
+    Ok(())
+}
+
+// The room's own copy of its transform and its dock objects, which are in world space, have to
+// move along with the room's MLVL entry
+fn patch_moved_area(
+    area: &mut mlvl_wrapper::MlvlArea,
+    area_move: door_randomizer::AreaMove,
+) -> Result<(), String>
+{
+    let mrea = area.mrea();
+    area_move.apply_to_transform(&mut mrea.area_transform[..]);
+    let scly = mrea.scly_section_mut();
+    for layer in scly.layers.as_mut_vec().iter_mut() {
+        for obj in layer.objects.as_mut_vec().iter_mut() {
+            if let Some(dock) = obj.property_data.as_dock_mut() {
+                let position = area_move.apply_to_point(
+                    [dock.position[0], dock.position[1], dock.position[2]]
+                );
+                let scale = area_move.apply_to_direction(
+                    [dock.scale[0], dock.scale[1], dock.scale[2]]
+                );
+                dock.position = position.into();
+                dock.scale = [scale[0].abs(), scale[1].abs(), scale[2].abs()].into();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn fix_artifact_of_truth_requirements(
+    area: &mut mlvl_wrapper::MlvlArea,
+    placed_pickups: &[PlacedPickup],
+    starting_artifacts: &HashSet<u32>,
+) -> Result<(), String>
+{
+    let truth_req_layer_id = area.layer_flags.layer_count;
+    // assert_eq!(truth_req_layer_id, ARTIFACT_OF_TRUTH_REQ_LAYER);
+
+    // Create a new layer that will be toggled on when the Artifact of Truth is collected
+    area.add_layer(b"Randomizer - Got Artifact 1\0".as_cstr());
+
+    let at_pickup_kind = placed_pickups[63].pickup_type.pickup_data().kind;
     for i in 0..12 {
         let layer_number = if i == 0 {
             truth_req_layer_id
@@ -2201,9 +3124,13 @@ fn fix_artifact_of_truth_requirements(
             i + 1
         };
         let kind = i + 29;
-        let exists = pickup_layout.iter()
-            .any(|pt| kind == pt.pickup_data().kind);
-        if exists && at_pickup_kind != kind {
+        let exists = placed_pickups.iter()
+            .any(|placed| kind == placed.pickup_type.pickup_data().kind);
+        if starting_artifacts.contains(&kind) {
+            // The REL gives artifacts the player starts with without collecting a pickup, so
+            // nothing else would activate their totems
+            area.layer_flags.flags |= 1 << layer_number;
+        } else if exists && at_pickup_kind != kind {
             // If the artifact exsts, but is not the artifact at the Artifact Temple, mark this
             // layer as inactive. It will be activated when the item is collected.
             area.layer_flags.flags &= !(1 << layer_number);
@@ -2216,10 +3143,9 @@ fn fix_artifact_of_truth_requirements(
         }
     }
 
-    let scly = area.mrea().scly_section_mut();
-
     // A relay on the new layer is created and connected to "Relay Show Progress 1"
-    let new_relay_instance_id = ps.fresh_instance_id_range.next().unwrap();
+    let new_relay_instance_id = area.new_object_id_from_layer_id(truth_req_layer_id as usize);
+    let scly = area.mrea().scly_section_mut();
     let new_relay = structs::SclyObject {
         instance_id: new_relay_instance_id,
         connections: vec![
@@ -2250,7 +3176,6 @@ fn fix_artifact_of_truth_requirements(
 }
 
 fn patch_artifact_hint_availability(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
     hint_behavior: ArtifactHintBehavior,
 ) -> Result<(), String>
@@ -2296,7 +3221,6 @@ fn patch_artifact_hint_availability(
 }
 
 fn patch_sun_tower_prevent_wild_before_flaahgra(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea
 ) -> Result<(), String>
 {
@@ -2311,12 +3235,11 @@ fn patch_sun_tower_prevent_wild_before_flaahgra(
 
 
 fn patch_sunchamber_prevent_wild_before_flaahgra(
-    ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea
 ) -> Result<(), String>
 {
+    let enable_sun_tower_layer_id = area.new_object_id_from_layer_id(1);
     let scly = area.mrea().scly_section_mut();
-    let enable_sun_tower_layer_id = ps.fresh_instance_id_range.next().unwrap();
     scly.layers.as_mut_vec()[1].objects.as_mut_vec().push(structs::SclyObject {
         instance_id: enable_sun_tower_layer_id,
         connections: vec![].into(),
@@ -2353,13 +3276,11 @@ fn patch_sunchamber_prevent_wild_before_flaahgra(
     Ok(())
 }
 
-fn patch_temple_security_station_cutscene_trigger(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_temple_security_station_cutscene_trigger(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
-    let scly = area.mrea().scly_section_mut();
-    let trigger = scly.layers.iter_mut()
-        .flat_map(|layer| layer.objects.iter_mut())
-        .find(|obj| obj.instance_id == 0x70067)
+    let mut graph = area.scly_graph();
+    let trigger = graph.object_mut(0x70067)
         .and_then(|obj| obj.property_data.as_trigger_mut())
         .unwrap();
     trigger.active = 0;
@@ -2367,7 +3288,7 @@ fn patch_temple_security_station_cutscene_trigger(_ps: &mut PatcherState, area:
     Ok(())
 }
 
-fn patch_ridley_phendrana_shorelines_cinematic(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_ridley_phendrana_shorelines_cinematic(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -2377,14 +3298,14 @@ fn patch_ridley_phendrana_shorelines_cinematic(_ps: &mut PatcherState, area: &mu
 
 fn make_elite_research_fight_prereq_patches(patcher: &mut PrimePatcher)
 {
-    patcher.add_scly_patch(resource_info!("03_mines.MREA").into(), |_ps, area| {
+    patcher.add_scly_patch(resource_info!("03_mines.MREA").into(), |area| {
         let flags = &mut area.layer_flags.flags;
         *flags |= 1 << 1; // Turn on "3rd pass elite bustout"
         *flags &= !(1 << 5); // Turn off the "dummy elite"
         Ok(())
     });
 
-    patcher.add_scly_patch(resource_info!("07_mines_electric.MREA").into(), |_ps, area| {
+    patcher.add_scly_patch(resource_info!("07_mines_electric.MREA").into(), |area| {
         let scly = area.mrea().scly_section_mut();
         scly.layers.as_mut_vec()[0].objects.as_mut_vec()
             .retain(|obj| obj.instance_id != 0x1B0525 && obj.instance_id != 0x1B0522);
@@ -2392,7 +3313,7 @@ fn make_elite_research_fight_prereq_patches(patcher: &mut PrimePatcher)
     });
 }
 
-fn patch_research_lab_hydra_barrier<'r>(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_research_lab_hydra_barrier<'r>(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -2407,15 +3328,15 @@ fn patch_research_lab_hydra_barrier<'r>(_ps: &mut PatcherState, area: &mut mlvl_
 }
 
 fn patch_research_lab_aether_exploding_wall<'r>(
-    ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea
+    area: &mut mlvl_wrapper::MlvlArea
 )
     -> Result<(), String>
 {
     // The room we're actually patching is Research Core..
+    let id = area.new_object_id_from_layer_id(0);
     let scly = area.mrea().scly_section_mut();
     let layer = &mut scly.layers.as_mut_vec()[0];
 
-    let id = ps.fresh_instance_id_range.next().unwrap();
     let obj = layer.objects.as_mut_vec().iter_mut()
         .find(|obj| obj.instance_id == 2622568)
         .unwrap();
@@ -2451,7 +3372,7 @@ fn patch_research_lab_aether_exploding_wall<'r>(
     Ok(())
 }
 
-fn patch_observatory_2nd_pass_solvablility<'r>(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_observatory_2nd_pass_solvablility<'r>(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -2471,15 +3392,16 @@ fn patch_observatory_2nd_pass_solvablility<'r>(_ps: &mut PatcherState, area: &mu
 }
 
 fn patch_main_ventilation_shaft_section_b_door<'r>(
-    ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea
+    area: &mut mlvl_wrapper::MlvlArea
 )
     -> Result<(), String>
 {
+    let trigger_id = area.new_object_id_from_layer_id(0);
     let scly = area.mrea().scly_section_mut();
     let layer = &mut scly.layers.as_mut_vec()[0];
 
     layer.objects.as_mut_vec().push(structs::SclyObject {
-        instance_id: ps.fresh_instance_id_range.next().unwrap(),
+        instance_id: trigger_id,
         property_data: structs::SclyProperty::Trigger(structs::Trigger {
                 name: b"Trigger_DoorOpen-component\0".as_cstr(),
                 position: [31.232622, 442.69165, -64.20529].into(),
@@ -2508,7 +3430,6 @@ fn patch_main_ventilation_shaft_section_b_door<'r>(
 }
 
 fn make_main_plaza_locked_door_two_ways<'r>(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     door_type: DoorType,
     config: &ParsedConfig,
@@ -2914,7 +3835,7 @@ fn patch_main_plaza_locked_door_map_icon(res: &mut structs::Resource,door_type:D
     Ok(())
 }
 
-fn patch_main_quarry_door_lock_0_02<'r>(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_main_quarry_door_lock_0_02<'r>(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -2923,7 +3844,7 @@ fn patch_main_quarry_door_lock_0_02<'r>(_ps: &mut PatcherState, area: &mut mlvl_
     Ok(())
 }
 
-fn patch_geothermal_core_door_lock_0_02<'r>(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_geothermal_core_door_lock_0_02<'r>(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -2932,7 +3853,7 @@ fn patch_geothermal_core_door_lock_0_02<'r>(_ps: &mut PatcherState, area: &mut m
     Ok(())
 }
 
-fn patch_hive_totem_boss_trigger_0_02(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_hive_totem_boss_trigger_0_02(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -2949,7 +3870,7 @@ fn patch_hive_totem_boss_trigger_0_02(_ps: &mut PatcherState, area: &mut mlvl_wr
     Ok(())
 }
 
-fn patch_ruined_courtyard_thermal_conduits_0_02(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_ruined_courtyard_thermal_conduits_0_02(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -2973,7 +3894,7 @@ fn patch_ruined_courtyard_thermal_conduits_0_02(_ps: &mut PatcherState, area: &m
     Ok(())
 }
 
-fn patch_thermal_conduits_damage_vulnerabilities(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_thermal_conduits_damage_vulnerabilities(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -3073,195 +3994,61 @@ fn is_missile_lock<'r>(obj: &structs::SclyObject<'r>) -> bool {
     }
 }
 
-fn patch_remove_missile_lock<'r>(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_remove_missile_lock<'r>(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
-    let scly = area.mrea().scly_section_mut();
-    let layer = &mut scly.layers.as_mut_vec()[0];
-    
-    // keep everything except for missile locks //
-    layer.objects.as_mut_vec().retain(|obj| !is_missile_lock(obj));
+    let missile_locks = area.scly_graph()
+        .objects()
+        .filter(|(layer, obj)| *layer == 0 && is_missile_lock(obj))
+        .map(|(_, obj)| obj.instance_id)
+        .collect();
+    area.remove_scly_objects(&missile_locks);
 
     Ok(())
 }
 
-fn remove_missile_locks<'a>(patcher: &mut PrimePatcher<'_, 'a>, overrides: &Vec<bool>)
+// Rooms with missile locks, in the order of `missile_lock_override`
+const MISSILE_LOCK_ROOMS: &[ResourceInfo] = &[
+    resource_info!("00j_over_hall.MREA"), // Temple Security Station
+    resource_info!("00a_over_hall.MREA"), // Waterfall Cavern
+    resource_info!("06_over_crashed_ship.MREA"), // Frigate Crash Site
+    resource_info!("00m_over_hall.MREA"), // Root Tunnel
+    resource_info!("03_over_rootcave.MREA"), // Root Cave
+    resource_info!("01_mainplaza.MREA"), // Main Plaza
+    resource_info!("19_hive_totem.MREA"), // Hive Totem
+    resource_info!("0b_connect_tunnel.MREA"), // Arboretum Access
+    resource_info!("08_courtyard.MREA"), // Arboretum (x2)
+    resource_info!("10_coreentrance.MREA"), // Gathering Hall
+    resource_info!("0e_connect_tunnel.MREA"), // Watery Hall Access
+    resource_info!("11_wateryhall.MREA"), // Watery Hall
+    resource_info!("monkey_shaft.MREA"), // Dynamo Access
+    resource_info!("18_halfpipe.MREA"), // Crossway
+    resource_info!("20_reflecting_pool.MREA"), // Reflecting Pool (x2)
+    resource_info!("15_over_burningtrail.MREA"), // Burning Trail
+    resource_info!("00_lava_elev_ice_d.MREA"), // Transport to Phendrana Drifts South
+    resource_info!("03_ice_ruins_b.MREA"), // Ice Ruins West
+    resource_info!("generic_z6.MREA"), // Canyon Entryway
+    resource_info!("05_ice_shorelines.MREA"), // Ruined Courtyard
+    resource_info!("11_ice_observatory.MREA"), // Observatory
+    resource_info!("03_monkey_upper.MREA"), // Ruined Gallery
+];
+
+// The room ids of the rooms that keep their missile locks
+fn missile_locked_rooms(remove_missile_locks: bool, overrides: &[bool]) -> Vec<u32>
 {
-    let mut idx = 0;
-
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("00j_over_hall.MREA").into(), // Temple Security Station
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("00a_over_hall.MREA").into(), // Waterfall Cavern
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("06_over_crashed_ship.MREA").into(), // Frigate Crash Site
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("00m_over_hall.MREA").into(), // Root Tunnel
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("03_over_rootcave.MREA").into(), // Root Cave
-            patch_remove_missile_lock,
-        );    
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("01_mainplaza.MREA").into(), // Main Plaza
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("19_hive_totem.MREA").into(), // Hive Totem
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("0b_connect_tunnel.MREA").into(), // Arboretum Access
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("08_courtyard.MREA").into(), // Arboretum (x2)
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("10_coreentrance.MREA").into(), // Gathering Hall
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("0e_connect_tunnel.MREA").into(), // Watery Hall Access
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("11_wateryhall.MREA").into(), // Watery Hall
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("monkey_shaft.MREA").into(), // Dynamo Access
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("18_halfpipe.MREA").into(), // Crossway
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("20_reflecting_pool.MREA").into(), // Reflecting Pool (x2)
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("15_over_burningtrail.MREA").into(), // Burning Trail
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("00_lava_elev_ice_d.MREA").into(), // Transport to Phendrana Drifts South
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("03_ice_ruins_b.MREA").into(), // Ice Ruins West
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("generic_z6.MREA").into(), // Canyon Entryway
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-    
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("05_ice_shorelines.MREA").into(), // Ruined Courtyard
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
-
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("11_ice_observatory.MREA").into(), // Observatory
-            patch_remove_missile_lock,
-        );
-    }
-    idx = idx + 1;
+    MISSILE_LOCK_ROOMS.iter()
+        .enumerate()
+        .filter(|&(idx, _)| !remove_missile_locks || overrides.get(idx).cloned().unwrap_or(false))
+        .map(|(_, room)| room.res_id)
+        .collect()
+}
 
-    if overrides.len() <= idx || !overrides[idx] {
-        patcher.add_scly_patch(
-            resource_info!("03_monkey_upper.MREA").into(), // Ruined Gallery
-            patch_remove_missile_lock,
-        );
+fn remove_missile_locks<'a>(patcher: &mut PrimePatcher<'_, 'a>, overrides: &Vec<bool>)
+{
+    for (idx, room) in MISSILE_LOCK_ROOMS.iter().enumerate() {
+        if overrides.len() <= idx || !overrides[idx] {
+            patcher.add_scly_patch((*room).into(), patch_remove_missile_lock);
+        }
     }
 }
 
@@ -3271,7 +4058,7 @@ fn elite_quarters_access_should_keep<'r>(obj: &structs::SclyObject<'r>) -> bool
     platform.is_none() // keep everything that isn't a platform
 }
 
-fn patch_elite_quarters_access(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_elite_quarters_access(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -3302,13 +4089,16 @@ fn is_door_lock<'r>(obj: &structs::SclyObject<'r>) -> bool {
     }
 }
 
-fn remove_mine_security_station_locks(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn remove_mine_security_station_locks(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
-    let scly = area.mrea().scly_section_mut();
-    let layer = &mut scly.layers.as_mut_vec()[0];
-    layer.objects.as_mut_vec().retain(|obj| !is_door_lock(obj));  // keep everything that isn't a door lock
-    
+    let door_locks = area.scly_graph()
+        .objects()
+        .filter(|(layer, obj)| *layer == 0 && is_door_lock(obj))
+        .map(|(_, obj)| obj.instance_id)
+        .collect();
+    area.remove_scly_objects(&door_locks);
+
     Ok(())
 }
 
@@ -3338,7 +4128,7 @@ fn is_forcefield<'r>(obj: &structs::SclyObject<'r>) -> bool {
     }
 }
 
-fn remove_forcefields(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn remove_forcefields(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -3356,22 +4146,21 @@ fn make_remove_forcefields_patch<'a>(patcher: &mut PrimePatcher<'_, 'a>)
 {
     patcher.add_scly_patch(
         resource_info!("11_mines.MREA").into(), // Metroid Quarantine B
-        move |_ps, area| remove_forcefields(_ps, area),
+        move |area| remove_forcefields(area),
     );
 
     patcher.add_scly_patch(
         resource_info!("01_mines_mainplaza.MREA").into(), // Main Quarry
-        move |_ps, area| remove_forcefields(_ps, area),
+        move |area| remove_forcefields(area),
     );
 
     patcher.add_scly_patch(
         resource_info!("05_mines_forcefields.MREA").into(), // Elite Control
-        move |_ps, area| remove_forcefields(_ps, area),
+        move |area| remove_forcefields(area),
     );
 }
 
 fn patch_spawn_point_position<'r>(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     new_position: Xyz,
 )
@@ -3414,7 +4203,6 @@ fn is_underwater_sound<'r>(obj: &structs::SclyObject<'r>) -> bool {
 
 /* Removes all water objects from the provided room */
 fn patch_remove_water<'r>(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
 )
 -> Result<(), String>
@@ -3431,7 +4219,6 @@ fn patch_remove_water<'r>(
 }
 
 fn patch_add_liquid<'r>(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     liquid_volume: &LiquidVolume,
     water_type: WaterType,
@@ -3467,7 +4254,6 @@ fn patch_add_liquid<'r>(
 }
 
 fn patch_full_underwater<'r>(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
 )
@@ -3545,7 +4331,6 @@ fn patch_full_underwater<'r>(
 }
 
 fn patch_transform_bounding_box<'r>(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     offset: Xyz,
     scale: Xyz,
@@ -3583,7 +4368,6 @@ fn is_area_damage_special_function<'r>(obj: &structs::SclyObject<'r>)
 }
 
 fn patch_deheat_room<'r>(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
 )
 -> Result<(), String>
@@ -3599,69 +4383,48 @@ fn patch_deheat_room<'r>(
 }
 
 fn patch_superheated_room<'r>(
-    _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
 )
 -> Result<(), String>
 {
+    // The area damage function and the counter it reports the player entering and leaving to are
+    // both new objects, so their IDs have to come from this area. The vanilla heated rooms also
+    // connect it to their own HUD effects and sounds, which don't exist here.
+    let special_function_id = area.new_object_id_from_layer_id(0);
+    let counter_id = area.new_object_id_from_layer_id(0);
+
+    let area_damage_counter = structs::SclyObject
+    {
+        instance_id: counter_id,
+        connections: vec![].into(),
+        property_data: structs::SclyProperty::Counter(
+            structs::Counter
+            {
+                name: b"Counter Area Damage-component\0".as_cstr(),
+                start_value: 0,
+                max_value: 1,
+                auto_reset: 0,
+                active: 1,
+            }
+        ),
+    };
+
     let area_damage_special_function = structs::SclyObject
     {
-        instance_id: 1310983,
+        instance_id: special_function_id,
         connections: vec![
             structs::Connection
             {
                 state: structs::ConnectionState::ENTERED,
                 message: structs::ConnectionMsg::INCREMENT,
-                target_object_id: 1310984
+                target_object_id: counter_id
             },
             structs::Connection
             {
                 state: structs::ConnectionState::EXITED,
                 message: structs::ConnectionMsg::DECREMENT,
-                target_object_id: 1310984
-            },
-            structs::Connection
-            {
-                state: structs::ConnectionState::ENTERED,
-                message: structs::ConnectionMsg::ACTIVATE,
-                target_object_id: 1310985
+                target_object_id: counter_id
             },
-            structs::Connection
-            {
-                state: structs::ConnectionState::EXITED,
-                message: structs::ConnectionMsg::DEACTIVATE,
-                target_object_id: 1310985
-            },
-            structs::Connection
-            {
-                state: structs::ConnectionState::ENTERED,
-                message: structs::ConnectionMsg::ACTIVATE,
-                target_object_id: 1310986
-            },
-            structs::Connection
-            {
-                state: structs::ConnectionState::EXITED,
-                message: structs::ConnectionMsg::DEACTIVATE,
-                target_object_id: 1310986
-            },
-            structs::Connection
-            {
-                state: structs::ConnectionState::ENTERED,
-                message: structs::ConnectionMsg::PLAY,
-                target_object_id: 1310987
-            },
-            structs::Connection
-            {
-                state: structs::ConnectionState::EXITED,
-                message: structs::ConnectionMsg::STOP,
-                target_object_id: 1310987
-            },
-            structs::Connection
-            {
-                state: structs::ConnectionState::ENTERED,
-                message: structs::ConnectionMsg::SET_TO_ZERO,
-                target_object_id: 1310988
-            }
         ].into(),
         property_data: structs::SclyProperty::SpecialFunction(
             structs::SpecialFunction
@@ -3688,11 +4451,12 @@ fn patch_superheated_room<'r>(
 
     let scly = area.mrea().scly_section_mut();
     let layer = &mut scly.layers.as_mut_vec()[0];
+    layer.objects.as_mut_vec().push(area_damage_counter);
     layer.objects.as_mut_vec().push(area_damage_special_function);
     Ok(())
 }
 
-fn patch_geothermal_core_destructible_rock_pal(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_geothermal_core_destructible_rock_pal(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -3716,7 +4480,7 @@ fn patch_geothermal_core_destructible_rock_pal(_ps: &mut PatcherState, area: &mu
     Ok(())
 }
 
-fn patch_ore_processing_destructible_rock_pal(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_ore_processing_destructible_rock_pal(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -3740,7 +4504,7 @@ fn patch_ore_processing_destructible_rock_pal(_ps: &mut PatcherState, area: &mut
     Ok(())
 }
 
-fn patch_main_quarry_door_lock_pal(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_main_quarry_door_lock_pal(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -3757,7 +4521,7 @@ fn patch_main_quarry_door_lock_pal(_ps: &mut PatcherState, area: &mut mlvl_wrapp
     Ok(())
 }
 
-fn patch_mines_security_station_soft_lock<'r>(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_mines_security_station_soft_lock<'r>(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -3779,7 +4543,7 @@ fn patch_mines_security_station_soft_lock<'r>(_ps: &mut PatcherState, area: &mut
     Ok(())
 }
 
-fn patch_gravity_chamber_stalactite_grapple_point<'r>(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea)
+fn patch_gravity_chamber_stalactite_grapple_point<'r>(area: &mut mlvl_wrapper::MlvlArea)
     -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
@@ -3815,7 +4579,7 @@ fn patch_main_menu(res: &mut structs::Resource) -> Result<(), String>
 {
     let frme = res.kind.as_frme_mut().unwrap();
 
-    frme.widgets.as_mut_vec().push(structs::FrmeWidget {
+    frme.add_widget(structs::FrmeWidget {
         name: b"textpane_identifier\0".as_cstr(),
         parent: b"kGSYS_HeadWidgetID\0".as_cstr(),
         use_anim_controller: 0,
@@ -3855,10 +4619,12 @@ fn patch_main_menu(res: &mut structs::Resource) -> Result<(), String>
         rotation_center: [0.0, 0.0, 0.0].into(),
         unknown0: 0,
         unknown1: 0,
-    });
+    })?;
 
-    let mut shadow_widget = frme.widgets.as_mut_vec().last().unwrap().clone();
-    shadow_widget.name = b"textpane_identifierb\0".as_cstr();
+    let shadow_widget = frme.duplicate_widget(
+        b"textpane_identifier",
+        b"textpane_identifierb\0".as_cstr(),
+    )?;
     let tp = match &mut shadow_widget.kind {
         structs::FrmeWidgetKind::TextPane(tp) => tp,
         _ => unreachable!(),
@@ -3869,13 +4635,11 @@ fn patch_main_menu(res: &mut structs::Resource) -> Result<(), String>
     shadow_widget.origin[1] -= -0.104353;
     shadow_widget.origin[2] -= 0.176318;
 
-    frme.widgets.as_mut_vec().push(shadow_widget);
-
     Ok(())
 }
 
 
-fn patch_credits(res: &mut structs::Resource, pickup_layout: &[PickupType])
+fn patch_credits(res: &mut structs::Resource, placed_pickups: &[PlacedPickup])
     -> Result<(), String>
 {
     use std::fmt::Write;
@@ -3910,16 +4674,13 @@ fn patch_credits(res: &mut structs::Resource, pickup_layout: &[PickupType])
         "&pop;",
     ).to_owned();
     for pickup_type in PICKUPS_TO_PRINT {
-        let room_idx = if let Some(i) = pickup_layout.iter().position(|i| i == pickup_type) {
-            i
+        let room_name = if let Some(placed) = placed_pickups.iter()
+            .find(|placed| placed.pickup_type == *pickup_type)
+        {
+            placed.room_name
         } else {
             continue
         };
-        let room_name = pickup_meta::PICKUP_LOCATIONS.iter()
-            .flat_map(|pak_locs| pak_locs.1.iter())
-            .flat_map(|loc| iter::repeat(loc.name).take(loc.pickup_locations.len()))
-            .nth(room_idx)
-            .unwrap();
         let pickup_name = pickup_type.name();
         write!(output, "\n\n{}: {}", pickup_name, room_name).unwrap();
     }
@@ -3935,24 +4696,213 @@ fn patch_credits(res: &mut structs::Resource, pickup_layout: &[PickupType])
     Ok(())
 }
 
+// Where a hinted room's hints lead once the seed's layout is applied
+#[derive(Clone, Debug)]
+struct HintTarget
+{
+    mlvl: u32,
+    mrea: u32,
+    room_idx: u32,
+    map_text_strg: u32,
+    // e.g. "the Wave Beam in Burn Dome (Magmoor Caverns)"
+    description: String,
+}
+
+// The changes to the HINT resource for a seed, along with the STRGs for the new text
+#[derive(Clone, Debug, Default)]
+struct HintPlan
+{
+    // Keyed by the room the hint leads to in the vanilla game
+    targets: HashMap<u32, HintTarget>,
+    // The new popup text for each retargeted hint, keyed by its index in the HINT
+    popup_text_strgs: HashMap<usize, u32>,
+    strgs: Vec<(u32, String)>,
+}
+
+// Hints lead players to upgrades: in the vanilla game each hinted room holds the upgrade it
+// hints at. The hints are rebuilt from the layout to lead to wherever the seed placed that
+// upgrade instead, with new popup and map text naming the item and its new room. When the
+// layout holds several copies of an item, the hinted locations that held it are matched up
+// with the locations it was placed at one-to-one, so that two hints never lead to the same
+// copy. A location that still holds its vanilla item keeps its hint.
+fn plan_hint_targets(
+    gc_disc: &structs::GcDisc,
+    placed_pickups: &[PlacedPickup],
+    asset_ids: &mut AssetIdAllocator,
+) -> Result<HintPlan, String>
+{
+    let hint_id = resource_info!("HINT_Hints_1.HINT").res_id;
+    let hint = gc_disc.find_resource("NoARAM.pak", |res| res.file_id == hint_id)
+        .ok_or_else(|| "Failed to find the HINT resource".to_string())?;
+    let hint = hint.kind.as_hint().unwrap();
+    let hinted_rooms: HashSet<u32> = hint.hints.iter()
+        .flat_map(|details| details.locations.iter().map(|loc| loc.mrea).collect::<Vec<_>>())
+        .collect();
+
+    // Every vanilla pickup location, in layout order
+    let locations = pickup_meta::PICKUP_LOCATIONS.iter()
+        .flat_map(|(pak_name, rooms)| {
+            rooms.iter().flat_map(move |room_info| {
+                room_info.pickup_locations.iter()
+                    .map(move |pickup_location| (*pak_name, room_info, pickup_location))
+            })
+        })
+        .enumerate();
+
+    // The vanilla item each hinted room hints at. Rooms with several pickups hint at their
+    // upgrade rather than an expansion.
+    let is_expansion = |kind: u32| [PickupType::Missile, PickupType::EnergyTank,
+                                    PickupType::PowerBombExpansion]
+        .iter()
+        .any(|pt| pt.pickup_data().kind == kind);
+    let mut hinted_items: Vec<(u32, usize, u32)> = vec![];
+    for (location_idx, (pak_name, room_info, pickup_location)) in locations {
+        if !hinted_rooms.contains(&room_info.room_id) {
+            continue;
+        }
+
+        let mrea = gc_disc.find_resource(pak_name, |res| res.file_id == room_info.room_id)
+            .ok_or_else(|| format!("Failed to find room 0x{:X} in {}", room_info.room_id, pak_name))?;
+        let mrea = mrea.kind.as_mrea().unwrap();
+        let scly = mrea.scly_section();
+        let kind = scly.layers.iter()
+            .nth(pickup_location.location.layer as usize)
+            .and_then(|layer| {
+                layer.objects.iter()
+                    .find(|obj| obj.instance_id == pickup_location.location.instance_id)
+                    .and_then(|obj| obj.property_data.as_pickup().map(|pickup| pickup.kind))
+            })
+            .ok_or_else(|| format!("Failed to find the pickup in room 0x{:X}", room_info.room_id))?;
+
+        let existing = hinted_items.iter().position(|&(room_id, _, _)| room_id == room_info.room_id);
+        match existing {
+            None => hinted_items.push((room_info.room_id, location_idx, kind)),
+            Some(i) if is_expansion(hinted_items[i].2) && !is_expansion(kind) => {
+                hinted_items[i] = (room_info.room_id, location_idx, kind);
+            },
+            Some(_) => (),
+        }
+    }
+
+    let mut used = HashSet::new();
+    // Locations that still hold their vanilla item are claimed first
+    for &(_, location_idx, kind) in &hinted_items {
+        if placed_pickups.get(location_idx).map(|placed| placed.pickup_type.pickup_data().kind) ==
+            Some(kind) {
+            used.insert(location_idx);
+        }
+    }
+
+    let mut plan = HintPlan::default();
+    for &(room_id, location_idx, kind) in &hinted_items {
+        let placed = if placed_pickups.get(location_idx)
+            .map(|placed| placed.pickup_type.pickup_data().kind) == Some(kind) {
+            placed_pickups.get(location_idx)
+        } else {
+            placed_pickups.iter()
+                .find(|placed| {
+                    placed.pickup_type.pickup_data().kind == kind &&
+                        !used.contains(&placed.location_idx)
+                })
+        };
+        let placed = match placed {
+            Some(placed) => placed,
+            // The seed doesn't contain this item
+            None => continue,
+        };
+        used.insert(placed.location_idx);
+
+        let world = World::from_pak(placed.pak_name).unwrap();
+        let map_text_strg = asset_ids.next_id()?;
+        let item_name = placed.pickup_type.name();
+        plan.strgs.push((map_text_strg, format!("{}\0", item_name)));
+        plan.targets.insert(room_id, HintTarget {
+            mlvl: world.mlvl(),
+            mrea: placed.room_id,
+            room_idx: placed.room_idx,
+            map_text_strg,
+            description: format!(
+                "the &push;&main-color=#FF6705B3;{}&pop; in &push;&main-color=#43CD80;{}&pop; ({})",
+                item_name, placed.room_name, world.as_string(),
+            ),
+        });
+    }
+
+    for (i, details) in hint.hints.iter().enumerate() {
+        let mut descriptions: Vec<&str> = vec![];
+        for location in details.locations.iter() {
+            if let Some(target) = plan.targets.get(&location.mrea) {
+                if !descriptions.contains(&&target.description[..]) {
+                    descriptions.push(&target.description);
+                }
+            }
+        }
+        if descriptions.is_empty() {
+            continue;
+        }
+        let popup_text_strg = asset_ids.next_id()?;
+        let text = format!("Samus, look for {}.\0", descriptions.join(" and "));
+        plan.strgs.push((popup_text_strg, text));
+        plan.popup_text_strgs.insert(i, popup_text_strg);
+    }
+    Ok(plan)
+}
+
+fn patch_hint_locations(res: &mut structs::Resource, plan: &HintPlan)
+    -> Result<(), String>
+{
+    let hint = res.kind.as_hint_mut().unwrap();
+    for (i, details) in hint.hints.as_mut_vec().iter_mut().enumerate() {
+        if let Some(&popup_text_strg) = plan.popup_text_strgs.get(&i) {
+            details.popup_text_strg = popup_text_strg;
+        }
+        for location in details.locations.as_mut_vec().iter_mut() {
+            if let Some(target) = plan.targets.get(&location.mrea) {
+                location.mlvl = target.mlvl;
+                location.mrea = target.mrea;
+                location.target_room_index = target.room_idx;
+                location.map_text_strg = target.map_text_strg;
+            }
+        }
+    }
+    Ok(())
+}
+
+// The HINT is in NoARAM.pak, so the text for the rewritten hints goes there too
+fn add_hint_strgs(file: &mut structs::FstEntryFile, plan: &HintPlan) -> Result<(), String>
+{
+    let pak = match file {
+        structs::FstEntryFile::Pak(pak) => pak,
+        _ => unreachable!(),
+    };
+    let strgs = plan.strgs.iter().map(|(id, text)| {
+        pickup_meta::build_resource(
+            *id,
+            structs::ResourceKind::Strg(structs::Strg::from_strings(vec![text.clone()])),
+        )
+    });
+    pak.resources.cursor().insert_after(strgs);
+    Ok(())
+}
+
 
+// `starting_state` is the index into STARTING_STATE_MARKER_ITEMS of the marker to give if the
+// REL has to give part of the starting items
 fn patch_starting_pickups(
     area: &mut mlvl_wrapper::MlvlArea,
-    mut starting_items: u64,
+    starting_items: &StartingItems,
+    starting_state: usize,
     debug_print: bool,
 ) -> Result<(), String>
 {
+    let marker = STARTING_STATE_MARKER_ITEMS[starting_state] as usize;
+    let mut starting_items = starting_items.clone();
+    if starting_items.needs_rel() {
+        starting_items.set_amount(marker, 1);
+    }
 
     let scly = area.mrea().scly_section_mut();
     let mut first = debug_print;
-    macro_rules! print_maybe {
-        ($first:ident, $($tts:tt)*) => {
-            if $first {
-                println!($($tts)*);
-            }
-
-        };
-    }
     for layer in scly.layers.iter_mut() {
         for obj in layer.objects.iter_mut() {
             let spawn_point = if let Some(spawn_point) = obj.property_data.as_spawn_point_mut() {
@@ -3961,82 +4911,27 @@ fn patch_starting_pickups(
                 continue;
             };
 
-            let mut fetch_bits = move |bits: u8| {
-                let ret = starting_items & ((1 << bits) - 1);
-                starting_items >>= bits;
-                ret as u32
-            };
-
-            print_maybe!(first, "Starting pickups set:");
-
-            spawn_point.scan_visor = fetch_bits(1);
-            print_maybe!(first, "    scan_visor: {}", spawn_point.scan_visor);
-
-            spawn_point.missiles = fetch_bits(8);
-            print_maybe!(first, "    missiles: {}", spawn_point.missiles);
-
-            spawn_point.energy_tanks = fetch_bits(4);
-            print_maybe!(first, "    energy_tanks: {}", spawn_point.energy_tanks);
-
-            spawn_point.power_bombs = fetch_bits(4);
-            print_maybe!(first, "    power_bombs: {}", spawn_point.power_bombs);
-
-            spawn_point.wave = fetch_bits(1);
-            print_maybe!(first, "    wave: {}", spawn_point.wave);
-
-            spawn_point.ice = fetch_bits(1);
-            print_maybe!(first, "    ice: {}", spawn_point.ice);
-
-            spawn_point.plasma = fetch_bits(1);
-            print_maybe!(first, "    plasma: {}", spawn_point.plasma);
-
-            spawn_point.charge = fetch_bits(1);
-            print_maybe!(first, "    charge: {}", spawn_point.charge);
-
-            spawn_point.morph_ball = fetch_bits(1);
-            print_maybe!(first, "    morph_ball: {}", spawn_point.morph_ball);
-
-            spawn_point.bombs = fetch_bits(1);
-            print_maybe!(first, "    bombs: {}", spawn_point.bombs);
-
-            spawn_point.spider_ball = fetch_bits(1);
-            print_maybe!(first, "    spider_ball: {}", spawn_point.spider_ball);
-
-            spawn_point.boost_ball = fetch_bits(1);
-            print_maybe!(first, "    boost_ball: {}", spawn_point.boost_ball);
-
-            spawn_point.varia_suit = fetch_bits(1);
-            print_maybe!(first, "    varia_suit: {}", spawn_point.varia_suit);
-
-            spawn_point.gravity_suit = fetch_bits(1);
-            print_maybe!(first, "    gravity_suit: {}", spawn_point.gravity_suit);
-
-            spawn_point.phazon_suit = fetch_bits(1);
-            print_maybe!(first, "    phazon_suit: {}", spawn_point.phazon_suit);
-
-            spawn_point.thermal_visor = fetch_bits(1);
-            print_maybe!(first, "    thermal_visor: {}", spawn_point.thermal_visor);
-
-            spawn_point.xray= fetch_bits(1);
-            print_maybe!(first, "    xray: {}", spawn_point.xray);
-
-            spawn_point.space_jump = fetch_bits(1);
-            print_maybe!(first, "    space_jump: {}", spawn_point.space_jump);
-
-            spawn_point.grapple = fetch_bits(1);
-            print_maybe!(first, "    grapple: {}", spawn_point.grapple);
-
-            spawn_point.super_missile = fetch_bits(1);
-            print_maybe!(first, "    super_missile: {}", spawn_point.super_missile);
-
-            spawn_point.wavebuster = fetch_bits(1);
-            print_maybe!(first, "    wavebuster: {}", spawn_point.wavebuster);
-
-            spawn_point.ice_spreader = fetch_bits(1);
-            print_maybe!(first, "    ice_spreader: {}", spawn_point.ice_spreader);
-
-            spawn_point.flamethrower = fetch_bits(1);
-            print_maybe!(first, "    flamethrower: {}", spawn_point.flamethrower);
+            let changed = starting_items.update_spawn_point(spawn_point);
+            if first {
+                println!("Starting pickups set:");
+                for (item, amount) in changed {
+                    if item != marker {
+                        println!("    {}: {}", StartingItems::item_name(item), amount);
+                    }
+                }
+                for item in starting_items.artifacts() {
+                    println!("    {}: 1 (given by the REL)", StartingItems::item_name(item as usize));
+                }
+                if let Some(visor) = starting_items.visor() {
+                    println!("    Selected visor: {}", visor);
+                }
+                if let Some(beam) = starting_items.beam() {
+                    println!("    Selected beam: {}", beam);
+                }
+                if let Some(energy) = starting_items.energy() {
+                    println!("    Energy: {}", energy);
+                }
+            }
 
             first = false;
         }
@@ -4045,18 +4940,175 @@ fn patch_starting_pickups(
 }
 
 include!("../compile_to_ppc/patches_config.rs");
+
+// The address of the only `bl` to `target` in [fn_addr, fn_end)
+fn find_call_site(dol: &DolPatcher, fn_addr: u32, fn_end: u32, target: u32) -> Result<u32, String>
+{
+    let mut call_sites = vec![];
+    for addr in (fn_addr..fn_end).step_by(4) {
+        let instr = dol.read_u32(addr)?;
+        if instr & 0xfc000003 != 0x48000001 {
+            continue
+        }
+        // Sign-extend the 24 bit displacement
+        let rel_addr = (((instr & 0x03fffffc) << 6) as i32) >> 6;
+        if addr.wrapping_add(rel_addr as u32) == target {
+            call_sites.push(addr);
+        }
+    }
+    match call_sites[..] {
+        [addr] => Ok(addr),
+        _ => Err(format!(
+            "Expected one call to {:x} from the function at {:x}, found {}",
+            target, fn_addr, call_sites.len(),
+        )),
+    }
+}
+
 fn create_rel_config_file(
+    dol: &DolPatcher,
     spawn_room: SpawnRoom,
     quickplay: bool,
-) -> Vec<u8>
+    version: Version,
+    config: &ParsedConfig,
+) -> Result<Vec<u8>, String>
 {
-    let config = RelConfig {
+    let chains = progressive_item_chains(config)?;
+    let mut progressive_items = [[0xFFFFFFFF; PROGRESSIVE_ITEM_CHAIN_LEN]; PROGRESSIVE_ITEM_CHAIN_COUNT];
+    for (chain, (_, items)) in progressive_items.iter_mut().zip(chains.iter()) {
+        for (kind, pickup_type) in chain.iter_mut().zip(items.iter()) {
+            *kind = pickup_type.pickup_data().kind;
+        }
+    }
+
+    // The REL's pickup hooks are shared by progressive items and multiworld
+    let hook_pickups = !chains.is_empty() || config.multiworld;
+
+    let mut starting_items = vec![&config.new_save_starting_items];
+    if !config.skip_frigate {
+        starting_items.push(&config.frigate_done_starting_items);
+    }
+    let mut starting_states = [StartingState {
+        artifacts: 0,
+        visor: 0xFFFFFFFF,
+        beam: 0xFFFFFFFF,
+        energy: 0xFFFFFFFF,
+    }; STARTING_STATE_COUNT];
+    for (state, items) in starting_states.iter_mut().zip(starting_items.iter()) {
+        state.artifacts = items.artifacts().fold(0, |bits, item| bits | 1 << (item - 29));
+        state.visor = items.visor().unwrap_or(0xFFFFFFFF);
+        state.beam = items.beam().unwrap_or(0xFFFFFFFF);
+        state.energy = items.energy().unwrap_or(0xFFFFFFFF);
+    }
+    let hook_starting_states = starting_items.iter().any(|items| items.needs_rel());
+
+    macro_rules! hook_symbol_addr {
+        ($sym:tt, $enabled:expr) => {
+            if !$enabled {
+                0
+            } else {
+                let s = mp1_symbol!($sym);
+                match version {
+                    Version::Ntsc0_00 => s.addr_0_00,
+                    Version::Ntsc0_01 => s.addr_0_01,
+                    Version::Ntsc0_02 => s.addr_0_02,
+                    Version::Pal      => s.addr_pal,
+                }.ok_or_else(|| format!(
+                    "Progressive items, multiworld and starting artifacts, visors, beams or \
+                     energy aren't supported for version {}",
+                    version,
+                ))?
+            }
+        }
+    }
+
+    // The calls the REL redirects. Each is looked up between the start of the function that
+    // makes it and the start of the function after it, and has to be the only call there to its
+    // target, so the REL patches exactly these instructions and nothing else.
+    macro_rules! hook_call_site {
+        ($fn_sym:tt, $next_sym:tt, $target:expr, $enabled:expr) => {
+            if !$enabled {
+                0
+            } else {
+                find_call_site(
+                    dol,
+                    hook_symbol_addr!($fn_sym, true),
+                    hook_symbol_addr!($next_sym, true),
+                    $target,
+                )?
+            }
+        }
+    }
+
+    let initialize_power_up_fn = hook_symbol_addr!(
+        "InitializePowerUp__12CPlayerStateFQ212CPlayerState9EItemTypei",
+        hook_pickups || hook_starting_states
+    );
+    let incr_pickup_fn = hook_symbol_addr!(
+        "IncrPickUp__12CPlayerStateFQ212CPlayerState9EItemTypei",
+        hook_pickups || hook_starting_states
+    );
+    let update_static_interference_fn = hook_symbol_addr!(
+        "UpdateStaticInterference__12CPlayerStateFR13CStateManagerRCf",
+        config.multiworld || hook_starting_states
+    );
+    let save_player_state_fn = hook_symbol_addr!(
+        "PutTo__12CPlayerStateFR13COutPutStream",
+        config.multiworld
+    );
+    let load_player_state_fn = hook_symbol_addr!(
+        "__ct__12CPlayerStateFR12CInputStream",
+        config.multiworld
+    );
+    let rel_config = RelConfig {
         quickplay_mlvl: if quickplay { spawn_room.mlvl } else { 0xFFFFFFFF },
         quickplay_mrea: if quickplay { spawn_room.mrea } else { 0xFFFFFFFF },
+        initialize_power_up_call: hook_call_site!(
+            "Touch__13CScriptPickupFR6CActorR13CStateManager",
+            "Think__13CScriptPickupFfR13CStateManager",
+            initialize_power_up_fn,
+            hook_pickups
+        ),
+        incr_pickup_call: hook_call_site!(
+            "Touch__13CScriptPickupFR6CActorR13CStateManager",
+            "Think__13CScriptPickupFfR13CStateManager",
+            incr_pickup_fn,
+            hook_pickups
+        ),
+        initialize_power_up_fn,
+        incr_pickup_fn,
+        update_static_interference_call: hook_call_site!(
+            "Update__13CStateManagerFf",
+            "PostUpdatePlayer__13CStateManagerFf",
+            update_static_interference_fn,
+            config.multiworld || hook_starting_states
+        ),
+        update_static_interference_fn,
+        save_player_state_call: hook_call_site!(
+            "PutTo__10CGameStateFR16CMemoryStreamOut",
+            "LoadGameFileState__10CGameStateFPCv",
+            save_player_state_fn,
+            config.multiworld
+        ),
+        save_player_state_fn,
+        load_player_state_call: hook_call_site!(
+            "LoadGameFileState__10CGameStateFPCv",
+            "InitializeMemoryWorlds__10CGameStateFv",
+            load_player_state_fn,
+            config.multiworld
+        ),
+        load_player_state_fn,
+        dc_flush_range_fn: hook_symbol_addr!("DCFlushRange", hook_pickups || hook_starting_states),
+        ic_invalidate_range_fn: hook_symbol_addr!(
+            "ICInvalidateRange",
+            hook_pickups || hook_starting_states
+        ),
+        starting_states,
+        progressive_items,
     };
     let mut buf = vec![0; mem::size_of::<RelConfig>()];
-    ssmarshal::serialize(&mut buf, &config).unwrap();
-    buf
+    ssmarshal::serialize(&mut buf, &rel_config).unwrap();
+    Ok(buf)
 }
 
 fn patch_dol<'r>(
@@ -4385,6 +5437,10 @@ pub struct ParsedConfig
     pub liquid_volumes: Vec<LiquidVolume>,
     pub aether_transforms: Vec<AetherTransform>,
     pub additional_items: Vec<AdditionalItem>,
+    pub pickup_overrides: Vec<PickupLocationOverride>,
+    pub progressive_items: Vec<ProgressiveItem>,
+    pub pickup_models: Vec<PickupModel>,
+    pub multiworld: bool,
     pub new_save_spawn_room: String,
     pub frigate_done_spawn_room: String,
     pub item_seed: u64,
@@ -4416,15 +5472,24 @@ pub struct ParsedConfig
     pub enable_vault_ledge_door: bool,
     pub artifact_hint_behavior: ArtifactHintBehavior,
     pub patch_vertical_to_blue: bool,
+    pub shuffle_door_destinations: bool,
+    pub shuffle_elevators: bool,
+    pub patch_hints: bool,
 
     pub flaahgra_music_files: Option<[nod_wrapper::FileWrapper; 2]>,
 
-    pub new_save_starting_items: u64,
-    pub frigate_done_starting_items: u64,
+    pub new_save_starting_items: StartingItems,
+    pub frigate_done_starting_items: StartingItems,
 
     pub comment: String,
     pub main_menu_message: String,
 
+    // Where to write the spoiler log, if anywhere. It's written as JSON if the path ends in
+    // ".json" and as text otherwise.
+    pub spoiler_path: Option<String>,
+    // If set, the spoiler is also stored on the disc, encrypted with this key
+    pub spoiler_key: Option<[u8; 32]>,
+
     pub quickplay: bool,
 
     pub bnr_game_name: Option<String>,
@@ -4483,7 +5548,8 @@ pub fn patch_iso<T>(mut config: ParsedConfig, mut pn: T) -> Result<(), String>
 
     let mut reader = Reader::new(&config.input_iso[..]);
 
-    let mut gc_disc: structs::GcDisc = reader.read(());
+    let mut gc_disc: structs::GcDisc = reader.try_read(())
+        .map_err(|e| format!("Failed to parse the input ISO: {}", e))?;
 
     let version = match (&gc_disc.header.game_identifier(), gc_disc.header.disc_id, gc_disc.header.version) {
         (b"GM8E01", 0, 0) => Version::Ntsc0_00,
@@ -4505,11 +5571,31 @@ pub fn patch_iso<T>(mut config: ParsedConfig, mut pn: T) -> Result<(), String>
         Err("The NTSC 0-01 and PAL versions of Metroid Prime are not current supported.")?;
     }
 
-    build_and_run_patches(&mut gc_disc, &config, version)?;
+    let (spoiler, warnings) = build_and_run_patches(&mut gc_disc, &config, version)?;
+    for warning in &warnings {
+        pn.notify_warning(warning);
+    }
 
     gc_disc.add_file("randomprime.txt", structs::FstEntryFile::Unknown(Reader::new(&ct)))?;
     gc_disc.add_file("mpdr.txt",structs::FstEntryFile::Unknown(Reader::new(&dt)))?;
 
+    if let Some(spoiler_path) = &config.spoiler_path {
+        let contents = if spoiler_path.to_lowercase().ends_with(".json") {
+            spoiler.to_json()?
+        } else {
+            spoiler.to_string()
+        };
+        fs::write(spoiler_path, contents)
+            .map_err(|e| format!("Failed to write the spoiler log: {}", e))?;
+    }
+    if let Some(key) = &config.spoiler_key {
+        let encrypted_spoiler = spoiler::encrypt_spoiler(&spoiler, key)?;
+        gc_disc.add_file(
+            spoiler::SPOILER_FILE_NAME,
+            structs::FstEntryFile::ExternalFile(Box::new(encrypted_spoiler)),
+        )?;
+    }
+
 
     if !config.is_item_randomized.unwrap_or(false) && version != Version::Ntsc0_01 && version != Version::Pal {
         let patches_rel_bytes = match version {
@@ -4615,15 +5701,65 @@ fn room_strg_id_from_mrea_id(mrea_id: u32) -> (u32, u32)
     (0, 0)
 }
 
+// The name of the room with the given MREA id, for the spoiler
+fn spoiler_room_name(mrea: u32) -> String
+{
+    if mrea == Elevator::end_game_elevator().mrea {
+        return "Credits".to_string();
+    }
+    for (pak_name, rooms) in pickup_meta::PICKUP_LOCATIONS.iter() {
+        if let Some(room_info) = rooms.iter().find(|room_info| room_info.room_id == mrea) {
+            let world = World::from_pak(pak_name).unwrap();
+            return format!("{} - {}", world.as_string(), room_info.name);
+        }
+    }
+    format!("0x{:08X}", mrea)
+}
+
+// Returns the spoiler and any warnings about the seed
 fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, version: Version)
-    -> Result<(), String>
+    -> Result<(Spoiler, Vec<String>), String>
 {
+    let mut warnings = vec![];
     let pickup_layout: Vec<_> = config.pickup_layout.iter()
         .map(|i| PickupType::from_idx(*i as usize).unwrap())
         .collect();
     let pickup_layout = &pickup_layout[..];
+    let placed_pickups = collect_placed_pickups(pickup_layout, config)?;
+    let placed_pickups = &placed_pickups[..];
 
-    let mut elevator_layout: Vec<_> = config.elevator_layout[..ELEVATORS.len()].iter()
+    // Pickups in a progressive item chain are listed under the chain's name, since which of its
+    // items they give depends on the order they're picked up in
+    let progressive_names: HashMap<_, _> = progressive_item_chains(config)?.into_iter()
+        .flat_map(|(name, items)| items.into_iter().map(move |item| (item, name)))
+        .collect();
+    let mut spoiler = Spoiler {
+        seed: config.seed,
+        starting_items: spoiler::starting_item_names(&config.new_save_starting_items),
+        frigate_done_starting_items: if config.skip_frigate {
+            None
+        } else {
+            Some(spoiler::starting_item_names(&config.frigate_done_starting_items))
+        },
+        locations: placed_pickups.iter()
+            .map(|placed| SpoilerLocation {
+                world: World::from_pak(placed.pak_name).unwrap().as_string(),
+                room: placed.room_name.to_string(),
+                item: progressive_names.get(&placed.pickup_type)
+                    .cloned()
+                    .unwrap_or_else(|| placed.pickup_type.name())
+                    .to_string(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let elevator_layout_indices = if config.shuffle_elevators {
+        door_randomizer::shuffle_elevators(&mut StdRng::seed_from_u64(config.seed), &config.elevator_layout)?
+    } else {
+        config.elevator_layout[..ELEVATORS.len()].to_vec()
+    };
+    let mut elevator_layout: Vec<_> = elevator_layout_indices.iter()
         .map(|i| ELEVATORS[*i as usize])
         .map(|elv| if config.skip_impact_crater && elv.name == "Crater Entry Point" {
                 Elevator::end_game_elevator()
@@ -4651,6 +5787,15 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
         idx = idx + 1;
     }
 
+    spoiler.elevators = ELEVATORS.iter()
+        .zip(elevator_layout.iter())
+        .filter(|(elv, _)| !elv.pak_name.is_empty())
+        .map(|(elv, dest)| SpoilerElevator {
+            elevator: elv.name.replace('\0', " "),
+            destination: spoiler_room_name(dest.mrea),
+        })
+        .collect();
+
     // The room the player spawns in after starting a new save
     let new_save_spawn_room = {
         if config.new_save_spawn_room.to_string() == "" { // if unspecified
@@ -4680,13 +5825,55 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
     // println!("frigate_done_spawn_room - 0x{:X}", frigate_done_spawn_room.mrea);
      
     let mut rng = StdRng::seed_from_u64(config.seed);
-    let artifact_totem_strings = build_artifact_temple_totem_scan_strings(pickup_layout, &mut rng);
+    let artifact_totem_strings = build_artifact_temple_totem_scan_strings(placed_pickups, &mut rng);
     let mut pickup_resources = collect_pickup_resources(gc_disc);
     let door_resources = collect_door_resources(gc_disc);
     let liquid_resources = collect_liquid_resources(gc_disc);
+    let (dock_shuffles, door_partners) = if config.shuffle_door_destinations {
+        plan_dock_shuffles(gc_disc, config, &[new_save_spawn_room, frigate_done_spawn_room])?
+    } else {
+        (vec![], HashMap::new())
+    };
+    // Starting artifacts are given through the Artifact Temple's layers, which are shared by
+    // both spawns
+    let mut starting_artifacts: HashSet<_> = config.new_save_starting_items.artifacts().collect();
+    if !config.skip_frigate {
+        starting_artifacts.extend(config.frigate_done_starting_items.artifacts());
+    }
+    let starting_artifacts = &starting_artifacts;
     if config.skip_hudmenus {
         add_skip_hudmemos_strgs(&mut pickup_resources);
     }
+    if !config.progressive_items.is_empty() && config.is_item_randomized.unwrap_or(false) {
+        Err("Progressive items can't be added to an ISO that has already been randomized")?
+    }
+    if config.multiworld && config.is_item_randomized.unwrap_or(false) {
+        Err("Multiworld can't be enabled on an ISO that has already been randomized")?
+    }
+    let mut asset_ids = AssetIdAllocator::default();
+    let progressive_item_strgs = add_progressive_item_strgs(
+        &mut pickup_resources,
+        config,
+        &mut asset_ids,
+    )?;
+    let custom_models = create_custom_pickup_models(
+        &mut pickup_resources,
+        config,
+        &mut asset_ids,
+    )?;
+    let (pickup_overrides, additional_item_overrides) = resolve_pickup_overrides(
+        &config.pickup_overrides,
+        &config.additional_items,
+        config.multiworld,
+        &mut pickup_resources,
+        &custom_models,
+        &mut asset_ids,
+    )?;
+    let hint_plan = if config.patch_hints && !config.is_item_randomized.unwrap_or(false) {
+        plan_hint_targets(gc_disc, placed_pickups, &mut asset_ids)?
+    } else {
+        HintPlan::default()
+    };
 
     // XXX These values need to out live the patcher
     let select_game_fmv_suffix = ["A", "B", "C"].choose(&mut rng).unwrap();
@@ -4766,18 +5953,18 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
     // Fix rooms with stupid spawn points
     patcher.add_scly_patch(
         resource_info!("1a_morphballtunnel.MREA").into(), // piston tunnel
-        move |_ps, area| patch_spawn_point_position(_ps, area, Xyz{x:124.57, y:-96.78, z:18.85}),
+        move |area| patch_spawn_point_position(area, Xyz{x:124.57, y:-96.78, z:18.85}),
     );
 
     patcher.add_scly_patch(
         resource_info!("00_mines_savestation_b.MREA").into(), // missile station mines
-        move |_ps, area| patch_spawn_point_position(_ps, area, Xyz{x:209.27, y:14.87, z:-140.29}),
+        move |area| patch_spawn_point_position(area, Xyz{x:209.27, y:14.87, z:-140.29}),
     );
 
     if config.biohazard_containment_alt_spawn {
         patcher.add_scly_patch(
             resource_info!("05_under_intro_zoo.MREA").into(), // biohazard containment
-            move |_ps, area| patch_spawn_point_position(_ps, area, Xyz{x:-148.91, y:247.18, z:-71.78}),
+            move |area| patch_spawn_point_position(area, Xyz{x:-148.91, y:247.18, z:-71.78}),
         );  
     }
 
@@ -4792,7 +5979,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
 
         patcher.add_scly_patch(
             (room.pak_name.as_bytes(), room.mrea),
-            move |_ps, area| patch_deheat_room(_ps, area),
+            move |area| patch_deheat_room(area),
         );
     }
 
@@ -4802,7 +5989,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
 
         patcher.add_scly_patch(
             (room.pak_name.as_bytes(), room.mrea),
-            move |_ps, area| patch_superheated_room(_ps, area),
+            move |area| patch_superheated_room(area),
         );
     }
 
@@ -4811,7 +5998,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
         let room = spawn_room_from_string(room_name.to_string());
         patcher.add_scly_patch(
             (room.pak_name.as_bytes(), room.mrea),
-            move |_ps, area| patch_remove_water(_ps, area),
+            move |area| patch_remove_water(area),
         );
     }
 
@@ -4836,7 +6023,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
 
         patcher.add_scly_patch(
             (room.pak_name.as_bytes(), room.mrea),
-            move |_ps, area| patch_add_liquid(_ps, area, liquid_volume, water_type, liquid_resources),
+            move |area| patch_add_liquid(area, liquid_volume, water_type, liquid_resources),
         );
     }
 
@@ -4846,7 +6033,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
         let room = spawn_room_from_string(room_name.to_string());
         patcher.add_scly_patch(
             (room.pak_name.as_bytes(), room.mrea),
-            move |_ps, area| patch_full_underwater(_ps, area, liquid_resources),
+            move |area| patch_full_underwater(area, liquid_resources),
         );
     }
 
@@ -4856,15 +6043,32 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
         let room = spawn_room_from_string(aether_transform.room.to_string());
         patcher.add_scly_patch(
             (room.pak_name.as_bytes(), room.mrea),
-            move |_ps, area| patch_transform_bounding_box(_ps, area, aether_transform.offset, aether_transform.scale),
+            move |area| patch_transform_bounding_box(area, aether_transform.offset, aether_transform.scale),
         );
     }
     
+    // Rewire which rooms the doors lead to
+    for shuffle in dock_shuffles.iter() {
+        patcher.add_resource_patch(
+            (&[shuffle.pak_name.as_bytes()], shuffle.mlvl, b"MLVL".into()),
+            move |res| patch_dock_connections(res, shuffle)
+        );
+        for &(mrea, _, area_move) in shuffle.moved_areas.iter() {
+            patcher.add_scly_patch(
+                (shuffle.pak_name.as_bytes(), mrea),
+                move |area| patch_moved_area(area, area_move)
+            );
+        }
+    }
+
     // Patch pickups and doors
-    let mut layout_iterator = pickup_layout.iter();
-    let mut door_rng = StdRng::seed_from_u64(config.seed);
+    let mut placed_pickups_iter = placed_pickups.iter();
+    let (door_colors, door_warning) =
+        roll_completable_door_colors(config, pickup_layout, &door_partners)?;
+    warnings.extend(door_warning);
     for (name, rooms) in pickup_meta::PICKUP_LOCATIONS.iter() { // for each .pak
         let world = World::from_pak(name).unwrap();
+        let world_name = world.as_string();
         let level = world as usize;
 
         if level == 0 && config.skip_frigate {continue;} // If we're skipping the frigate, there's nothing to patch
@@ -4872,7 +6076,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
         for room_info in rooms.iter() { // for each room in the pak
             // patch the item locations
             if !config.is_item_randomized.unwrap_or(false) {
-                 patcher.add_scly_patch((name.as_bytes(), room_info.room_id), move |_, area| {
+                 patcher.add_scly_patch((name.as_bytes(), room_info.room_id), move |area| {
                     // Remove objects
                     let layers = area.mrea().scly_section_mut().layers.as_mut_vec();
                     for otr in room_info.objects_to_remove {
@@ -4881,24 +6085,30 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
                     }
                     Ok(())
                 });
-                let iter = room_info.pickup_locations.iter().zip(&mut layout_iterator);
-                for (&pickup_location, &pickup_type) in iter {
+                let iter = room_info.pickup_locations.iter().enumerate().zip(&mut placed_pickups_iter);
+                for ((location_idx, &pickup_location), &placed) in iter {
                     // 1 in 1024 chance of a missile being shiny means a player is likely to see a
                     // shiny missile every 40ish games (assuming most players collect about half of the
                     // missiles)
-                    let pickup_type = if pickup_type == PickupType::Missile && rng.gen_ratio(1, 1024) {
-                        PickupType::ShinyMissile
+                    let placed = if placed.pickup_type == PickupType::Missile && rng.gen_ratio(1, 1024) {
+                        PlacedPickup { pickup_type: PickupType::ShinyMissile, ..placed }
                     } else {
-                        pickup_type
+                        placed
                     };
+                    let pickup_type = placed.pickup_type;
+                    let mut pickup_override = pickup_overrides.get(&(room_info.room_id, location_idx))
+                        .cloned()
+                        .unwrap_or_default();
+                    if pickup_override.hudmemo_strg.is_none() {
+                        pickup_override.hudmemo_strg = progressive_item_strgs.get(&pickup_type).cloned();
+                    }
                     patcher.add_scly_patch(
                         (name.as_bytes(), room_info.room_id),
-                        move |ps, area| modify_pickups_in_mrea(
-                                ps,
+                        move |area| modify_pickups_in_mrea(
                                 area,
-                                pickup_type,
+                                placed,
                                 pickup_location,
-                                0xFFFFFFFF,
+                                pickup_override,
                                 pickup_resources,
                                 config
                             )
@@ -4912,32 +6122,13 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
             {
                 if door_location.dock_number.is_none() { continue; }
                 let door_index = door_location.dock_number.unwrap() as usize;
-                
-                // println!("excluded_doors[{}][{}][{}]", level, room_info.name.to_string(), door_index);
-                let door_specification = &config.excluded_doors[level][room_info.name][door_index];
-
-                let is_vertical_door =  (room_info.room_id == 0x11BD63B7 && door_index == 0) || // Tower Chamber
-                                        (room_info.room_id == 0x0D72F1F7 && door_index == 1) || // Tower of Light
-                                        (room_info.room_id == 0xFB54A0CB && door_index == 4) || // Hall of the Elders 
-                                        (room_info.room_id == 0xE1981EFC && door_index == 0) || // Elder Chamber
-                                        (room_info.room_id == 0x43E4CC25 && door_index == 1) || // Research Lab Hydra
-                                        (room_info.room_id == 0x37BBB33C && door_index == 1) || // Observatory Access
-                                        (room_info.room_id == 0xD8E905DD && door_index == 1) || // Research Core Access
-                                        (room_info.room_id == 0x21B4BFF6 && door_index == 1) || // Research Lab Aether
-                                        (room_info.room_id == 0x3F375ECC && door_index == 2) || // Omega Research
-                                        (room_info.room_id == 0xF517A1EA && door_index == 1) || // Dynamo Access (Careful of Chozo room w/ same name)
-                                        (room_info.room_id == 0x8A97BB54 && door_index == 1) || // Elite Research
-                                        (room_info.room_id == 0xA20201D4                   ) || // Security Access B (both doors)
-                                        (room_info.room_id == 0x956F1552 && door_index == 1) || // Mine Security Station
-                                        (room_info.room_id == 0xC50AF17A && door_index == 2) || // Elite Control
-                                        (room_info.room_id == 0x90709AAC && door_index == 1);   // Ventilation Shaft
-
-                let mut door_type = calculate_door_type(name,&mut door_rng,&config.door_weights); // randomly pick a door color using weights
-                
-                if door_specification != "random" && door_specification != "default" {
-                    door_type = DoorType::from_string(door_specification.to_string()).unwrap();
-                }
-                
+
+                let is_vertical_door = is_vertical_door(room_info.room_id, door_index);
+
+                // Doors without a color are left alone
+                let door_color = door_colors.get(&(room_info.room_id, door_index as u32)).cloned();
+                let mut door_type = door_color.unwrap_or(DoorType::Blue);
+
                 if is_vertical_door {
                     if config.patch_vertical_to_blue {
                         door_type = DoorType::VerticalBlue;
@@ -4947,11 +6138,18 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
                     }
                 }
 
-                if (door_specification != "default") || (is_vertical_door && config.patch_vertical_to_blue)
+                if door_color.is_some() || (is_vertical_door && config.patch_vertical_to_blue)
                 {
+                    spoiler.doors.push(SpoilerDoor {
+                        world: world_name.clone(),
+                        room: room_info.name.to_string(),
+                        dock: door_index as u32,
+                        color: format!("{:?}", door_type),
+                    });
+
                     patcher.add_scly_patch(
                         (name.as_bytes(), room_info.room_id),
-                        move |_ps, area| patch_door(_ps, area,door_location,door_type, BlastShieldType::Missile, door_resources,config.powerbomb_lockpick)
+                        move |area| patch_door(area,door_location,door_type, BlastShieldType::Missile, door_resources,config.powerbomb_lockpick)
                     );
                     
                     if config.patch_map && room_info.mapa_id != 0 {
@@ -4966,17 +6164,32 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
     }
 
     // add additional items //
-    for item in config.additional_items.iter()
+    let additional_items = config.additional_items.iter()
+        .zip(&placed_pickups[pickup_layout.len()..])
+        .zip(additional_item_overrides.iter().cloned());
+    for ((item, &placed), mut pickup_override) in additional_items
     {
-        let room = spawn_room_from_string(item.room.to_string());
+        if pickup_override.hudmemo_strg.is_none() {
+            pickup_override.hudmemo_strg = progressive_item_strgs.get(&placed.pickup_type).cloned();
+        }
         patcher.add_scly_patch(
-            (room.pak_name.as_bytes(), room.mrea),
-            move |_ps, area| patch_add_item(_ps, area, PickupType::from_string(item.item_type.to_string()), item.position, pickup_resources, config),
+            (placed.pak_name.as_bytes(), placed.room_id),
+            move |area| patch_add_item(area, placed, item.position, pickup_override, pickup_resources, config),
         );
     }
 
     if !config.is_item_randomized.unwrap_or(false) {
-        let rel_config;
+        let dol = match gc_disc.find_file("default.dol").and_then(|f| f.file()) {
+            Some(structs::FstEntryFile::Unknown(reader)) => DolPatcher::new(reader.clone()),
+            _ => Err("Failed to find default.dol")?,
+        };
+        let rel_config = create_rel_config_file(
+            &dol,
+            new_save_spawn_room,
+            config.quickplay,
+            version,
+            config,
+        )?;
         if config.skip_frigate {
             patcher.add_file_patch(
                 b"default.dol",
@@ -4989,7 +6202,6 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
                 )
             );
             patcher.add_file_patch(b"Metroid1.pak", empty_frigate_pak);
-            rel_config = create_rel_config_file(new_save_spawn_room, config.quickplay);
         } else {
             patcher.add_file_patch(
                 b"default.dol",
@@ -5003,9 +6215,8 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
             );
             patcher.add_scly_patch(
                 resource_info!("01_intro_hanger.MREA").into(),
-                move |_ps, area| patch_frigate_teleporter(area, frigate_done_spawn_room)
+                move |area| patch_frigate_teleporter(area, frigate_done_spawn_room)
             );
-            rel_config = create_rel_config_file(new_save_spawn_room, config.quickplay);
         }
 
         gc_disc.add_file(
@@ -5022,14 +6233,14 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
         // New Save Room Starting Items //
         patcher.add_scly_patch(
             (new_save_spawn_room.pak_name.as_bytes(), new_save_spawn_room.mrea),
-            move |_ps, area| patch_starting_pickups(area, config.new_save_starting_items, false)
+            move |area| patch_starting_pickups(area, &config.new_save_starting_items, 0, false)
         );
 
         // Post Frigate Starting Items //
         if !config.skip_frigate && frigate_done_spawn_room.mrea != new_save_spawn_room.mrea { // but only if it won't override an existing patch
             patcher.add_scly_patch(
                 (frigate_done_spawn_room.pak_name.as_bytes(), frigate_done_spawn_room.mrea),
-                move |_ps, area| patch_starting_pickups(area, config.frigate_done_starting_items, false)
+                move |area| patch_starting_pickups(area, &config.frigate_done_starting_items, 1, false)
             );
         }
 
@@ -5065,20 +6276,28 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
 
         patcher.add_resource_patch(
             resource_info!("STRG_Credits.STRG").into(),
-            |res| patch_credits(res, &pickup_layout)
+            |res| patch_credits(res, placed_pickups)
         );
 
+        if config.patch_hints {
+            patcher.add_file_patch(b"NoARAM.pak", |file| add_hint_strgs(file, &hint_plan));
+            patcher.add_resource_patch(
+                resource_info!("HINT_Hints_1.HINT").into(),
+                |res| patch_hint_locations(res, &hint_plan)
+            );
+        }
+
         patcher.add_resource_patch(
             resource_info!("!MinesWorld_Master.SAVW").into(),
             patch_mines_savw_for_phazon_suit_scan
         );
         patcher.add_scly_patch(
             resource_info!("07_stonehenge.MREA").into(),
-            |ps, area| fix_artifact_of_truth_requirements(ps, area, &pickup_layout)
+            |area| fix_artifact_of_truth_requirements(area, placed_pickups, starting_artifacts)
         );
         patcher.add_scly_patch(
             resource_info!("07_stonehenge.MREA").into(),
-            |ps, area| patch_artifact_hint_availability(ps, area, config.artifact_hint_behavior)
+            |area| patch_artifact_hint_availability(area, config.artifact_hint_behavior)
         );
 
         patcher.add_resource_patch(
@@ -5107,7 +6326,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
         if config.remove_hall_of_the_elders_forcefield {
             patcher.add_scly_patch(
                 resource_info!("17_chozo_bowling.MREA").into(), // Hall of the elders
-                move |_ps, area| remove_forcefields(_ps, area),
+                move |area| remove_forcefields(area),
             );
         }
 
@@ -5205,11 +6424,17 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
             "default" => DoorType::Blue,
             _         => DoorType::from_string(door_specification.to_string()).unwrap(),
         };
+        spoiler.doors.push(SpoilerDoor {
+            world: World::ChozoRuins.as_string(),
+            room: "Main Plaza".to_string(),
+            dock: 4,
+            color: format!("{:?}", door_type),
+        });
 
         {
             patcher.add_scly_patch(
                 resource_info!("01_mainplaza.MREA").into(),
-                move |ps,area| make_main_plaza_locked_door_two_ways(ps, area, door_type, &config, &door_resources)
+                move |area| make_main_plaza_locked_door_two_ways(area, door_type, &config, &door_resources)
             );
         }
 
@@ -5222,5 +6447,5 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &ParsedConfig, v
     }
 
     patcher.run(gc_disc)?;
-    Ok(())
+    Ok((spoiler, warnings))
 }
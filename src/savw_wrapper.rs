@@ -0,0 +1,179 @@
+use structs::{LayerToggle, Savw, ScannableObject, SclyObject};
+
+use std::collections::HashMap;
+
+// Collects the world state that needs to be persisted in a world's SAVW. Patches that add
+// memory relays, layers, doors, or scannable objects register them here, as do patches that
+// delete objects, and the changes are merged into the SAVW once every patch for the world has
+// run.
+#[derive(Clone, Debug, Default)]
+pub struct SavwEditor
+{
+    memory_relays: Vec<u32>,
+    layer_toggles: Vec<LayerToggle>,
+    doors: Vec<u32>,
+    // Scans whose logbook category is `None` take it from their SCAN resource when applied
+    scans: Vec<(u32, Option<u32>)>,
+    // The scan each object registered with `add_scly_object` brought with it
+    object_scans: HashMap<u32, u32>,
+    // Deleted objects, whose memory relay and door entries are taken out of the SAVW before
+    // anything is added, so an instance ID that's handed out again starts from scratch
+    removed_objects: Vec<u32>,
+}
+
+impl SavwEditor
+{
+    pub fn new() -> SavwEditor
+    {
+        SavwEditor::default()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.memory_relays.is_empty() && self.layer_toggles.is_empty() &&
+            self.doors.is_empty() && self.scans.is_empty() && self.removed_objects.is_empty()
+    }
+
+    pub fn add_memory_relay(&mut self, instance_id: u32)
+    {
+        if !self.memory_relays.contains(&instance_id) {
+            self.memory_relays.push(instance_id);
+        }
+    }
+
+    pub fn add_layer_toggle(&mut self, area_index: u32, layer_index: u32)
+    {
+        let toggle = LayerToggle { area_id: area_index, layer_index };
+        if !self.layer_toggles.contains(&toggle) {
+            self.layer_toggles.push(toggle);
+        }
+    }
+
+    pub fn add_door(&mut self, instance_id: u32)
+    {
+        if !self.doors.contains(&instance_id) {
+            self.doors.push(instance_id);
+        }
+    }
+
+    pub fn add_scan(&mut self, scan: u32, logbook_category: u32)
+    {
+        self.add_scan_inner(scan, Some(logbook_category));
+    }
+
+    // Add a scan whose logbook category should be looked up from its SCAN resource
+    pub fn add_object_scan(&mut self, scan: u32)
+    {
+        self.add_scan_inner(scan, None);
+    }
+
+    fn add_scan_inner(&mut self, scan: u32, logbook_category: Option<u32>)
+    {
+        if scan == 0xFFFFFFFF {
+            return;
+        }
+        match self.scans.iter_mut().find(|(i, _)| *i == scan) {
+            // An explicit category takes precedence over looking it up
+            Some((_, category)) => if category.is_none() {
+                *category = logbook_category;
+            },
+            None => self.scans.push((scan, logbook_category)),
+        }
+    }
+
+    // The scans whose logbook category has to be looked up before applying
+    pub fn unresolved_scans(&self) -> impl Iterator<Item = u32> + '_
+    {
+        self.scans.iter()
+            .filter(|(_, category)| category.is_none())
+            .map(|(scan, _)| *scan)
+    }
+
+    // Register whatever parts of a (newly created) object need to be saved
+    pub fn add_scly_object(&mut self, obj: &SclyObject)
+    {
+        let prop = &obj.property_data;
+        if prop.is_memory_relay() {
+            self.add_memory_relay(obj.instance_id);
+        } else if prop.is_door() {
+            self.add_door(obj.instance_id);
+        }
+
+        let scan = if let Some(door) = prop.as_door() {
+            door.actor_params.scan_params.scan
+        } else if let Some(actor) = prop.as_actor() {
+            actor.actor_params.scan_params.scan
+        } else if let Some(pickup) = prop.as_pickup() {
+            pickup.actor_params.scan_params.scan
+        } else {
+            return
+        };
+        self.add_object_scan(scan);
+        if scan != 0xFFFFFFFF {
+            self.object_scans.insert(obj.instance_id, scan);
+        }
+    }
+
+    // Forget everything saved for a deleted object. Its scan is only dropped if it was added
+    // along with the object and no other registered object uses it, since the SAVW's own scans
+    // are shared by every object in the world with the same SCAN. Layer toggles belong to
+    // layers rather than objects, so they stay.
+    pub fn remove_object(&mut self, instance_id: u32)
+    {
+        self.memory_relays.retain(|&id| id != instance_id);
+        self.doors.retain(|&id| id != instance_id);
+        if let Some(scan) = self.object_scans.remove(&instance_id) {
+            if !self.object_scans.values().any(|&other| other == scan) {
+                self.scans.retain(|&(other, _)| other != scan);
+            }
+        }
+        if !self.removed_objects.contains(&instance_id) {
+            self.removed_objects.push(instance_id);
+        }
+    }
+
+    // Merge the registered entries into the SAVW, skipping any that are already present, after
+    // removing the entries of deleted objects. `scan_categories` maps SCAN ids to their logbook
+    // category for the scans that were added without one; scans missing from it are put in
+    // category 0.
+    pub fn apply(&self, savw: &mut Savw, scan_categories: &HashMap<u32, u32>)
+    {
+        savw.memory_relay_array.as_mut_vec().retain(|id| !self.removed_objects.contains(id));
+        savw.door_array.as_mut_vec().retain(|id| !self.removed_objects.contains(id));
+        {
+            let relays = savw.memory_relay_array.as_mut_vec();
+            for id in &self.memory_relays {
+                if !relays.contains(id) {
+                    relays.push(*id);
+                }
+            }
+        }
+        {
+            let toggles = savw.layer_toggle_array.as_mut_vec();
+            for toggle in &self.layer_toggles {
+                if !toggles.contains(toggle) {
+                    toggles.push(toggle.clone());
+                }
+            }
+        }
+        {
+            let doors = savw.door_array.as_mut_vec();
+            for id in &self.doors {
+                if !doors.contains(id) {
+                    doors.push(*id);
+                }
+            }
+        }
+        {
+            let scans = savw.scan_array.as_mut_vec();
+            for &(scan, logbook_category) in &self.scans {
+                if !scans.iter().any(|i| i.scan == scan) {
+                    let logbook_category = logbook_category
+                        .or_else(|| scan_categories.get(&scan).cloned())
+                        .unwrap_or(0);
+                    scans.push(ScannableObject { scan, logbook_category });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use chacha20poly1305::{
+    aead::{Aead, NewAead, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::Serialize;
+
+use crate::starting_items::StartingItems;
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+};
+
+// The name of the file the encrypted spoiler is stored in on the disc
+pub const SPOILER_FILE_NAME: &str = "spoiler.bin";
+
+// Starts the encrypted spoiler file, followed by the nonce and the ciphertext. It's also
+// authenticated along with the spoiler, so the header can't be swapped out either.
+const SPOILER_MAGIC: &[u8] = b"RPSPOIL2";
+const NONCE_LEN: usize = 12;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SpoilerLocation
+{
+    pub world: String,
+    pub room: String,
+    pub item: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SpoilerElevator
+{
+    pub elevator: String,
+    pub destination: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SpoilerDoor
+{
+    pub world: String,
+    pub room: String,
+    pub dock: u32,
+    pub color: String,
+}
+
+// Everything a player would want to know about a patched ISO without playing it
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Spoiler
+{
+    pub seed: u64,
+    pub starting_items: BTreeMap<String, u32>,
+    // Only present if the frigate isn't skipped
+    pub frigate_done_starting_items: Option<BTreeMap<String, u32>>,
+    pub locations: Vec<SpoilerLocation>,
+    pub elevators: Vec<SpoilerElevator>,
+    pub doors: Vec<SpoilerDoor>,
+}
+
+pub fn starting_item_names(starting_items: &StartingItems) -> BTreeMap<String, u32>
+{
+    starting_items.iter()
+        .filter(|&(_, amount)| amount > 0)
+        .map(|(item, amount)| (StartingItems::item_name(item), amount))
+        .collect()
+}
+
+impl Spoiler
+{
+    pub fn to_json(&self) -> Result<String, String>
+    {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize the spoiler: {}", e))
+    }
+}
+
+impl fmt::Display for Spoiler
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        writeln!(f, "Seed: {}", self.seed)?;
+
+        writeln!(f)?;
+        writeln!(f, "Starting items:")?;
+        for (item, amount) in &self.starting_items {
+            writeln!(f, "    {}: {}", item, amount)?;
+        }
+
+        if let Some(frigate_done_starting_items) = &self.frigate_done_starting_items {
+            writeln!(f)?;
+            writeln!(f, "Starting items after the frigate:")?;
+            for (item, amount) in frigate_done_starting_items {
+                writeln!(f, "    {}: {}", item, amount)?;
+            }
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Items:")?;
+        for location in &self.locations {
+            writeln!(f, "    {} - {}: {}", location.world, location.room, location.item)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Elevators:")?;
+        for elevator in &self.elevators {
+            writeln!(f, "    {}: {}", elevator.elevator, elevator.destination)?;
+        }
+
+        if !self.doors.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Doors:")?;
+            for door in &self.doors {
+                writeln!(f, "    {} - {} (dock {}): {}", door.world, door.room, door.dock, door.color)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Keys are given as 64 hex digits
+pub fn parse_spoiler_key(key: &str) -> Result<[u8; 32], String>
+{
+    let key = key.trim();
+    if key.len() != 64 || !key.is_ascii() {
+        Err("The spoiler key must be 64 hex digits")?
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&key[i * 2..i * 2 + 2], 16)
+            .map_err(|_| "The spoiler key must be 64 hex digits".to_string())?;
+    }
+    Ok(bytes)
+}
+
+// Encrypts the spoiler's JSON with ChaCha20-Poly1305. The nonce is random, so the same key is
+// never used with the same nonce twice and patching the same spoiler twice gives different files.
+pub fn encrypt_spoiler(spoiler: &Spoiler, key: &[u8; 32]) -> Result<Vec<u8>, String>
+{
+    let json = spoiler.to_json()?;
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.try_fill_bytes(&mut nonce)
+        .map_err(|e| format!("Failed to generate a nonce for the spoiler: {}", e))?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let payload = Payload { msg: json.as_bytes(), aad: SPOILER_MAGIC };
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), payload)
+        .map_err(|_| "Failed to encrypt the spoiler".to_string())?;
+    Ok([SPOILER_MAGIC, &nonce[..], &ciphertext[..]].concat())
+}
+
+// Returns the spoiler's JSON
+pub fn decrypt_spoiler(encrypted: &[u8], key: &[u8; 32]) -> Result<String, String>
+{
+    if !encrypted.starts_with(SPOILER_MAGIC) {
+        Err("The encrypted spoiler is from an older version or isn't a spoiler at all")?
+    }
+    let encrypted = &encrypted[SPOILER_MAGIC.len()..];
+    if encrypted.len() < NONCE_LEN {
+        Err("The encrypted spoiler is truncated")?
+    }
+    let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let payload = Payload { msg: ciphertext, aad: SPOILER_MAGIC };
+    let json = cipher.decrypt(Nonce::from_slice(nonce), payload)
+        .map_err(|_| "Failed to decrypt the spoiler; the key is wrong or the spoiler is corrupt")?;
+    String::from_utf8(json)
+        .map_err(|e| format!("The decrypted spoiler isn't valid text: {}", e))
+}
@@ -0,0 +1,340 @@
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    pickup_meta::PickupType,
+    structs,
+};
+
+use std::collections::HashMap;
+
+// The number of CPlayerState::EItemTypes
+const ITEM_TYPE_COUNT: usize = 41;
+
+const POWER_BEAM: usize = 0;
+const COMBAT_VISOR: usize = 17;
+const ENERGY_TANKS: usize = 24;
+const FIRST_ARTIFACT: usize = 29;
+
+// The visors and beams that can be selected at the start, as their name, their
+// CPlayerState::EPlayerVisor or EBeamId, and the item the player needs to have to use them
+const VISORS: &[(&str, u32, usize)] = &[
+    ("Combat Visor", 0, COMBAT_VISOR),
+    ("X-Ray Visor", 1, 13),
+    ("Scan Visor", 2, 5),
+    ("Thermal Visor", 3, 9),
+];
+const BEAMS: &[(&str, u32, usize)] = &[
+    ("Power Beam", 0, POWER_BEAM),
+    ("Ice Beam", 1, 1),
+    ("Wave Beam", 2, 2),
+    ("Plasma Beam", 3, 3),
+];
+
+// The order the items are packed in the old u64 format, along with how many bits each one gets
+const PACKED_ITEMS: &[(usize, u8)] = &[
+    (5, 1), // Scan Visor
+    (4, 8), // Missiles
+    (24, 4), // Energy Tanks
+    (7, 4), // Power Bombs
+    (2, 1), // Wave Beam
+    (1, 1), // Ice Beam
+    (3, 1), // Plasma Beam
+    (10, 1), // Charge Beam
+    (16, 1), // Morph Ball
+    (6, 1), // Morph Ball Bombs
+    (19, 1), // Spider Ball
+    (18, 1), // Boost Ball
+    (22, 1), // Varia Suit
+    (21, 1), // Gravity Suit
+    (23, 1), // Phazon Suit
+    (9, 1), // Thermal Visor
+    (13, 1), // X-Ray Visor
+    (15, 1), // Space Jump Boots
+    (12, 1), // Grapple Beam
+    (11, 1), // Super Missile
+    (28, 1), // Wavebuster
+    (14, 1), // Ice Spreader
+    (8, 1), // Flamethrower
+];
+
+// What the player starts with, indexed by CPlayerState::EItemType. Items that are `None` are
+// left as they are in the spawn point.
+//
+// Deserializes from either an object mapping item names to amounts, eg
+// `{"Missile": 25, "Energy Tank": 3, "Artifact of Truth": 1}`, or the bit packed u64 older
+// configs use. Every `PickupType` can be named, as well as "Power Beam" and "Combat Visor",
+// which the player has unless they're given as 0. Spaces and dashes in the names are optional.
+// Items left out of an object are set to 0; the u64 leaves the Power Beam and Combat Visor
+// alone and can't give artifacts.
+//
+// The selected visor and beam and the player's energy can be set too, by putting the items in
+// an `items` object next to them:
+// `{"items": {"Missile": 25, "Ice Beam": 1}, "beam": "Ice Beam", "visor": "Scan Visor", "energy": 50}`.
+// The player has to have the visor and beam, and the energy can't be more than their tanks
+// hold. Anything left out stays as the game sets it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StartingItems
+{
+    amounts: [Option<u32>; ITEM_TYPE_COUNT],
+    visor: Option<u32>,
+    beam: Option<u32>,
+    energy: Option<u32>,
+}
+
+fn normalize_name(name: &str) -> String
+{
+    name.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn item_names() -> impl Iterator<Item = (&'static str, usize)>
+{
+    // Nothing is a health refill, which isn't something to start with
+    PickupType::iter()
+        .filter(|pt| *pt != PickupType::Nothing)
+        .map(|pt| (pt.name(), pt.pickup_data().kind as usize))
+        .chain(vec![("Power Beam", POWER_BEAM), ("Combat Visor", COMBAT_VISOR)])
+}
+
+impl StartingItems
+{
+    pub fn from_u64(mut starting_items: u64) -> Self
+    {
+        let mut fetch_bits = move |bits: u8| {
+            let ret = starting_items & ((1 << bits) - 1);
+            starting_items >>= bits;
+            ret as u32
+        };
+
+        let mut amounts = [None; ITEM_TYPE_COUNT];
+        for &(item, bits) in PACKED_ITEMS {
+            amounts[item] = Some(fetch_bits(bits));
+        }
+        StartingItems { amounts, visor: None, beam: None, energy: None }
+    }
+
+    pub fn from_names(items: &HashMap<String, u32>) -> Result<Self, String>
+    {
+        // Only items a spawn point can hold are reset, so that the Power Beam and Combat Visor
+        // don't have to be given explicitly
+        let mut amounts: [Option<u32>; ITEM_TYPE_COUNT] = [None; ITEM_TYPE_COUNT];
+        for (item, amount) in amounts.iter_mut().enumerate() {
+            if item != POWER_BEAM && item != COMBAT_VISOR {
+                *amount = Some(0);
+            }
+        }
+
+        for (name, &amount) in items.iter() {
+            let item = item_names()
+                .find(|(item_name, _)| normalize_name(item_name) == normalize_name(name))
+                .map(|(_, item)| item)
+                .ok_or_else(|| format!("Unknown starting item '{}'", name))?;
+            // Pickups that share an item type (eg Power Bomb and Power Bomb Expansion) add up
+            let total = amounts[item].unwrap_or(0).checked_add(amount)
+                .ok_or_else(|| format!("Too many starting {}", name))?;
+            amounts[item] = Some(total);
+        }
+        Ok(StartingItems { amounts, visor: None, beam: None, energy: None })
+    }
+
+    // Select a visor and beam and set the player's energy, each of which is left alone if it's
+    // None
+    pub fn with_selection(
+        mut self,
+        visor: Option<&str>,
+        beam: Option<&str>,
+        energy: Option<u32>,
+    ) -> Result<Self, String>
+    {
+        self.visor = self.select(visor, VISORS, "visor")?;
+        self.beam = self.select(beam, BEAMS, "beam")?;
+
+        if let Some(energy) = energy {
+            // An unchanged number of tanks could be anything up to the maximum of 14
+            let tanks = self.amount(ENERGY_TANKS).unwrap_or(14);
+            let max_energy = 99 + 100 * tanks;
+            if energy == 0 || energy > max_energy {
+                Err(format!(
+                    "The starting energy must be between 1 and {} with {} energy tanks, not {}",
+                    max_energy, tanks, energy,
+                ))?
+            }
+        }
+        self.energy = energy;
+        Ok(self)
+    }
+
+    // The EPlayerVisor or EBeamId of one of `choices`, which the player must have
+    fn select(
+        &self,
+        name: Option<&str>,
+        choices: &[(&str, u32, usize)],
+        kind: &str,
+    ) -> Result<Option<u32>, String>
+    {
+        let name = match name {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        let &(choice_name, id, item) = choices.iter()
+            .find(|(choice_name, _, _)| normalize_name(choice_name) == normalize_name(name))
+            .ok_or_else(|| format!("Unknown starting {} '{}'", kind, name))?;
+        if self.amount(item) == Some(0) {
+            Err(format!("The starting {} is the {}, which isn't a starting item", kind, choice_name))?
+        }
+        Ok(Some(id))
+    }
+
+    pub fn amount(&self, item: usize) -> Option<u32>
+    {
+        self.amounts.get(item).cloned().flatten()
+    }
+
+    // The items that are set, along with their amounts
+    pub fn iter(&self) -> impl Iterator<Item = (usize, u32)> + '_
+    {
+        self.amounts.iter()
+            .enumerate()
+            .filter_map(|(item, amount)| amount.map(|amount| (item, amount)))
+    }
+
+    pub fn set_amount(&mut self, item: usize, amount: u32)
+    {
+        self.amounts[item] = Some(amount);
+    }
+
+    // The CPlayerState::EItemTypes of the artifacts the player starts with
+    pub fn artifacts(&self) -> impl Iterator<Item = u32> + '_
+    {
+        (FIRST_ARTIFACT..ITEM_TYPE_COUNT)
+            .filter(move |&item| self.amount(item).unwrap_or(0) > 0)
+            .map(|item| item as u32)
+    }
+
+    // The CPlayerState::EPlayerVisor to start with
+    pub fn visor(&self) -> Option<u32>
+    {
+        self.visor
+    }
+
+    // The CPlayerState::EBeamId to start with
+    pub fn beam(&self) -> Option<u32>
+    {
+        self.beam
+    }
+
+    pub fn energy(&self) -> Option<u32>
+    {
+        self.energy
+    }
+
+    // Whether any of this needs the REL, because a spawn point can't hold it
+    pub fn needs_rel(&self) -> bool
+    {
+        self.artifacts().next().is_some() ||
+            self.visor.is_some() ||
+            self.beam.is_some() ||
+            self.energy.is_some()
+    }
+
+    // Spawn points can only hold the items before the artifacts; the REL gives the rest
+    pub fn update_spawn_point(&self, spawn_point: &mut structs::SpawnPoint) -> Vec<(usize, u32)>
+    {
+        let mut changed = vec![];
+        for (item, amount) in self.amounts[..FIRST_ARTIFACT].iter().enumerate() {
+            let amount = match amount {
+                Some(amount) => *amount,
+                None => continue,
+            };
+            let field = match item {
+                0 => &mut spawn_point.power,
+                1 => &mut spawn_point.ice,
+                2 => &mut spawn_point.wave,
+                3 => &mut spawn_point.plasma,
+                4 => &mut spawn_point.missiles,
+                5 => &mut spawn_point.scan_visor,
+                6 => &mut spawn_point.bombs,
+                7 => &mut spawn_point.power_bombs,
+                8 => &mut spawn_point.flamethrower,
+                9 => &mut spawn_point.thermal_visor,
+                10 => &mut spawn_point.charge,
+                11 => &mut spawn_point.super_missile,
+                12 => &mut spawn_point.grapple,
+                13 => &mut spawn_point.xray,
+                14 => &mut spawn_point.ice_spreader,
+                15 => &mut spawn_point.space_jump,
+                16 => &mut spawn_point.morph_ball,
+                17 => &mut spawn_point.combat_visor,
+                18 => &mut spawn_point.boost_ball,
+                19 => &mut spawn_point.spider_ball,
+                20 => &mut spawn_point.power_suit,
+                21 => &mut spawn_point.gravity_suit,
+                22 => &mut spawn_point.varia_suit,
+                23 => &mut spawn_point.phazon_suit,
+                24 => &mut spawn_point.energy_tanks,
+                25 => &mut spawn_point.unknown0,
+                26 => &mut spawn_point.health_refill,
+                27 => &mut spawn_point.unknown1,
+                _ => &mut spawn_point.wavebuster,
+            };
+            *field = amount;
+            changed.push((item, amount));
+        }
+        changed
+    }
+
+    // A name for an item type, for printing
+    pub fn item_name(item: usize) -> String
+    {
+        item_names()
+            .find(|&(name, i)| i == item && !name.ends_with("Expansion"))
+            .map(|(name, _)| name.to_string())
+            .unwrap_or_else(|| format!("Item {}", item))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StartingItemsConfig
+{
+    Packed(u64),
+    WithSelection(StartingSelectionConfig),
+    Named(HashMap<String, u32>),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StartingSelectionConfig
+{
+    items: HashMap<String, u32>,
+    #[serde(default)]
+    visor: Option<String>,
+    #[serde(default)]
+    beam: Option<String>,
+    #[serde(default)]
+    energy: Option<u32>,
+}
+
+impl<'de> Deserialize<'de> for StartingItems
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de>
+    {
+        match StartingItemsConfig::deserialize(deserializer)? {
+            StartingItemsConfig::Packed(bits) => Ok(StartingItems::from_u64(bits)),
+            StartingItemsConfig::WithSelection(config) => {
+                StartingItems::from_names(&config.items)
+                    .and_then(|starting_items| starting_items.with_selection(
+                        config.visor.as_deref(),
+                        config.beam.as_deref(),
+                        config.energy,
+                    ))
+                    .map_err(serde::de::Error::custom)
+            },
+            StartingItemsConfig::Named(items) => StartingItems::from_names(&items)
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use auto_struct_macros::auto_struct;
 
 use reader_writer::{
-    CStr, FourCC, LazyArray, IteratorArray, Readable, Reader, RoArray, Uncached, RoArrayIter,
-    Writable,
+    CStr, FourCC, LazyArray, IteratorArray, Readable, ReadError, ReadResult, Reader, RoArray,
+    Uncached, RoArrayIter, Writable,
 };
 use reader_writer::typenum::*;
 use reader_writer::generic_array::GenericArray;
@@ -18,7 +18,7 @@ fn bool_to_opt(b: bool) -> Option<()>
     }
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Ancs<'r>
 {
@@ -29,7 +29,7 @@ pub struct Ancs<'r>
     pub anim_set: AnimationSet<'r>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct CharacterSet<'r>
 {
@@ -42,7 +42,7 @@ pub struct CharacterSet<'r>
     pub char_info: LazyArray<'r, CharacterInfo<'r>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct CharacterInfo<'r>
 {
@@ -56,9 +56,10 @@ pub struct CharacterInfo<'r>
     pub cskr: u32,
     pub cinf: u32,
 
+    #[auto_struct(derive = animation_names.len() as u32)]
     pub animation_count: u32,
     #[auto_struct(init = (animation_count as usize, info_type_count))]
-    pub animation_names: RoArray<'r, AnimationName<'r>>,
+    pub animation_names: LazyArray<'r, AnimationName<'r>>,
 
     pub pas_database: PasDatabase<'r>,
     #[auto_struct(init = info_type_count)]
@@ -76,9 +77,10 @@ pub struct CharacterInfo<'r>
     pub animation_aabbs: Option<RoArray<'r, AnimationAABB<'r>>>,
 
     #[auto_struct(init = bool_to_opt(info_type_count > 1))]
+    #[auto_struct(derive = effects.as_ref().map(|a| a.len() as u32))]
     pub effect_count: Option<u32>,
     #[auto_struct(init = effect_count.map(|i| (i as usize, ())))]
-    pub effects: Option<RoArray<'r, Effect<'r>>>,
+    pub effects: Option<LazyArray<'r, Effect<'r>>>,
 
     #[auto_struct(init = bool_to_opt(info_type_count > 3))]
     pub overlay_cmdl: Option<u32>,
@@ -101,8 +103,46 @@ pub struct CharacterInfo<'r>
     pub animation_indexed_aabbs: Option<RoArray<'r, AnimationIndexedAABB>>,
 }
 
+impl<'r> CharacterInfo<'r>
+{
+    // Every particle resource referenced by this character, either directly or through one of
+    // its effects.
+    pub fn particle_dependencies(&self) -> Vec<(u32, FourCC)>
+    {
+        let mut deps = self.particles.dependencies();
+        if let Some(effects) = &self.effects {
+            for effect in effects.iter() {
+                deps.extend(effect.components.iter().map(|comp| (comp.file_id, comp.type_)));
+            }
+        }
+        deps
+    }
+
+    // Point every reference to the particle `old_id` at `new_id` instead
+    pub fn replace_particle(&mut self, old_id: u32, new_id: u32)
+    {
+        self.particles.replace(old_id, new_id);
+        if let Some(effects) = &mut self.effects {
+            for effect in effects.as_mut_vec().iter_mut() {
+                for comp in effect.components.as_mut_vec().iter_mut() {
+                    if comp.file_id == old_id {
+                        comp.file_id = new_id;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn animation_name(&self, index: u32) -> Option<CStr<'r>>
+    {
+        self.animation_names.iter()
+            .find(|name| name.index == index)
+            .map(|name| name.name.clone())
+    }
+}
+
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct AnimationName<'r>
 {
@@ -116,7 +156,7 @@ pub struct AnimationName<'r>
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct PasDatabase<'r>
 {
@@ -131,7 +171,7 @@ pub struct PasDatabase<'r>
 
 // PasDatabase inner details {{{
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct PasAnimState<'r>
 {
@@ -141,10 +181,10 @@ pub struct PasAnimState<'r>
     #[auto_struct(init = (param_info_count as usize, ()))]
     pub param_info: RoArray<'r, PasAnimStateParamInfo<'r>>,
     #[auto_struct(init = (anim_info_count as usize, param_info.clone()))]
-    pub anim_info: RoArray<'r, PasAnimStateAnimInfo<'r>>,
+    pub anim_info: LazyArray<'r, PasAnimStateAnimInfo<'r>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct PasAnimStateParamInfo<'r>
 {
@@ -157,7 +197,7 @@ pub struct PasAnimStateParamInfo<'r>
     pub data1: RoArray<'r, u8>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct PasAnimStateAnimInfo<'r>
 {
@@ -169,7 +209,7 @@ pub struct PasAnimStateAnimInfo<'r>
     pub items: IteratorArray<'r, PasAnimStateAnimInfoInner<'r>, RoArrayIter<'r, PasAnimStateParamInfo<'r>>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct PasAnimStateAnimInfoInner<'r>
 {
@@ -181,7 +221,7 @@ pub struct PasAnimStateAnimInfoInner<'r>
 
 // }}}
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct ParticleResData<'r>
 {
@@ -196,20 +236,51 @@ pub struct ParticleResData<'r>
     #[auto_struct(derive = swhc_assets.len() as u32)]
     pub swhc_asset_count: u32,
     #[auto_struct(init = (swhc_asset_count as usize, ()))]
-    pub swhc_assets: RoArray<'r, u32>,
+    pub swhc_assets: LazyArray<'r, u32>,
 
     #[auto_struct(derive = unknowns.len() as u32)]
     pub unknown_count: u32,
     #[auto_struct(init = (unknown_count as usize, ()))]
-    pub unknowns: RoArray<'r, u32>,
+    pub unknowns: LazyArray<'r, u32>,
 
     #[auto_struct(init = bool_to_opt(info_type_count > 5))]
+    #[auto_struct(derive = elsc_assets.as_ref().map(|a| a.len() as u32))]
     pub elsc_count: Option<u32>,
     #[auto_struct(init = elsc_count.map(|i| (i as usize, ())))]
-    pub elsc_assets: Option<RoArray<'r, u32>>,
+    pub elsc_assets: Option<LazyArray<'r, u32>>,
 }
 
-#[auto_struct(Readable, Writable)]
+impl<'r> ParticleResData<'r>
+{
+    pub fn dependencies(&self) -> Vec<(u32, FourCC)>
+    {
+        let mut deps: Vec<_> = self.part_assets.iter()
+            .map(|id| (*id, FourCC::from_bytes(b"PART")))
+            .collect();
+        deps.extend(self.swhc_assets.iter().map(|id| (*id, FourCC::from_bytes(b"SWHC"))));
+        if let Some(elsc_assets) = &self.elsc_assets {
+            deps.extend(elsc_assets.iter().map(|id| (*id, FourCC::from_bytes(b"ELSC"))));
+        }
+        deps
+    }
+
+    pub fn replace(&mut self, old_id: u32, new_id: u32)
+    {
+        let arrays = Some(&mut self.part_assets).into_iter()
+            .chain(Some(&mut self.swhc_assets))
+            .chain(Some(&mut self.unknowns))
+            .chain(self.elsc_assets.as_mut());
+        for array in arrays {
+            for id in array.as_mut_vec().iter_mut() {
+                if *id == old_id {
+                    *id = new_id;
+                }
+            }
+        }
+    }
+}
+
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct AnimationAABB<'r>
 {
@@ -217,7 +288,7 @@ pub struct AnimationAABB<'r>
     pub aabb: GenericArray<f32, U6>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct AnimationIndexedAABB
 {
@@ -226,17 +297,18 @@ pub struct AnimationIndexedAABB
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Effect<'r>
 {
     pub name: CStr<'r>,
+    #[auto_struct(derive = components.len() as u32)]
     pub component_count: u32,
     #[auto_struct(init = (component_count as usize, ()))]
-    pub components: RoArray<'r, EffectComponent<'r>>,
+    pub components: LazyArray<'r, EffectComponent<'r>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct EffectComponent<'r>
 {
@@ -250,7 +322,7 @@ pub struct EffectComponent<'r>
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct AnimationSet<'r>
 {
@@ -287,7 +359,7 @@ pub struct AnimationSet<'r>
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Animation<'r>
 {
@@ -295,8 +367,56 @@ pub struct Animation<'r>
     pub meta: MetaAnimation<'r>,
 }
 
+impl<'r> AnimationSet<'r>
+{
+    pub fn find_animation(&self, name: &[u8]) -> Option<Animation<'r>>
+    {
+        self.animations.iter()
+            .find(|anim| anim.name.to_bytes() == name)
+            .map(|anim| anim.into_owned())
+    }
+
+    // Every ANIM referenced by a PRIM in any of the set's animations
+    pub fn anim_ids(&self) -> Vec<u32>
+    {
+        let mut ids = vec![];
+        for anim in self.animations.iter() {
+            for prim in anim.meta.primitives() {
+                if !ids.contains(&prim.anim) {
+                    ids.push(prim.anim);
+                }
+            }
+        }
+        ids
+    }
+
+    // Point every PRIM and animation resource that uses the ANIM `old_anim` at `new_anim`
+    // instead. If `new_evnt` is given, the matching animation resources will use it too.
+    pub fn replace_anim(&mut self, old_anim: u32, new_anim: u32, new_evnt: Option<u32>)
+    {
+        for anim in self.animations.as_mut_vec().iter_mut() {
+            anim.meta.for_each_primitive_mut(&mut |prim| {
+                if prim.anim == old_anim {
+                    prim.anim = new_anim;
+                }
+            });
+        }
+        if let Some(animation_resources) = &mut self.animation_resources {
+            for res in animation_resources.as_mut_vec().iter_mut() {
+                if res.anim == old_anim {
+                    res.anim = new_anim;
+                    if let Some(evnt) = new_evnt {
+                        res.evnt = evnt;
+                    }
+                }
+            }
+        }
+    }
+}
+
 // Uncached allows for recursion without the struct having infinite size
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MetaAnimation<'r>
 {
     Play(Uncached<'r, MetaAnimationPlay<'r>>),
@@ -306,22 +426,81 @@ pub enum MetaAnimation<'r>
     Sequence(Uncached<'r, MetaAnimationSequence<'r>>),
 }
 
+impl<'r> MetaAnimation<'r>
+{
+    // The PRIMs (the leaves that actually play an ANIM) this animation is built from
+    pub fn primitives(&self) -> Vec<MetaAnimationPlay<'r>>
+    {
+        let mut prims = vec![];
+        self.collect_primitives(&mut prims);
+        prims
+    }
+
+    fn collect_primitives(&self, prims: &mut Vec<MetaAnimationPlay<'r>>)
+    {
+        match self {
+            MetaAnimation::Play(play) => prims.push(play.get().into_owned()),
+            MetaAnimation::Blend(blend) | MetaAnimation::PhaseBlend(blend) => {
+                let blend = blend.get();
+                blend.anim_a.collect_primitives(prims);
+                blend.anim_b.collect_primitives(prims);
+            },
+            MetaAnimation::Random(random) => {
+                for pair in random.get().anims.iter() {
+                    pair.meta.collect_primitives(prims);
+                }
+            },
+            MetaAnimation::Sequence(seq) => {
+                for meta in seq.get().anims.iter() {
+                    meta.collect_primitives(prims);
+                }
+            },
+        }
+    }
+
+    pub fn for_each_primitive_mut<F>(&mut self, f: &mut F)
+        where F: FnMut(&mut MetaAnimationPlay<'r>)
+    {
+        match self {
+            MetaAnimation::Play(play) => f(play.get_mut()),
+            MetaAnimation::Blend(blend) | MetaAnimation::PhaseBlend(blend) => {
+                let blend = blend.get_mut();
+                blend.anim_a.for_each_primitive_mut(f);
+                blend.anim_b.for_each_primitive_mut(f);
+            },
+            MetaAnimation::Random(random) => {
+                for pair in random.get_mut().anims.as_mut_vec().iter_mut() {
+                    pair.meta.for_each_primitive_mut(f);
+                }
+            },
+            MetaAnimation::Sequence(seq) => {
+                for meta in seq.get_mut().anims.as_mut_vec().iter_mut() {
+                    meta.for_each_primitive_mut(f);
+                }
+            },
+        }
+    }
+}
+
 
 impl<'r> Readable<'r> for MetaAnimation<'r>
 {
     type Args = ();
-    fn read_from(reader: &mut Reader<'r>, (): ()) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<Self>
     {
-        let kind: u32 = reader.read(());
+        let kind: u32 = reader.try_read(())?;
         let res = match kind {
-            0 => MetaAnimation::Play(reader.read(())),
-            1 => MetaAnimation::Blend(reader.read(())),
-            2 => MetaAnimation::PhaseBlend(reader.read(())),
-            3 => MetaAnimation::Random(reader.read(())),
-            4 => MetaAnimation::Sequence(reader.read(())),
-            n => panic!("Unexpected MetaAnimation tag: {}", n),
+            0 => MetaAnimation::Play(reader.try_read(())?),
+            1 => MetaAnimation::Blend(reader.try_read(())?),
+            2 => MetaAnimation::PhaseBlend(reader.try_read(())?),
+            3 => MetaAnimation::Random(reader.try_read(())?),
+            4 => MetaAnimation::Sequence(reader.try_read(())?),
+            n => {
+                let msg = format!("Unexpected MetaAnimation tag: {}", n);
+                Err(ReadError::invalid_value(reader, msg))?
+            },
         };
-        res
+        Ok(res)
     }
 
     fn size(&self) -> usize
@@ -351,7 +530,7 @@ impl<'r> Writable for MetaAnimation<'r>
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct MetaAnimationPlay<'r>
 {
@@ -362,7 +541,7 @@ pub struct MetaAnimationPlay<'r>
     pub unknown1: u32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct MetaAnimationBlend<'r>
 {
@@ -372,16 +551,17 @@ pub struct MetaAnimationBlend<'r>
     pub unknown1: u8,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct MetaAnimationRandom<'r>
 {
+    #[auto_struct(derive = anims.len() as u32)]
     pub anim_count: u32,
     #[auto_struct(init = (anim_count as usize, ()))]
-    pub anims: RoArray<'r, MetaAnimationRandomPair<'r>>,
+    pub anims: LazyArray<'r, MetaAnimationRandomPair<'r>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct MetaAnimationRandomPair<'r>
 {
@@ -389,16 +569,17 @@ pub struct MetaAnimationRandomPair<'r>
     pub probability: u32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct MetaAnimationSequence<'r>
 {
+    #[auto_struct(derive = anims.len() as u32)]
     pub anim_count: u32,
     #[auto_struct(init = (anim_count as usize, ()))]
-    pub anims: RoArray<'r, MetaAnimation<'r>>,
+    pub anims: LazyArray<'r, MetaAnimation<'r>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Transition<'r>
 {
@@ -409,6 +590,7 @@ pub struct Transition<'r>
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MetaTransition<'r>
 {
     Animation(Uncached<'r, MetaTransitionAnimation<'r>>),
@@ -420,17 +602,20 @@ pub enum MetaTransition<'r>
 impl<'r> Readable<'r> for MetaTransition<'r>
 {
     type Args = ();
-    fn read_from(reader: &mut Reader<'r>, (): ()) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<Self>
     {
-        let kind: u32 = reader.read(());
+        let kind: u32 = reader.try_read(())?;
         let res = match kind {
-            0 => MetaTransition::Animation(reader.read(())),
-            1 => MetaTransition::Transition(reader.read(())),
-            2 => MetaTransition::PhaseTransition(reader.read(())),
+            0 => MetaTransition::Animation(reader.try_read(())?),
+            1 => MetaTransition::Transition(reader.try_read(())?),
+            2 => MetaTransition::PhaseTransition(reader.try_read(())?),
             3 => MetaTransition::NoTransition,
-            _ => panic!("TODO"),
+            n => {
+                let msg = format!("Unexpected MetaTransition tag: {}", n);
+                Err(ReadError::invalid_value(reader, msg))?
+            },
         };
-        res
+        Ok(res)
     }
 
     fn size(&self) -> usize
@@ -457,14 +642,14 @@ impl<'r> Writable for MetaTransition<'r>
     }
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct MetaTransitionAnimation<'r>
 {
     pub meta: MetaAnimation<'r>,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct MetaTransitionTransition
 {
@@ -476,7 +661,7 @@ pub struct MetaTransitionTransition
 }
 
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct AdditiveAnimation
 {
@@ -485,7 +670,7 @@ pub struct AdditiveAnimation
     pub fade_out: f32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct HalfTransition<'r>
 {
@@ -493,7 +678,7 @@ pub struct HalfTransition<'r>
     pub meta: MetaTransition<'r>,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct AnimationResource
 {
@@ -1,145 +1,183 @@
 use auto_struct_macros::auto_struct;
 
-use reader_writer::{Readable, Reader, RoArray};
+use reader_writer::{Readable, ReadError, ReadResult, Reader, RoArray, Writable};
 use reader_writer::generic_array::{GenericArray, typenum:: *};
 
+use std::io;
+
 #[derive(Debug, Clone)]
 pub enum Anim<'r>
 {
     Uncompressed(AnimUncompressed<'r>),
-    Compressed(AnimCompressed<'r>),
+    // Only the header of compressed animations is parsed, the bitstream following it is kept
+    // as-is.
+    Compressed(AnimCompressed<'r>, Reader<'r>),
+}
+
+impl<'r> Anim<'r>
+{
+    pub fn evnt(&self) -> u32
+    {
+        match self {
+            Anim::Uncompressed(anim) => anim.evnt,
+            Anim::Compressed(anim, _) => anim.evnt,
+        }
+    }
+
+    pub fn set_evnt(&mut self, evnt: u32)
+    {
+        match self {
+            Anim::Uncompressed(anim) => anim.evnt = evnt,
+            Anim::Compressed(anim, _) => anim.evnt = evnt,
+        }
+    }
+
+    pub fn duration(&self) -> f32
+    {
+        match self {
+            Anim::Uncompressed(anim) => anim.duration.time,
+            Anim::Compressed(anim, _) => anim.duration,
+        }
+    }
 }
 
 
 impl<'r> Readable<'r> for Anim<'r>
 {
     type Args = ();
-    fn read_from(reader: &mut Reader<'r>, (): ()) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<Self>
     {
-        let kind: u32 = reader.read(());
+        let kind: u32 = reader.try_read(())?;
         let res = match kind {
-            0 => Anim::Uncompressed(reader.read(())),
-            2 => Anim::Compressed(reader.read(())),
-            i => panic!("Invalid ANIM kind {}", i),
+            0 => Anim::Uncompressed(reader.try_read(())?),
+            2 => {
+                let header = reader.try_read(())?;
+                let tail = reader.clone();
+                reader.advance(tail.len());
+                Anim::Compressed(header, tail)
+            },
+            i => Err(ReadError::invalid_value(reader, format!("Invalid ANIM kind {}", i)))?,
         };
-        res
+        Ok(res)
     }
 
     fn size(&self) -> usize
     {
         u32::fixed_size().unwrap() + match self {
             Anim::Uncompressed(ref i) => i.size(),
-            Anim::Compressed(ref i) => i.size(),
+            Anim::Compressed(ref i, ref tail) => i.size() + tail.len(),
         }
     }
 }
 
-/*
 impl<'r> Writable for Anim<'r>
 {
     fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
     {
         Ok(match self {
             Anim::Uncompressed(anim) => 0u32.write_to(writer)? + anim.write_to(writer)?,
-            Anim::Compressed(anim) => 2u32.write_to(writer)? + anim.write_to(writer)?,
+            Anim::Compressed(anim, tail) => {
+                let len = 2u32.write_to(writer)? + anim.write_to(writer)?;
+                writer.write_all(tail)?;
+                len + tail.len() as u64
+            },
         })
     }
 }
-*/
 
-#[auto_struct(Readable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct AnimUncompressed<'r>
 {
-    duration: CharAnimTime,
-    key_interval: CharAnimTime,
-    key_count: u32,
-    root_bone_id: u32,
+    pub duration: CharAnimTime,
+    pub key_interval: CharAnimTime,
+    pub key_count: u32,
+    pub root_bone_id: u32,
 
     #[auto_struct(derive = bone_channel_index_array.len() as u32)]
-    bone_channel_index_count: u32,
+    pub bone_channel_index_count: u32,
     #[auto_struct(init = (bone_channel_index_count as usize, ()))]
-    bone_channel_index_array: RoArray<u8, 'r>,
+    pub bone_channel_index_array: RoArray<'r, u8>,
 
     #[auto_struct(derive = translation_channel_index_array.len() as u32)]
-    translation_channel_index_count: u32,
+    pub translation_channel_index_count: u32,
     #[auto_struct(init = (translation_channel_index_count as usize, ()))]
-    translation_channel_index_array: RoArray<u8, 'r>,
+    pub translation_channel_index_array: RoArray<'r, u8>,
 
     #[auto_struct(derive = rotation_key_array.len() as u32)]
-    rotation_key_count: u32,
+    pub rotation_key_count: u32,
     #[auto_struct(init = (rotation_key_count as usize, ()))]
-    rotation_key_array: RoArray<GenericArray<f32, U4>, 'r>,
+    pub rotation_key_array: RoArray<'r, GenericArray<f32, U4>>,
 
     #[auto_struct(derive = translation_key_array.len() as u32)]
-    translation_key_count: u32,
+    pub translation_key_count: u32,
     #[auto_struct(init = (translation_key_count as usize, ()))]
-    translation_key_array: RoArray<GenericArray<f32, U3>, 'r>,
+    pub translation_key_array: RoArray<'r, GenericArray<f32, U3>>,
 
-    evnt: u32,
+    pub evnt: u32,
 }
 
 
-#[auto_struct(Readable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct AnimCompressed<'r>
 {
-    scratch_size: u32,
-    evnt: u32,
+    pub scratch_size: u32,
+    pub evnt: u32,
 
     #[auto_struct(expect = 0x1)]
-    unknown0: u32,
+    pub unknown0: u32,
 
-    duration: f32,
-    interval: f32,
-    root_bone_id: u32,
-    looping_flag: u32,
-    rotation_divisor: u32,
-    translation_multiplier: f32,
+    pub duration: f32,
+    pub interval: f32,
+    pub root_bone_id: u32,
+    pub looping_flag: u32,
+    pub rotation_divisor: u32,
+    pub translation_multiplier: f32,
 
-    bone_channel_count: u32,
+    pub bone_channel_count: u32,
 
     #[auto_struct(expect = 0x1)]
-    unknown1: u32,
+    pub unknown1: u32,
 
-    key_bitmap_length: u32,
+    pub key_bitmap_length: u32,
     #[auto_struct(init = ((((key_bitmap_length + 31) & !31) / 32) as usize, ()))]
-    key_bitmap_array: RoArray<u32, 'r>,
+    pub key_bitmap_array: RoArray<'r, u32>,
 
-    bone_channel_count_2: u32,
+    pub bone_channel_count_2: u32,
 
     // #[auto_struct(derive = bone_channel_descriptor_array.len() as u32)]
     // bone_channel_descriptor_count: u32,
     // #[auto_struct(init = (bone_channel_descriptor_count as usize, ()))]
-    // bone_channel_descriptor_array: RoArray<BoneChannelDescriptor, 'r>,
+    // bone_channel_descriptor_array: RoArray<'r, BoneChannelDescriptor>,
 }
 
-#[auto_struct(Readable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct BoneChannelDescriptor
 {
-    bone_id: u32,
-    rotation_key_count: u16,
-    intial_rotation_x: i16,
-    rotation_bits_x: u8,
-    intial_rotation_y: i16,
-    rotation_bits_y: u8,
-    intial_rotation_z: i16,
-    rotation_bits_z: u8,
-
-    translation_key_count: u16,
-    intial_translation_x: i16,
-    translation_bits_x: u8,
-    intial_translation_y: i16,
-    translation_bits_y: u8,
-    intial_translation_z: i16,
-    translation_bits_z: u8,
+    pub bone_id: u32,
+    pub rotation_key_count: u16,
+    pub intial_rotation_x: i16,
+    pub rotation_bits_x: u8,
+    pub intial_rotation_y: i16,
+    pub rotation_bits_y: u8,
+    pub intial_rotation_z: i16,
+    pub rotation_bits_z: u8,
+
+    pub translation_key_count: u16,
+    pub intial_translation_x: i16,
+    pub translation_bits_x: u8,
+    pub intial_translation_y: i16,
+    pub translation_bits_y: u8,
+    pub intial_translation_z: i16,
+    pub translation_bits_z: u8,
 }
 
-#[auto_struct(Readable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct CharAnimTime
 {
-    time: f32,
-    differential_state: u32,
+    pub time: f32,
+    pub differential_state: u32,
 }
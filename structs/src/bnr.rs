@@ -3,7 +3,7 @@ use auto_struct_macros::auto_struct;
 use reader_writer::{FourCC, FixedArray, RoArray};
 use reader_writer::typenum::{U4096, U2048, U32, U64, U128, U5, Sum};
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Bnr<'r>
 {
@@ -21,8 +21,8 @@ pub struct Bnr<'r>
 }
 
 
-#[auto_struct(Readable, Writable, FixedSize)]
-#[derive(Debug, Clone)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
+#[derive(Debug, Clone, Default)]
 pub struct BnrMetadata
 {
     pub game_name: FixedArray<u8, U32>,// 0x20
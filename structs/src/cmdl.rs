@@ -6,7 +6,7 @@ use reader_writer::generic_array::GenericArray;
 
 // We don't need to modify CMDLs, so most of the details are left out.
 // We only actually care about reading out the TXTR file ids.
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Cmdl<'r>
 {
@@ -38,7 +38,7 @@ pub struct Cmdl<'r>
     pub data_sections: IteratorArray<'r, CmdlDataSection<'r>, RoArrayIter<'r, u32>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct CmdlMaterialSet<'r>
 {
@@ -54,7 +54,7 @@ pub struct CmdlMaterialSet<'r>
     pub remainder: RoArray<'r, u8>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct CmdlDataSection<'r>
 {
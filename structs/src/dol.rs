@@ -8,7 +8,7 @@ use reader_writer::typenum::*;
 
 
 pub type DolSegementsIter<S> = ZipIter<GenericArrayIter<u32, S>, GenericArrayIter<u32, S>>;
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 // XXX We're assuming that all of the segments are contigious and in order, which isn't
 //     necessarily the case but is true for prime
@@ -52,7 +52,7 @@ pub struct Dol<'r>
     pub data_segments: IteratorArray<'r, DolSegment<'r>, DolSegementsIter<U11>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct DolSegment<'r>
 {
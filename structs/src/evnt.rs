@@ -1,8 +1,8 @@
 use auto_struct_macros::auto_struct;
 
-use reader_writer::{CStr, FourCC, LazyArray, RoArray};
+use reader_writer::{CStr, FourCC, LazyArray};
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Evnt<'r>
 {
@@ -12,12 +12,12 @@ pub struct Evnt<'r>
     #[auto_struct(derive = loop_events.len() as u32)]
     pub loop_event_count: u32,
     #[auto_struct(init = (loop_event_count as usize, ()))]
-    pub loop_events: RoArray<'r, LoopEvent<'r>>,
+    pub loop_events: LazyArray<'r, LoopEvent<'r>>,
 
     #[auto_struct(derive = user_events.len() as u32)]
     pub user_event_count: u32,
     #[auto_struct(init = (user_event_count as usize, ()))]
-    pub user_events: RoArray<'r, UserEvent<'r>>,
+    pub user_events: LazyArray<'r, UserEvent<'r>>,
 
     #[auto_struct(derive = effect_events.len() as u32)]
     pub effect_event_count: u32,
@@ -28,15 +28,82 @@ pub struct Evnt<'r>
     #[auto_struct(derive = sound_events.as_ref().map(|a| a.len() as u32))]
     pub sound_event_count: Option<u32>,
     #[auto_struct(init = sound_event_count.map(|i| (i as usize, ())))]
-    pub sound_events: Option<RoArray<'r, SoundEvent<'r>>>,
+    pub sound_events: Option<LazyArray<'r, SoundEvent<'r>>>,
 
     #[auto_struct(pad_align = 32)]
     _pad: (),
 }
 
+impl<'r> Evnt<'r>
+{
+    // The particle resources spawned by this EVNT's effect events
+    pub fn particle_dependencies(&self) -> Vec<(u32, FourCC)>
+    {
+        let mut deps = vec![];
+        for event in self.effect_events.iter() {
+            let dep = (event.effect_file_id, event.effect_type);
+            if !deps.contains(&dep) {
+                deps.push(dep);
+            }
+        }
+        deps
+    }
+
+    pub fn sound_ids(&self) -> Vec<u32>
+    {
+        let mut ids = vec![];
+        if let Some(sound_events) = &self.sound_events {
+            for event in sound_events.iter() {
+                if !ids.contains(&event.sound_id) {
+                    ids.push(event.sound_id);
+                }
+            }
+        }
+        ids
+    }
+
+    pub fn replace_effect(&mut self, old_id: u32, new_id: u32)
+    {
+        for event in self.effect_events.as_mut_vec().iter_mut() {
+            if event.effect_file_id == old_id {
+                event.effect_file_id = new_id;
+            }
+        }
+    }
+
+    // Drop every event that only applies to a character other than `character_index`. Events
+    // with a negative character index apply to all characters and are kept.
+    pub fn retain_character(&mut self, character_index: i32)
+    {
+        let keep = |base: &EventBase| {
+            base.character_index < 0 || base.character_index == character_index
+        };
+        self.loop_events.as_mut_vec().retain(|e| keep(&e.base));
+        self.user_events.as_mut_vec().retain(|e| keep(&e.base));
+        self.effect_events.as_mut_vec().retain(|e| keep(&e.base));
+        if let Some(sound_events) = &mut self.sound_events {
+            sound_events.as_mut_vec().retain(|e| keep(&e.base));
+        }
+    }
+
+    pub fn retain_effect_events<F>(&mut self, f: F)
+        where F: FnMut(&EffectEvent<'r>) -> bool
+    {
+        self.effect_events.as_mut_vec().retain(f);
+    }
+
+    pub fn retain_sound_events<F>(&mut self, f: F)
+        where F: FnMut(&SoundEvent<'r>) -> bool
+    {
+        if let Some(sound_events) = &mut self.sound_events {
+            sound_events.as_mut_vec().retain(f);
+        }
+    }
+}
+
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct EventBase<'r>
 {
@@ -52,7 +119,7 @@ pub struct EventBase<'r>
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct AnimTime
 {
@@ -60,7 +127,7 @@ pub struct AnimTime
     pub differential_state: u32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct LoopEvent<'r>
 {
@@ -68,7 +135,7 @@ pub struct LoopEvent<'r>
     pub unknown: u8,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct UserEvent<'r>
 {
@@ -77,7 +144,7 @@ pub struct UserEvent<'r>
     pub bone_name: CStr<'r>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct EffectEvent<'r>
 {
@@ -91,7 +158,7 @@ pub struct EffectEvent<'r>
     pub transform_type: u32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct SoundEvent<'r>
 {
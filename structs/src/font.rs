@@ -1,8 +1,10 @@
 use auto_struct_macros::auto_struct;
 
-use reader_writer::{CStr, FourCC, RoArray};
+use reader_writer::{CStr, FourCC, LazyArray};
 
-#[auto_struct(Readable, Writable)]
+use crate::Txtr;
+
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Font<'r>
 {
@@ -28,37 +30,250 @@ pub struct Font<'r>
     #[auto_struct(derive = glyphs.len() as u32)]
     glyph_count: u32,
     #[auto_struct(init = (glyph_count as usize, ()))]
-    glyphs: RoArray<'r, FontGlyph>,
+    pub glyphs: LazyArray<'r, FontGlyph>,
 
     #[auto_struct(derive = kernings.len() as u32)]
     kerning_count: u32,
     #[auto_struct(init = (kerning_count as usize, ()))]
-    kernings: RoArray<'r, FontKerning>,
+    pub kernings: LazyArray<'r, FontKerning>,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+// A glyph to be added to a font, along with its image. The texels use the same encoding as the
+// font's texture.
+#[derive(Debug, Clone)]
+pub struct GlyphBitmap
+{
+    pub utf16_char: u16,
+    pub width: u32,
+    pub height: u32,
+    pub texels: Vec<u8>,
+
+    pub left_padding: u32,
+    pub print_head_advance: u32,
+    pub right_padding: u32,
+    pub vertical_offset: u32,
+}
+
+impl<'r> Font<'r>
+{
+    pub fn glyph(&self, utf16_char: u16) -> Option<FontGlyph>
+    {
+        self.glyphs.iter()
+            .find(|glyph| glyph.utf16_char == utf16_char)
+            .map(|glyph| glyph.into_owned())
+    }
+
+    // Add or replace a glyph, keeping the table sorted by character
+    pub fn set_glyph(&mut self, glyph: FontGlyph)
+    {
+        let glyphs = self.glyphs.as_mut_vec();
+        match glyphs.binary_search_by_key(&glyph.utf16_char, |g| g.utf16_char) {
+            Ok(i) => glyphs[i] = glyph,
+            Err(i) => glyphs.insert(i, glyph),
+        }
+    }
+
+    // The index of the first kerning pair for `utf16_char`. The kerning table is sorted by its
+    // first character, so a character without any pairs gets the index they would be inserted at.
+    pub fn kerning_start_index(&self, utf16_char: u16) -> u32
+    {
+        self.kernings.iter()
+            .position(|kerning| kerning.char1 >= utf16_char)
+            .unwrap_or_else(|| self.kernings.len()) as u32
+    }
+
+    // Append the bitmaps below the existing image in the font's texture atlas and add glyphs
+    // for them. The existing glyphs' UV coordinates are rescaled to match the taller atlas.
+    // `txtr` must be the texture referenced by `self.txtr`.
+    pub fn add_glyphs(&mut self, txtr: &mut Txtr, bitmaps: &[GlyphBitmap]) -> Result<(), String>
+    {
+        const GLYPH_SPACING: u32 = 1;
+
+        let width = txtr.width as u32;
+        let old_height = txtr.height as u32;
+        let mut texels = txtr.texels()?;
+
+        // Pack the bitmaps into rows
+        let mut positions = vec![];
+        let (mut x, mut y, mut row_height) = (0, old_height + GLYPH_SPACING, 0);
+        for bitmap in bitmaps {
+            if bitmap.texels.len() != (bitmap.width * bitmap.height) as usize {
+                Err(format!("Glyph 0x{:04X} has the wrong number of texels", bitmap.utf16_char))?
+            }
+            if bitmap.width > width {
+                Err(format!("Glyph 0x{:04X} is wider than the font's texture", bitmap.utf16_char))?
+            }
+            if x + bitmap.width > width {
+                x = 0;
+                y += row_height + GLYPH_SPACING;
+                row_height = 0;
+            }
+            positions.push((x, y));
+            x += bitmap.width + GLYPH_SPACING;
+            row_height = row_height.max(bitmap.height);
+        }
+        // Keep the height a multiple of the largest GX block height
+        let new_height = (y + row_height + 7) & !7;
+        if new_height > 1024 {
+            Err(format!("Font texture would be too tall ({} texels)", new_height))?
+        }
+
+        texels.resize((width * new_height) as usize, 0);
+        for (bitmap, &(x, y)) in bitmaps.iter().zip(positions.iter()) {
+            for row in 0..bitmap.height {
+                let src = (row * bitmap.width) as usize;
+                let dst = ((y + row) * width + x) as usize;
+                texels[dst..dst + bitmap.width as usize]
+                    .copy_from_slice(&bitmap.texels[src..src + bitmap.width as usize]);
+            }
+        }
+        txtr.set_texels(width as u16, new_height as u16, &texels)?;
+
+        let scale = old_height as f32 / new_height as f32;
+        for glyph in self.glyphs.as_mut_vec().iter_mut() {
+            glyph.top_uv_coordinate *= scale;
+            glyph.bottom_uv_coordinate *= scale;
+        }
+
+        for (bitmap, &(x, y)) in bitmaps.iter().zip(positions.iter()) {
+            let kerning_start_index = self.kerning_start_index(bitmap.utf16_char);
+            self.set_glyph(FontGlyph {
+                utf16_char: bitmap.utf16_char,
+                left_uv_coordinate: x as f32 / width as f32,
+                top_uv_coordinate: y as f32 / new_height as f32,
+                right_uv_coordinate: (x + bitmap.width) as f32 / width as f32,
+                bottom_uv_coordinate: (y + bitmap.height) as f32 / new_height as f32,
+                left_padding: bitmap.left_padding,
+                print_head_advance: bitmap.print_head_advance,
+                right_padding: bitmap.right_padding,
+                width: bitmap.width,
+                height: bitmap.height,
+                vertical_offset: bitmap.vertical_offset,
+                kerning_start_index,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct FontGlyph
 {
-    utf16_char: u16,
-    left_uv_coordinate: f32,
-    top_uv_coordinate: f32,
-    right_uv_coordinate: f32,
-    bottom_uv_coordinate: f32,
-    left_padding: u32,
-    print_head_advance: u32,
-    right_padding: u32,
-    width: u32,
-    height: u32,
-    vertical_offset: u32,
-    kerning_start_index: u32,
+    pub utf16_char: u16,
+    pub left_uv_coordinate: f32,
+    pub top_uv_coordinate: f32,
+    pub right_uv_coordinate: f32,
+    pub bottom_uv_coordinate: f32,
+    pub left_padding: u32,
+    pub print_head_advance: u32,
+    pub right_padding: u32,
+    pub width: u32,
+    pub height: u32,
+    pub vertical_offset: u32,
+    pub kerning_start_index: u32,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct FontKerning
 {
-    char1: u16,
-    char2: u16,
-    kerning_adjust: i32,
+    pub char1: u16,
+    pub char2: u16,
+    pub kerning_adjust: i32,
+}
+
+#[test]
+fn test_add_glyphs()
+{
+    use reader_writer::{CStrConversionExtension, Readable, Reader};
+
+    // An 8x4 C8 texture with a single entry palette
+    let mut data = vec![0, 0, 0, 5, 0, 8, 0, 4, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0];
+    data.extend((0..32).map(|i| i as u8));
+    let mut txtr: Txtr = Reader::new(&data[..]).read(());
+    assert_eq!(txtr.size(), data.len());
+    assert_eq!(txtr.texels().unwrap(), (0..32).collect::<Vec<u8>>());
+
+    let mut font = Font {
+        unknown0: 0,
+        line_height: 0,
+        vertical_offset: 0,
+        line_margin: 0,
+        unknown1: 0,
+        unknown2: 0,
+        unknown3: 0,
+        font_size: 0,
+        name: b"test\0".as_cstr(),
+        txtr: 0,
+        txtr_fmt: 0,
+        glyphs: vec![].into(),
+        kernings: vec![].into(),
+    };
+    font.add_glyphs(&mut txtr, &[GlyphBitmap {
+        utf16_char: 0x41,
+        width: 2,
+        height: 2,
+        texels: vec![0xFF; 4],
+        left_padding: 0,
+        print_head_advance: 2,
+        right_padding: 0,
+        vertical_offset: 0,
+    }]).unwrap();
+
+    assert_eq!((txtr.width, txtr.height), (8, 8));
+    let texels = txtr.texels().unwrap();
+    assert_eq!(&texels[..32], &(0..32).collect::<Vec<u8>>()[..]);
+    assert_eq!(&texels[40..42], &[0xFF, 0xFF]);
+    assert_eq!(&texels[48..50], &[0xFF, 0xFF]);
+    let glyph = font.glyph(0x41).unwrap();
+    assert_eq!(glyph.top_uv_coordinate, 5.0 / 8.0);
+    assert_eq!(glyph.right_uv_coordinate, 2.0 / 8.0);
+}
+
+#[test]
+fn test_add_glyphs_kerning_start_index()
+{
+    use reader_writer::{CStrConversionExtension, Reader};
+
+    // A 8x8 C8 texture with a single entry palette
+    let mut data = vec![0, 0, 0, 5, 0, 8, 0, 8, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0];
+    data.extend(vec![0; 64]);
+    let mut txtr: Txtr = Reader::new(&data[..]).read(());
+
+    let kerning = |char1, char2| FontKerning { char1, char2, kerning_adjust: -1 };
+    let mut font = Font {
+        unknown0: 0,
+        line_height: 0,
+        vertical_offset: 0,
+        line_margin: 0,
+        unknown1: 0,
+        unknown2: 0,
+        unknown3: 0,
+        font_size: 0,
+        name: b"test\0".as_cstr(),
+        txtr: 0,
+        txtr_fmt: 0,
+        glyphs: vec![].into(),
+        kernings: vec![kerning(0x41, 0x56), kerning(0x41, 0x57), kerning(0x54, 0x41)].into(),
+    };
+    let bitmap = |utf16_char| GlyphBitmap {
+        utf16_char,
+        width: 1,
+        height: 1,
+        texels: vec![0xFF],
+        left_padding: 0,
+        print_head_advance: 1,
+        right_padding: 0,
+        vertical_offset: 0,
+    };
+    font.add_glyphs(&mut txtr, &[bitmap(0x41), bitmap(0x50), bitmap(0x54), bitmap(0x5A)])
+        .unwrap();
+
+    // Glyphs with kerning pairs point at their first pair, the others at where theirs would go
+    let index = |utf16_char| font.glyph(utf16_char).unwrap().kerning_start_index;
+    assert_eq!(index(0x41), 0);
+    assert_eq!(index(0x50), 2);
+    assert_eq!(index(0x54), 2);
+    assert_eq!(index(0x5A), 3);
 }
@@ -1,14 +1,14 @@
 use auto_struct_macros::auto_struct;
 
 use reader_writer::{
-    CStr, FourCC, LazyArray, Readable, Reader, RoArray, Writable,
+    CStr, FourCC, LazyArray, Readable, ReadError, ReadResult, Reader, Writable,
 };
 use reader_writer::generic_array::GenericArray;
 use reader_writer::generic_array::typenum:: *;
 
 use std::io;
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Frme<'r>
 {
@@ -32,7 +32,134 @@ pub struct Frme<'r>
     _pad: (),
 }
 
-#[auto_struct(Readable, Writable)]
+// Widgets form a tree through their parent names, and a widget must come after its parent in
+// the widget list. Text panes don't reference the string they display; their text is set by the
+// game's code at runtime.
+impl<'r> Frme<'r>
+{
+    pub fn widget_index(&self, name: &[u8]) -> Option<usize>
+    {
+        self.widgets.iter().position(|w| w.name.to_bytes() == name)
+    }
+
+    pub fn widget(&self, name: &[u8]) -> Option<FrmeWidget<'r>>
+    {
+        self.widgets.iter()
+            .find(|w| w.name.to_bytes() == name)
+            .map(|w| w.into_owned())
+    }
+
+    pub fn widget_mut(&mut self, name: &[u8]) -> Option<&mut FrmeWidget<'r>>
+    {
+        self.widgets.iter_mut().find(|w| w.name.to_bytes() == name)
+    }
+
+    // The names of the widgets whose parent is `name`
+    pub fn children(&self, name: &[u8]) -> Vec<Vec<u8>>
+    {
+        self.widgets.iter()
+            .filter(|w| w.parent.to_bytes() == name)
+            .map(|w| w.name.to_bytes().to_vec())
+            .collect()
+    }
+
+    // `name` and the names of all of its descendants, parents before children
+    fn subtree(&self, name: &[u8]) -> Vec<Vec<u8>>
+    {
+        let mut names = vec![name.to_vec()];
+        let mut i = 0;
+        while i < names.len() {
+            let children = self.children(&names[i]);
+            names.extend(children);
+            i += 1;
+        }
+        names
+    }
+
+    pub fn add_widget(&mut self, widget: FrmeWidget<'r>) -> Result<(), String>
+    {
+        if self.widget_index(widget.name.to_bytes()).is_some() {
+            Err(format!("FRME already has a widget named {:?}", widget.name))?
+        }
+        if self.widget_index(widget.parent.to_bytes()).is_none() {
+            Err(format!("FRME has no widget named {:?} to use as a parent", widget.parent))?
+        }
+        self.widgets.as_mut_vec().push(widget);
+        Ok(())
+    }
+
+    // Copy an existing widget under a new name, keeping its parent
+    pub fn duplicate_widget(&mut self, name: &[u8], new_name: CStr<'r>)
+        -> Result<&mut FrmeWidget<'r>, String>
+    {
+        let mut widget = self.widget(name)
+            .ok_or_else(|| format!("FRME has no widget named {:?}", String::from_utf8_lossy(name)))?;
+        widget.name = new_name;
+        self.add_widget(widget)?;
+        Ok(self.widgets.as_mut_vec().last_mut().unwrap())
+    }
+
+    // Remove a widget along with all of its descendants, returning the removed widgets
+    pub fn remove_widget(&mut self, name: &[u8]) -> Vec<FrmeWidget<'r>>
+    {
+        let names = self.subtree(name);
+        let widgets = self.widgets.as_mut_vec();
+        let mut removed = vec![];
+        let mut i = 0;
+        while i < widgets.len() {
+            if names.iter().any(|n| &n[..] == widgets[i].name.to_bytes()) {
+                removed.push(widgets.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+        removed
+    }
+
+    // Attach a widget (and its descendants) to a different parent
+    pub fn reparent_widget(&mut self, name: &[u8], new_parent: CStr<'r>) -> Result<(), String>
+    {
+        if self.widget_index(new_parent.to_bytes()).is_none() {
+            Err(format!("FRME has no widget named {:?} to use as a parent", new_parent))?
+        }
+        if self.subtree(name).iter().any(|n| &n[..] == new_parent.to_bytes()) {
+            Err(format!("Widget {:?} can't be made a child of itself", new_parent))?
+        }
+        let mut removed = self.remove_widget(name);
+        if removed.is_empty() {
+            Err(format!("FRME has no widget named {:?}", String::from_utf8_lossy(name)))?
+        }
+        removed[0].parent = new_parent;
+        // Re-adding the subtree at the end keeps every widget after its parent
+        self.widgets.as_mut_vec().extend(removed);
+        Ok(())
+    }
+
+    pub fn translate_widget(&mut self, name: &[u8], offset: [f32; 3]) -> Result<(), String>
+    {
+        let widget = self.widget_mut(name)
+            .ok_or_else(|| format!("FRME has no widget named {:?}", String::from_utf8_lossy(name)))?;
+        for (o, d) in widget.origin.iter_mut().zip(offset.iter()) {
+            *o += d;
+        }
+        Ok(())
+    }
+
+    pub fn dependencies(&self) -> Vec<(u32, FourCC)>
+    {
+        let mut deps = vec![];
+        for widget in self.widgets.iter() {
+            if let Some(dep) = widget.kind.dependency() {
+                if !deps.contains(&dep) {
+                    deps.push(dep);
+                }
+            }
+        }
+        deps
+    }
+}
+
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct FrmeWidget<'r>
 {
@@ -67,6 +194,7 @@ pub struct FrmeWidget<'r>
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FrmeWidgetKind<'r>
 {
     Head,// HWIG
@@ -104,42 +232,73 @@ impl<'r> FrmeWidgetKind<'r>
             FrmeWidgetKind::Image(_) => b"IMGP".into(),
         }
     }
+
+    pub fn texture(&self) -> Option<u32>
+    {
+        match self {
+            FrmeWidgetKind::Energy(widget) => Some(widget.txtr),
+            FrmeWidgetKind::Image(widget) => Some(widget.texture),
+            _ => None,
+        }
+    }
+
+    // Returns false if the widget doesn't display a texture
+    pub fn set_texture(&mut self, txtr: u32) -> bool
+    {
+        match self {
+            FrmeWidgetKind::Energy(widget) => widget.txtr = txtr,
+            FrmeWidgetKind::Image(widget) => widget.texture = txtr,
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn dependency(&self) -> Option<(u32, FourCC)>
+    {
+        match self {
+            FrmeWidgetKind::Model(widget) => Some((widget.model, b"CMDL".into())),
+            FrmeWidgetKind::TextPane(widget) => Some((widget.font, b"FONT".into())),
+            _ => self.texture().map(|txtr| (txtr, b"TXTR".into())),
+        }
+    }
 }
 
 impl<'r> Readable<'r> for FrmeWidgetKind<'r>
 {
     type Args = (FourCC, u32);
-    fn read_from(reader: &mut Reader<'r>, (fourcc, version): Self::Args) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (fourcc, version): Self::Args)
+        -> ReadResult<Self>
     {
-        if fourcc == b"HWIG".into() {
+        Ok(if fourcc == b"HWIG".into() {
             FrmeWidgetKind::Head
         } else if fourcc == b"BWIG".into() {
             FrmeWidgetKind::Base
         } else if fourcc == b"CAMR".into() {
-            FrmeWidgetKind::Camera(reader.read(()))
+            FrmeWidgetKind::Camera(reader.try_read(())?)
         } else if fourcc == b"LITE".into() {
-            FrmeWidgetKind::Light(reader.read(()))
+            FrmeWidgetKind::Light(reader.try_read(())?)
         } else if fourcc == b"MODL".into() {
-            FrmeWidgetKind::Model(reader.read(()))
+            FrmeWidgetKind::Model(reader.try_read(())?)
         } else if fourcc == b"TXPN".into() {
-            FrmeWidgetKind::TextPane(reader.read(version))
+            FrmeWidgetKind::TextPane(reader.try_read(version)?)
         } else if fourcc == b"METR".into() {
-            FrmeWidgetKind::Meter(reader.read(()))
+            FrmeWidgetKind::Meter(reader.try_read(())?)
         } else if fourcc == b"ENRG".into() {
-            FrmeWidgetKind::Energy(reader.read(()))
+            FrmeWidgetKind::Energy(reader.try_read(())?)
         } else if fourcc == b"GRUP".into() {
-            FrmeWidgetKind::Group(reader.read(()))
+            FrmeWidgetKind::Group(reader.try_read(())?)
         } else if fourcc == b"TBGP".into() {
-            FrmeWidgetKind::TableGroup(reader.read(()))
+            FrmeWidgetKind::TableGroup(reader.try_read(())?)
         } else if fourcc == b"PANE".into() {
-            FrmeWidgetKind::Pane(reader.read(()))
+            FrmeWidgetKind::Pane(reader.try_read(())?)
         } else if fourcc == b"SLGP".into() {
-            FrmeWidgetKind::Slider(reader.read(()))
+            FrmeWidgetKind::Slider(reader.try_read(())?)
         } else if fourcc == b"IMGP".into() {
-            FrmeWidgetKind::Image(reader.read(()))
+            FrmeWidgetKind::Image(reader.try_read(())?)
         } else {
-            panic!("Invalid Frme widget fourcc {:?}", fourcc)
-        }
+            let msg = format!("Invalid Frme widget fourcc {:?}", fourcc);
+            Err(ReadError::invalid_value(reader, msg))?
+        })
     }
 
     fn size(&self) -> usize
@@ -185,7 +344,7 @@ impl<'r> Writable for FrmeWidgetKind<'r>
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct CameraWidget
 {
@@ -205,7 +364,7 @@ pub struct CameraWidget
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct LightWidget
 {
@@ -223,14 +382,14 @@ pub struct LightWidget
     pub cutoff: Option<f32>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct EnergyWidget
 {
     pub txtr: u32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct MeterWidget
 {
@@ -240,7 +399,7 @@ pub struct MeterWidget
     pub worker_count: u32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct GroupWidget
 {
@@ -248,7 +407,7 @@ pub struct GroupWidget
     pub unknown: u8,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct TableGroupWidget
 {
@@ -269,7 +428,7 @@ pub struct TableGroupWidget
     pub unknown11: u16,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct SliderWidget
 {
@@ -279,7 +438,7 @@ pub struct SliderWidget
     pub increment: f32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct PaneWidget
 {
@@ -288,7 +447,7 @@ pub struct PaneWidget
     pub scale_center: GenericArray<f32, U3>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct TextPaneWidget
 {
@@ -315,7 +474,7 @@ pub struct TextPaneWidget
     // TODO: If Frme::version == 1, then theres three extra fields
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct ImageWidget<'r>
 {
@@ -326,15 +485,15 @@ pub struct ImageWidget<'r>
     #[auto_struct(derive = quad_coords.len() as u32)]
     quad_coord_count: u32,
     #[auto_struct(init = (quad_coord_count as usize, ()))]
-    pub quad_coords: RoArray<'r, GenericArray<f32, U3>>,
+    pub quad_coords: LazyArray<'r, GenericArray<f32, U3>>,
 
     #[auto_struct(derive = uv_coords.len() as u32)]
     uv_coord_count: u32,
     #[auto_struct(init = (uv_coord_count as usize, ()))]
-    pub uv_coords: RoArray<'r, GenericArray<f32, U2>>,
+    pub uv_coords: LazyArray<'r, GenericArray<f32, U2>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct ModelWidget
 {
@@ -1,6 +1,6 @@
 use auto_struct_macros::auto_struct;
 
-use reader_writer::{CStr, Reader, Readable, RoArray, WithRead, Writable};
+use reader_writer::{CStr, Reader, Readable, ReadError, ReadResult, RoArray, WithRead, Writable};
 use reader_writer::typenum::*;
 use reader_writer::generic_array::GenericArray;
 
@@ -28,20 +28,27 @@ pub struct GcDisc<'r>
 impl<'r> Readable<'r> for GcDisc<'r>
 {
     type Args = ();
-    fn read_from(reader: &mut Reader<'r>, (): ()) -> GcDisc<'r>
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<GcDisc<'r>>
     {
         let start = reader.clone();
-        let header: GcDiscHeader = reader.read(());
-        let header_info = reader.read(());
-        let apploader = reader.read(());
-
+        let header: GcDiscHeader = reader.try_read(())
+            .map_err(|e| e.in_field("GcDisc.header"))?;
+        let header_info = reader.try_read(())
+            .map_err(|e| e.in_field("GcDisc.header_info"))?;
+        let apploader = reader.try_read(())
+            .map_err(|e| e.in_field("GcDisc.apploader"))?;
+
+        start.check_len(header.fst_offset as usize)?;
         let fst_start = start.offset(header.fst_offset as usize);
-        let root_fst_entry: RawFstEntry = fst_start.clone().read(());
+        let root_fst_entry: RawFstEntry = fst_start.clone().try_read(())
+            .map_err(|e| e.in_field("GcDisc.file_system_root"))?;
 
-        let fst_len = root_fst_entry.length as usize;
-        let string_table_start = fst_start.offset(fst_len * RawFstEntry::fixed_size().unwrap());
+        let fst_len = root_fst_entry.length as usize * RawFstEntry::fixed_size().unwrap();
+        fst_start.check_len(fst_len)?;
+        let string_table_start = fst_start.offset(fst_len);
 
-        let fst = { fst_start }.read((0, start, string_table_start));
+        let fst = { fst_start }.try_read((0, start, string_table_start))
+            .map_err(|e| e.in_field("GcDisc.file_system_root"))?;
 
         let gc_disc = GcDisc {
             header: header,
@@ -49,7 +56,7 @@ impl<'r> Readable<'r> for GcDisc<'r>
             apploader: apploader,
             file_system_root: fst,
         };
-        gc_disc
+        Ok(gc_disc)
     }
 
     fn fixed_size() -> Option<usize>
@@ -65,6 +72,7 @@ pub trait ProgressNotifier
     fn notify_writing_header(&mut self);
     fn notify_flushing_to_disk(&mut self);
     fn notify_stacking_warning(&mut self);
+    fn notify_warning(&mut self, msg: &str);
 }
 
 pub trait WriteExt
@@ -135,7 +143,7 @@ impl<'r> GcDisc<'r>
     }
 }
 
-#[auto_struct(Readable, FixedSize, Writable)]
+#[auto_struct(Readable, FixedSize, Writable, Serde)]
 #[derive(Debug)]
 pub struct GcDiscHeader
 {
@@ -184,7 +192,7 @@ impl GcDiscHeader
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 pub struct GcDiscApploader<'r>
 {
     pub date: GenericArray<u8, U16>,
@@ -207,28 +215,37 @@ pub enum FstEntry<'r>
 impl<'r> Readable<'r> for FstEntry<'r>
 {
     type Args = (u32, Reader<'r>, Reader<'r>);
-    fn read_from(reader: &mut Reader<'r>, (self_offset, disc_start, string_table): Self::Args)
-        -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (self_offset, disc_start, string_table): Self::Args)
+        -> ReadResult<Self>
     {
         let reader_start = reader.clone();
-        let raw: RawFstEntry = reader.read(());
-        let name = string_table.offset(raw.name_offset as usize).read::<CStr<'r>>(());
+        let raw: RawFstEntry = reader.try_read(())?;
+        string_table.check_len(raw.name_offset as usize)?;
+        let name = string_table.offset(raw.name_offset as usize).try_read::<CStr<'r>>(())?;
         if raw.flags == 1 {
             let mut entries = vec![];
             loop {
                 let bytes_read = reader_start.len() - reader.len();
                 let index = (bytes_read / RawFstEntry::fixed_size().unwrap()) as u32;
-                if index >= (raw.length - self_offset) {
+                if index >= raw.length.saturating_sub(self_offset) {
                     break
                 }
-                entries.push(reader.read((index, disc_start.clone(), string_table.clone())));
+                let entry = reader.try_read((index, disc_start.clone(), string_table.clone()))
+                    .map_err(|e| e.in_field(name.to_string_lossy().into_owned()))?;
+                entries.push(entry);
             }
-            FstEntry::Dir(name, entries)
+            Ok(FstEntry::Dir(name, entries))
         } else {
+            if disc_start.len() < raw.offset as usize + raw.length as usize {
+                Err(ReadError::invalid_value(reader, format!(
+                    "File {:?} extends past the end of the disc",
+                    name.to_string_lossy()
+                )))?
+            }
             let file = FstEntryFile::Unknown(
                 disc_start.offset(raw.offset as usize).truncated(raw.length as usize)
             );
-            FstEntry::File(name, file, Some(raw.offset))
+            Ok(FstEntry::File(name, file, Some(raw.offset)))
         }
     }
 
@@ -362,7 +379,7 @@ impl<'r> FstEntry<'r>
     }
 }
 
-#[auto_struct(Readable, FixedSize, Writable)]
+#[auto_struct(Readable, FixedSize, Writable, Serde)]
 #[derive(Debug)]
 struct RawFstEntry
 {
@@ -452,10 +469,18 @@ impl<'r> FstEntry<'r>
     }
 
     pub fn guess_kind(&mut self)
+    {
+        if let Err(e) = self.try_guess_kind() {
+            panic!("{}", e)
+        }
+    }
+
+    // Parse the file's contents based on its extension, if it hasn't been already
+    pub fn try_guess_kind(&mut self) -> ReadResult<()>
     {
         let (name, file) = match self {
             FstEntry::File(name, file, _) => (name, file),
-            _ => return,
+            _ => return Ok(()),
         };
         let name = name.to_bytes();
         let len = name.len();
@@ -467,8 +492,9 @@ impl<'r> FstEntry<'r>
         if ext == *b"pak" {
             *file = match file {
                 FstEntryFile::Unknown(ref reader)
-                    => FstEntryFile::Pak(reader.clone().read(())),
-                FstEntryFile::Pak(_) => return,
+                    => FstEntryFile::Pak(reader.clone().try_read(())
+                        .map_err(|e| e.in_field(String::from_utf8_lossy(name)))?),
+                FstEntryFile::Pak(_) => return Ok(()),
                 _ => panic!("Unexpected fst file type while trying to guess pak."),
             }
         }
@@ -476,8 +502,9 @@ impl<'r> FstEntry<'r>
         if ext == *b"thp" {
             *file = match file {
                 FstEntryFile::Unknown(ref reader)
-                    => FstEntryFile::Thp(reader.clone().read(())),
-                FstEntryFile::Thp(_) => return,
+                    => FstEntryFile::Thp(reader.clone().try_read(())
+                        .map_err(|e| e.in_field(String::from_utf8_lossy(name)))?),
+                FstEntryFile::Thp(_) => return Ok(()),
                 _ => panic!("Unexpected fst file type while trying to guess thp."),
             }
         }
@@ -485,11 +512,13 @@ impl<'r> FstEntry<'r>
         if ext == *b"bnr" {
             *file = match file {
                 FstEntryFile::Unknown(ref reader)
-                    => FstEntryFile::Bnr(reader.clone().read(())),
-                FstEntryFile::Bnr(_) => return,
+                    => FstEntryFile::Bnr(reader.clone().try_read(())
+                        .map_err(|e| e.in_field(String::from_utf8_lossy(name)))?),
+                FstEntryFile::Bnr(_) => return Ok(()),
                 _ => panic!("Unexpected fst file type while trying to guess bnr."),
             }
         }
+        Ok(())
     }
 
     pub fn dir_files_iter_mut<'a>(&'a mut self) -> DirFilesIterMut<'a, 'r>
@@ -1,7 +1,7 @@
 use auto_struct_macros::auto_struct;
 use reader_writer::{CStr, LazyArray};
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Hint<'r>
 {
@@ -16,7 +16,7 @@ pub struct Hint<'r>
     pub hints: LazyArray<'r, HintDetails<'r>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct HintDetails<'r>
 {
@@ -32,7 +32,7 @@ pub struct HintDetails<'r>
     pub locations: LazyArray<'r, HintLocation>,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct HintLocation
 {
@@ -41,3 +41,73 @@ pub struct HintLocation
     pub target_room_index: u32,
     pub map_text_strg: u32,
 }
+
+impl<'r> Hint<'r>
+{
+    pub fn new() -> Hint<'r>
+    {
+        Hint { hints: vec![].into() }
+    }
+
+    pub fn find(&self, name: &CStr) -> Option<usize>
+    {
+        self.hints.iter().position(|hint| hint.hint_name == *name)
+    }
+
+    pub fn hint_mut(&mut self, name: &CStr) -> Option<&mut HintDetails<'r>>
+    {
+        self.hints.as_mut_vec().iter_mut().find(|hint| hint.hint_name == *name)
+    }
+
+    // Hints are looked up by name, so names must be unique
+    pub fn add_hint(&mut self, hint: HintDetails<'r>) -> Result<(), String>
+    {
+        if self.find(&hint.hint_name).is_some() {
+            Err(format!("There is already a hint named {:?}", hint.hint_name))?
+        }
+        self.hints.as_mut_vec().push(hint);
+        Ok(())
+    }
+
+    pub fn remove_hint(&mut self, name: &CStr) -> Option<HintDetails<'r>>
+    {
+        let idx = self.find(name)?;
+        Some(self.hints.as_mut_vec().remove(idx))
+    }
+}
+
+impl<'r> Default for Hint<'r>
+{
+    fn default() -> Hint<'r>
+    {
+        Hint::new()
+    }
+}
+
+impl<'r> HintDetails<'r>
+{
+    // A hint with no locations. `intermediate_time` and `normal_time` are how many seconds pass
+    // before the hint is shown, and `text_time` is how long its popup stays up.
+    pub fn new(
+        hint_name: CStr<'r>,
+        intermediate_time: f32,
+        normal_time: f32,
+        popup_text_strg: u32,
+        text_time: u32,
+    ) -> HintDetails<'r>
+    {
+        HintDetails {
+            hint_name,
+            intermediate_time,
+            normal_time,
+            popup_text_strg,
+            text_time,
+            locations: vec![].into(),
+        }
+    }
+
+    pub fn add_location(&mut self, location: HintLocation)
+    {
+        self.locations.as_mut_vec().push(location);
+    }
+}
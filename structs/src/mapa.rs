@@ -5,7 +5,7 @@ use reader_writer::typenum::*;
 use reader_writer::generic_array::GenericArray;
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Mapa<'r>
 {
@@ -60,7 +60,7 @@ pub enum MapaObjectType {
     DoorPlasmaFloor2   = 15,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct MapaObject
 {
@@ -82,7 +82,7 @@ impl MapaObject
     }
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct MapaSurfaceHeader
 {
@@ -92,7 +92,7 @@ pub struct MapaSurfaceHeader
     pub border_table_start: u32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct MapaSurface<'r>
 {
@@ -107,7 +107,7 @@ pub struct MapaSurface<'r>
     pub borders: RoArray<'r, MapaBorder<'r>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct MapaPrimitive<'r>
 {
@@ -121,7 +121,7 @@ pub struct MapaPrimitive<'r>
     pub _pad: (),
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct MapaBorder<'r>
 {
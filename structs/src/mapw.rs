@@ -2,7 +2,7 @@ use auto_struct_macros::auto_struct;
 
 use reader_writer::{LazyArray};
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Mapw<'r>
 {
@@ -1,13 +1,13 @@
 use auto_struct_macros::auto_struct;
-use reader_writer::{CStr, FourCC, IteratorArray, LazyArray, Readable, Reader, RoArray,
-                    RoArrayIter, Writable};
+use reader_writer::{CStr, FourCC, IteratorArray, LazyArray, Readable, Reader, ReadError,
+                    ReadResult, RoArray, RoArrayIter, Writable};
 use reader_writer::typenum::*;
 use reader_writer::generic_array::GenericArray;
 
 use std::io;
 use std::iter::Peekable;
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Clone, Debug)]
 pub struct Mlvl<'r>
 {
@@ -63,7 +63,7 @@ pub struct Mlvl<'r>
 }
 
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Clone, Debug)]
 pub struct MemoryRelayConn
 {
@@ -73,7 +73,7 @@ pub struct MemoryRelayConn
     pub active: u8,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Clone, Debug)]
 pub struct Area<'r>
 {
@@ -84,9 +84,10 @@ pub struct Area<'r>
 
     pub internal_id: u32,
 
-    pub attached_area_count: u32,
+    #[auto_struct(derive = attached_areas.len() as u32)]
+    attached_area_count: u32,
     #[auto_struct(init = (attached_area_count as usize, ()))]
-    pub attached_areas: RoArray<'r, u16>,
+    pub attached_areas: LazyArray<'r, u16>,
 
     // Not actually unknown, length of an array that's always empty...
     #[auto_struct(expect = 0)]
@@ -97,10 +98,10 @@ pub struct Area<'r>
     #[auto_struct(derive = docks.len() as u32)]
     dock_count: u32,
     #[auto_struct(init = (dock_count as usize, ()))]
-    pub docks: RoArray<'r, Dock<'r>>,
+    pub docks: LazyArray<'r, Dock<'r>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Clone, Debug)]
 pub struct AreaDependenciesInner<'r>
 {
@@ -119,6 +120,7 @@ pub struct AreaDependenciesInner<'r>
 // by an offset array. This is difficult to model, so it uses hand-written reading/
 // writing code.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AreaDependencies<'r>
 {
     pub deps: IteratorArray<'r, LazyArray<'r, Dependency>, LayerDepCountIter<'r>>
@@ -127,13 +129,24 @@ pub struct AreaDependencies<'r>
 impl<'r> Readable<'r> for AreaDependencies<'r>
 {
     type Args = ();
-    fn read_from(reader: &mut Reader<'r>, (): ()) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<Self>
     {
-        let inner: AreaDependenciesInner = reader.read(());
+        let inner: AreaDependenciesInner = reader.try_read(())?;
+
+        // LayerDepCountIter relies on the offsets being in order and in bounds
+        let mut prev = 0;
+        for offset in inner.dependency_offsets.iter() {
+            if offset < prev || offset > inner.dependencies.len() as u32 {
+                let msg = format!("Invalid layer dependency offset {}", offset);
+                Err(ReadError::invalid_value(reader, msg).in_field("AreaDependencies"))?
+            }
+            prev = offset;
+        }
 
-        let mut data_start = inner.dependencies.data_start();
+        // Freshly read, so the array is always backed by the buffer
+        let mut data_start = inner.dependencies.data_start().unwrap();
         let iter = LayerDepCountIter::new(inner);
-        AreaDependencies { deps: data_start.read(iter), }
+        Ok(AreaDependencies { deps: data_start.try_read(iter)?, })
     }
 
     fn size(&self) -> usize
@@ -205,21 +218,21 @@ impl<'r> ExactSizeIterator for LayerDepCountIter<'r>
     }
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Clone, Debug, PartialEq)]
 pub struct Dependency
 {
     pub asset_id: u32,
     pub asset_type: FourCC,
 }
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Clone, Debug)]
 pub struct Dock<'r>
 {
     #[auto_struct(derive = connecting_docks.len() as u32 )]
     connecting_dock_count: u32,
     #[auto_struct(init = (connecting_dock_count as usize, ()))]
-    pub connecting_docks: RoArray<'r, DockConnection>,
+    pub connecting_docks: LazyArray<'r, DockConnection>,
 
     #[auto_struct(derive = dock_coordinates.len() as u32 )]
     dock_coordinate_count: u32,
@@ -227,15 +240,15 @@ pub struct Dock<'r>
     pub dock_coordinates: RoArray<'r, GenericArray<f32, U3>>,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
-#[derive(Clone, Debug)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DockConnection
 {
     pub array_index: u32,
     pub dock_index: u32,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Clone, Debug)]
 pub struct AudioGroup
 {
@@ -243,7 +256,7 @@ pub struct AudioGroup
     pub agsc: u32,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Clone, Debug)]
 pub struct AreaLayerFlags
 {
@@ -252,7 +265,7 @@ pub struct AreaLayerFlags
 }
 
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Clone, Debug)]
 struct AreaLayerNamesArgs<'r>
 {
@@ -309,14 +322,46 @@ impl<'r> AreaLayerNames<'r>
     }
 }
 
+// Layer names are written as a list of names for each area
+#[cfg(feature = "serde")]
+impl<'r> serde::Serialize for AreaLayerNames<'r>
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let names = self.0.iter()
+            .map(|area| area.iter().map(|name| name.to_string_lossy()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        names.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, 'r> serde::Deserialize<'de> for AreaLayerNames<'r>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        let names = <Vec<Vec<std::ffi::CString>>>::deserialize(deserializer)?;
+        Ok(AreaLayerNames(names.into_iter()
+            .map(|area| area.into_iter().map(CStr::Owned).collect())
+            .collect()))
+    }
+}
+
 impl<'r> Readable<'r> for AreaLayerNames<'r>
 {
     type Args = u32;
-    fn read_from(reader: &mut Reader<'r>, count: u32) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, count: u32) -> ReadResult<Self>
     {
-        let args: AreaLayerNamesArgs = reader.read(());
-        assert_eq!(args.layer_names_offsets.len(), count as usize);
-        AreaLayerNames::new(args.layer_names_offsets, args.layer_names)
+        let start = reader.clone();
+        let args: AreaLayerNamesArgs = reader.try_read(())?;
+        if args.layer_names_offsets.len() != count as usize {
+            Err(ReadError::unexpected_value(
+                &start,
+                &(count as usize),
+                &args.layer_names_offsets.len(),
+            ).in_field("AreaLayerNames.layer_names_offsets"))?
+        }
+        Ok(AreaLayerNames::new(args.layer_names_offsets, args.layer_names))
     }
 
     fn size(&self) -> usize
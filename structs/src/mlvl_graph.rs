@@ -0,0 +1,268 @@
+use reader_writer::{LazyArray, Readable};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::mlvl::{Area, Dock, DockConnection, Mlvl};
+
+// A dock is identified by the index of its area in the MLVL and its index within that area
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DockId
+{
+    pub area: usize,
+    pub dock: usize,
+}
+
+impl DockId
+{
+    pub fn new(area: usize, dock: usize) -> DockId
+    {
+        DockId { area, dock }
+    }
+}
+
+impl From<DockConnection> for DockId
+{
+    fn from(conn: DockConnection) -> DockId
+    {
+        DockId::new(conn.array_index as usize, conn.dock_index as usize)
+    }
+}
+
+impl From<DockId> for DockConnection
+{
+    fn from(id: DockId) -> DockConnection
+    {
+        DockConnection {
+            array_index: id.area as u32,
+            dock_index: id.dock as u32,
+        }
+    }
+}
+
+// A view over the areas of a MLVL and the docks connecting them. Every area and dock is parsed
+// up front so that they can be handed out by reference. Connections are kept symmetric and each
+// area's list of attached areas is kept in sync with its docks.
+pub struct MlvlGraph<'a, 'r>
+{
+    mlvl: &'a mut Mlvl<'r>,
+}
+
+fn owned<'s, 'r, T>(array: &'s LazyArray<'r, T>) -> &'s [T]
+    where T: Readable<'r>,
+          T::Args: Clone,
+{
+    match array {
+        LazyArray::Owned(ref vec) => vec,
+        LazyArray::Borrowed(_) => unreachable!(),
+    }
+}
+
+impl<'a, 'r> MlvlGraph<'a, 'r>
+{
+    pub fn new(mlvl: &'a mut Mlvl<'r>) -> MlvlGraph<'a, 'r>
+    {
+        for area in mlvl.areas.as_mut_vec().iter_mut() {
+            area.attached_areas.as_mut_vec();
+            for dock in area.docks.as_mut_vec().iter_mut() {
+                dock.connecting_docks.as_mut_vec();
+            }
+        }
+        MlvlGraph { mlvl }
+    }
+
+    pub fn mlvl(&mut self) -> &mut Mlvl<'r>
+    {
+        self.mlvl
+    }
+
+    pub fn areas(&self) -> &[Area<'r>]
+    {
+        owned(&self.mlvl.areas)
+    }
+
+    pub fn area_count(&self) -> usize
+    {
+        self.areas().len()
+    }
+
+    pub fn area_index_by_mrea(&self, mrea: u32) -> Option<usize>
+    {
+        self.areas().iter().position(|area| area.mrea == mrea)
+    }
+
+    pub fn area_index_by_internal_id(&self, internal_id: u32) -> Option<usize>
+    {
+        self.areas().iter().position(|area| area.internal_id == internal_id)
+    }
+
+    pub fn docks(&self, area: usize) -> &[Dock<'r>]
+    {
+        owned(&self.areas()[area].docks)
+    }
+
+    pub fn dock(&self, id: DockId) -> Option<&Dock<'r>>
+    {
+        self.areas().get(id.area)
+            .and_then(|area| owned(&area.docks).get(id.dock))
+    }
+
+    // The docks on the other side of the given dock. In practice there is at most one.
+    pub fn connections(&self, id: DockId) -> Vec<DockId>
+    {
+        self.dock(id)
+            .map(|dock| owned(&dock.connecting_docks).iter().map(|&c| c.into()).collect())
+            .unwrap_or_default()
+    }
+
+    // Every connection leaving the given area, as (dock in this area, dock it leads to) pairs
+    pub fn exits(&self, area: usize) -> Vec<(DockId, DockId)>
+    {
+        let mut exits = vec![];
+        for dock in 0..self.docks(area).len() {
+            let id = DockId::new(area, dock);
+            exits.extend(self.connections(id).into_iter().map(|to| (id, to)));
+        }
+        exits
+    }
+
+    // The areas directly reachable from the given area, in dock order
+    pub fn adjacent_areas(&self, area: usize) -> Vec<usize>
+    {
+        let mut areas = vec![];
+        for (_, to) in self.exits(area) {
+            if to.area != area && !areas.contains(&to.area) {
+                areas.push(to.area);
+            }
+        }
+        areas
+    }
+
+    // Every area that can be reached by walking through docks from the given area, including
+    // the area itself
+    pub fn reachable_areas(&self, from: usize) -> HashSet<usize>
+    {
+        let mut seen: HashSet<usize> = [from].iter().cloned().collect();
+        let mut queue: VecDeque<usize> = [from].iter().cloned().collect();
+        while let Some(area) = queue.pop_front() {
+            for next in self.adjacent_areas(area) {
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    // The shortest route between two areas, as the (exit dock, entrance dock) pair for each
+    // door passed through along the way
+    pub fn path(&self, from: usize, to: usize) -> Option<Vec<(DockId, DockId)>>
+    {
+        let mut came_from: HashMap<usize, (DockId, DockId)> = HashMap::new();
+        let mut queue: VecDeque<usize> = [from].iter().cloned().collect();
+        while let Some(area) = queue.pop_front() {
+            if area == to {
+                let mut path = vec![];
+                let mut area = to;
+                while area != from {
+                    let step = came_from[&area];
+                    path.push(step);
+                    area = step.0.area;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for (exit, entrance) in self.exits(area) {
+                if entrance.area != from && !came_from.contains_key(&entrance.area) {
+                    came_from.insert(entrance.area, (exit, entrance));
+                    queue.push_back(entrance.area);
+                }
+            }
+        }
+        None
+    }
+
+    fn check_dock(&self, id: DockId) -> Result<(), String>
+    {
+        if self.dock(id).is_none() {
+            Err(format!("Area {} has no dock {}", id.area, id.dock))?
+        }
+        Ok(())
+    }
+
+    fn dock_mut(&mut self, id: DockId) -> &mut Dock<'r>
+    {
+        &mut self.mlvl.areas.as_mut_vec()[id.area].docks.as_mut_vec()[id.dock]
+    }
+
+    // Rebuild an area's attached area list from its docks. Existing entries are kept in their
+    // original order so that untouched areas don't change.
+    fn update_attached_areas(&mut self, area: usize)
+    {
+        let adjacent = self.adjacent_areas(area);
+        let attached = self.mlvl.areas.as_mut_vec()[area].attached_areas.as_mut_vec();
+        attached.retain(|a| adjacent.contains(&(*a as usize)));
+        for a in adjacent {
+            if !attached.contains(&(a as u16)) {
+                attached.push(a as u16);
+            }
+        }
+    }
+
+    // Remove every connection to and from the given dock. Returns the docks it was connected to.
+    pub fn disconnect(&mut self, id: DockId) -> Result<Vec<DockId>, String>
+    {
+        self.check_dock(id)?;
+        let others = self.connections(id);
+        self.dock_mut(id).connecting_docks.as_mut_vec().clear();
+        for &other in &others {
+            self.dock_mut(other).connecting_docks.as_mut_vec()
+                .retain(|&c| DockId::from(c) != id);
+        }
+
+        self.update_attached_areas(id.area);
+        for other in &others {
+            self.update_attached_areas(other.area);
+        }
+        Ok(others)
+    }
+
+    // Connect two docks to each other, replacing whatever either of them was connected to
+    // before. Docks that lose their connection this way are left unconnected.
+    pub fn connect(&mut self, a: DockId, b: DockId) -> Result<(), String>
+    {
+        self.check_dock(a)?;
+        self.check_dock(b)?;
+        if a == b {
+            Err(format!("Dock {} of area {} can't be connected to itself", a.dock, a.area))?
+        }
+
+        self.disconnect(a)?;
+        self.disconnect(b)?;
+        self.dock_mut(a).connecting_docks.as_mut_vec().push(b.into());
+        self.dock_mut(b).connecting_docks.as_mut_vec().push(a.into());
+        self.update_attached_areas(a.area);
+        self.update_attached_areas(b.area);
+        Ok(())
+    }
+
+    // Exchange the destinations of two docks: if a leads to x and b leads to y, afterwards a
+    // leads to y and b leads to x (and vice versa), so both sides of every door stay consistent
+    pub fn swap_destinations(&mut self, a: DockId, b: DockId) -> Result<(), String>
+    {
+        self.check_dock(a)?;
+        self.check_dock(b)?;
+        let a_to = self.connections(a);
+        let b_to = self.connections(b);
+        if a_to.len() != 1 || b_to.len() != 1 {
+            Err(format!("Docks {:?} and {:?} must each have exactly one connection", a, b))?
+        }
+        let (x, y) = (a_to[0], b_to[0]);
+        if a == y || b == x {
+            // The two docks lead to each other, there's nothing to swap
+            return Ok(());
+        }
+        self.connect(a, y)?;
+        self.connect(b, x)?;
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 
 use auto_struct_macros::auto_struct;
-use reader_writer::{LCow, IteratorArray, Readable, Reader, RoArray, RoArrayIter, Writable};
+use reader_writer::{LCow, IteratorArray, Readable, Reader, ReadResult, RoArray, RoArrayIter, Writable};
 use reader_writer::typenum::*;
 use reader_writer::generic_array::GenericArray;
 
@@ -9,7 +9,7 @@ use std::io;
 use crate::scly::Scly;
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Clone, Debug)]
 pub struct Mrea<'r>
 {
@@ -62,8 +62,8 @@ impl<'r> Mrea<'r>
     {
         let section = self.sections.iter().nth(self.scly_section_idx as usize).unwrap();
         match section {
-            LCow::Owned(MreaSection::Unknown(ref reader)) => LCow::Owned(reader.clone().read(())),
-            LCow::Borrowed(MreaSection::Unknown(ref reader)) => LCow::Owned(reader.clone().read(())),
+            LCow::Owned(MreaSection::Unknown(ref data)) => LCow::Owned(read_scly(data)),
+            LCow::Borrowed(MreaSection::Unknown(ref data)) => LCow::Owned(read_scly(data)),
             LCow::Owned(MreaSection::Scly(scly)) => LCow::Owned(scly),
             LCow::Borrowed(MreaSection::Scly(scly)) => LCow::Borrowed(scly),
         }
@@ -76,19 +76,48 @@ impl<'r> Mrea<'r>
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub enum MreaSection<'r>
 {
-    Unknown(Reader<'r>),
+    Unknown(RoArray<'r, u8>),
     Scly(Scly<'r>),
 }
 
+fn read_scly<'r>(data: &RoArray<'r, u8>) -> Scly<'r>
+{
+    data.data_start()
+        .expect("Deserialized SCLY sections are always in their parsed form")
+        .read(())
+}
+
+// The SCLY section is always written in its parsed form, so that it can be edited by hand and
+// parsed again after being deserialized
+#[cfg(feature = "serde")]
+impl<'r> serde::Serialize for MreaSection<'r>
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        match self {
+            MreaSection::Unknown(data) if data.as_bytes().starts_with(b"SCLY") => {
+                let scly: Scly = data.data_start().unwrap().try_read(())
+                    .map_err(serde::ser::Error::custom)?;
+                serializer.serialize_newtype_variant("MreaSection", 1, "Scly", &scly)
+            },
+            MreaSection::Unknown(data) =>
+                serializer.serialize_newtype_variant("MreaSection", 0, "Unknown", data),
+            MreaSection::Scly(scly) =>
+                serializer.serialize_newtype_variant("MreaSection", 1, "Scly", scly),
+        }
+    }
+}
+
 impl<'r> MreaSection<'r>
 {
     // XXX A nicer/more clear name, maybe?
     pub fn convert_to_scly(&mut self) -> &mut Scly<'r>
     {
         *self = match *self {
-            MreaSection::Unknown(ref reader) => MreaSection::Scly(reader.clone().read(())),
+            MreaSection::Unknown(ref data) => MreaSection::Scly(read_scly(data)),
             MreaSection::Scly(ref mut scly) => return scly,
         };
         match *self {
@@ -101,17 +130,15 @@ impl<'r> MreaSection<'r>
 impl<'r> Readable<'r> for MreaSection<'r>
 {
     type Args = u32;
-    fn read_from(reader: &mut Reader<'r>, size: u32) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, size: u32) -> ReadResult<Self>
     {
-        let res = MreaSection::Unknown(reader.truncated(size as usize));
-        reader.advance(size as usize);
-        res
+        Ok(MreaSection::Unknown(reader.try_read((size as usize, ()))?))
     }
 
     fn size(&self) -> usize
     {
         match *self {
-            MreaSection::Unknown(ref reader) => reader.len(),
+            MreaSection::Unknown(ref data) => data.len(),
             MreaSection::Scly(ref scly) => scly.size()
         }
     }
@@ -122,10 +149,7 @@ impl<'r> Writable for MreaSection<'r>
     fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
     {
         match *self {
-            MreaSection::Unknown(ref reader) => {
-                writer.write_all(&reader)?;
-                Ok(reader.len() as u64)
-            },
+            MreaSection::Unknown(ref data) => data.write_to(writer),
             MreaSection::Scly(ref scly) => scly.write_to(writer),
         }
     }
@@ -1,6 +1,6 @@
 use auto_struct_macros::auto_struct;
 use reader_writer::{DiffList, DiffListSourceCursor, AsDiffListSourceCursor, FourCC, Readable,
-                    Reader, RoArray, Writable,
+                    Reader, ReadError, ReadResult, RoArray, Writable,
                     align_byte_count};
 
 
@@ -15,15 +15,18 @@ use crate::{
     mapw::Mapw,
     mlvl::Mlvl,
     mrea::Mrea,
+    part::Part,
     savw::Savw,
     scan::Scan,
     strg::Strg,
 };
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Clone, Debug)]
 pub struct Pak<'r>
 {
+    // Only used to find the resources while reading
+    #[cfg_attr(feature = "serde", serde(skip, default = "Reader::dummy"))]
     pub start: Reader<'r>,
     #[auto_struct(expect = 0x00030005)]
     version: u32,
@@ -64,7 +67,7 @@ pub struct Pak<'r>
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct NamedResource<'r>
 {
@@ -76,7 +79,7 @@ pub struct NamedResource<'r>
 }
 
 
-#[auto_struct(Readable, FixedSize, Writable)]
+#[auto_struct(Readable, FixedSize, Writable, Serde)]
 #[derive(Debug, Clone, Copy)]
 pub struct ResourceInfo
 {
@@ -171,6 +174,7 @@ impl<'r> DiffListSourceCursor for ResourceSourceCursor<'r>
 
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Resource<'r>
 {
     pub compressed: bool,
@@ -203,11 +207,14 @@ impl<'r> Readable<'r> for Resource<'r>
 {
     type Args = ResourceInfo;
     #[cfg(debug_assertions)]
-    fn read_from(reader: &mut Reader<'r>, info: Self::Args) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, info: Self::Args) -> ReadResult<Self>
     {
         if info.compressed > 1 {
-            panic!("Bad info.compressed")
+            Err(ReadError::invalid_value(reader, format!(
+                "Bad compression flag {} for resource {:08x}", info.compressed, info.file_id
+            )))?
         };
+        reader.check_len(info.size as usize)?;
         let res = Resource {
             compressed: info.compressed == 1,
             file_id: info.file_id,
@@ -215,18 +222,19 @@ impl<'r> Readable<'r> for Resource<'r>
             original_offset: info.offset,
         };
         reader.advance(info.size as usize);
-        res
+        Ok(res)
     }
     #[cfg(not(debug_assertions))]
-    fn read_from(reader: &mut Reader<'r>, info: Self::Args) -> Self
+    fn try_read_from(reader: &mut Reader<'r>, info: Self::Args) -> ReadResult<Self>
     {
+        reader.check_len(info.size as usize)?;
         let res = Resource {
             compressed: info.compressed == 1,
             file_id: info.file_id,
             kind: ResourceKind::Unknown(reader.truncated(info.size as usize), info.fourcc),
         };
         reader.advance(info.size as usize);
-        res
+        Ok(res)
     }
 
     fn size(&self) -> usize
@@ -244,12 +252,17 @@ impl<'r> Writable for Resource<'r>
 }
 
 macro_rules! build_resource_data {
-    ($($name:ident, $fourcc:expr, $accessor:ident, $accessor_mut:ident,)*) => {
+    ($($name:ident, $fourcc:expr, $accessor:ident, $accessor_mut:ident,
+       $try_accessor:ident, $try_accessor_mut:ident,)*) => {
 
         #[derive(Clone, Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub enum ResourceKind<'r>
         {
+            // Unparsed resources are deserialized as External ones
+            #[cfg_attr(feature = "serde", serde(skip_deserializing))]
             Unknown(Reader<'r>, FourCC),
+            #[cfg_attr(feature = "serde", serde(alias = "Unknown"))]
             External(Vec<u8>, FourCC),
             $($name($name<'r>),)*
         }
@@ -266,45 +279,69 @@ macro_rules! build_resource_data {
             }
 
             pub fn guess_kind(&mut self)
+            {
+                if let Err(e) = self.try_guess_kind() {
+                    panic!("{}", e)
+                }
+            }
+
+            pub fn try_guess_kind(&mut self) -> ReadResult<()>
             {
                 let (mut reader, fourcc) = match *self {
                     ResourceKind::Unknown(ref reader, fourcc) => (reader.clone(), fourcc),
-                    _ => return,
+                    _ => return Ok(()),
                 };
 
                 if false { }
                 $(else if fourcc == $fourcc.into() {
-                    *self = ResourceKind::$name(reader.read(()));
+                    *self = ResourceKind::$name(reader.try_read(())?);
                 })*
+                Ok(())
             }
 
             $(
                 pub fn $accessor(&self) -> Option<Cow<$name<'r>>>
+                {
+                    match self.$try_accessor() {
+                        Ok(res) => res,
+                        Err(e) => panic!("{}", e),
+                    }
+                }
+
+                pub fn $try_accessor(&self) -> ReadResult<Option<Cow<$name<'r>>>>
                 {
                     match *self {
-                        ResourceKind::$name(ref inst) => Some(Cow::Borrowed(inst)),
+                        ResourceKind::$name(ref inst) => Ok(Some(Cow::Borrowed(inst))),
                         ResourceKind::Unknown(ref reader, fourcc) => {
                             if fourcc == $fourcc.into() {
-                                Some(Cow::Owned(reader.clone().read(())))
+                                Ok(Some(Cow::Owned(reader.clone().try_read(())?)))
                             } else {
-                                None
+                                Ok(None)
                             }
                         },
-                        _ => None,
+                        _ => Ok(None),
                     }
                 }
 
                 pub fn $accessor_mut(&mut self) -> Option<&mut $name<'r>>
                 {
-                    self.guess_kind();
+                    match self.$try_accessor_mut() {
+                        Ok(res) => res,
+                        Err(e) => panic!("{}", e),
+                    }
+                }
+
+                pub fn $try_accessor_mut(&mut self) -> ReadResult<Option<&mut $name<'r>>>
+                {
+                    self.try_guess_kind()?;
                     match *self {
-                        ResourceKind::$name(ref mut inst) => Some(inst),
-                        _ => None,
+                        ResourceKind::$name(ref mut inst) => Ok(Some(inst)),
+                        _ => Ok(None),
                     }
                 }
             )*
 
-            fn size(&self) -> usize
+            pub fn size(&self) -> usize
             {
                 match *self {
                     ResourceKind::Unknown(ref data, _) => data.len(),
@@ -313,7 +350,7 @@ macro_rules! build_resource_data {
                 }
             }
 
-            fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
+            pub fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
             {
                 match *self {
                     ResourceKind::Unknown(ref data, _) => {
@@ -332,15 +369,16 @@ macro_rules! build_resource_data {
 }
 
 build_resource_data!(
-    Evnt, b"EVNT", as_evnt, as_evnt_mut,
-    Frme, b"FRME", as_frme, as_frme_mut,
-    Hint, b"HINT", as_hint, as_hint_mut,
-    Mapa, b"MAPA", as_mapa, as_mapa_mut,
-    Mapw, b"MAPW", as_mapw, as_mapw_mut,
-    Mlvl, b"MLVL", as_mlvl, as_mlvl_mut,
-    Mrea, b"MREA", as_mrea, as_mrea_mut,
-    Savw, b"SAVW", as_savw, as_savw_mut,
-    Scan, b"SCAN", as_scan, as_scan_mut,
-    Strg, b"STRG", as_strg, as_strg_mut,
+    Evnt, b"EVNT", as_evnt, as_evnt_mut, try_as_evnt, try_as_evnt_mut,
+    Frme, b"FRME", as_frme, as_frme_mut, try_as_frme, try_as_frme_mut,
+    Hint, b"HINT", as_hint, as_hint_mut, try_as_hint, try_as_hint_mut,
+    Mapa, b"MAPA", as_mapa, as_mapa_mut, try_as_mapa, try_as_mapa_mut,
+    Mapw, b"MAPW", as_mapw, as_mapw_mut, try_as_mapw, try_as_mapw_mut,
+    Mlvl, b"MLVL", as_mlvl, as_mlvl_mut, try_as_mlvl, try_as_mlvl_mut,
+    Mrea, b"MREA", as_mrea, as_mrea_mut, try_as_mrea, try_as_mrea_mut,
+    Part, b"PART", as_part, as_part_mut, try_as_part, try_as_part_mut,
+    Savw, b"SAVW", as_savw, as_savw_mut, try_as_savw, try_as_savw_mut,
+    Scan, b"SCAN", as_scan, as_scan_mut, try_as_scan, try_as_scan_mut,
+    Strg, b"STRG", as_strg, as_strg_mut, try_as_strg, try_as_strg_mut,
 );
 
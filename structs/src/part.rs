@@ -1,14 +1,678 @@
 use auto_struct_macros::auto_struct;
 
-// This is intentionally _very_ incomplete. The particle format is huge, so only some of
-// the substructures are implemented.
+use reader_writer::{FourCC, LazyArray, Readable, ReadError, ReadResult, Reader, Writable};
 
+use std::io;
 
-use reader_writer::RoArray;
+// Particle systems (PART, ELSC, SWHC, CRSC and WPSC) are stored as a list of keyword/element
+// pairs terminated by "_END". Every element starts with a FourCC naming its class, and the
+// class determines which arguments follow. Each argument is either a nested element or a
+// literal, so rather than defining a struct per class, the format is described by a grammar
+// (`property_kind` and `element_args`) and parsed into a generic tree.
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ElementKind
+{
+    Int,
+    Real,
+    Vector,
+    Color,
+    ModVector,
+    Emitter,
+    Uv,
+    Bool,
+    // A reference to another resource. The resource type is unknown for collision responses.
+    Asset(#[cfg_attr(feature = "serde", serde(with = "asset_type"))] Option<&'static [u8; 4]>),
+    SpawnSystem,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Arg
+{
+    Element(ElementKind),
+    Int,
+    Real,
+    Bool,
+    Id(Option<&'static [u8; 4]>),
+    Tag(&'static [u8; 4]),
+    Keyframes(ElementKind),
+    SpawnSystem,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParticleSystem<'r>
+{
+    // GPSM, ELSM, SWSH, CRSM or WPSM
+    pub magic: FourCC,
+    pub properties: Vec<ParticleProperty<'r>>,
+}
+
+// The name PART resources go by in `ResourceKind`
+pub type Part<'r> = ParticleSystem<'r>;
+
+// `ElementKind::Asset` refers to one of the static FourCCs used by the grammar
+#[cfg(feature = "serde")]
+mod asset_type
+{
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use reader_writer::FourCC;
+
+    const ASSET_TYPES: &[&[u8; 4]] = &[b"CMDL", b"CRSC", b"ELSC", b"PART", b"SWHC", b"TXTR"];
+
+    pub fn serialize<S: Serializer>(fourcc: &Option<&'static [u8; 4]>, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        fourcc.map(FourCC::from_bytes).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D)
+        -> Result<Option<&'static [u8; 4]>, D::Error>
+    {
+        match <Option<FourCC>>::deserialize(deserializer)? {
+            Some(fourcc) => ASSET_TYPES.iter()
+                .find(|t| FourCC::from_bytes(t) == fourcc)
+                .map(|t| Some(*t))
+                .ok_or_else(|| de::Error::custom(format!("Unknown asset type {}", fourcc))),
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParticleProperty<'r>
+{
+    pub name: FourCC,
+    pub value: ParticleElement<'r>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParticleElement<'r>
+{
+    pub kind: ElementKind,
+    pub class: FourCC,
+    pub args: Vec<ParticleValue<'r>>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParticleValue<'r>
+{
+    Element(ParticleElement<'r>),
+    Int(i32),
+    Real(f32),
+    Bool(bool),
+    Id(u32, Option<FourCC>),
+    Tag(FourCC),
+    Keyframes(Keyframes),
+    SpawnSystem(Kssm<'r>),
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Keyframes
+{
+    pub percent: u32,
+    pub unknown0: u32,
+    pub looping: bool,
+    pub unknown1: bool,
+    pub loop_end: u32,
+    pub loop_start: u32,
+    pub keys: Vec<Keyframe>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Keyframe
+{
+    Int(i32),
+    Real(f32),
+    Vector([f32; 3]),
+    Color([f32; 4]),
+}
+
+fn property_kind(magic: &[u8; 4], name: &[u8; 4]) -> Option<ElementKind>
+{
+    use self::ElementKind::*;
+    Some(match (magic, name) {
+        (b"GPSM", b"PMCL") | (b"GPSM", b"COLR") | (b"GPSM", b"LCLR") => Color,
+        (b"GPSM", b"LFOR") | (b"GPSM", b"GRTE") | (b"GPSM", b"LENG") | (b"GPSM", b"LINT") |
+        (b"GPSM", b"LSLA") | (b"GPSM", b"ROTA") | (b"GPSM", b"PSTS") | (b"GPSM", b"SIZE") |
+        (b"GPSM", b"WIDT") | (b"GPSM", b"ADV1") | (b"GPSM", b"ADV2") | (b"GPSM", b"ADV3") |
+        (b"GPSM", b"ADV4") | (b"GPSM", b"ADV5") | (b"GPSM", b"ADV6") | (b"GPSM", b"ADV7") |
+        (b"GPSM", b"ADV8") => Real,
+        (b"GPSM", b"CSSD") | (b"GPSM", b"MAXP") | (b"GPSM", b"LFOT") | (b"GPSM", b"LTME") |
+        (b"GPSM", b"LTYP") | (b"GPSM", b"NDSY") | (b"GPSM", b"MBSP") | (b"GPSM", b"NCSY") |
+        (b"GPSM", b"PISY") | (b"GPSM", b"SESD") | (b"GPSM", b"PSLT") | (b"GPSM", b"PSWT") |
+        (b"GPSM", b"SEED") | (b"GPSM", b"SISY") | (b"GPSM", b"SSSD") | (b"GPSM", b"XTAD") => Int,
+        (b"GPSM", b"ILOC") | (b"GPSM", b"IVEC") | (b"GPSM", b"LDIR") | (b"GPSM", b"LOFF") |
+        (b"GPSM", b"SEPO") | (b"GPSM", b"PMSC") | (b"GPSM", b"PMOP") | (b"GPSM", b"PMRT") |
+        (b"GPSM", b"POFS") | (b"GPSM", b"PSIV") | (b"GPSM", b"PSOV") | (b"GPSM", b"SSPO") => Vector,
+        (b"GPSM", b"PSVM") | (b"GPSM", b"VEL1") | (b"GPSM", b"VEL2") | (b"GPSM", b"VEL3") |
+        (b"GPSM", b"VEL4") => ModVector,
+        (b"GPSM", b"CIND") | (b"GPSM", b"AAPH") | (b"GPSM", b"FXLL") | (b"GPSM", b"LINE") |
+        (b"GPSM", b"LIT_") | (b"GPSM", b"MBLR") | (b"GPSM", b"OPTS") | (b"GPSM", b"PMAB") |
+        (b"GPSM", b"PMUS") | (b"GPSM", b"PMOO") | (b"GPSM", b"SORT") | (b"GPSM", b"VMD1") |
+        (b"GPSM", b"VMD2") | (b"GPSM", b"VMD3") | (b"GPSM", b"VMD4") | (b"GPSM", b"ZBUF") |
+        (b"GPSM", b"ORNT") | (b"GPSM", b"RSOP") | (b"GPSM", b"INDM") | (b"GPSM", b"VMPC") => Bool,
+        (b"GPSM", b"EMTR") => Emitter,
+        (b"GPSM", b"TEXR") | (b"GPSM", b"TIND") => Uv,
+        (b"GPSM", b"ICTS") | (b"GPSM", b"IDTS") | (b"GPSM", b"IITS") => Asset(Some(b"PART")),
+        (b"GPSM", b"PMDL") => Asset(Some(b"CMDL")),
+        (b"GPSM", b"SSWH") => Asset(Some(b"SWHC")),
+        (b"GPSM", b"SELC") => Asset(Some(b"ELSC")),
+        (b"GPSM", b"KSSM") => SpawnSystem,
+
+        (b"ELSM", b"LIFE") | (b"ELSM", b"SLIF") | (b"ELSM", b"SCNT") | (b"ELSM", b"SSEG") => Int,
+        (b"ELSM", b"GRAT") | (b"ELSM", b"AMPL") | (b"ELSM", b"AMPD") | (b"ELSM", b"LWD1") |
+        (b"ELSM", b"LWD2") | (b"ELSM", b"LWD3") => Real,
+        (b"ELSM", b"COLR") | (b"ELSM", b"LCL1") | (b"ELSM", b"LCL2") | (b"ELSM", b"LCL3") => Color,
+        (b"ELSM", b"IEMT") | (b"ELSM", b"FEMT") => Emitter,
+        (b"ELSM", b"SSWH") => Asset(Some(b"SWHC")),
+        (b"ELSM", b"GPSM") | (b"ELSM", b"EPSM") => Asset(Some(b"PART")),
+        (b"ELSM", b"ZERY") => Bool,
+
+        (b"SWSH", b"PSLT") | (b"SWSH", b"LENG") | (b"SWSH", b"SIDE") | (b"SWSH", b"SPLN") |
+        (b"SWSH", b"TSPN") => Int,
+        (b"SWSH", b"TIME") | (b"SWSH", b"LRAD") | (b"SWSH", b"RRAD") | (b"SWSH", b"IROT") |
+        (b"SWSH", b"ROTM") => Real,
+        (b"SWSH", b"COLR") => Color,
+        (b"SWSH", b"POFS") | (b"SWSH", b"IVEL") | (b"SWSH", b"NPOS") => Vector,
+        (b"SWSH", b"VELM") | (b"SWSH", b"VLM2") => ModVector,
+        (b"SWSH", b"TEXR") => Uv,
+        (b"SWSH", b"LLRD") | (b"SWSH", b"CROS") | (b"SWSH", b"VLS1") | (b"SWSH", b"VLS2") |
+        (b"SWSH", b"SROT") | (b"SWSH", b"WIRE") | (b"SWSH", b"TEXW") | (b"SWSH", b"AALP") |
+        (b"SWSH", b"ZBUF") | (b"SWSH", b"ORNT") | (b"SWSH", b"CRND") => Bool,
+
+        (b"CRSM", b"RNGE") | (b"CRSM", b"FOFF") => Real,
+        // Every other collision response keyword refers to a particle system or sound
+        (b"CRSM", _) => Asset(None),
+
+        (b"WPSM", b"IORN") | (b"WPSM", b"IVEC") | (b"WPSM", b"PSOV") | (b"WPSM", b"PSCL") |
+        (b"WPSM", b"POFS") | (b"WPSM", b"OFST") => Vector,
+        (b"WPSM", b"PSVM") => ModVector,
+        (b"WPSM", b"PCOL") => Color,
+        (b"WPSM", b"PSLT") | (b"WPSM", b"PJFX") => Int,
+        (b"WPSM", b"TRAT") | (b"WPSM", b"RNGE") | (b"WPSM", b"FOFF") => Real,
+        (b"WPSM", b"VMD2") | (b"WPSM", b"APSO") | (b"WPSM", b"HOMG") | (b"WPSM", b"AP11") |
+        (b"WPSM", b"AP21") | (b"WPSM", b"AS11") | (b"WPSM", b"AS12") | (b"WPSM", b"AS13") |
+        (b"WPSM", b"EWTR") | (b"WPSM", b"LWTR") | (b"WPSM", b"SWTR") => Bool,
+        (b"WPSM", b"APSM") | (b"WPSM", b"APS2") => Asset(Some(b"PART")),
+        (b"WPSM", b"ASW1") | (b"WPSM", b"ASW2") | (b"WPSM", b"ASW3") => Asset(Some(b"SWHC")),
+        (b"WPSM", b"OHEF") => Asset(Some(b"CMDL")),
+        (b"WPSM", b"COLR") => Asset(Some(b"CRSC")),
+
+        _ => return None,
+    })
+}
+
+fn element_args(kind: ElementKind, class: &[u8; 4]) -> Option<&'static [Arg]>
+{
+    use self::Arg::*;
+    use self::ElementKind as K;
+    const INT: Arg = Element(K::Int);
+    const REAL: Arg = Element(K::Real);
+    const VECTOR: Arg = Element(K::Vector);
+    const COLOR: Arg = Element(K::Color);
+    const MOD_VECTOR: Arg = Element(K::ModVector);
+    const BOOL: Arg = Element(K::Bool);
+
+    if class == b"NONE" {
+        return Some(&[]);
+    }
+    Some(match (kind, class) {
+        (K::Int, b"KEYE") | (K::Int, b"KEYP") => &[Keyframes(K::Int)],
+        (K::Int, b"CNST") => &[Int],
+        (K::Int, b"IMPL") | (K::Int, b"ILPT") => &[INT],
+        (K::Int, b"DETH") | (K::Int, b"ADD_") | (K::Int, b"IRND") | (K::Int, b"MULT") |
+        (K::Int, b"RAND") | (K::Int, b"MODU") | (K::Int, b"SUB_") | (K::Int, b"ITRL") => &[INT, INT],
+        (K::Int, b"CLMP") | (K::Int, b"CHAN") | (K::Int, b"SPAH") => &[INT, INT, INT],
+        (K::Int, b"PULS") => &[INT, INT, INT, INT],
+        (K::Int, b"TSCL") => &[REAL],
+        (K::Int, b"GTCP") | (K::Int, b"GAPC") | (K::Int, b"GEMT") => &[],
+
+        (K::Real, b"KEYE") | (K::Real, b"KEYP") => &[Keyframes(K::Real)],
+        (K::Real, b"CNST") => &[Real],
+        (K::Real, b"LFTW") | (K::Real, b"ADD_") | (K::Real, b"IRND") | (K::Real, b"RAND") |
+        (K::Real, b"MULT") | (K::Real, b"ISWT") | (K::Real, b"SUB_") => &[REAL, REAL],
+        (K::Real, b"CHAN") => &[REAL, REAL, INT],
+        (K::Real, b"CLMP") | (K::Real, b"SINE") => &[REAL, REAL, REAL],
+        (K::Real, b"DOTP") => &[VECTOR, VECTOR],
+        (K::Real, b"PULS") => &[INT, INT, REAL, REAL],
+        (K::Real, b"SCAL") | (K::Real, b"RLPT") => &[REAL],
+        (K::Real, b"CLTN") | (K::Real, b"CEQL") => &[REAL, REAL, REAL, REAL],
+        (K::Real, b"CRNG") => &[REAL, REAL, REAL, REAL, REAL],
+        (K::Real, b"VMAG") | (K::Real, b"VXTR") | (K::Real, b"VYTR") | (K::Real, b"VZTR") => &[VECTOR],
+        (K::Real, b"CEXT") => &[INT],
+        (K::Real, b"ITRL") => &[INT, REAL],
+        (K::Real, b"GTCR") | (K::Real, b"GTCG") | (K::Real, b"GTCB") | (K::Real, b"GTCA") => &[COLOR],
+        (K::Real, b"PAP1") | (K::Real, b"PAP2") | (K::Real, b"PAP3") | (K::Real, b"PAP4") |
+        (K::Real, b"PAP5") | (K::Real, b"PAP6") | (K::Real, b"PAP7") | (K::Real, b"PAP8") |
+        (K::Real, b"PSLL") | (K::Real, b"PRLW") => &[],
+
+        (K::Vector, b"KEYE") | (K::Vector, b"KEYP") => &[Keyframes(K::Vector)],
+        (K::Vector, b"CNST") => &[REAL, REAL, REAL],
+        (K::Vector, b"CONE") => &[VECTOR, REAL],
+        (K::Vector, b"CHAN") => &[VECTOR, VECTOR, INT],
+        (K::Vector, b"ANGC") => &[REAL, REAL, REAL, REAL, REAL],
+        (K::Vector, b"ADD_") | (K::Vector, b"MULT") | (K::Vector, b"SUB_") => &[VECTOR, VECTOR],
+        (K::Vector, b"CCLU") => &[VECTOR, VECTOR, INT, REAL],
+        (K::Vector, b"CIRC") => &[VECTOR, VECTOR, REAL, REAL, REAL],
+        (K::Vector, b"RTOV") => &[REAL],
+        (K::Vector, b"PULS") => &[INT, INT, VECTOR, VECTOR],
+        (K::Vector, b"CTVC") => &[COLOR],
+        (K::Vector, b"PVEL") | (K::Vector, b"PLCO") | (K::Vector, b"PLOC") |
+        (K::Vector, b"PSOF") | (K::Vector, b"PSOU") | (K::Vector, b"PSOR") |
+        (K::Vector, b"PSTR") => &[],
+
+        (K::Color, b"KEYE") | (K::Color, b"KEYP") => &[Keyframes(K::Color)],
+        (K::Color, b"CNST") => &[REAL, REAL, REAL, REAL],
+        (K::Color, b"CHAN") => &[COLOR, COLOR, INT],
+        (K::Color, b"CFDE") => &[COLOR, COLOR, REAL, REAL],
+        (K::Color, b"FADE") => &[COLOR, COLOR, REAL],
+        (K::Color, b"KPIN") => &[COLOR],
+        (K::Color, b"MULT") => &[COLOR, COLOR],
+        (K::Color, b"PULS") => &[INT, INT, COLOR, COLOR],
+        (K::Color, b"PCOL") => &[],
+
+        (K::ModVector, b"IMPL") | (K::ModVector, b"EMPL") | (K::ModVector, b"LMPL") =>
+            &[VECTOR, REAL, REAL, REAL, BOOL],
+        (K::ModVector, b"CHAN") => &[MOD_VECTOR, MOD_VECTOR, INT],
+        (K::ModVector, b"BNCE") => &[VECTOR, VECTOR, REAL, REAL, BOOL],
+        (K::ModVector, b"CNST") => &[REAL, REAL, REAL],
+        (K::ModVector, b"GRAV") | (K::ModVector, b"SPOS") => &[VECTOR],
+        (K::ModVector, b"EXPL") => &[REAL, REAL],
+        (K::ModVector, b"PULS") => &[INT, INT, MOD_VECTOR, MOD_VECTOR],
+        (K::ModVector, b"WIND") => &[VECTOR, REAL],
+        (K::ModVector, b"SWRL") => &[VECTOR, VECTOR, REAL, REAL],
+
+        (K::Emitter, b"SETR") => &[Tag(b"ILOC"), VECTOR, Tag(b"IVEC"), VECTOR],
+        (K::Emitter, b"SEMR") => &[VECTOR, VECTOR],
+        (K::Emitter, b"SPHE") => &[VECTOR, REAL, REAL],
+        (K::Emitter, b"ASPH") => &[VECTOR, REAL, REAL, REAL, REAL, REAL, REAL],
 
+        (K::Uv, b"CNST") => &[Element(K::Asset(Some(b"TXTR")))],
+        (K::Uv, b"ATEX") => &[Element(K::Asset(Some(b"TXTR"))), INT, INT, INT, INT, INT, BOOL],
 
-#[auto_struct(Readable)]
+        (K::Bool, b"CNST") => &[Bool],
+
+        (K::Asset(None), _) => &[Id(None)],
+        (K::Asset(Some(b"PART")), _) => &[Id(Some(b"PART"))],
+        (K::Asset(Some(b"CMDL")), _) => &[Id(Some(b"CMDL"))],
+        (K::Asset(Some(b"SWHC")), _) => &[Id(Some(b"SWHC"))],
+        (K::Asset(Some(b"ELSC")), _) => &[Id(Some(b"ELSC"))],
+        (K::Asset(Some(b"CRSC")), _) => &[Id(Some(b"CRSC"))],
+        (K::Asset(Some(b"TXTR")), _) => &[Id(Some(b"TXTR"))],
+
+        (K::SpawnSystem, b"CNST") => &[SpawnSystem],
+
+        _ => return None,
+    })
+}
+
+impl<'r> Readable<'r> for ParticleSystem<'r>
+{
+    type Args = ();
+    fn try_read_from(reader: &mut Reader<'r>, (): ()) -> ReadResult<Self>
+    {
+        let magic: FourCC = reader.try_read(())?;
+        let mut properties = vec![];
+        loop {
+            let name: FourCC = reader.try_read(())?;
+            if name == b"_END".into() {
+                break;
+            }
+            let kind = match property_kind(magic.as_bytes(), name.as_bytes()) {
+                Some(kind) => kind,
+                None => {
+                    let msg = format!("Unknown {} property {}", magic, name);
+                    Err(ReadError::invalid_value(reader, msg))?
+                },
+            };
+            let value = reader.try_read(kind)
+                .map_err(|e| e.in_field(format!("{}.{}", magic, name)))?;
+            properties.push(ParticleProperty { name, value });
+        }
+        Ok(ParticleSystem { magic, properties })
+    }
+
+    fn size(&self) -> usize
+    {
+        FourCC::fixed_size().unwrap() * (2 + self.properties.len()) +
+            self.properties.iter().map(|p| p.value.size()).sum::<usize>()
+    }
+}
+
+impl<'r> Writable for ParticleSystem<'r>
+{
+    fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
+    {
+        let mut s = self.magic.write_to(writer)?;
+        for prop in &self.properties {
+            s += prop.name.write_to(writer)?;
+            s += prop.value.write_to(writer)?;
+        }
+        s += FourCC::from_bytes(b"_END").write_to(writer)?;
+        Ok(s)
+    }
+}
+
+impl<'r> Readable<'r> for ParticleElement<'r>
+{
+    type Args = ElementKind;
+    fn try_read_from(reader: &mut Reader<'r>, kind: ElementKind) -> ReadResult<Self>
+    {
+        let class: FourCC = reader.try_read(())?;
+        let arg_kinds = match element_args(kind, class.as_bytes()) {
+            Some(arg_kinds) => arg_kinds,
+            None => {
+                let msg = format!("Unknown {:?} element {}", kind, class);
+                Err(ReadError::invalid_value(reader, msg))?
+            },
+        };
+        let mut args = Vec::with_capacity(arg_kinds.len());
+        for arg in arg_kinds {
+            args.push(match *arg {
+                Arg::Element(kind) => ParticleValue::Element(reader.try_read(kind)?),
+                Arg::Int => ParticleValue::Int(reader.try_read(())?),
+                Arg::Real => ParticleValue::Real(reader.try_read(())?),
+                Arg::Bool => ParticleValue::Bool(reader.try_read::<u8>(())? != 0),
+                Arg::Id(fourcc) => {
+                    ParticleValue::Id(reader.try_read(())?, fourcc.map(FourCC::from_bytes))
+                },
+                Arg::Tag(expected) => {
+                    let tag: FourCC = reader.try_read(())?;
+                    if tag != expected.into() {
+                        let expected = FourCC::from_bytes(expected);
+                        Err(ReadError::unexpected_value(reader, &expected, &tag))?
+                    }
+                    ParticleValue::Tag(tag)
+                },
+                Arg::Keyframes(kind) => ParticleValue::Keyframes(reader.try_read(kind)?),
+                Arg::SpawnSystem => ParticleValue::SpawnSystem(reader.try_read(())?),
+            });
+        }
+        Ok(ParticleElement { kind, class, args })
+    }
+
+    fn size(&self) -> usize
+    {
+        FourCC::fixed_size().unwrap() + self.args.iter().map(|a| a.size()).sum::<usize>()
+    }
+}
+
+impl<'r> Writable for ParticleElement<'r>
+{
+    fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
+    {
+        let mut s = self.class.write_to(writer)?;
+        for arg in &self.args {
+            s += arg.write_to(writer)?;
+        }
+        Ok(s)
+    }
+}
+
+impl<'r> ParticleValue<'r>
+{
+    fn size(&self) -> usize
+    {
+        match self {
+            ParticleValue::Element(elem) => elem.size(),
+            ParticleValue::Bool(_) => 1,
+            ParticleValue::Keyframes(keys) => keys.size(),
+            ParticleValue::SpawnSystem(kssm) => kssm.size(),
+            _ => 4,
+        }
+    }
+}
+
+impl<'r> Writable for ParticleValue<'r>
+{
+    fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
+    {
+        match self {
+            ParticleValue::Element(elem) => elem.write_to(writer),
+            ParticleValue::Int(i) => i.write_to(writer),
+            ParticleValue::Real(f) => f.write_to(writer),
+            ParticleValue::Bool(b) => (*b as u8).write_to(writer),
+            ParticleValue::Id(id, _) => id.write_to(writer),
+            ParticleValue::Tag(tag) => tag.write_to(writer),
+            ParticleValue::Keyframes(keys) => keys.write_to(writer),
+            ParticleValue::SpawnSystem(kssm) => kssm.write_to(writer),
+        }
+    }
+}
+
+impl<'r> Readable<'r> for Keyframes
+{
+    type Args = ElementKind;
+    fn try_read_from(reader: &mut Reader<'r>, kind: ElementKind) -> ReadResult<Self>
+    {
+        let percent = reader.try_read(())?;
+        let unknown0 = reader.try_read(())?;
+        let looping = reader.try_read::<u8>(())? != 0;
+        let unknown1 = reader.try_read::<u8>(())? != 0;
+        let loop_end = reader.try_read(())?;
+        let loop_start = reader.try_read(())?;
+        let key_count: u32 = reader.try_read(())?;
+        let keys = (0..key_count).map(|_| Ok(match kind {
+            ElementKind::Int => Keyframe::Int(reader.try_read(())?),
+            ElementKind::Real => Keyframe::Real(reader.try_read(())?),
+            ElementKind::Vector => Keyframe::Vector([
+                reader.try_read(())?, reader.try_read(())?, reader.try_read(())?,
+            ]),
+            ElementKind::Color => Keyframe::Color([
+                reader.try_read(())?, reader.try_read(())?, reader.try_read(())?,
+                reader.try_read(())?,
+            ]),
+            _ => {
+                let msg = format!("Invalid keyframe kind {:?}", kind);
+                Err(ReadError::invalid_value(reader, msg))?
+            },
+        })).collect::<ReadResult<_>>()?;
+        Ok(Keyframes { percent, unknown0, looping, unknown1, loop_end, loop_start, keys })
+    }
+
+    fn size(&self) -> usize
+    {
+        u32::fixed_size().unwrap() * 5 + 2 + self.keys.iter().map(|key| match key {
+            Keyframe::Int(_) | Keyframe::Real(_) => 4,
+            Keyframe::Vector(_) => 12,
+            Keyframe::Color(_) => 16,
+        }).sum::<usize>()
+    }
+}
+
+impl Writable for Keyframes
+{
+    fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
+    {
+        let mut s = self.percent.write_to(writer)?;
+        s += self.unknown0.write_to(writer)?;
+        s += (self.looping as u8).write_to(writer)?;
+        s += (self.unknown1 as u8).write_to(writer)?;
+        s += self.loop_end.write_to(writer)?;
+        s += self.loop_start.write_to(writer)?;
+        s += (self.keys.len() as u32).write_to(writer)?;
+        for key in &self.keys {
+            s += match key {
+                Keyframe::Int(i) => i.write_to(writer)?,
+                Keyframe::Real(f) => f.write_to(writer)?,
+                Keyframe::Vector(v) => v.iter().map(|f| f.write_to(writer)).sum::<io::Result<u64>>()?,
+                Keyframe::Color(c) => c.iter().map(|f| f.write_to(writer)).sum::<io::Result<u64>>()?,
+            };
+        }
+        Ok(s)
+    }
+}
+
+impl<'r> ParticleElement<'r>
+{
+    // A CNST color element with constant components
+    pub fn constant_color(color: [f32; 4]) -> ParticleElement<'r>
+    {
+        ParticleElement {
+            kind: ElementKind::Color,
+            class: b"CNST".into(),
+            args: color.iter()
+                .map(|c| ParticleValue::Element(ParticleElement {
+                    kind: ElementKind::Real,
+                    class: b"CNST".into(),
+                    args: vec![ParticleValue::Real(*c)],
+                }))
+                .collect(),
+        }
+    }
+
+    pub fn for_each_element_mut<F>(&mut self, f: &mut F)
+        where F: FnMut(&mut ParticleElement<'r>)
+    {
+        f(self);
+        for arg in self.args.iter_mut() {
+            if let ParticleValue::Element(elem) = arg {
+                elem.for_each_element_mut(f);
+            }
+        }
+    }
+
+    fn for_each_value<F>(&self, f: &mut F)
+        where F: FnMut(&ParticleValue<'r>)
+    {
+        for arg in self.args.iter() {
+            f(arg);
+            if let ParticleValue::Element(elem) = arg {
+                elem.for_each_value(f);
+            }
+        }
+    }
+
+    fn for_each_value_mut<F>(&mut self, f: &mut F)
+        where F: FnMut(&mut ParticleValue<'r>)
+    {
+        for arg in self.args.iter_mut() {
+            f(arg);
+            if let ParticleValue::Element(elem) = arg {
+                elem.for_each_value_mut(f);
+            }
+        }
+    }
+}
+
+impl<'r> ParticleSystem<'r>
+{
+    pub fn property(&self, name: &[u8; 4]) -> Option<&ParticleElement<'r>>
+    {
+        self.properties.iter()
+            .find(|prop| prop.name == name.into())
+            .map(|prop| &prop.value)
+    }
+
+    pub fn property_mut(&mut self, name: &[u8; 4]) -> Option<&mut ParticleElement<'r>>
+    {
+        self.properties.iter_mut()
+            .find(|prop| prop.name == name.into())
+            .map(|prop| &mut prop.value)
+    }
+
+    // Replace the value of a property, adding it if it isn't already present
+    pub fn set_property(&mut self, name: &[u8; 4], value: ParticleElement<'r>)
+    {
+        if let Some(prop) = self.property_mut(name) {
+            *prop = value;
+        } else {
+            self.properties.push(ParticleProperty { name: name.into(), value });
+        }
+    }
+
+    // Every resource referenced by the system whose type is known
+    pub fn dependencies(&self) -> Vec<(u32, FourCC)>
+    {
+        let mut deps = vec![];
+        for prop in &self.properties {
+            prop.value.for_each_value(&mut |value| match value {
+                ParticleValue::Id(id, Some(fourcc)) if *id != 0 && *id != 0xFFFFFFFF => {
+                    deps.push((*id, *fourcc));
+                },
+                ParticleValue::SpawnSystem(kssm) => {
+                    for list in kssm.lists.iter() {
+                        for item in list.items.iter() {
+                            deps.push((item.part, b"PART".into()));
+                        }
+                    }
+                },
+                _ => (),
+            });
+        }
+        deps.dedup();
+        deps
+    }
+
+    pub fn replace_asset(&mut self, old_id: u32, new_id: u32)
+    {
+        for prop in self.properties.iter_mut() {
+            prop.value.for_each_value_mut(&mut |value| match value {
+                ParticleValue::Id(id, _) if *id == old_id => *id = new_id,
+                ParticleValue::SpawnSystem(kssm) => {
+                    for list in kssm.lists.as_mut_vec().iter_mut() {
+                        for item in list.items.as_mut_vec().iter_mut() {
+                            if item.part == old_id {
+                                item.part = new_id;
+                            }
+                        }
+                    }
+                },
+                _ => (),
+            });
+        }
+    }
+
+    // Apply `f` to every constant color and color keyframe in the system. Colors computed at
+    // runtime (e.g. from the particle's own color) are left alone.
+    pub fn map_colors<F>(&mut self, mut f: F)
+        where F: FnMut([f32; 4]) -> [f32; 4]
+    {
+        for prop in self.properties.iter_mut() {
+            prop.value.for_each_element_mut(&mut |elem| {
+                if elem.kind != ElementKind::Color {
+                    return;
+                }
+                if elem.class == b"CNST".into() {
+                    let mut color = [0.0; 4];
+                    let mut constants = elem.args.iter().filter_map(|arg| match arg {
+                        ParticleValue::Element(ParticleElement { class, args, .. })
+                            if *class == b"CNST".into() => match args[..] {
+                                [ParticleValue::Real(f)] => Some(f),
+                                _ => None,
+                            },
+                        _ => None,
+                    });
+                    for c in color.iter_mut() {
+                        match constants.next() {
+                            Some(f) => *c = f,
+                            None => return,
+                        }
+                    }
+                    *elem = ParticleElement::constant_color(f(color));
+                } else if elem.class == b"KEYE".into() || elem.class == b"KEYP".into() {
+                    if let [ParticleValue::Keyframes(keys)] = &mut elem.args[..] {
+                        for key in keys.keys.iter_mut() {
+                            if let Keyframe::Color(color) = key {
+                                *color = f(*color);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Kssm<'r>
 {
@@ -16,22 +680,24 @@ pub struct Kssm<'r>
     pub unknown1: u32,
     pub end_frame: u32,
     pub unknown2: u32,
+    #[auto_struct(derive = lists.len() as u32)]
     pub list_count: u32,
     #[auto_struct(init = (list_count as usize, ()))]
-    pub lists: RoArray<'r, KssmFrameInfo<'r>>,
+    pub lists: LazyArray<'r, KssmFrameInfo<'r>>,
 }
 
-#[auto_struct(Readable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct KssmFrameInfo<'r>
 {
     pub frame: u32,
+    #[auto_struct(derive = items.len() as u32)]
     pub item_count: u32,
     #[auto_struct(init = (item_count as usize, ()))]
-    pub items: RoArray<'r, KssmFrameInfoItem>,
+    pub items: LazyArray<'r, KssmFrameInfoItem>,
 }
 
-#[auto_struct(Readable)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct KssmFrameInfoItem
 {
@@ -40,3 +706,47 @@ pub struct KssmFrameInfoItem
     pub unknown1: u32,
     pub unknown2: u32,
 }
+
+#[test]
+fn test_particle_round_trip()
+{
+    let mut data = vec![];
+    data.extend_from_slice(b"GPSMCOLRCNSTCNST");
+    for c in &[1.0f32, 0.5, 0.25, 1.0][..] {
+        if data.len() > 16 {
+            data.extend_from_slice(b"CNST");
+        }
+        data.extend_from_slice(&c.to_be_bytes());
+    }
+    data.extend_from_slice(b"TEXRATEXCNST");
+    data.extend_from_slice(&0x12345678u32.to_be_bytes());
+    for _ in 0..5 {
+        data.extend_from_slice(b"CNST");
+        data.extend_from_slice(&3i32.to_be_bytes());
+    }
+    data.extend_from_slice(b"CNST\x01");
+    data.extend_from_slice(b"ICTSNONEZBUFCNST\x00_END");
+
+    let mut part: ParticleSystem = Reader::new(&data[..]).read(());
+    assert_eq!(part.properties.len(), 4);
+    assert_eq!(part.size(), data.len());
+    assert_eq!(part.dependencies(), vec![(0x12345678, b"TXTR".into())]);
+
+    let mut written = vec![];
+    part.write_to(&mut written).unwrap();
+    assert_eq!(written, data);
+
+    part.replace_asset(0x12345678, 0x87654321);
+    part.map_colors(|[r, g, b, a]| [b, g, r, a]);
+    let mut written = vec![];
+    part.write_to(&mut written).unwrap();
+    let part: ParticleSystem = Reader::new(&written[..]).read(());
+    assert_eq!(part.dependencies(), vec![(0x87654321, b"TXTR".into())]);
+    match &part.property(b"COLR").unwrap().args[0] {
+        ParticleValue::Element(elem) => match elem.args[..] {
+            [ParticleValue::Real(r)] => assert_eq!(r, 0.25),
+            _ => panic!(),
+        },
+        _ => panic!(),
+    }
+}
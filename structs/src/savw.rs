@@ -1,7 +1,7 @@
 use auto_struct_macros::auto_struct;
 use reader_writer::{LazyArray, RoArray};
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Clone, Debug)]
 pub struct Savw<'r>
 {
@@ -21,17 +21,17 @@ pub struct Savw<'r>
     #[auto_struct(derive = memory_relay_array.len() as u32)]
     memory_relay_count: u32,
     #[auto_struct(init = (memory_relay_count as usize, ()))]
-    pub memory_relay_array: RoArray<'r, u32>,
+    pub memory_relay_array: LazyArray<'r, u32>,
 
     #[auto_struct(derive = layer_toggle_array.len() as u32)]
     layer_toggle_count: u32,
     #[auto_struct(init = (layer_toggle_count as usize, ()))]
-    pub layer_toggle_array: RoArray<'r, LayerToggle>,
+    pub layer_toggle_array: LazyArray<'r, LayerToggle>,
 
     #[auto_struct(derive = door_array.len() as u32)]
     door_count: u32,
     #[auto_struct(init = (door_count as usize, ()))]
-    pub door_array: RoArray<'r, u32>,
+    pub door_array: LazyArray<'r, u32>,
 
     #[auto_struct(derive = scan_array.len() as u32)]
     scan_count: u32,
@@ -42,16 +42,16 @@ pub struct Savw<'r>
     _pad: (),
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
-#[derive(Clone, Debug)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct LayerToggle
 {
     pub area_id: u32,
     pub layer_index: u32,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
-#[derive(Clone, Debug)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ScannableObject
 {
     pub scan: u32,
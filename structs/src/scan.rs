@@ -4,7 +4,7 @@ use reader_writer::typenum::*;
 use reader_writer::generic_array::GenericArray;
 use std::marker::PhantomData;
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct Scan<'r>
 {
@@ -27,7 +27,7 @@ pub struct Scan<'r>
     pub _dummy: PhantomData<&'r ()>,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct ScanImage
 {
@@ -1,6 +1,6 @@
 use auto_struct_macros::auto_struct;
 
-use reader_writer::{FourCC, LCow, RoArray, LazyArray, Readable, Reader, Writable};
+use reader_writer::{FourCC, LCow, RoArray, LazyArray, Readable, ReadError, ReadResult, Reader, Writable};
 
 use std::io;
 use std::borrow::Cow;
@@ -9,7 +9,7 @@ use std::fmt;
 use crate::scly_props;
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Scly<'r>
 {
@@ -30,7 +30,7 @@ pub struct Scly<'r>
     pub layers: LazyArray<'r, SclyLayer<'r>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct SclyLayer<'r>
 {
@@ -58,7 +58,7 @@ impl<'r> SclyLayer<'r>
     }
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct SclyObject<'r>
 {
@@ -79,15 +79,56 @@ pub struct SclyObject<'r>
     pub property_data: SclyProperty<'r>,
 }
 
+// Only accept the typed form of a property if it accounts for every byte of the object, so that a
+// definition that doesn't match the data can never change what gets written back out
+fn read_typed_property<'r, T>(object_type: u8, data: &RoArray<'r, u8>) -> ReadResult<T>
+    where T: Readable<'r, Args = ()>
+{
+    let type_name = scly_object_type_name(object_type).unwrap_or("unknown object");
+    // Properties that were deserialized were already given their typed form if they had one
+    let mut reader = match data.data_start() {
+        Some(reader) => reader,
+        None => Err(ReadError::invalid_value(
+            &Reader::dummy(),
+            format!("The data of this {} was deserialized without being parsed", type_name),
+        ))?,
+    };
+    let inst = reader.try_read(()).map_err(|e| e.in_field(type_name))?;
+    if reader.len() != 0 {
+        Err(ReadError::invalid_value(
+            &reader,
+            format!("{} bytes of this {} weren't parsed", reader.len(), type_name),
+        ))?
+    }
+    Ok(inst)
+}
+
+// The typed form of a property, for when there's no way to report an error. A definition that
+// doesn't match the data is a bug in the templates, so it's only caught in debug builds.
+fn expect_typed_property<'r, T>(object_type: u8, data: &RoArray<'r, u8>) -> Option<T>
+    where T: Readable<'r, Args = ()>
+{
+    match read_typed_property(object_type, data) {
+        Ok(inst) => Some(inst),
+        Err(e) => {
+            if cfg!(debug_assertions) {
+                panic!("Failed to parse SCLY property: {}", e);
+            }
+            None
+        },
+    }
+}
+
 macro_rules! build_scly_property {
     ($($name:ident, $is_check:ident, $accessor:ident, $accessor_mut:ident,)*) => {
 
         #[derive(Clone, Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
         pub enum SclyProperty<'r>
         {
             Unknown {
                 object_type: u8,
-                data: Reader<'r>
+                data: RoArray<'r, u8>,
             },
 
             $($name(scly_props::$name<'r>),)*
@@ -104,20 +145,39 @@ macro_rules! build_scly_property {
                 }
             }
 
-            pub fn guess_kind(&mut self)
+            pub fn object_type_name(&self) -> Option<&'static str>
+            {
+                scly_object_type_name(self.object_type())
+            }
+
+            // Parses the property if its object type has a typed definition. Fails, leaving the
+            // property as it is, if the data doesn't match that definition exactly.
+            pub fn try_guess_kind(&mut self) -> ReadResult<()>
             {
-                let (mut reader, object_type) = match *self {
+                let (data, object_type) = match *self {
                     SclyProperty::Unknown { ref data, object_type }
                         => (data.clone(), object_type),
-                    _ => return,
+                    _ => return Ok(()),
                 };
                 *self = if false {
-                    return
+                    return Ok(())
                 } $(else if object_type == <scly_props::$name as SclyPropertyData>::OBJECT_TYPE {
-                    SclyProperty::$name(reader.read(()))
+                    SclyProperty::$name(read_typed_property(object_type, &data)?)
                 })* else {
-                    return
+                    return Ok(())
                 };
+                Ok(())
+            }
+
+            // Like try_guess_kind, but a definition that doesn't match the data is only reported
+            // in debug builds
+            pub fn guess_kind(&mut self)
+            {
+                if let Err(e) = self.try_guess_kind() {
+                    if cfg!(debug_assertions) {
+                        panic!("Failed to parse SCLY property: {}", e);
+                    }
+                }
             }
 
             $(
@@ -137,7 +197,7 @@ macro_rules! build_scly_property {
                         SclyProperty::$name(ref inst) => Some(Cow::Borrowed(inst)),
                         SclyProperty::Unknown { ref data, object_type, .. } => {
                             if object_type == <scly_props::$name as SclyPropertyData>::OBJECT_TYPE {
-                                Some(Cow::Owned(data.clone().read(())))
+                                expect_typed_property(object_type, data).map(Cow::Owned)
                             } else {
                                 None
                             }
@@ -148,7 +208,7 @@ macro_rules! build_scly_property {
 
                 pub fn $accessor_mut(&mut self) -> Option<&mut scly_props::$name<'r>>
                 {
-                    let (mut data, object_type) = match *self {
+                    let (data, object_type) = match *self {
                         SclyProperty::Unknown { ref data, object_type, .. } =>
                             (data.clone(), object_type),
                         SclyProperty::$name(ref mut inst) => return Some(inst),
@@ -157,7 +217,7 @@ macro_rules! build_scly_property {
                     if object_type != <scly_props::$name as SclyPropertyData>::OBJECT_TYPE {
                         return None
                     }
-                    *self = SclyProperty::$name(data.read(()));
+                    *self = SclyProperty::$name(expect_typed_property(object_type, &data)?);
                     match *self {
                         SclyProperty::$name(ref mut inst) => return Some(inst),
                         _ => panic!(),
@@ -169,14 +229,13 @@ macro_rules! build_scly_property {
         impl<'r> Readable<'r> for SclyProperty<'r>
         {
             type Args = (u8, usize);
-            fn read_from(reader: &mut Reader<'r>, (otype, size): Self::Args) -> Self
+            fn try_read_from(reader: &mut Reader<'r>, (otype, size): Self::Args)
+                -> ReadResult<Self>
             {
-                let prop = SclyProperty::Unknown {
+                Ok(SclyProperty::Unknown {
                     object_type: otype,
-                    data: reader.truncated(size),
-                };
-                reader.advance(size);
-                prop
+                    data: reader.try_read((size, ()))?,
+                })
             }
 
             fn size(&self) -> usize
@@ -188,15 +247,43 @@ macro_rules! build_scly_property {
             }
         }
 
+        // Objects of known types are always written in their parsed form so that they can be
+        // edited by hand, regardless of whether they've been parsed yet
+        #[cfg(feature = "serde")]
+        impl<'r> serde::Serialize for SclyProperty<'r>
+        {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            {
+                use serde::ser::SerializeStructVariant;
+                let mut prop = self.clone();
+                prop.try_guess_kind().map_err(serde::ser::Error::custom)?;
+                let mut variant_index = 0;
+                if let SclyProperty::Unknown { object_type, ref data } = prop {
+                    let mut s = serializer.serialize_struct_variant(
+                        "SclyProperty", variant_index, "Unknown", 2
+                    )?;
+                    s.serialize_field("object_type", &object_type)?;
+                    s.serialize_field("data", data)?;
+                    return s.end()
+                }
+                $(
+                    variant_index += 1;
+                    if let SclyProperty::$name(ref inst) = prop {
+                        return serializer.serialize_newtype_variant(
+                            "SclyProperty", variant_index, stringify!($name), inst
+                        )
+                    }
+                )*
+                unreachable!()
+            }
+        }
+
         impl<'r> Writable for SclyProperty<'r>
         {
             fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
             {
                 match *self {
-                    SclyProperty::Unknown { ref data, .. } => {
-                        writer.write_all(&data)?;
-                        Ok(data.len() as u64)
-                    },
+                    SclyProperty::Unknown { ref data, .. } => data.write_to(writer),
                     $(SclyProperty::$name(ref i) => i.write_to(writer),)*
                 }
             }
@@ -205,29 +292,19 @@ macro_rules! build_scly_property {
     };
 }
 
-build_scly_property!(
-    Actor,             is_actor,              as_actor,              as_actor_mut,
-    DamageableTrigger, is_damageable_trigger, as_damageable_trigger, as_damageable_trigger_mut,
-    Dock,              is_dock,               as_dock,               as_dock_mut,
-    Door,              is_door,               as_door,               as_door_mut,
-    Effect,            is_effect,             as_effect,             as_effect_mut,
-    HudMemo,           is_hud_memo,           as_hud_memo,           as_hud_memo_mut,
-    MemoryRelay,       is_memory_relay,       as_memory_relay,       as_memory_relay_mut,
-    Pickup,            is_pickup,             as_pickup,             as_pickup_mut,
-    Platform,          is_platform,           as_platform,           as_platform_mut,
-    PlayerActor,       is_player_actor,       as_player_actor,       as_player_actor_mut,
-    PlayerHint,        is_player_hint,        as_player_hint,        as_player_hint_mut,
-    PointOfInterest,   is_point_of_interest,  as_point_of_interest,  as_point_of_interest_mut,
-    Relay,             is_relay,              as_relay,              as_relay_mut,
-    Sound,             is_sound,              as_sound,              as_sound_mut,
-    SpawnPoint,        is_spawn_point,        as_spawn_point,        as_spawn_point_mut,
-    SpecialFunction,   is_special_function,   as_special_function,   as_special_function_mut,
-    StreamedAudio,     is_streamed_audio,     as_streamed_audio,     as_streamed_audio_mut,
-    Timer,             is_timer,              as_timer,              as_timer_mut,
-    Trigger,           is_trigger,            as_trigger,            as_trigger_mut,
-    Water,             is_water,              as_water,              as_water_mut,
-    WorldTransporter,  is_world_transporter,  as_world_transporter,  as_world_transporter_mut,
-);
+// Every object type other than the ones in UNNAMED_OBJECT_TYPES starts with its instance name
+fn read_instance_name(object_type: u8, data: &[u8]) -> Option<&std::ffi::CStr>
+{
+    if UNNAMED_OBJECT_TYPES.contains(&object_type) {
+        return None
+    }
+    let name = data.get(4..)?;
+    let len = name.iter().position(|b| *b == 0)?;
+    std::ffi::CStr::from_bytes_with_nul(&name[..len + 1]).ok()
+}
+
+// The list of typed objects is generated from the templates in generated/templates/MP1
+generated::scly_property!();
 
 pub trait SclyPropertyData
 {
@@ -235,7 +312,7 @@ pub trait SclyPropertyData
 }
 
 
-#[auto_struct(Readable, FixedSize, Writable)]
+#[auto_struct(Readable, FixedSize, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Connection
 {
@@ -268,10 +345,10 @@ macro_rules! build_scly_conn_field {
         {
             type Args = ();
 
-            fn read_from(reader: &mut Reader<'r>, (): Self::Args) -> Self
+            fn try_read_from(reader: &mut Reader<'r>, (): Self::Args) -> ReadResult<Self>
             {
-                let i = reader.read(());
-                $struct_name(i)
+                let i = reader.try_read(())?;
+                Ok($struct_name(i))
             }
 
             fn fixed_size() -> Option<usize>
@@ -287,6 +364,50 @@ macro_rules! build_scly_conn_field {
                 self.0.write_to(writer)
             }
         }
+
+        // Known values are written by name, anything else as a number
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $struct_name
+        {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            {
+                match self.0 {
+                    $($value => serializer.serialize_str(stringify!($field)),)+
+                    n => serializer.serialize_u32(n),
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $struct_name
+        {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+            {
+                struct Visitor;
+                impl<'de> serde::de::Visitor<'de> for Visitor
+                {
+                    type Value = $struct_name;
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result
+                    {
+                        write!(f, "a {} name or number", stringify!($struct_name))
+                    }
+
+                    fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E>
+                    {
+                        match s {
+                            $(stringify!($field) => Ok($struct_name::$field),)+
+                            _ => Err(E::unknown_variant(s, &[$(stringify!($field),)+])),
+                        }
+                    }
+
+                    fn visit_u64<E: serde::de::Error>(self, n: u64) -> Result<Self::Value, E>
+                    {
+                        Ok($struct_name(n as u32))
+                    }
+                }
+                deserializer.deserialize_any(Visitor)
+            }
+        }
     };
 }
 
@@ -342,3 +463,46 @@ build_scly_conn_field!(ConnectionMsg {
     PLAY = 0x14,
     ALERT = 0x15,
 });
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_scly_json_round_trip()
+{
+    use reader_writer::CStrConversionExtension;
+
+    let mut data = vec![];
+    SclyObject {
+        instance_id: 0x0004_0001,
+        connections: vec![
+            Connection {
+                state: ConnectionState::ZERO,
+                message: ConnectionMsg::RESET_AND_START,
+                target_object_id: 0x0004_0002,
+            },
+            Connection {
+                state: ConnectionState(0x42),
+                message: ConnectionMsg::ACTIVATE,
+                target_object_id: 0x0004_0003,
+            },
+        ].into(),
+        property_data: SclyProperty::Timer(scly_props::Timer {
+            name: b"my timer\0".as_cstr(),
+            start_time: 1.5,
+            max_random_add: 0.0,
+            reset_to_zero: 0,
+            start_immediately: 1,
+            active: 1,
+        }),
+    }.write_to(&mut data).unwrap();
+
+    // Round trip through the unparsed form, which should still be written as a Timer
+    let obj: SclyObject = Reader::new(&data[..]).read(());
+    let json = serde_json::to_string(&obj).unwrap();
+    assert!(json.contains("\"Timer\"") && json.contains("\"my timer\""));
+    assert!(json.contains("\"RESET_AND_START\"") && json.contains("66"));
+
+    let obj: SclyObject = serde_json::from_str(&json).unwrap();
+    let mut written = vec![];
+    obj.write_to(&mut written).unwrap();
+    assert_eq!(data, written);
+}
@@ -0,0 +1,191 @@
+use reader_writer::LazyArray;
+
+use std::collections::HashSet;
+use std::ffi::CStr;
+
+use crate::{Connection, ConnectionMsg, ConnectionState, Scly, SclyLayer, SclyObject};
+
+// A view over the objects of a SCLY section for looking them up and following the connections
+// between them. Every layer is parsed up front so that objects can be handed out by reference.
+pub struct SclyGraph<'a, 'r>
+{
+    scly: &'a mut Scly<'r>,
+}
+
+fn layer_objects<'s, 'r>(layer: &'s SclyLayer<'r>) -> &'s [SclyObject<'r>]
+{
+    match layer.objects {
+        LazyArray::Owned(ref objects) => objects,
+        LazyArray::Borrowed(_) => unreachable!(),
+    }
+}
+
+fn matches(conn: &Connection, state: Option<ConnectionState>, message: Option<ConnectionMsg>)
+    -> bool
+{
+    state.map(|s| conn.state == s).unwrap_or(true)
+        && message.map(|m| conn.message == m).unwrap_or(true)
+}
+
+impl<'a, 'r> SclyGraph<'a, 'r>
+{
+    pub fn new(scly: &'a mut Scly<'r>) -> SclyGraph<'a, 'r>
+    {
+        for layer in scly.layers.as_mut_vec().iter_mut() {
+            layer.objects.as_mut_vec();
+        }
+        SclyGraph { scly }
+    }
+
+    pub fn scly(&mut self) -> &mut Scly<'r>
+    {
+        self.scly
+    }
+
+    fn layers(&self) -> &[SclyLayer<'r>]
+    {
+        match self.scly.layers {
+            LazyArray::Owned(ref layers) => layers,
+            LazyArray::Borrowed(_) => unreachable!(),
+        }
+    }
+
+    // Every object along with the index of the layer it's on
+    pub fn objects<'s>(&'s self) -> impl Iterator<Item = (usize, &'s SclyObject<'r>)> + 's
+    {
+        self.layers().iter()
+            .enumerate()
+            .flat_map(|(i, layer)| layer_objects(layer).iter().map(move |obj| (i, obj)))
+    }
+
+    pub fn contains(&self, instance_id: u32) -> bool
+    {
+        self.object(instance_id).is_some()
+    }
+
+    pub fn object(&self, instance_id: u32) -> Option<&SclyObject<'r>>
+    {
+        self.objects()
+            .find(|(_, obj)| obj.instance_id == instance_id)
+            .map(|(_, obj)| obj)
+    }
+
+    pub fn object_mut(&mut self, instance_id: u32) -> Option<&mut SclyObject<'r>>
+    {
+        self.scly.layers.as_mut_vec().iter_mut()
+            .flat_map(|layer| layer.objects.as_mut_vec().iter_mut())
+            .find(|obj| obj.instance_id == instance_id)
+    }
+
+    pub fn layer_of(&self, instance_id: u32) -> Option<usize>
+    {
+        self.objects()
+            .find(|(_, obj)| obj.instance_id == instance_id)
+            .map(|(layer, _)| layer)
+    }
+
+    // The IDs of every object with the given instance name. Names aren't unique, so there may be
+    // any number of them.
+    pub fn find_by_name(&self, name: &CStr) -> Vec<u32>
+    {
+        self.objects()
+            .filter(|(_, obj)| obj.property_data.name() == Some(name))
+            .map(|(_, obj)| obj.instance_id)
+            .collect()
+    }
+
+    // The connections from the given object, optionally only those with the given state and/or
+    // message
+    pub fn outgoing(
+        &self,
+        instance_id: u32,
+        state: Option<ConnectionState>,
+        message: Option<ConnectionMsg>,
+    ) -> Vec<Connection>
+    {
+        self.object(instance_id)
+            .map(|obj| {
+                obj.connections.iter()
+                    .filter(|conn| matches(conn, state, message))
+                    .map(|conn| conn.into_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // The connections to the given object, along with the ID of the object that sends each of
+    // them
+    pub fn incoming(
+        &self,
+        instance_id: u32,
+        state: Option<ConnectionState>,
+        message: Option<ConnectionMsg>,
+    ) -> Vec<(u32, Connection)>
+    {
+        let mut conns = vec![];
+        for (_, obj) in self.objects() {
+            for conn in obj.connections.iter() {
+                if conn.target_object_id == instance_id && matches(&conn, state, message) {
+                    conns.push((obj.instance_id, conn.into_owned()));
+                }
+            }
+        }
+        conns
+    }
+
+    pub fn add_connection(&mut self, instance_id: u32, conn: Connection) -> Result<(), String>
+    {
+        let obj = self.object_mut(instance_id)
+            .ok_or_else(|| format!("There is no object with ID 0x{:08X}", instance_id))?;
+        obj.connections.as_mut_vec().push(conn);
+        Ok(())
+    }
+
+    // Remove the connections from `instance_id` to `target_id` that match the filter. Returns how
+    // many were removed.
+    pub fn remove_connections(
+        &mut self,
+        instance_id: u32,
+        target_id: u32,
+        state: Option<ConnectionState>,
+        message: Option<ConnectionMsg>,
+    ) -> usize
+    {
+        let obj = match self.object_mut(instance_id) {
+            Some(obj) => obj,
+            None => return 0,
+        };
+        let conns = obj.connections.as_mut_vec();
+        let len = conns.len();
+        conns.retain(|conn| !(conn.target_object_id == target_id && matches(conn, state, message)));
+        len - conns.len()
+    }
+
+    // Delete the given objects along with every connection to them, so that nothing is left
+    // pointing at an object that no longer exists. Returns the removed objects.
+    pub fn remove_objects(&mut self, instance_ids: &HashSet<u32>) -> Vec<SclyObject<'r>>
+    {
+        let mut removed = vec![];
+        for layer in self.scly.layers.as_mut_vec().iter_mut() {
+            let objects = layer.objects.as_mut_vec();
+            let (gone, kept) = objects.drain(..)
+                .partition(|obj| instance_ids.contains(&obj.instance_id));
+            *objects = kept;
+            removed.extend::<Vec<_>>(gone);
+
+            for obj in objects.iter_mut() {
+                if obj.connections.iter().any(|c| instance_ids.contains(&c.target_object_id)) {
+                    obj.connections.as_mut_vec()
+                        .retain(|c| !instance_ids.contains(&c.target_object_id));
+                }
+            }
+        }
+        removed
+    }
+
+    pub fn remove_object(&mut self, instance_id: u32) -> Option<SclyObject<'r>>
+    {
+        let ids = [instance_id].iter().cloned().collect();
+        self.remove_objects(&ids).pop()
+    }
+}
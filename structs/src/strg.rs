@@ -5,7 +5,7 @@ use reader_writer::{
     RoArrayIter,
 };
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Strg<'r>
 {
@@ -69,7 +69,7 @@ impl<'r> ExactSizeIterator for StrgLangIter<'r>
 
 
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 struct StrgLang
 {
@@ -77,7 +77,7 @@ struct StrgLang
     pub offset: u32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct StrgStringTable<'r>
 {
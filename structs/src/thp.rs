@@ -2,7 +2,7 @@ use auto_struct_macros::auto_struct;
 
 use reader_writer::{FourCC, IteratorArray, LazyArray, Readable, RoArray, RoArrayIter};
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Thp<'r>
 {
@@ -62,7 +62,7 @@ impl<'r> Thp<'r>
     }
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct ThpComponents<'r>
 {
@@ -73,7 +73,7 @@ pub struct ThpComponents<'r>
     pub components: IteratorArray<'r, ThpComponent, RoArrayIter<'r, u8>>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct ThpComponent
 {
@@ -85,7 +85,7 @@ pub struct ThpComponent
     pub audio_info: Option<ThpAudioInfo>,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct ThpVideoInfo
 {
@@ -93,7 +93,7 @@ pub struct ThpVideoInfo
     pub height: u32,
 }
 
-#[auto_struct(Readable, Writable, FixedSize)]
+#[auto_struct(Readable, Writable, FixedSize, Serde)]
 #[derive(Debug, Clone)]
 pub struct ThpAudioInfo
 {
@@ -102,7 +102,7 @@ pub struct ThpAudioInfo
     pub samples_count: u32,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct ThpFrameData<'r>
 {
@@ -2,7 +2,7 @@ use auto_struct_macros::auto_struct;
 
 use reader_writer::{/* IteratorArray,*/ LazyArray};
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, Serde)]
 #[derive(Debug, Clone)]
 pub struct Txtr<'r>
 {
@@ -13,28 +13,232 @@ pub struct Txtr<'r>
     pub height: u16,
     pub mipmap_count: u32,
 
-    // TODO Palettes...
+    #[auto_struct(init = if is_paletted(format) { Some(()) } else { None })]
+    pub palette: Option<TxtrPalette<'r>>,
 
-    #[auto_struct(init = (format_pixel_bytes(format, (height * width) as usize), ()))]
+    // The pixel data for every mipmap, stored in GX's tiled block order
+    #[auto_struct(init = (mipmaps_byte_count(format, width, height, mipmap_count), ()))]
     pub pixel_data: LazyArray<'r, u8>,
-    // TODO: Mipmaps
+}
 
+#[auto_struct(Readable, Writable, Serde)]
+#[derive(Debug, Clone)]
+pub struct TxtrPalette<'r>
+{
+    pub format: u32,
+    pub width: u16,
+    pub height: u16,
+    #[auto_struct(init = (width as usize * height as usize, ()))]
+    pub entries: LazyArray<'r, u16>,
 }
 
-fn format_pixel_bytes(format: u32, pixels: usize) -> usize
+fn is_paletted(format: u32) -> bool
+{
+    format == 0x4 || format == 0x5 || format == 0x6
+}
+
+// The (width, height, bits per pixel) of a GX texture block
+fn format_block(format: u32) -> (usize, usize, usize)
 {
     match format {
-        0x0 => pixels / 2,
-        0x1 => pixels,
-        0x2 => pixels,
-        0x3 => pixels * 2,
-        0x4 => pixels / 2,
-        0x5 => pixels,
-        0x6 => pixels * 2,
-        0x7 => pixels * 2,
-        0x8 => pixels * 2,
-        0x9 => pixels * 4,
-        0xA => pixels / 2,
-        _ => panic!(),
+        0x0 => (8, 8, 4), // I4
+        0x1 => (8, 4, 8), // I8
+        0x2 => (8, 4, 8), // IA4
+        0x3 => (4, 4, 16), // IA8
+        0x4 => (8, 8, 4), // C4
+        0x5 => (8, 4, 8), // C8
+        0x6 => (4, 4, 16), // C14X2
+        0x7 => (4, 4, 16), // RGB565
+        0x8 => (4, 4, 16), // RGB5A3
+        0x9 => (4, 4, 32), // RGBA8
+        0xA => (8, 8, 4), // CMPR
+        _ => panic!("Invalid TXTR format {}", format),
+    }
+}
+
+fn level_byte_count(format: u32, width: usize, height: usize) -> usize
+{
+    let (block_w, block_h, bpp) = format_block(format);
+    let blocks_x = (width + block_w - 1) / block_w;
+    let blocks_y = (height + block_h - 1) / block_h;
+    blocks_x * blocks_y * block_w * block_h * bpp / 8
+}
+
+fn mipmaps_byte_count(format: u32, width: u16, height: u16, mipmap_count: u32) -> usize
+{
+    (0..mipmap_count)
+        .map(|i| {
+            let w = (width as usize >> i).max(1);
+            let h = (height as usize >> i).max(1);
+            level_byte_count(format, w, h)
+        })
+        .sum()
+}
+
+fn rgb565_to_rgb(color: u16) -> [u8; 3]
+{
+    let (r, g, b) = ((color >> 11) as u8 & 0x1F, (color >> 5) as u8 & 0x3F, color as u8 & 0x1F);
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]
+}
+
+fn rgb_to_rgb565(rgb: [u8; 3]) -> u16
+{
+    ((rgb[0] as u16 >> 3) << 11) | ((rgb[1] as u16 >> 2) << 5) | (rgb[2] as u16 >> 3)
+}
+
+// Recolor an RGB5A3 color, keeping its alpha and whether it is stored as RGB555 or RGB4A3
+fn map_rgb5a3<F: FnMut([u8; 3]) -> [u8; 3]>(color: u16, f: &mut F) -> u16
+{
+    if color & 0x8000 != 0 {
+        let channel = |shift: u16| {
+            let c = (color >> shift) as u8 & 0x1F;
+            (c << 3) | (c >> 2)
+        };
+        let rgb = f([channel(10), channel(5), channel(0)]);
+        0x8000 | ((rgb[0] as u16 >> 3) << 10) | ((rgb[1] as u16 >> 3) << 5) | (rgb[2] as u16 >> 3)
+    } else {
+        let channel = |shift: u16| ((color >> shift) as u8 & 0xF) * 0x11;
+        let rgb = f([channel(8), channel(4), channel(0)]);
+        (color & 0x7000) | ((rgb[0] as u16 >> 4) << 8) | ((rgb[1] as u16 >> 4) << 4) | (rgb[2] as u16 >> 4)
+    }
+}
+
+fn map_u16s(data: &mut [u8], mut f: impl FnMut(u16) -> u16)
+{
+    for chunk in data.chunks_exact_mut(2) {
+        let color = f(u16::from_be_bytes([chunk[0], chunk[1]]));
+        chunk.copy_from_slice(&color.to_be_bytes());
+    }
+}
+
+impl<'r> Txtr<'r>
+{
+    // The byte (and nibble, for 4 bit formats) holding texel (x, y) of the first mipmap
+    fn texel_location(&self, x: usize, y: usize) -> (usize, bool)
+    {
+        let (block_w, block_h, bpp) = format_block(self.format);
+        let blocks_x = (self.width as usize + block_w - 1) / block_w;
+        let block = (y / block_h) * blocks_x + x / block_w;
+        let texel = block * block_w * block_h + (y % block_h) * block_w + x % block_w;
+        (texel * bpp / 8, bpp == 4 && texel % 2 == 1)
+    }
+
+    fn check_texel_format(&self) -> Result<(), String>
+    {
+        match self.format {
+            0x0 | 0x1 | 0x4 | 0x5 => Ok(()),
+            format => Err(format!("TXTR format {} doesn't have single byte texels", format)),
+        }
+    }
+
+    // The texels of the first mipmap in row-major order. Only formats with at most 8 bits
+    // per texel and a single channel are supported (I4, I8, C4 and C8).
+    pub fn texels(&self) -> Result<Vec<u8>, String>
+    {
+        self.check_texel_format()?;
+        let mut texels = Vec::with_capacity(self.width as usize * self.height as usize);
+        for y in 0..self.height as usize {
+            for x in 0..self.width as usize {
+                let (offset, low_nibble) = self.texel_location(x, y);
+                let byte = self.pixel_data.get(offset).map(|b| *b).unwrap_or(0);
+                texels.push(match (format_block(self.format).2, low_nibble) {
+                    (4, false) => byte >> 4,
+                    (4, true) => byte & 0xF,
+                    _ => byte,
+                });
+            }
+        }
+        Ok(texels)
+    }
+
+    // Replace the image with the given row-major texels. Any mipmaps are dropped.
+    pub fn set_texels(&mut self, width: u16, height: u16, texels: &[u8]) -> Result<(), String>
+    {
+        self.check_texel_format()?;
+        if texels.len() != width as usize * height as usize {
+            Err(format!("Expected {} texels, got {}", width as usize * height as usize, texels.len()))?
+        }
+        self.width = width;
+        self.height = height;
+        self.mipmap_count = 1;
+        let mut data = vec![0u8; level_byte_count(self.format, width as usize, height as usize)];
+        let bpp = format_block(self.format).2;
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                let texel = texels[y * width as usize + x];
+                let (offset, low_nibble) = self.texel_location(x, y);
+                match (bpp, low_nibble) {
+                    (4, false) => data[offset] |= (texel & 0xF) << 4,
+                    (4, true) => data[offset] |= texel & 0xF,
+                    _ => data[offset] = texel,
+                }
+            }
+        }
+        self.pixel_data = data.into();
+        Ok(())
+    }
+
+    // Pass the color of every texel (or palette entry) through `f`, leaving alpha alone.
+    // Intensity formats have no color to change and are left as they are.
+    pub fn map_colors<F: FnMut([u8; 3]) -> [u8; 3]>(&mut self, mut f: F)
+    {
+        if let Some(palette) = &mut self.palette {
+            for entry in palette.entries.as_mut_vec().iter_mut() {
+                *entry = match palette.format {
+                    0x1 => rgb_to_rgb565(f(rgb565_to_rgb(*entry))),
+                    0x2 => map_rgb5a3(*entry, &mut f),
+                    _ => *entry, // IA8
+                };
+            }
+            return;
+        }
+
+        let data = self.pixel_data.as_mut_vec();
+        match self.format {
+            0x7 => map_u16s(data, |color| rgb_to_rgb565(f(rgb565_to_rgb(color)))),
+            0x8 => map_u16s(data, |color| map_rgb5a3(color, &mut f)),
+            0x9 => {
+                // Each 4x4 block holds the alpha and red of its texels, then their green and blue
+                for block in data.chunks_exact_mut(64) {
+                    for i in 0..16 {
+                        let rgb = f([block[2 * i + 1], block[32 + 2 * i], block[32 + 2 * i + 1]]);
+                        block[2 * i + 1] = rgb[0];
+                        block[32 + 2 * i] = rgb[1];
+                        block[32 + 2 * i + 1] = rgb[2];
+                    }
+                }
+            },
+            0xA => {
+                // Each 4x4 sub-block is two RGB565 endpoints followed by 2 bit indices. Which
+                // endpoint is larger decides whether the block has a transparent index, so the
+                // endpoints may need to be swapped (and the indices with them) after recoloring.
+                for block in data.chunks_exact_mut(8) {
+                    let c0 = u16::from_be_bytes([block[0], block[1]]);
+                    let c1 = u16::from_be_bytes([block[2], block[3]]);
+                    let mut new_c0 = rgb_to_rgb565(f(rgb565_to_rgb(c0)));
+                    let mut new_c1 = rgb_to_rgb565(f(rgb565_to_rgb(c1)));
+                    if c0 > c1 {
+                        if new_c0 < new_c1 {
+                            std::mem::swap(&mut new_c0, &mut new_c1);
+                            // 0 <-> 1 and 2 <-> 3
+                            block[4..].iter_mut().for_each(|b| *b ^= 0x55);
+                        } else if new_c0 == new_c1 {
+                            // Every index is the same color now, but 3 would be transparent
+                            block[4..].iter_mut().for_each(|b| *b = 0);
+                        }
+                    } else if new_c0 > new_c1 {
+                        std::mem::swap(&mut new_c0, &mut new_c1);
+                        // 0 <-> 1, 2 (the midpoint) and 3 (transparent) stay
+                        block[4..].iter_mut().for_each(|b| {
+                            let low_bits = !(*b >> 1) & 0x55;
+                            *b ^= low_bits;
+                        });
+                    }
+                    block[..2].copy_from_slice(&new_c0.to_be_bytes());
+                    block[2..4].copy_from_slice(&new_c1.to_be_bytes());
+                }
+            },
+            _ => (), // I4, I8, IA4 and IA8
+        }
     }
 }
@@ -0,0 +1,365 @@
+use reader_writer::{FourCC, Readable, Reader, Writable};
+use structs::{Anim, AnimationSet, CharacterInfo, Evnt};
+
+fn push_u32(bytes: &mut Vec<u8>, x: u32)
+{
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+fn push_f32(bytes: &mut Vec<u8>, x: f32)
+{
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+fn push_str(bytes: &mut Vec<u8>, s: &str)
+{
+    bytes.extend_from_slice(s.as_bytes());
+    bytes.push(0);
+}
+
+fn play(bytes: &mut Vec<u8>, anim: u32)
+{
+    push_u32(bytes, 0);
+    push_u32(bytes, anim);
+    push_u32(bytes, 0);
+    push_str(bytes, "prim");
+    push_f32(bytes, 1.0);
+    push_u32(bytes, 0);
+}
+
+// Three animations: a PRIM of 0x10, a blend of 0x11 and 0x12 and a sequence of a PRIM of 0x10
+// and a random choice between 0x12 and 0x13
+fn animation_set_bytes() -> Vec<u8>
+{
+    let mut bytes = vec![];
+    bytes.extend_from_slice(&4u16.to_be_bytes());
+    push_u32(&mut bytes, 3);
+
+    push_str(&mut bytes, "idle");
+    play(&mut bytes, 0x10);
+
+    push_str(&mut bytes, "walk");
+    push_u32(&mut bytes, 1);
+    play(&mut bytes, 0x11);
+    play(&mut bytes, 0x12);
+    push_f32(&mut bytes, 0.5);
+    bytes.push(0);
+
+    push_str(&mut bytes, "attack");
+    push_u32(&mut bytes, 4);
+    push_u32(&mut bytes, 2);
+    play(&mut bytes, 0x10);
+    push_u32(&mut bytes, 3);
+    push_u32(&mut bytes, 2);
+    play(&mut bytes, 0x12);
+    push_u32(&mut bytes, 50);
+    play(&mut bytes, 0x13);
+    push_u32(&mut bytes, 50);
+
+    // No transitions, the default one being NoTransition
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 3);
+    // No additive animations
+    push_u32(&mut bytes, 0);
+    push_f32(&mut bytes, 0.25);
+    push_f32(&mut bytes, 0.25);
+    // No half transitions
+    push_u32(&mut bytes, 0);
+
+    push_u32(&mut bytes, 2);
+    for &(anim, evnt) in &[(0x10, 0x20), (0x12, 0x22)] {
+        push_u32(&mut bytes, anim);
+        push_u32(&mut bytes, evnt);
+    }
+    bytes
+}
+
+fn character_info_bytes() -> Vec<u8>
+{
+    let mut bytes = vec![];
+    push_u32(&mut bytes, 0);
+    bytes.extend_from_slice(&6u16.to_be_bytes());
+    push_str(&mut bytes, "character");
+    for &id in &[0x100, 0x101, 0x102] {
+        push_u32(&mut bytes, id);
+    }
+
+    push_u32(&mut bytes, 2);
+    for &(index, name) in &[(0, "idle"), (2, "attack")] {
+        push_u32(&mut bytes, index);
+        push_str(&mut bytes, "");
+        push_str(&mut bytes, name);
+    }
+
+    bytes.extend_from_slice(b"PAS4");
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 0);
+
+    // One PART, one SWHC, one unknown that happens to share the PART's id and one ELSC
+    for &id in &[0x200, 0x201, 0x200, 0x202] {
+        push_u32(&mut bytes, 1);
+        push_u32(&mut bytes, id);
+    }
+
+    push_u32(&mut bytes, 0);
+    // No AABBs
+    push_u32(&mut bytes, 0);
+
+    push_u32(&mut bytes, 1);
+    push_str(&mut bytes, "effect");
+    push_u32(&mut bytes, 1);
+    push_str(&mut bytes, "component");
+    bytes.extend_from_slice(b"PART");
+    push_u32(&mut bytes, 0x203);
+    push_str(&mut bytes, "bone");
+    push_f32(&mut bytes, 1.0);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 0);
+
+    push_u32(&mut bytes, 0xFFFFFFFF);
+    push_u32(&mut bytes, 0xFFFFFFFF);
+    // No animation indices
+    push_u32(&mut bytes, 0);
+    bytes
+}
+
+fn event_base(bytes: &mut Vec<u8>, character_index: i32)
+{
+    bytes.extend_from_slice(&0u16.to_be_bytes());
+    push_str(bytes, "event");
+    bytes.extend_from_slice(&0u16.to_be_bytes());
+    push_f32(bytes, 0.0);
+    push_u32(bytes, 0);
+    push_u32(bytes, 0);
+    bytes.push(0);
+    push_f32(bytes, 1.0);
+    bytes.extend_from_slice(&character_index.to_be_bytes());
+    push_u32(bytes, 0);
+}
+
+// Effect events for characters -1 (all), 0 and 1 and sound events for characters 0 and 1
+fn evnt_bytes() -> Vec<u8>
+{
+    let mut bytes = vec![];
+    push_u32(&mut bytes, 2);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 0);
+
+    push_u32(&mut bytes, 3);
+    for &(character_index, id) in &[(-1, 0x300), (0, 0x301), (1, 0x300)] {
+        event_base(&mut bytes, character_index);
+        push_u32(&mut bytes, 0);
+        bytes.extend_from_slice(b"PART");
+        push_u32(&mut bytes, id);
+        push_str(&mut bytes, "bone");
+        push_u32(&mut bytes, 1);
+        push_u32(&mut bytes, 0);
+    }
+
+    push_u32(&mut bytes, 2);
+    for &(character_index, id) in &[(0, 0x400), (1, 0x401)] {
+        event_base(&mut bytes, character_index);
+        push_u32(&mut bytes, id);
+        push_f32(&mut bytes, 1.0);
+        push_f32(&mut bytes, 10.0);
+    }
+
+    while bytes.len() % 32 != 0 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+fn part(id: u32) -> (u32, FourCC)
+{
+    (id, FourCC::from_bytes(b"PART"))
+}
+
+#[test]
+fn test_animation_set_editing()
+{
+    let bytes = animation_set_bytes();
+    let mut anim_set: AnimationSet = Reader::new(&bytes).read(());
+
+    let mut written = vec![];
+    anim_set.write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+
+    assert_eq!(anim_set.anim_ids(), vec![0x10, 0x11, 0x12, 0x13]);
+    let attack = anim_set.find_animation(b"attack").unwrap();
+    let prims: Vec<_> = attack.meta.primitives().iter().map(|prim| prim.anim).collect();
+    assert_eq!(prims, vec![0x10, 0x12, 0x13]);
+    assert!(anim_set.find_animation(b"jump").is_none());
+
+    anim_set.replace_anim(0x12, 0x14, Some(0x24));
+    anim_set.replace_anim(0x10, 0x15, None);
+    assert_eq!(anim_set.anim_ids(), vec![0x15, 0x11, 0x14, 0x13]);
+    let resources: Vec<_> = anim_set.animation_resources.as_ref().unwrap().iter()
+        .map(|res| (res.anim, res.evnt))
+        .collect();
+    assert_eq!(resources, vec![(0x15, 0x20), (0x14, 0x24)]);
+
+    // The edits survive being written out
+    let mut written = vec![];
+    anim_set.write_to(&mut written).unwrap();
+    assert_eq!(written.len(), bytes.len());
+    let reread: AnimationSet = Reader::new(&written).read(());
+    assert_eq!(reread.anim_ids(), vec![0x15, 0x11, 0x14, 0x13]);
+}
+
+#[test]
+fn test_for_each_primitive_mut()
+{
+    let bytes = animation_set_bytes();
+    let anim_set: AnimationSet = Reader::new(&bytes).read(());
+    let mut walk = anim_set.find_animation(b"walk").unwrap();
+
+    let mut visited = vec![];
+    walk.meta.for_each_primitive_mut(&mut |prim| {
+        visited.push(prim.anim);
+        prim.anim += 0x100;
+    });
+    assert_eq!(visited, vec![0x11, 0x12]);
+    let prims: Vec<_> = walk.meta.primitives().iter().map(|prim| prim.anim).collect();
+    assert_eq!(prims, vec![0x111, 0x112]);
+}
+
+#[test]
+fn test_character_info_particles()
+{
+    let bytes = character_info_bytes();
+    let mut char_info: CharacterInfo = Reader::new(&bytes).read(());
+
+    let mut written = vec![];
+    char_info.write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+
+    assert_eq!(char_info.particle_dependencies(), vec![
+        part(0x200),
+        (0x201, FourCC::from_bytes(b"SWHC")),
+        (0x202, FourCC::from_bytes(b"ELSC")),
+        part(0x203),
+    ]);
+    assert_eq!(char_info.animation_name(2).unwrap().to_bytes(), b"attack");
+    assert!(char_info.animation_name(1).is_none());
+
+    // Every reference is replaced, whichever array or effect it's in
+    char_info.replace_particle(0x200, 0x210);
+    char_info.replace_particle(0x203, 0x213);
+    assert_eq!(char_info.particles.unknowns.iter().next().map(|id| *id), Some(0x210));
+    assert_eq!(char_info.particle_dependencies(), vec![
+        part(0x210),
+        (0x201, FourCC::from_bytes(b"SWHC")),
+        (0x202, FourCC::from_bytes(b"ELSC")),
+        part(0x213),
+    ]);
+}
+
+#[test]
+fn test_evnt_editing()
+{
+    let bytes = evnt_bytes();
+    let mut evnt: Evnt = Reader::new(&bytes).read(());
+
+    let mut written = vec![];
+    evnt.write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+
+    assert_eq!(evnt.particle_dependencies(), vec![part(0x300), part(0x301)]);
+    assert_eq!(evnt.sound_ids(), vec![0x400, 0x401]);
+
+    evnt.replace_effect(0x300, 0x310);
+    assert_eq!(evnt.particle_dependencies(), vec![part(0x310), part(0x301)]);
+
+    // Events for every character are kept along with those for the requested one
+    let mut retained = evnt.clone();
+    retained.retain_character(1);
+    assert_eq!(retained.particle_dependencies(), vec![part(0x310)]);
+    assert_eq!(retained.effect_events.len(), 2);
+    assert_eq!(retained.sound_ids(), vec![0x401]);
+
+    evnt.retain_effect_events(|event| event.effect_file_id != 0x310);
+    evnt.retain_sound_events(|event| event.sound_id == 0x400);
+    assert_eq!(evnt.particle_dependencies(), vec![part(0x301)]);
+    assert_eq!(evnt.sound_ids(), vec![0x400]);
+
+    let mut written = vec![];
+    evnt.write_to(&mut written).unwrap();
+    let reread: Evnt = Reader::new(&written).read(());
+    assert_eq!(reread.effect_events.len(), 1);
+    assert_eq!(reread.sound_ids(), vec![0x400]);
+}
+
+#[test]
+fn test_anim_evnt()
+{
+    let mut bytes = vec![];
+    push_u32(&mut bytes, 0);
+    push_f32(&mut bytes, 2.5);
+    push_u32(&mut bytes, 0);
+    push_f32(&mut bytes, 0.5);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 5);
+    push_u32(&mut bytes, 0);
+    for _ in 0..4 {
+        push_u32(&mut bytes, 0);
+    }
+    push_u32(&mut bytes, 0x500);
+
+    let mut anim: Anim = Reader::new(&bytes).read(());
+    assert_eq!(anim.evnt(), 0x500);
+    assert_eq!(anim.duration(), 2.5);
+
+    anim.set_evnt(0x501);
+    let mut written = vec![];
+    anim.write_to(&mut written).unwrap();
+    assert_eq!(written.len(), bytes.len());
+    assert_eq!(written[written.len() - 4..], 0x501u32.to_be_bytes());
+
+    let mut written = vec![];
+    Reader::new(&bytes).read::<Anim>(()).write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+
+    // Anything other than an uncompressed or compressed animation is rejected
+    let mut bad = bytes.clone();
+    bad[3] = 1;
+    assert!(Reader::new(&bad).try_read::<Anim>(()).is_err());
+}
+
+#[test]
+fn test_compressed_anim_round_trip()
+{
+    let mut bytes = vec![];
+    push_u32(&mut bytes, 2);
+    for &x in &[0x100, 0x600, 1] {
+        push_u32(&mut bytes, x);
+    }
+    push_f32(&mut bytes, 1.5);
+    push_f32(&mut bytes, 0.5);
+    for &x in &[0, 1, 0x8000] {
+        push_u32(&mut bytes, x);
+    }
+    push_f32(&mut bytes, 1.0);
+    // One bone channel and a 4 bit key bitmap
+    for &x in &[1, 1, 4, 0xF, 1] {
+        push_u32(&mut bytes, x);
+    }
+    // The bitstream after the header is kept as-is
+    bytes.extend_from_slice(&[0xAB; 13]);
+
+    let mut anim: Anim = Reader::new(&bytes).read(());
+    assert!(matches!(anim, Anim::Compressed(..)));
+    assert_eq!(anim.size(), bytes.len());
+    assert_eq!(anim.evnt(), 0x600);
+    assert_eq!(anim.duration(), 1.5);
+
+    let mut written = vec![];
+    anim.write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+
+    anim.set_evnt(0x601);
+    let mut written = vec![];
+    anim.write_to(&mut written).unwrap();
+    assert_eq!(written[8..12], 0x601u32.to_be_bytes());
+    assert_eq!(written[12..], bytes[12..]);
+}
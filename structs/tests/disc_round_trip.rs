@@ -0,0 +1,128 @@
+// Parses every resource on a vanilla disc and checks that writing it back out reproduces the
+// original bytes. Point RANDOMPRIME_VANILLA_ISO at an unmodified NTSC or PAL ISO to run it;
+// without it the test does nothing.
+
+use flate2::{Decompress, FlushDecompress};
+use memmap::Mmap;
+use reader_writer::{FourCC, Reader};
+use structs::{FstEntryFile, GcDisc, Resource, ResourceKind};
+
+use std::{borrow::Cow, collections::BTreeMap, env, fs::File};
+
+const ISO_ENV_VAR: &str = "RANDOMPRIME_VANILLA_ISO";
+
+fn decompress<'a>(res: &Resource<'a>, data: &Reader<'a>) -> Result<Cow<'a, [u8]>, String>
+{
+    if !res.compressed {
+        return Ok(Cow::Borrowed(&data[..]))
+    }
+    let mut reader = data.clone();
+    let size: u32 = reader.try_read(()).map_err(|e| e.to_string())?;
+    let _header: u16 = reader.try_read(()).map_err(|e| e.to_string())?;
+    let mut output = vec![0; size as usize];
+    Decompress::new(false)
+        .decompress(&reader, &mut output, FlushDecompress::Finish)
+        .map_err(|e| e.to_string())?;
+    Ok(Cow::Owned(output))
+}
+
+// Parse everything that can be parsed. Resources are read lazily, so without this most of the
+// data would just be copied through. Every object whose template doesn't match its data is
+// reported.
+fn parse_deeply(kind: &mut ResourceKind) -> Result<(), String>
+{
+    let mut errors = vec![];
+    if let ResourceKind::Mrea(mrea) = kind {
+        let scly = mrea.scly_section_mut();
+        for layer in scly.layers.as_mut_vec().iter_mut() {
+            for obj in layer.objects.as_mut_vec().iter_mut() {
+                if let Err(e) = obj.property_data.try_guess_kind() {
+                    errors.push(format!("object {:08X}: {}", obj.instance_id, e));
+                }
+            }
+        }
+    }
+    if !errors.is_empty() {
+        Err(errors.join("; "))?
+    }
+    Ok(())
+}
+
+fn round_trip(fourcc: FourCC, compressed: bool, data: &[u8]) -> Result<bool, String>
+{
+    let mut kind = ResourceKind::Unknown(Reader::new(data), fourcc);
+    kind.try_guess_kind().map_err(|e| e.to_string())?;
+    if let ResourceKind::Unknown(_, _) = kind {
+        return Ok(false)
+    }
+    parse_deeply(&mut kind)?;
+
+    let mut written = vec![];
+    kind.write_to(&mut written).map_err(|e| e.to_string())?;
+    if written.len() != kind.size() {
+        Err(format!("size() is {} but {} bytes were written", kind.size(), written.len()))?
+    }
+
+    // Uncompressed resources are padded to 32 bytes by the PAK, which the parsed form may not
+    // reproduce
+    let padding = data.len().checked_sub(written.len())
+        .ok_or_else(|| format!("wrote {} bytes, expected {}", written.len(), data.len()))?;
+    if padding >= 32 || (compressed && padding != 0) {
+        Err(format!("wrote {} bytes, expected {}", written.len(), data.len()))?
+    }
+    if let Some(i) = written.iter().zip(data.iter()).position(|(a, b)| a != b) {
+        Err(format!("first difference at offset 0x{:x}", i))?
+    }
+    Ok(true)
+}
+
+#[test]
+fn test_vanilla_disc_round_trip()
+{
+    let path = match env::var_os(ISO_ENV_VAR) {
+        Some(path) => path,
+        None => {
+            eprintln!("{} isn't set, skipping the vanilla disc round trip test", ISO_ENV_VAR);
+            return
+        },
+    };
+    let file = File::open(&path).unwrap();
+    let mmap = unsafe { Mmap::map(&file) }.unwrap();
+    let mut gc_disc: GcDisc = Reader::new(&mmap[..]).try_read(()).unwrap();
+
+    let mut checked = BTreeMap::new();
+    let mut failures = vec![];
+    for (path, entry) in gc_disc.file_system_root.dir_files_iter_mut() {
+        if !path.to_ascii_lowercase().ends_with(b".pak") {
+            continue
+        }
+        let path = String::from_utf8_lossy(&path).into_owned();
+        entry.try_guess_kind().unwrap_or_else(|e| panic!("Failed to parse {}: {}", path, e));
+        let pak = match entry.file_mut() {
+            Some(FstEntryFile::Pak(pak)) => pak,
+            _ => unreachable!(),
+        };
+
+        for res in pak.resources.iter() {
+            let data = match &res.kind {
+                ResourceKind::Unknown(data, _) => data.clone(),
+                _ => unreachable!(),
+            };
+            let result = decompress(&res, &data)
+                .and_then(|data| round_trip(res.fourcc(), res.compressed, &data));
+            match result {
+                Ok(true) => *checked.entry(res.fourcc()).or_insert(0) += 1,
+                Ok(false) => (),
+                Err(e) => failures.push(format!(
+                    "{} {:08X}.{}: {}", path, res.file_id, res.fourcc(), e
+                )),
+            }
+        }
+    }
+
+    for (fourcc, count) in &checked {
+        eprintln!("{}: {} resources round tripped", fourcc, count);
+    }
+    assert!(failures.is_empty(), "{} resources failed:\n{}", failures.len(), failures.join("\n"));
+    assert!(!checked.is_empty());
+}
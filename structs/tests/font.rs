@@ -0,0 +1,112 @@
+use reader_writer::{Readable, Reader, Writable};
+use structs::{Font, GlyphBitmap, Txtr};
+
+fn push_u32(bytes: &mut Vec<u8>, x: u32)
+{
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+fn push_f32(bytes: &mut Vec<u8>, x: f32)
+{
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+fn glyph(bytes: &mut Vec<u8>, utf16_char: u16, left_uv: f32, kerning_start_index: u32)
+{
+    bytes.extend_from_slice(&utf16_char.to_be_bytes());
+    for &uv in &[left_uv, 0.0, left_uv + 0.25, 0.5] {
+        push_f32(bytes, uv);
+    }
+    for &x in &[0, 4, 1, 4, 4, 0, kerning_start_index] {
+        push_u32(bytes, x);
+    }
+}
+
+// Two glyphs, 'A' and 'V', with a kerning pair for each order
+fn font_bytes() -> Vec<u8>
+{
+    let mut bytes = b"FONT".to_vec();
+    for &x in &[2, 0, 10, 2, 1] {
+        push_u32(&mut bytes, x);
+    }
+    bytes.extend_from_slice(&[1, 0]);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 12);
+    bytes.extend_from_slice(b"Test Font\0");
+    push_u32(&mut bytes, 0x1234);
+    push_u32(&mut bytes, 0);
+
+    push_u32(&mut bytes, 2);
+    glyph(&mut bytes, 0x41, 0.0, 0);
+    glyph(&mut bytes, 0x56, 0.5, 1);
+
+    push_u32(&mut bytes, 2);
+    for &(char1, char2, adjust) in &[(0x41u16, 0x56u16, -1i32), (0x56, 0x41, -2)] {
+        bytes.extend_from_slice(&char1.to_be_bytes());
+        bytes.extend_from_slice(&char2.to_be_bytes());
+        bytes.extend_from_slice(&adjust.to_be_bytes());
+    }
+    bytes
+}
+
+// An 8x4 C8 texture with a single entry palette
+fn txtr_bytes() -> Vec<u8>
+{
+    let mut bytes = vec![0, 0, 0, 5, 0, 8, 0, 4, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0];
+    bytes.extend(vec![0; 32]);
+    bytes
+}
+
+#[test]
+fn test_font_round_trip()
+{
+    let bytes = font_bytes();
+    let font: Font = Reader::new(&bytes).read(());
+    assert_eq!(font.size(), bytes.len());
+    assert_eq!(font.name.to_bytes(), b"Test Font");
+    assert_eq!(font.txtr, 0x1234);
+    assert_eq!(font.glyph(0x56).unwrap().left_uv_coordinate, 0.5);
+    assert!(font.glyph(0x42).is_none());
+
+    let mut written = vec![];
+    font.write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+}
+
+#[test]
+fn test_font_add_glyphs_round_trip()
+{
+    let bytes = font_bytes();
+    let mut font: Font = Reader::new(&bytes).read(());
+    let txtr_bytes = txtr_bytes();
+    let mut txtr: Txtr = Reader::new(&txtr_bytes).read(());
+
+    font.add_glyphs(&mut txtr, &[GlyphBitmap {
+        utf16_char: 0x42,
+        width: 2,
+        height: 2,
+        texels: vec![0xFF; 4],
+        left_padding: 0,
+        print_head_advance: 2,
+        right_padding: 0,
+        vertical_offset: 0,
+    }]).unwrap();
+
+    let mut written = vec![];
+    font.write_to(&mut written).unwrap();
+    assert_eq!(written.len(), bytes.len() + 46);
+    let reread: Font = Reader::new(&written).read(());
+    let chars: Vec<_> = reread.glyphs.iter().map(|glyph| glyph.utf16_char).collect();
+    assert_eq!(chars, [0x41, 0x42, 0x56]);
+    assert_eq!(reread.kernings.len(), 2);
+
+    // The existing glyphs were squashed into the top half of the taller texture
+    assert_eq!(reread.glyph(0x41).unwrap().bottom_uv_coordinate, 0.25);
+    let added = reread.glyph(0x42).unwrap();
+    assert_eq!((added.width, added.height, added.kerning_start_index), (2, 2, 1));
+
+    let mut written = vec![];
+    txtr.write_to(&mut written).unwrap();
+    let reread: Txtr = Reader::new(&written).read(());
+    assert_eq!((reread.width, reread.height), (8, 8));
+}
@@ -0,0 +1,180 @@
+use reader_writer::{CStrConversionExtension, FourCC, Reader, Writable};
+use structs::Frme;
+
+fn push_u32(bytes: &mut Vec<u8>, x: u32)
+{
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+fn push_f32s(bytes: &mut Vec<u8>, xs: &[f32])
+{
+    for x in xs {
+        bytes.extend_from_slice(&x.to_be_bytes());
+    }
+}
+
+fn widget(bytes: &mut Vec<u8>, fourcc: &[u8; 4], name: &str, parent: &str, kind: &[u8])
+{
+    bytes.extend_from_slice(fourcc);
+    for s in &[name, parent] {
+        bytes.extend_from_slice(s.as_bytes());
+        bytes.push(0);
+    }
+    bytes.extend_from_slice(&[0, 1, 1, 0]);
+    push_f32s(bytes, &[1.0; 4]);
+    push_u32(bytes, 0);
+    bytes.extend_from_slice(kind);
+    // Not a worker
+    bytes.push(0);
+    push_f32s(bytes, &[1.0, 2.0, 3.0]);
+    push_f32s(bytes, &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+    push_f32s(bytes, &[0.0; 3]);
+    push_u32(bytes, 0);
+    bytes.extend_from_slice(&[0; 2]);
+}
+
+// BASE
+// |- group
+// |  |- model (CMDL 0x10)
+// |  '- image (TXTR 0x20)
+// '- energy (TXTR 0x21)
+fn frme_bytes() -> Vec<u8>
+{
+    let mut bytes = vec![];
+    for &x in &[0, 0, 1, 0, 5] {
+        push_u32(&mut bytes, x);
+    }
+
+    widget(&mut bytes, b"BWIG", "BASE", "kGSYS_DummyWidgetID", &[]);
+    widget(&mut bytes, b"GRUP", "group", "BASE", &[0, 0, 0]);
+
+    let mut model = vec![];
+    for &x in &[0x10, 0, 1] {
+        push_u32(&mut model, x);
+    }
+    widget(&mut bytes, b"MODL", "model", "group", &model);
+
+    let mut image = vec![];
+    for &x in &[0x20, 0, 0, 1] {
+        push_u32(&mut image, x);
+    }
+    push_f32s(&mut image, &[0.0; 3]);
+    push_u32(&mut image, 0);
+    widget(&mut bytes, b"IMGP", "image", "group", &image);
+
+    widget(&mut bytes, b"ENRG", "energy", "BASE", &0x21u32.to_be_bytes());
+
+    while bytes.len() % 32 != 0 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+fn widget_names(frme: &Frme) -> Vec<String>
+{
+    frme.widgets.iter()
+        .map(|w| String::from_utf8(w.name.to_bytes().to_vec()).unwrap())
+        .collect()
+}
+
+fn model_count(frme: &Frme) -> u32
+{
+    let mut bytes = vec![];
+    frme.write_to(&mut bytes).unwrap();
+    u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]])
+}
+
+#[test]
+fn test_frme_queries()
+{
+    let bytes = frme_bytes();
+    let frme: Frme = Reader::new(&bytes).read(());
+
+    let mut written = vec![];
+    frme.write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+
+    assert_eq!(frme.widget_index(b"image"), Some(3));
+    assert_eq!(frme.widget_index(b"missing"), None);
+    assert_eq!(frme.children(b"group"), vec![b"model".to_vec(), b"image".to_vec()]);
+    assert_eq!(frme.children(b"BASE"), vec![b"group".to_vec(), b"energy".to_vec()]);
+    assert_eq!(frme.widget(b"energy").unwrap().kind.texture(), Some(0x21));
+    assert_eq!(frme.dependencies(), vec![
+        (0x10, FourCC::from_bytes(b"CMDL")),
+        (0x20, FourCC::from_bytes(b"TXTR")),
+        (0x21, FourCC::from_bytes(b"TXTR")),
+    ]);
+}
+
+#[test]
+fn test_frme_add_and_duplicate()
+{
+    let bytes = frme_bytes();
+    let mut frme: Frme = Reader::new(&bytes).read(());
+
+    let copy = frme.duplicate_widget(b"model", b"model2\0".as_cstr()).unwrap();
+    assert_eq!(copy.parent.to_bytes(), b"group");
+    assert_eq!(widget_names(&frme).last().unwrap(), "model2");
+    assert_eq!(frme.children(b"group").len(), 3);
+
+    // Names must be unique and parents must exist
+    assert!(frme.duplicate_widget(b"model", b"image\0".as_cstr()).is_err());
+    assert!(frme.duplicate_widget(b"missing", b"new\0".as_cstr()).is_err());
+    let mut orphan = frme.widget(b"image").unwrap();
+    orphan.name = b"orphan\0".as_cstr();
+    orphan.parent = b"missing\0".as_cstr();
+    assert!(frme.add_widget(orphan).is_err());
+    assert_eq!(frme.widgets.len(), 6);
+
+    // The model count follows the widgets
+    assert_eq!(model_count(&frme), 2);
+    frme.remove_widget(b"model");
+    assert_eq!(model_count(&frme), 1);
+}
+
+#[test]
+fn test_frme_remove_and_reparent()
+{
+    let bytes = frme_bytes();
+    let mut frme: Frme = Reader::new(&bytes).read(());
+
+    // A widget can't become its own descendant
+    assert!(frme.reparent_widget(b"group", b"image\0".as_cstr()).is_err());
+    assert!(frme.reparent_widget(b"group", b"missing\0".as_cstr()).is_err());
+    assert!(frme.reparent_widget(b"missing", b"BASE\0".as_cstr()).is_err());
+    assert_eq!(widget_names(&frme), vec!["BASE", "group", "model", "image", "energy"]);
+
+    // The subtree is moved to the end so that every widget still follows its parent
+    frme.reparent_widget(b"group", b"energy\0".as_cstr()).unwrap();
+    assert_eq!(widget_names(&frme), vec!["BASE", "energy", "group", "model", "image"]);
+    assert_eq!(frme.widget(b"group").unwrap().parent.to_bytes(), b"energy");
+    assert_eq!(frme.widget(b"model").unwrap().parent.to_bytes(), b"group");
+
+    let removed = frme.remove_widget(b"energy");
+    assert_eq!(removed.len(), 4);
+    assert_eq!(widget_names(&frme), vec!["BASE"]);
+    assert!(frme.remove_widget(b"missing").is_empty());
+}
+
+#[test]
+fn test_frme_edit_widgets()
+{
+    let bytes = frme_bytes();
+    let mut frme: Frme = Reader::new(&bytes).read(());
+
+    frme.translate_widget(b"image", [1.0, -2.0, 0.5]).unwrap();
+    assert!(frme.translate_widget(b"missing", [0.0; 3]).is_err());
+    assert!(frme.widget_mut(b"energy").unwrap().kind.set_texture(0x31));
+    assert!(!frme.widget_mut(b"model").unwrap().kind.set_texture(0x32));
+
+    let mut written = vec![];
+    frme.write_to(&mut written).unwrap();
+    let reread: Frme = Reader::new(&written).read(());
+    let origin: Vec<f32> = reread.widget(b"image").unwrap().origin.iter().cloned().collect();
+    assert_eq!(origin, vec![2.0, 0.0, 3.5]);
+    assert_eq!(reread.dependencies(), vec![
+        (0x10, FourCC::from_bytes(b"CMDL")),
+        (0x20, FourCC::from_bytes(b"TXTR")),
+        (0x31, FourCC::from_bytes(b"TXTR")),
+    ]);
+}
@@ -0,0 +1,39 @@
+use reader_writer::{CStrConversionExtension, Reader, Writable};
+use structs::{Hint, HintDetails, HintLocation};
+
+fn location(mrea: u32, target_room_index: u32) -> HintLocation
+{
+    HintLocation {
+        mlvl: 0x83F6FF6F,
+        mrea,
+        target_room_index,
+        map_text_strg: 0x12345678,
+    }
+}
+
+#[test]
+fn test_hint_authoring()
+{
+    let mut hint = Hint::new();
+    let mut details = HintDetails::new(b"Hint A\0".as_cstr(), 5.0, 10.0, 0xAAAA0000, 7);
+    details.add_location(location(1, 2));
+    hint.add_hint(details).unwrap();
+    hint.add_hint(HintDetails::new(b"Hint B\0".as_cstr(), 1.0, 2.0, 0xBBBB0000, 3)).unwrap();
+    assert!(hint.add_hint(HintDetails::new(b"Hint A\0".as_cstr(), 0.0, 0.0, 0, 0)).is_err());
+
+    hint.hint_mut(&b"Hint B\0".as_cstr()).unwrap().add_location(location(3, 4));
+    assert_eq!(hint.find(&b"Hint B\0".as_cstr()), Some(1));
+
+    let mut bytes = vec![];
+    hint.write_to(&mut bytes).unwrap();
+    let mut parsed: Hint = Reader::new(&bytes).try_read(()).unwrap();
+    assert_eq!(parsed.hints.len(), 2);
+    let b = parsed.hints.iter().nth(1).unwrap();
+    assert_eq!(b.popup_text_strg, 0xBBBB0000);
+    assert_eq!(b.locations.iter().map(|l| l.mrea).collect::<Vec<_>>(), vec![3]);
+
+    let removed = parsed.remove_hint(&b"Hint A\0".as_cstr()).unwrap();
+    assert_eq!(removed.locations.len(), 1);
+    assert_eq!(parsed.find(&b"Hint A\0".as_cstr()), None);
+    assert_eq!(parsed.find(&b"Hint B\0".as_cstr()), Some(0));
+}
@@ -0,0 +1,134 @@
+use reader_writer::{Reader, Writable};
+use structs::{DockId, Mlvl, MlvlGraph};
+
+fn push_u32(bytes: &mut Vec<u8>, x: u32)
+{
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+// (area, dock) <-> (area, dock)
+type Link = ((usize, usize), (usize, usize));
+
+// A minimal MLVL whose areas are connected by the given links
+fn mlvl_bytes(dock_counts: &[usize], links: &[Link]) -> Vec<u8>
+{
+    let mut bytes = vec![];
+    for &x in &[0xDEAFBABE, 0x11, 0, 0, 0, 0, dock_counts.len() as u32, 1] {
+        push_u32(&mut bytes, x);
+    }
+    for (area, &dock_count) in dock_counts.iter().enumerate() {
+        push_u32(&mut bytes, 0);
+        bytes.extend_from_slice(&[0; 18 * 4]);
+        push_u32(&mut bytes, 0x1000 + area as u32);
+        push_u32(&mut bytes, area as u32);
+
+        // Attached areas are left empty, the graph only relies on the docks
+        push_u32(&mut bytes, 0);
+        push_u32(&mut bytes, 0);
+        // No dependencies, no dependency layers
+        push_u32(&mut bytes, 0);
+        push_u32(&mut bytes, 0);
+
+        push_u32(&mut bytes, dock_count as u32);
+        for dock in 0..dock_count {
+            let conns: Vec<_> = links.iter()
+                .filter_map(|&(a, b)| {
+                    if a == (area, dock) {
+                        Some(b)
+                    } else if b == (area, dock) {
+                        Some(a)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            push_u32(&mut bytes, conns.len() as u32);
+            for (a, d) in conns {
+                push_u32(&mut bytes, a as u32);
+                push_u32(&mut bytes, d as u32);
+            }
+            push_u32(&mut bytes, 0);
+        }
+    }
+    push_u32(&mut bytes, 0);
+    bytes.push(0);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 0);
+    bytes.push(0);
+    push_u32(&mut bytes, dock_counts.len() as u32);
+    for _ in dock_counts {
+        push_u32(&mut bytes, 0);
+        bytes.extend_from_slice(&[0; 8]);
+    }
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, dock_counts.len() as u32);
+    for _ in dock_counts {
+        push_u32(&mut bytes, 0);
+    }
+    while bytes.len() % 32 != 0 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+// 0 - 1 - 2, with 3 off on its own
+fn test_bytes() -> Vec<u8>
+{
+    mlvl_bytes(&[1, 2, 1, 1], &[((0, 0), (1, 0)), ((1, 1), (2, 0))])
+}
+
+#[test]
+fn test_mlvl_graph_queries()
+{
+    let bytes = test_bytes();
+    let mut mlvl: Mlvl = Reader::new(&bytes).try_read(()).unwrap();
+    let graph = MlvlGraph::new(&mut mlvl);
+
+    assert_eq!(graph.area_count(), 4);
+    assert_eq!(graph.area_index_by_mrea(0x1002), Some(2));
+    assert_eq!(graph.connections(DockId::new(1, 1)), vec![DockId::new(2, 0)]);
+    assert_eq!(graph.adjacent_areas(1), vec![0, 2]);
+    assert_eq!(graph.reachable_areas(0), [0, 1, 2].iter().cloned().collect());
+    assert_eq!(graph.path(0, 2), Some(vec![
+        (DockId::new(0, 0), DockId::new(1, 0)),
+        (DockId::new(1, 1), DockId::new(2, 0)),
+    ]));
+    assert_eq!(graph.path(2, 2), Some(vec![]));
+    assert_eq!(graph.path(0, 3), None);
+}
+
+#[test]
+fn test_mlvl_graph_rewire()
+{
+    let bytes = test_bytes();
+    let mut mlvl: Mlvl = Reader::new(&bytes).try_read(()).unwrap();
+    let mut graph = MlvlGraph::new(&mut mlvl);
+
+    // 1 -> 2 becomes 1 -> 3, and 2 is cut off
+    graph.connect(DockId::new(1, 1), DockId::new(3, 0)).unwrap();
+    assert_eq!(graph.connections(DockId::new(3, 0)), vec![DockId::new(1, 1)]);
+    assert!(graph.connections(DockId::new(2, 0)).is_empty());
+    assert_eq!(graph.path(0, 2), None);
+    assert_eq!(graph.path(0, 3).unwrap().len(), 2);
+
+    // 0 -> 1 and 1 -> 3 become 0 -> 3 and 1 -> 1
+    graph.swap_destinations(DockId::new(0, 0), DockId::new(1, 1)).unwrap();
+    assert_eq!(graph.connections(DockId::new(0, 0)), vec![DockId::new(3, 0)]);
+    assert_eq!(graph.connections(DockId::new(1, 0)), vec![DockId::new(1, 1)]);
+    assert_eq!(graph.adjacent_areas(0), vec![3]);
+    assert!(graph.adjacent_areas(1).is_empty());
+
+    assert!(graph.connect(DockId::new(0, 0), DockId::new(0, 0)).is_err());
+    assert!(graph.connect(DockId::new(0, 1), DockId::new(3, 0)).is_err());
+
+    // The edits survive being written out, including the attached area lists
+    let mut written = vec![];
+    mlvl.write_to(&mut written).unwrap();
+    let mut mlvl: Mlvl = Reader::new(&written).try_read(()).unwrap();
+    let attached: Vec<Vec<u16>> = mlvl.areas.iter()
+        .map(|area| area.attached_areas.iter().map(|a| *a).collect())
+        .collect();
+    assert_eq!(attached, vec![vec![3], vec![], vec![], vec![0]]);
+    let graph = MlvlGraph::new(&mut mlvl);
+    assert_eq!(graph.connections(DockId::new(3, 0)), vec![DockId::new(0, 0)]);
+}
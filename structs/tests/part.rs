@@ -0,0 +1,194 @@
+use reader_writer::{FourCC, Reader};
+use structs::{ParticleValue, ResourceKind};
+
+fn push_tag(bytes: &mut Vec<u8>, tag: &[u8; 4])
+{
+    bytes.extend_from_slice(tag);
+}
+
+fn push_u32(bytes: &mut Vec<u8>, x: u32)
+{
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+fn push_real(bytes: &mut Vec<u8>, x: f32)
+{
+    push_tag(bytes, b"CNST");
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+fn push_int(bytes: &mut Vec<u8>, x: i32)
+{
+    push_tag(bytes, b"CNST");
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+fn push_vector(bytes: &mut Vec<u8>, v: [f32; 3])
+{
+    push_tag(bytes, b"CNST");
+    for x in &v {
+        push_real(bytes, *x);
+    }
+}
+
+// Laid out like the pickup sparkle effects on the disc: an emitter, a color fade, a textured
+// flipbook, a child system spawned on death and a spawn table for a second child.
+fn sparkle_bytes() -> Vec<u8>
+{
+    let mut bytes = vec![];
+    push_tag(&mut bytes, b"GPSM");
+
+    push_tag(&mut bytes, b"PSLT");
+    push_int(&mut bytes, 60);
+    push_tag(&mut bytes, b"MAXP");
+    push_int(&mut bytes, 24);
+    push_tag(&mut bytes, b"GRTE");
+    push_real(&mut bytes, 0.5);
+    push_tag(&mut bytes, b"LTME");
+    push_tag(&mut bytes, b"RAND");
+    push_int(&mut bytes, 20);
+    push_int(&mut bytes, 30);
+
+    push_tag(&mut bytes, b"EMTR");
+    push_tag(&mut bytes, b"SETR");
+    push_tag(&mut bytes, b"ILOC");
+    push_vector(&mut bytes, [0.0, 0.0, 0.5]);
+    push_tag(&mut bytes, b"IVEC");
+    push_tag(&mut bytes, b"CONE");
+    push_vector(&mut bytes, [0.0, 0.0, 1.0]);
+    push_real(&mut bytes, 0.25);
+
+    push_tag(&mut bytes, b"VEL1");
+    push_tag(&mut bytes, b"GRAV");
+    push_vector(&mut bytes, [0.0, 0.0, -0.01]);
+
+    push_tag(&mut bytes, b"COLR");
+    push_tag(&mut bytes, b"KEYP");
+    for &x in &[0, 0] {
+        push_u32(&mut bytes, x);
+    }
+    bytes.extend_from_slice(&[0, 0]);
+    for &x in &[0, 0, 2] {
+        push_u32(&mut bytes, x);
+    }
+    for c in &[[1.0f32, 0.75, 0.25, 1.0], [1.0, 0.5, 0.0, 0.0]] {
+        for x in c {
+            bytes.extend_from_slice(&x.to_be_bytes());
+        }
+    }
+
+    push_tag(&mut bytes, b"SIZE");
+    push_tag(&mut bytes, b"SINE");
+    push_real(&mut bytes, 0.2);
+    push_real(&mut bytes, 0.1);
+    push_real(&mut bytes, 0.0);
+
+    push_tag(&mut bytes, b"TEXR");
+    push_tag(&mut bytes, b"ATEX");
+    push_tag(&mut bytes, b"CNST");
+    push_u32(&mut bytes, 0x0BAD_F00D);
+    for &x in &[8, 8, 64, 2, 0] {
+        push_int(&mut bytes, x);
+    }
+    push_tag(&mut bytes, b"CNST");
+    bytes.push(1);
+
+    push_tag(&mut bytes, b"ICTS");
+    push_tag(&mut bytes, b"CNST");
+    push_u32(&mut bytes, 0x1111_2222);
+    push_tag(&mut bytes, b"IDTS");
+    push_tag(&mut bytes, b"NONE");
+
+    push_tag(&mut bytes, b"KSSM");
+    push_tag(&mut bytes, b"CNST");
+    for &x in &[0, 0, 30, 0] {
+        push_u32(&mut bytes, x);
+    }
+    push_u32(&mut bytes, 1);
+    push_u32(&mut bytes, 15);
+    push_u32(&mut bytes, 1);
+    for &x in &[0x3333_4444, 0, 0, 0] {
+        push_u32(&mut bytes, x);
+    }
+
+    for tag in &[b"LIT_", b"ZBUF"] {
+        push_tag(&mut bytes, tag);
+        push_tag(&mut bytes, b"CNST");
+        bytes.push(0);
+    }
+    push_tag(&mut bytes, b"_END");
+    bytes
+}
+
+fn parse(bytes: &[u8]) -> ResourceKind<'_>
+{
+    let mut kind = ResourceKind::Unknown(Reader::new(bytes), b"PART".into());
+    kind.try_guess_kind().unwrap();
+    kind
+}
+
+#[test]
+fn test_part_round_trip()
+{
+    let bytes = sparkle_bytes();
+    let mut kind = parse(&bytes);
+    let part = kind.as_part_mut().unwrap();
+    assert_eq!(part.magic, b"GPSM".into());
+    assert_eq!(part.properties.len(), 14);
+    assert_eq!(part.dependencies(), vec![
+        (0x0BAD_F00D, FourCC::from_bytes(b"TXTR")),
+        (0x1111_2222, FourCC::from_bytes(b"PART")),
+        (0x3333_4444, FourCC::from_bytes(b"PART")),
+    ]);
+    match &part.property(b"COLR").unwrap().args[..] {
+        [ParticleValue::Keyframes(keys)] => assert_eq!(keys.keys.len(), 2),
+        _ => panic!(),
+    }
+
+    assert_eq!(kind.size(), bytes.len());
+    let mut written = vec![];
+    kind.write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+}
+
+#[test]
+fn test_part_replace_asset()
+{
+    let bytes = sparkle_bytes();
+    let mut kind = parse(&bytes);
+    let part = kind.as_part_mut().unwrap();
+    part.replace_asset(0x1111_2222, 0x5555_6666);
+    part.replace_asset(0x3333_4444, 0x7777_8888);
+
+    let mut written = vec![];
+    kind.write_to(&mut written).unwrap();
+    assert_eq!(written.len(), bytes.len());
+    let reparsed = parse(&written);
+    assert_eq!(reparsed.as_part().unwrap().dependencies(), vec![
+        (0x0BAD_F00D, FourCC::from_bytes(b"TXTR")),
+        (0x5555_6666, FourCC::from_bytes(b"PART")),
+        (0x7777_8888, FourCC::from_bytes(b"PART")),
+    ]);
+}
+
+#[test]
+fn test_part_rejects_bad_data()
+{
+    let check = |bytes: &[u8]| {
+        let mut kind = ResourceKind::Unknown(Reader::new(bytes), b"PART".into());
+        kind.try_guess_kind().unwrap_err().to_string()
+    };
+
+    let mut bytes = sparkle_bytes();
+    let i = bytes.windows(4).position(|w| w == b"MAXP").unwrap();
+    bytes[i..i + 4].copy_from_slice(b"XXXX");
+    assert!(check(&bytes).contains("Unknown GPSM property XXXX"));
+
+    let mut bytes = sparkle_bytes();
+    let i = bytes.windows(4).position(|w| w == b"IVEC").unwrap();
+    bytes[i..i + 4].copy_from_slice(b"IVEX");
+    assert!(check(&bytes).contains("IVEX"));
+
+    let bytes = sparkle_bytes();
+    check(&bytes[..bytes.len() - 4]);
+}
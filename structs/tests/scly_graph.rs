@@ -0,0 +1,97 @@
+use reader_writer::CStrConversionExtension;
+use structs::{
+    Connection, ConnectionMsg, ConnectionState, Relay, Scly, SclyGraph, SclyLayer, SclyObject,
+    SclyProperty, Timer,
+};
+
+fn conn(state: ConnectionState, message: ConnectionMsg, target_object_id: u32) -> Connection
+{
+    Connection { state, message, target_object_id }
+}
+
+fn relay<'r>(instance_id: u32, name: &'r [u8], connections: Vec<Connection>) -> SclyObject<'r>
+{
+    SclyObject {
+        instance_id,
+        connections: connections.into(),
+        property_data: SclyProperty::Relay(Relay {
+            name: name.as_cstr(),
+            active: 1,
+        }),
+    }
+}
+
+fn test_scly<'r>() -> Scly<'r>
+{
+    let timer = SclyObject {
+        instance_id: 1,
+        connections: vec![
+            conn(ConnectionState::ZERO, ConnectionMsg::SET_TO_ZERO, 2),
+            conn(ConnectionState::ZERO, ConnectionMsg::DEACTIVATE, 3),
+        ].into(),
+        property_data: SclyProperty::Timer(Timer {
+            name: b"timer\0".as_cstr(),
+            start_time: 1.0,
+            max_random_add: 0.0,
+            reset_to_zero: 0,
+            start_immediately: 1,
+            active: 1,
+        }),
+    };
+    let mut layer0 = SclyLayer::new();
+    layer0.objects = vec![
+        timer,
+        relay(2, b"relay\0", vec![conn(ConnectionState::ZERO, ConnectionMsg::ACTIVATE, 3)]),
+    ].into();
+    let mut layer1 = SclyLayer::new();
+    layer1.objects = vec![relay(3, b"relay\0", vec![])].into();
+    Scly {
+        unknown: 1,
+        layers: vec![layer0, layer1].into(),
+    }
+}
+
+#[test]
+fn test_scly_graph_queries()
+{
+    let mut scly = test_scly();
+    let graph = SclyGraph::new(&mut scly);
+
+    assert!(graph.contains(3));
+    assert!(!graph.contains(4));
+    assert_eq!(graph.layer_of(3), Some(1));
+    assert_eq!(graph.find_by_name(&b"relay\0".as_cstr()), vec![2, 3]);
+    assert_eq!(graph.find_by_name(&b"timer\0".as_cstr()), vec![1]);
+
+    assert_eq!(graph.outgoing(1, None, None).len(), 2);
+    let conns = graph.outgoing(1, None, Some(ConnectionMsg::DEACTIVATE));
+    assert_eq!(conns.len(), 1);
+    assert_eq!(conns[0].target_object_id, 3);
+
+    let senders: Vec<_> = graph.incoming(3, Some(ConnectionState::ZERO), None)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(senders, vec![1, 2]);
+    assert!(graph.incoming(3, Some(ConnectionState::ACTIVE), None).is_empty());
+}
+
+#[test]
+fn test_scly_graph_remove_object()
+{
+    let mut scly = test_scly();
+    let mut graph = SclyGraph::new(&mut scly);
+
+    let removed = graph.remove_object(3).unwrap();
+    assert_eq!(removed.instance_id, 3);
+    assert!(!graph.contains(3));
+    assert!(graph.objects().all(|(_, obj)| {
+        obj.connections.iter().all(|c| c.target_object_id != 3)
+    }));
+    assert_eq!(graph.outgoing(1, None, None).len(), 1);
+    assert!(graph.remove_object(3).is_none());
+
+    assert_eq!(graph.remove_connections(1, 2, None, Some(ConnectionMsg::ACTIVATE)), 0);
+    assert_eq!(graph.remove_connections(1, 2, None, None), 1);
+    assert!(graph.incoming(2, None, None).is_empty());
+}
@@ -0,0 +1,329 @@
+// Property and fuzz tests that don't need a disc. Randomly generated resources must survive a
+// write/read/write cycle unchanged, and mangled versions of them must never make the parser
+// panic.
+
+use reader_writer::{CStrConversionExtension, FourCC, LazyUtf16beStr, Reader, Readable, Writable};
+use structs::{
+    Connection, ConnectionMsg, ConnectionState, Counter, MemoryRelay, Relay, ResourceKind, Scly,
+    SclyLayer, SclyObject, SclyProperty, Strg, StrgStringTable, Switch, Timer,
+};
+
+use std::{ffi::CString, panic};
+
+const ITERATIONS: usize = 200;
+
+// xorshift64*, so that failures are reproducible without pulling in an RNG crate
+struct Rng(u64);
+
+impl Rng
+{
+    fn next(&mut self) -> u64
+    {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize
+    {
+        (self.next() % n as u64) as usize
+    }
+
+    fn byte(&mut self) -> u8
+    {
+        self.next() as u8
+    }
+
+    fn f32(&mut self) -> f32
+    {
+        (self.next() % 10_000) as f32 / 16.0
+    }
+
+    fn name(&mut self) -> CString
+    {
+        let len = self.below(24);
+        CString::new((0..len).map(|_| b'a' + self.below(26) as u8).collect::<Vec<_>>()).unwrap()
+    }
+
+    fn text(&mut self) -> String
+    {
+        let len = self.below(40);
+        let mut s: String = (0..len)
+            .map(|_| match self.below(8) {
+                0 => 'é',
+                1 => '&',
+                2 => '\u{2642}',
+                _ => (b'a' + self.below(26) as u8) as char,
+            })
+            .collect();
+        s.push('\0');
+        s
+    }
+}
+
+fn random_property<'r>(rng: &mut Rng) -> SclyProperty<'r>
+{
+    match rng.below(6) {
+        0 => SclyProperty::Timer(Timer {
+            name: rng.name().into(),
+            start_time: rng.f32(),
+            max_random_add: rng.f32(),
+            reset_to_zero: rng.byte() & 1,
+            start_immediately: rng.byte() & 1,
+            active: rng.byte() & 1,
+        }),
+        1 => SclyProperty::Relay(Relay {
+            name: rng.name().into(),
+            active: rng.byte() & 1,
+        }),
+        2 => SclyProperty::MemoryRelay(MemoryRelay {
+            name: rng.name().into(),
+            unknown: rng.byte(),
+            active: rng.byte() & 1,
+        }),
+        3 => SclyProperty::Counter(Counter {
+            name: rng.name().into(),
+            start_value: rng.next() as u32,
+            max_value: rng.next() as u32,
+            auto_reset: rng.byte() & 1,
+            active: rng.byte() & 1,
+        }),
+        4 => SclyProperty::Switch(Switch {
+            name: rng.name().into(),
+            active: rng.byte() & 1,
+            opened: rng.byte() & 1,
+            close_on_opened: rng.byte() & 1,
+        }),
+        _ => {
+            // An object type this crate doesn't know about
+            let len = rng.below(64);
+            let data: Vec<u8> = (0..len).map(|_| rng.byte()).collect();
+            SclyProperty::Unknown {
+                object_type: 0xFF,
+                data: data.into(),
+            }
+        },
+    }
+}
+
+fn random_scly<'r>(rng: &mut Rng) -> Scly<'r>
+{
+    let layers = (0..1 + rng.below(4))
+        .map(|_| {
+            let mut layer = SclyLayer::new();
+            layer.unknown = rng.byte();
+            layer.objects = (0..rng.below(8))
+                .map(|_| SclyObject {
+                    instance_id: rng.next() as u32,
+                    connections: (0..rng.below(4))
+                        .map(|_| Connection {
+                            state: ConnectionState(rng.below(0x21) as u32),
+                            message: ConnectionMsg(rng.below(0x16) as u32),
+                            target_object_id: rng.next() as u32,
+                        })
+                        .collect::<Vec<_>>()
+                        .into(),
+                    property_data: random_property(rng),
+                })
+                .collect::<Vec<_>>()
+                .into();
+            layer
+        })
+        .collect::<Vec<_>>();
+    Scly {
+        unknown: 1,
+        layers: layers.into(),
+    }
+}
+
+fn random_strg<'r>(rng: &mut Rng) -> Strg<'r>
+{
+    let string_count = 1 + rng.below(8);
+    let langs = [b"ENGL", b"FREN", b"GERM", b"SPAN", b"ITAL", b"JAPN"];
+    let mut strg = Strg::from_strings(vec![]);
+    strg.string_tables = langs[..1 + rng.below(langs.len())].iter()
+        .map(|lang| StrgStringTable {
+            lang: FourCC::from_bytes(lang),
+            strings: (0..string_count)
+                .map(|_| LazyUtf16beStr::Owned(rng.text()))
+                .collect::<Vec<_>>()
+                .into(),
+        })
+        .collect::<Vec<_>>()
+        .into();
+    strg
+}
+
+fn to_bytes<T: Writable>(t: &T) -> Vec<u8>
+{
+    let mut bytes = vec![];
+    t.write_to(&mut bytes).unwrap();
+    bytes
+}
+
+// Written -> read (lazily) -> written, and then again with everything parsed
+fn assert_round_trips<'r, T>(value: &T, bytes: &'r [u8])
+    where T: Readable<'r, Args = ()> + Writable,
+{
+    assert_eq!(value.size(), bytes.len());
+    let parsed: T = Reader::new(bytes).try_read(()).unwrap();
+    assert_eq!(parsed.size(), bytes.len());
+    assert_eq!(to_bytes(&parsed), bytes);
+}
+
+#[test]
+fn test_scly_round_trip()
+{
+    let mut rng = Rng(0x5C1F_0B1E_C7_u64);
+    for _ in 0..ITERATIONS {
+        let scly = random_scly(&mut rng);
+        let bytes = to_bytes(&scly);
+        assert_round_trips(&scly, &bytes);
+
+        let mut parsed: Scly = Reader::new(&bytes[..]).read(());
+        for layer in parsed.layers.as_mut_vec().iter_mut() {
+            for obj in layer.objects.as_mut_vec().iter_mut() {
+                obj.property_data.guess_kind();
+            }
+        }
+        assert_eq!(to_bytes(&parsed), bytes);
+    }
+}
+
+#[test]
+fn test_strg_round_trip()
+{
+    let mut rng = Rng(0x0057_1216);
+    for _ in 0..ITERATIONS {
+        let strg = random_strg(&mut rng);
+        let bytes = to_bytes(&strg);
+        assert_round_trips(&strg, &bytes);
+
+        let parsed: Strg = Reader::new(&bytes[..]).read(());
+        for (orig, parsed) in strg.string_tables.iter().zip(parsed.string_tables.iter()) {
+            assert_eq!(orig.lang, parsed.lang);
+            for (a, b) in orig.strings.iter().zip(parsed.strings.iter()) {
+                assert!(*a == *b);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cstr_props_round_trip()
+{
+    let relay = Relay {
+        name: b"relay\0".as_cstr(),
+        active: 1,
+    };
+    let bytes = to_bytes(&relay);
+    assert_eq!(&bytes[..], b"\0\0\0\x02relay\0\x01");
+    assert_round_trips(&relay, &bytes);
+}
+
+#[test]
+fn test_mismatched_props_are_an_error()
+{
+    let relay = Relay {
+        name: b"relay\0".as_cstr(),
+        active: 1,
+    };
+    let mut bytes = to_bytes(&relay);
+    bytes.push(0);
+    let mut prop = SclyProperty::Unknown {
+        object_type: 0x15,
+        data: Reader::new(&bytes).read((bytes.len(), ())),
+    };
+    let err = prop.try_guess_kind().unwrap_err();
+    assert_eq!(err.to_string(), "1 bytes of this Relay weren't parsed at offset 0xb");
+    assert!(matches!(prop, SclyProperty::Unknown { .. }));
+    assert_eq!(prop.object_type_name(), Some("Relay"));
+    assert_eq!(to_bytes(&prop), bytes);
+}
+
+#[test]
+fn test_malformed_resources_are_an_error()
+{
+    let strg = to_bytes(&Strg::from_strings(vec!["a string\0".to_owned()]));
+    let mut kind = ResourceKind::Unknown(Reader::new(&strg[..strg.len() - 4]), b"STRG".into());
+    assert!(kind.try_as_strg().is_err());
+    assert!(kind.try_as_strg_mut().is_err());
+    assert!(matches!(kind, ResourceKind::Unknown(..)));
+    assert!(kind.try_as_scan().unwrap().is_none());
+
+    let mut kind = ResourceKind::Unknown(Reader::new(&strg), b"STRG".into());
+    assert!(kind.try_as_strg().unwrap().is_some());
+    assert!(kind.try_as_strg_mut().unwrap().is_some());
+    assert!(matches!(kind, ResourceKind::Strg(_)));
+}
+
+fn mutate(rng: &mut Rng, bytes: &[u8]) -> Vec<u8>
+{
+    let mut bytes = bytes.to_vec();
+    for _ in 0..1 + rng.below(4) {
+        match rng.below(6) {
+            0 if !bytes.is_empty() => {
+                let i = rng.below(bytes.len());
+                bytes[i] ^= 1 << rng.below(8);
+            },
+            1 if !bytes.is_empty() => {
+                let i = rng.below(bytes.len());
+                bytes[i] = rng.byte();
+            },
+            2 => {
+                let len = rng.below(bytes.len() + 1);
+                bytes.truncate(len);
+            },
+            _ => {
+                let i = rng.below(bytes.len() + 1);
+                bytes.insert(i, rng.byte());
+            },
+        }
+    }
+    bytes
+}
+
+// Parse `bytes` as a resource of the given kind. The parser may reject the data, but it must do
+// so by returning an error, and anything it accepts must be writable.
+fn check_parse(fourcc: FourCC, bytes: &[u8])
+{
+    let mut kind = ResourceKind::Unknown(Reader::new(bytes), fourcc);
+    if kind.try_guess_kind().is_err() {
+        return
+    }
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        kind.write_to(&mut vec![])
+    }));
+    match res {
+        Ok(Ok(_)) => (),
+        Ok(Err(e)) => panic!("Failed to write an accepted {}: {}", fourcc, e),
+        Err(_) => panic!("Panicked writing an accepted {} ({:02x?})", fourcc, bytes),
+    }
+}
+
+#[test]
+fn test_fuzz_parsers()
+{
+    let mut rng = Rng(0xF022_1E55);
+    let mut seeds = vec![];
+    for _ in 0..8 {
+        seeds.push((FourCC::from_bytes(b"STRG"), to_bytes(&random_strg(&mut rng))));
+    }
+
+    let fourccs = [
+        b"EVNT", b"FRME", b"HINT", b"MAPA", b"MAPW", b"MLVL", b"MREA", b"PART", b"SAVW", b"SCAN",
+        b"STRG",
+    ];
+    for _ in 0..ITERATIONS * 10 {
+        let (fourcc, bytes) = if rng.below(2) == 0 {
+            let (fourcc, seed) = &seeds[rng.below(seeds.len())];
+            (*fourcc, mutate(&mut rng, seed))
+        } else {
+            let len = rng.below(256);
+            let bytes = (0..len).map(|_| rng.byte()).collect::<Vec<_>>();
+            (FourCC::from_bytes(fourccs[rng.below(fourccs.len())]), bytes)
+        };
+        check_parse(fourcc, &bytes);
+    }
+}
@@ -0,0 +1,96 @@
+use reader_writer::{Reader, Writable};
+use structs::{Txtr, TxtrPalette};
+
+// Swap the red and blue channels
+fn swap_red_blue(rgb: [u8; 3]) -> [u8; 3]
+{
+    [rgb[2], rgb[1], rgb[0]]
+}
+
+fn txtr<'r>(format: u32, pixel_data: Vec<u8>) -> Txtr<'r>
+{
+    Txtr {
+        format,
+        width: 4,
+        height: 4,
+        mipmap_count: 1,
+        palette: None,
+        pixel_data: pixel_data.into(),
+    }
+}
+
+fn round_trip(txtr: &Txtr) -> Vec<u8>
+{
+    let mut bytes = vec![];
+    txtr.write_to(&mut bytes).unwrap();
+    let parsed: Txtr = Reader::new(&bytes).read(());
+    parsed.pixel_data.iter().map(|b| *b).collect()
+}
+
+#[test]
+fn test_map_colors_direct()
+{
+    // Pure red in RGB565
+    let mut rgb565 = txtr(0x7, [0xF8, 0x00].repeat(16));
+    rgb565.map_colors(swap_red_blue);
+    assert_eq!(round_trip(&rgb565), [0x00, 0x1F].repeat(16));
+
+    // Opaque RGB555 red and half transparent RGB4A3 red
+    let mut rgb5a3 = txtr(0x8, [0xFC, 0x00, 0x4F, 0x00].repeat(8));
+    rgb5a3.map_colors(swap_red_blue);
+    assert_eq!(round_trip(&rgb5a3), [0x80, 0x1F, 0x40, 0x0F].repeat(8));
+
+    // Alpha and red for 16 texels, then green and blue
+    let mut rgba8 = txtr(0x9, [[0x80, 0xFF].repeat(16), [0x10, 0x20].repeat(16)].concat());
+    rgba8.map_colors(swap_red_blue);
+    assert_eq!(round_trip(&rgba8), [[0x80, 0x20].repeat(16), [0x10, 0xFF].repeat(16)].concat());
+
+    // Intensity formats are left alone
+    let mut i8 = txtr(0x1, (0..32).collect());
+    i8.map_colors(swap_red_blue);
+    assert_eq!(round_trip(&i8), (0..32).collect::<Vec<u8>>());
+}
+
+#[test]
+fn test_map_colors_palette()
+{
+    let mut c8 = txtr(0x5, [0, 1, 1, 0, 1, 0, 0, 1].repeat(4));
+    c8.palette = Some(TxtrPalette {
+        format: 0x2,
+        width: 2,
+        height: 1,
+        entries: vec![0xFC00, 0x4F00].into(),
+    });
+    c8.map_colors(swap_red_blue);
+    let entries = c8.palette.as_ref().unwrap().entries.iter().map(|e| *e).collect::<Vec<_>>();
+    assert_eq!(entries, [0x801F, 0x400F]);
+    assert_eq!(round_trip(&c8), [0, 1, 1, 0, 1, 0, 0, 1].repeat(4));
+}
+
+#[test]
+fn test_map_colors_cmpr()
+{
+    let cmpr = |blocks: &[[u8; 8]]| txtr(0xA, blocks.concat().repeat(4 / blocks.len()));
+
+    // Opaque blocks stay opaque. Red > blue, but once swapped the endpoints are reordered and
+    // the indices remapped to match.
+    let mut opaque = cmpr(&[[0xF8, 0x00, 0x00, 0x1F, 0b00_01_10_11, 0, 0, 0]]);
+    opaque.map_colors(swap_red_blue);
+    assert_eq!(
+        round_trip(&opaque),
+        [0xF8, 0x00, 0x00, 0x1F, 0b01_00_11_10, 0x55, 0x55, 0x55].repeat(4),
+    );
+
+    // Blocks with transparency keep it, and the transparent index
+    let mut transparent = cmpr(&[[0x00, 0x1F, 0xF8, 0x00, 0b00_01_10_11, 0, 0, 0]]);
+    transparent.map_colors(swap_red_blue);
+    assert_eq!(
+        round_trip(&transparent),
+        [0x00, 0x1F, 0xF8, 0x00, 0b01_00_10_11, 0x55, 0x55, 0x55].repeat(4),
+    );
+
+    // Endpoints that become equal can't stay opaque with index 3
+    let mut collapsed = cmpr(&[[0xF8, 0x00, 0x00, 0x1F, 0xFF, 0xFF, 0xFF, 0xFF]]);
+    collapsed.map_colors(|_| [0xFF, 0xFF, 0xFF]);
+    assert_eq!(round_trip(&collapsed), [0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0].repeat(4));
+}
@@ -0,0 +1,374 @@
+use rand::{rngs::StdRng, SeedableRng};
+use randomprime::{door_randomizer, elevators::ELEVATORS};
+use reader_writer::Reader;
+use structs::{DockId, Mlvl, MlvlGraph};
+
+use std::collections::{HashMap, HashSet};
+
+fn push_u32(bytes: &mut Vec<u8>, x: u32)
+{
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+fn push_f32(bytes: &mut Vec<u8>, x: f32)
+{
+    bytes.extend_from_slice(&x.to_be_bytes());
+}
+
+// (area, dock) <-> (area, dock)
+type Link = ((usize, usize), (usize, usize));
+
+struct TestArea
+{
+    aabb: [f32; 6],
+    docks: Vec<Vec<[f32; 3]>>,
+}
+
+// A minimal MLVL whose areas are connected by the given links
+fn mlvl_bytes(areas: &[TestArea], links: &[Link]) -> Vec<u8>
+{
+    let mut bytes = vec![];
+    for &x in &[0xDEAFBABE, 0x11, 0, 0, 0, 0, areas.len() as u32, 1] {
+        push_u32(&mut bytes, x);
+    }
+    for (i, area) in areas.iter().enumerate() {
+        push_u32(&mut bytes, 0);
+        for &x in &[1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0.] {
+            push_f32(&mut bytes, x);
+        }
+        for &x in &area.aabb {
+            push_f32(&mut bytes, x);
+        }
+        push_u32(&mut bytes, 0x1000 + i as u32);
+        push_u32(&mut bytes, i as u32);
+
+        // No attached areas, no dependencies, no dependency layers
+        push_u32(&mut bytes, 0);
+        push_u32(&mut bytes, 0);
+        push_u32(&mut bytes, 0);
+        push_u32(&mut bytes, 0);
+
+        push_u32(&mut bytes, area.docks.len() as u32);
+        for (dock, coords) in area.docks.iter().enumerate() {
+            let conns: Vec<_> = links.iter()
+                .filter_map(|&(a, b)| {
+                    if a == (i, dock) {
+                        Some(b)
+                    } else if b == (i, dock) {
+                        Some(a)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            push_u32(&mut bytes, conns.len() as u32);
+            for (a, d) in conns {
+                push_u32(&mut bytes, a as u32);
+                push_u32(&mut bytes, d as u32);
+            }
+            push_u32(&mut bytes, coords.len() as u32);
+            for coord in coords {
+                for &x in coord {
+                    push_f32(&mut bytes, x);
+                }
+            }
+        }
+    }
+    push_u32(&mut bytes, 0);
+    bytes.push(0);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 0);
+    bytes.push(0);
+    push_u32(&mut bytes, areas.len() as u32);
+    for _ in areas {
+        push_u32(&mut bytes, 0);
+        bytes.extend_from_slice(&[0; 8]);
+    }
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, areas.len() as u32);
+    for _ in areas {
+        push_u32(&mut bytes, 0);
+    }
+    while bytes.len() % 32 != 0 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+fn plain_areas(dock_counts: &[usize]) -> Vec<TestArea>
+{
+    dock_counts.iter()
+        .map(|&count| TestArea { aabb: [0.; 6], docks: vec![vec![]; count] })
+        .collect()
+}
+
+// A hub (0) with a loop of rooms hanging off it and a few rooms off to the side:
+// 0 - 1 - 2 - 3 - 4 - 0, 5 hanging off 2, and 0 - 6 - 7
+fn loop_world() -> Vec<u8>
+{
+    mlvl_bytes(&plain_areas(&[3, 2, 3, 2, 2, 1, 2, 1]), &[
+        ((0, 0), (1, 0)),
+        ((1, 1), (2, 0)),
+        ((2, 1), (3, 0)),
+        ((3, 1), (4, 0)),
+        ((4, 1), (0, 1)),
+        ((2, 2), (5, 0)),
+        ((0, 2), (6, 0)),
+        ((6, 1), (7, 0)),
+    ])
+}
+
+fn all_docks(graph: &MlvlGraph) -> Vec<DockId>
+{
+    (0..graph.area_count())
+        .flat_map(|area| (0..graph.docks(area).len()).map(move |dock| DockId::new(area, dock)))
+        .collect()
+}
+
+#[test]
+fn test_shuffled_docks_stay_connected()
+{
+    let bytes = loop_world();
+    for seed in 0..50 {
+        let mut mlvl: Mlvl = Reader::new(&bytes).read(());
+        let mut graph = MlvlGraph::new(&mut mlvl);
+        let docks = all_docks(&graph);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let links = door_randomizer::shuffle_docks(&mut graph, &mut rng, &docks, &[0]).unwrap();
+        assert_eq!(links.len(), 3);
+
+        // Every room can still be reached from the start, every door leads exactly one place
+        // and both sides of each door agree
+        assert_eq!(graph.reachable_areas(0), (0..8).collect::<HashSet<_>>());
+        for dock in all_docks(&graph) {
+            let conns = graph.connections(dock);
+            assert_eq!(conns.len(), 1);
+            assert_eq!(graph.connections(conns[0]), vec![dock]);
+            assert_ne!(conns[0].area, dock.area);
+        }
+
+        // The doors making up the loop can't be lined up once rewired, so they're left alone
+        for &(a, b) in &[((0, 0), (1, 0)), ((1, 1), (2, 0)), ((2, 1), (3, 0)), ((3, 1), (4, 0)),
+                         ((4, 1), (0, 1))]
+        {
+            let (a, b) = (DockId::new(a.0, a.1), DockId::new(b.0, b.1));
+            assert_eq!(graph.connections(a), vec![b]);
+        }
+    }
+}
+
+#[test]
+fn test_shuffle_uses_start_areas()
+{
+    // Area 3 can only be reached from area 2, which is where the player starts
+    let bytes = mlvl_bytes(&plain_areas(&[1, 1, 2, 1]), &[
+        ((0, 0), (1, 0)),
+        ((2, 0), (3, 0)),
+    ]);
+    for seed in 0..20 {
+        let mut mlvl: Mlvl = Reader::new(&bytes).read(());
+        let mut graph = MlvlGraph::new(&mut mlvl);
+        let docks = all_docks(&graph);
+        let mut rng = StdRng::seed_from_u64(seed);
+        // Dock 2.1 isn't connected to anything, so it doesn't take part
+        door_randomizer::shuffle_docks(&mut graph, &mut rng, &docks, &[2]).unwrap();
+        assert!(graph.reachable_areas(2).contains(&3));
+    }
+}
+
+#[test]
+fn test_align_areas_lines_up_rewired_doors()
+{
+    // A door in the +x wall of area 0 connected to a door in the -y wall of area 1
+    let areas = [
+        TestArea {
+            aabb: [0., 0., 0., 10., 10., 10.],
+            docks: vec![vec![[10., 4., 0.], [10., 6., 0.], [10., 6., 3.], [10., 4., 3.]]],
+        },
+        TestArea {
+            aabb: [100., 0., 0., 110., 10., 10.],
+            docks: vec![vec![[104., 0., 0.], [106., 0., 0.], [106., 0., 3.], [104., 0., 3.]]],
+        },
+    ];
+    let bytes = mlvl_bytes(&areas, &[((0, 0), (1, 0))]);
+    let mut mlvl: Mlvl = Reader::new(&bytes).read(());
+    let graph = MlvlGraph::new(&mut mlvl);
+
+    let links = [(DockId::new(0, 0), DockId::new(1, 0))];
+    let moves = door_randomizer::align_areas(&graph, &links, &[0]).unwrap();
+    assert_eq!(moves.len(), 1);
+    let (area, area_move) = moves[0];
+    assert_eq!(area, 1);
+
+    // The doors meet, and area 1 is now on the far side of the door from area 0
+    let center = area_move.apply_to_point([105., 0., 1.5]);
+    for (a, b) in center.iter().zip([10., 5., 1.5].iter()) {
+        assert!((a - b).abs() < 1e-3, "{:?}", center);
+    }
+    let area_center = area_move.apply_to_point([105., 5., 5.]);
+    assert!(area_center[0] > 10.);
+
+    // Untouched doors don't move anything
+    assert!(door_randomizer::align_areas(&graph, &[], &[0]).unwrap().is_empty());
+}
+
+// A box-shaped room at `origin` with doorways in the middle of the given walls
+fn box_area(origin: [f32; 3], walls: &[char]) -> TestArea
+{
+    let [x, y, z] = origin;
+    let docks = walls.iter()
+        .map(|wall| match wall {
+            'E' => vec![[x + 10., y + 4., z], [x + 10., y + 6., z], [x + 10., y + 6., z + 3.]],
+            'W' => vec![[x, y + 4., z], [x, y + 6., z], [x, y + 6., z + 3.]],
+            'N' => vec![[x + 4., y + 10., z], [x + 6., y + 10., z], [x + 6., y + 10., z + 3.]],
+            _ => vec![[x + 4., y, z], [x + 6., y, z], [x + 6., y, z + 3.]],
+        })
+        .collect();
+    TestArea { aabb: [x, y, z, x + 10., y + 10., z + 10.], docks }
+}
+
+fn dock_center(area: &TestArea, dock: usize, area_move: &door_randomizer::AreaMove) -> [f32; 3]
+{
+    let coords = &area.docks[dock];
+    let mut center = [0.; 3];
+    for c in coords {
+        let p = area_move.apply_to_point(*c);
+        for i in 0..3 {
+            center[i] += p[i] / coords.len() as f32;
+        }
+    }
+    center
+}
+
+#[test]
+fn test_shuffled_doors_line_up()
+{
+    // A hub (0) with a door in each wall, rooms off each of them and another room past room 1
+    let areas = [
+        box_area([0., 0., 0.], &['E', 'W', 'N', 'S']),
+        box_area([100., 0., 0.], &['W', 'E']),
+        box_area([200., 0., 0.], &['N']),
+        box_area([300., 0., 0.], &['E']),
+        box_area([400., 0., 0.], &['S']),
+        box_area([500., 0., 50.], &['N']),
+    ];
+    let bytes = mlvl_bytes(&areas, &[
+        ((0, 0), (1, 0)),
+        ((0, 1), (2, 0)),
+        ((0, 2), (3, 0)),
+        ((0, 3), (4, 0)),
+        ((1, 1), (5, 0)),
+    ]);
+    for seed in 0..20 {
+        let mut mlvl: Mlvl = Reader::new(&bytes).read(());
+        let mut graph = MlvlGraph::new(&mut mlvl);
+        let docks = all_docks(&graph);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let links = door_randomizer::shuffle_docks(&mut graph, &mut rng, &docks, &[0]).unwrap();
+        assert_eq!(links.len(), 5);
+        let moves: HashMap<_, _> = door_randomizer::align_areas(&graph, &links, &[0]).unwrap()
+            .into_iter()
+            .collect();
+        assert!(!moves.contains_key(&0));
+        let area_move = |area: usize| moves.get(&area).cloned()
+            .unwrap_or_else(door_randomizer::AreaMove::identity);
+
+        // Both sides of every door meet, with the rooms on opposite sides of it
+        for area in 0..graph.area_count() {
+            for (from, to) in graph.exits(area) {
+                let a = dock_center(&areas[from.area], from.dock, &area_move(from.area));
+                let b = dock_center(&areas[to.area], to.dock, &area_move(to.area));
+                let distance = (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f32>().sqrt();
+                assert!(distance < 1e-3, "seed {}: {:?} and {:?} are {} apart",
+                        seed, from, to, distance);
+
+                let room_center = |area: usize| {
+                    let aabb = &areas[area].aabb;
+                    area_move(area).apply_to_point([
+                        (aabb[0] + aabb[3]) / 2.,
+                        (aabb[1] + aabb[4]) / 2.,
+                        (aabb[2] + aabb[5]) / 2.,
+                    ])
+                };
+                let (from_center, to_center) = (room_center(from.area), room_center(to.area));
+                let side = (from_center[0] - a[0]) * (to_center[0] - a[0]) +
+                    (from_center[1] - a[1]) * (to_center[1] - a[1]);
+                assert!(side < 0., "seed {}: {:?} and {:?} overlap", seed, from, to);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_align_areas_rejects_doors_that_cant_meet()
+{
+    // Two rooms joined by two doors, rewired as a loop the second door can't close
+    let areas = [
+        box_area([0., 0., 0.], &['E', 'W']),
+        box_area([100., 0., 0.], &['S', 'N']),
+    ];
+    let bytes = mlvl_bytes(&areas, &[((0, 0), (1, 0)), ((0, 1), (1, 1))]);
+    let mut mlvl: Mlvl = Reader::new(&bytes).read(());
+    let graph = MlvlGraph::new(&mut mlvl);
+    let links = [
+        (DockId::new(0, 0), DockId::new(1, 0)),
+        (DockId::new(0, 1), DockId::new(1, 1)),
+    ];
+    let err = door_randomizer::align_areas(&graph, &links, &[0]).unwrap_err();
+    assert!(err.contains("don't line up"), "{}", err);
+
+    // Which is why shuffling leaves doors in a loop alone
+    let mut mlvl: Mlvl = Reader::new(&bytes).read(());
+    let mut graph = MlvlGraph::new(&mut mlvl);
+    let docks = all_docks(&graph);
+    let mut rng = StdRng::seed_from_u64(0);
+    assert!(door_randomizer::shuffle_docks(&mut graph, &mut rng, &docks, &[0]).unwrap().is_empty());
+}
+
+#[test]
+fn test_shuffled_elevators_connect_every_world()
+{
+    let vanilla: Vec<u8> = ELEVATORS.iter().map(|elv| elv.default_dest).collect();
+    let world = |i: usize| ELEVATORS[i].pak_name;
+    let worlds: HashSet<_> = ELEVATORS.iter().map(|elv| elv.pak_name).collect();
+    for seed in 0..20 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let layout = door_randomizer::shuffle_elevators(&mut rng, &vanilla).unwrap();
+
+        let mut seen: HashSet<_> = [world(0)].iter().cloned().collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, &dest) in layout.iter().enumerate() {
+                assert_eq!(layout[dest as usize] as usize, i);
+                if seen.contains(world(i)) && seen.insert(world(dest as usize)) {
+                    changed = true;
+                }
+            }
+        }
+        assert_eq!(seen, worlds);
+    }
+}
+
+#[test]
+fn test_shuffled_elevators_keep_configured_destinations()
+{
+    // Connect the first elevator to an elevator in another world that it isn't connected to
+    // in vanilla, in both directions
+    let mut base: Vec<u8> = ELEVATORS.iter().map(|elv| elv.default_dest).collect();
+    let other = (0..ELEVATORS.len())
+        .find(|&i| {
+            ELEVATORS[i].pak_name != ELEVATORS[0].pak_name &&
+                ELEVATORS[i].pak_name != "Metroid7.pak" &&
+                i != base[0] as usize
+        })
+        .unwrap();
+    base[0] = other as u8;
+    base[other] = 0;
+    for seed in 0..20 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let layout = door_randomizer::shuffle_elevators(&mut rng, &base).unwrap();
+        assert_eq!(layout[0] as usize, other);
+        assert_eq!(layout[other], 0);
+    }
+}
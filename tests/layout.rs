@@ -0,0 +1,51 @@
+use randomprime::{elevators::ELEVATORS, layout::Layout, parse_layout, write_layout};
+
+fn vanilla_elevators() -> Vec<u8>
+{
+    ELEVATORS.iter()
+        .map(|elv| elv.default_dest)
+        .chain(Some(ELEVATORS.len() as u8))
+        .collect()
+}
+
+// Parses `text` as both a layout string and a `Layout`, checking that writing either of them
+// back out gives the same string
+fn check_round_trip(text: &str, has_scan_visor: bool)
+{
+    let (pickups, elevators, _) = parse_layout(text).unwrap();
+    assert_eq!(write_layout(&pickups, &elevators, has_scan_visor).unwrap(), text);
+
+    let layout = Layout::from_layout_string(text).unwrap();
+    assert_eq!(layout.scan_visor, has_scan_visor);
+    assert_eq!(layout.to_layout_string().unwrap(), text);
+
+    let json = serde_json::to_string(&layout).unwrap();
+    let from_json: Layout = serde_json::from_str(&json).unwrap();
+    assert_eq!(from_json, layout);
+    assert_eq!(from_json.to_layout_string().unwrap(), text);
+}
+
+#[test]
+fn test_layout_round_trip()
+{
+    let pickups: Vec<u8> = (0..100).map(|i| (i * 7 % 36) as u8).collect();
+    let text = write_layout(&pickups, &vanilla_elevators(), false).unwrap();
+    assert!(!text.contains('!'));
+    check_round_trip(&text, false);
+}
+
+#[test]
+fn test_scan_visor_layout_round_trip()
+{
+    // Pickups that need the '!' form
+    let pickups: Vec<u8> = (0..100).map(|i| (i * 7 % 37) as u8).collect();
+    let text = write_layout(&pickups, &vanilla_elevators(), true).unwrap();
+    assert!(text.contains(".!"));
+    check_round_trip(&text, true);
+    assert!(write_layout(&pickups, &vanilla_elevators(), false).is_err());
+
+    // The '!' form is kept even when nothing in the layout needs it
+    let pickups: Vec<u8> = (0..100).map(|i| (i * 7 % 36) as u8).collect();
+    let text = write_layout(&pickups, &vanilla_elevators(), true).unwrap();
+    check_round_trip(&text, true);
+}
@@ -0,0 +1,247 @@
+use rand::{rngs::StdRng, SeedableRng};
+use randomprime::{
+    door_meta::DoorType,
+    logic::{self, LogicOptions, RoomGraph},
+    pickup_meta::PickupType,
+    starting_items::StartingItems,
+};
+
+use std::collections::HashMap;
+
+// The index of the Ruined Nursery's pickup, which needs bombs to reach
+const RUINED_NURSERY: usize = 10;
+
+fn generated_layout(seed: u64, item_pool: &HashMap<PickupType, u32>, options: &LogicOptions)
+    -> Vec<PickupType>
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    logic::generate_layout(&mut rng, item_pool, options).unwrap()
+}
+
+#[test]
+fn test_default_pool_is_completable()
+{
+    let options = LogicOptions::default();
+    let layout = generated_layout(1234, &logic::default_item_pool(), &options);
+    assert_eq!(layout.len(), 100);
+    logic::verify_layout(&layout, &options).unwrap();
+    assert_eq!(generated_layout(1234, &logic::default_item_pool(), &options), layout);
+}
+
+#[test]
+fn test_impossible_layout_fails()
+{
+    let options = LogicOptions::default();
+    let mut layout = generated_layout(1234, &logic::default_item_pool(), &options);
+
+    // The bombs can't be behind bombs
+    let bombs = layout.iter().position(|&pt| pt == PickupType::MorphBallBomb).unwrap();
+    layout.swap(bombs, RUINED_NURSERY);
+    let err = logic::verify_layout(&layout, &options).unwrap_err();
+    assert!(err.contains("isn't completable"), "{}", err);
+    assert!(err.contains("Ruined Nursery: Morph Ball Bomb"), "{}", err);
+
+    // Unless the player already has them
+    let mut options = options;
+    options.starting_inventory.insert(PickupType::MorphBall, 1);
+    options.starting_inventory.insert(PickupType::MorphBallBomb, 1);
+    logic::verify_layout(&layout, &options).unwrap();
+
+    assert!(logic::verify_layout(&layout[1..], &options).is_err());
+}
+
+#[test]
+fn test_starting_inventory_is_used_for_placement()
+{
+    let mut item_pool = logic::default_item_pool();
+    item_pool.insert(PickupType::MorphBall, 0);
+    item_pool.insert(PickupType::MorphBallBomb, 0);
+
+    // Without them nothing needing bombs can be reached
+    assert!(logic::generate_layout(&mut StdRng::seed_from_u64(1), &item_pool, &LogicOptions::default())
+        .is_err());
+
+    let mut options = LogicOptions::default();
+    options.starting_inventory.insert(PickupType::MorphBall, 1);
+    options.starting_inventory.insert(PickupType::MorphBallBomb, 1);
+    let layout = generated_layout(1, &item_pool, &options);
+    logic::verify_layout(&layout, &options).unwrap();
+    assert_eq!(layout.iter().filter(|&&pt| pt == PickupType::Nothing).count(), 2);
+}
+
+#[test]
+fn test_starting_inventory()
+{
+    // 26 missiles, 3 power bombs, 2 energy tanks and the Morph Ball
+    let starting_items = StartingItems::from_u64(26 << 1 | 2 << 9 | 3 << 13 | 1 << 21);
+    let inventory = logic::starting_inventory(&starting_items);
+    let mut expected = HashMap::new();
+    expected.insert(PickupType::Missile, 6);
+    expected.insert(PickupType::PowerBomb, 1);
+    expected.insert(PickupType::EnergyTank, 2);
+    expected.insert(PickupType::MorphBall, 1);
+    assert_eq!(inventory, expected);
+}
+
+#[test]
+fn test_parse_item_pool()
+{
+    let mut names = HashMap::new();
+    names.insert("missile".to_string(), 10);
+    names.insert("Energy Tank".to_string(), 2);
+    let pool = logic::parse_item_pool(&names).unwrap();
+    assert_eq!(pool.get(&PickupType::Missile), Some(&10));
+    assert_eq!(pool.get(&PickupType::EnergyTank), Some(&2));
+
+    names.insert("Spring Ball".to_string(), 1);
+    let err = logic::parse_item_pool(&names).unwrap_err();
+    assert!(err.contains("Spring Ball"), "{}", err);
+}
+
+// The room the test room graph starts in, and a room between it and the Artifact of Truth
+const HUB: u32 = 0xFFFF0001;
+const TRUTH_ANTECHAMBER: u32 = 0xFFFF0002;
+
+// Every pickup's room is through its own door out of the hub, bar the Artifact of Truth's, which
+// is one more door past a room of its own
+fn room_graph(layout: &[PickupType]) -> RoomGraph
+{
+    let truth_room = truth_room(layout);
+    let mut graph = RoomGraph::new(HUB);
+    let mut rooms: Vec<u32> = logic::location_rooms().map(|(_, room_info)| room_info.room_id).collect();
+    rooms.dedup();
+    for (dock, &room) in rooms.iter().enumerate() {
+        let from = if room == truth_room {
+            graph.add_door(HUB, dock as u32, TRUTH_ANTECHAMBER);
+            TRUTH_ANTECHAMBER
+        } else {
+            HUB
+        };
+        graph.add_door(from, dock as u32, room);
+        graph.add_door(room, 0, from);
+    }
+    graph
+}
+
+fn truth_room(layout: &[PickupType]) -> u32
+{
+    let truth = layout.iter().position(|&pt| pt == PickupType::ArtifactOfTruth).unwrap();
+    logic::location_rooms().nth(truth).unwrap().1.room_id
+}
+
+// The door out of the hub on the way to the Artifact of Truth
+fn truth_door(layout: &[PickupType]) -> (u32, u32)
+{
+    let truth_room = truth_room(layout);
+    let mut rooms: Vec<u32> = logic::location_rooms().map(|(_, room_info)| room_info.room_id).collect();
+    rooms.dedup();
+    (HUB, rooms.iter().position(|&room| room == truth_room).unwrap() as u32)
+}
+
+// Door colors that shut the way to the Artifact of Truth
+fn truth_locked_doors(layout: &[PickupType]) -> HashMap<(u32, u32), DoorType>
+{
+    let mut doors = HashMap::new();
+    doors.insert(truth_door(layout), DoorType::Disabled);
+    doors
+}
+
+#[test]
+fn test_doors_on_the_way_are_checked()
+{
+    let layout = generated_layout(1234, &logic::default_item_pool(), &LogicOptions::default());
+    let wave_door = truth_door(&layout);
+    let mut options = LogicOptions {
+        door_types: vec![(wave_door, DoorType::Purple)].into_iter().collect(),
+        ..LogicOptions::default()
+    };
+
+    // Door colors can't be checked without knowing which doors are on the way
+    let err = logic::verify_layout(&layout, &options).unwrap_err();
+    assert!(err.contains("room graph"), "{}", err);
+
+    // The Artifact of Truth's own room has no wave doors, but the way there does, so the Wave
+    // Beam can't be in it
+    options.room_graph = Some(room_graph(&layout));
+    logic::verify_layout(&layout, &options).unwrap();
+    let mut layout = layout;
+    let wave_beam = layout.iter().position(|&pt| pt == PickupType::WaveBeam).unwrap();
+    let truth = layout.iter().position(|&pt| pt == PickupType::ArtifactOfTruth).unwrap();
+    layout.swap(wave_beam, truth);
+    let err = logic::verify_layout(&layout, &options).unwrap_err();
+    assert!(err.contains("Wave Beam"), "{}", err);
+
+    // Generated layouts keep the Wave Beam out from behind it
+    for seed in 0..5 {
+        let layout = generated_layout(seed, &logic::default_item_pool(), &options);
+        logic::verify_layout(&layout, &options).unwrap();
+    }
+
+    // Rooms the graph doesn't know about can't be checked either
+    options.room_graph = Some(RoomGraph::new(HUB));
+    let err = logic::verify_layout(&layout, &options).unwrap_err();
+    assert!(err.contains("isn't in the room graph"), "{}", err);
+}
+
+#[test]
+fn test_roll_completable_doors()
+{
+    let layout = generated_layout(1234, &logic::default_item_pool(), &LogicOptions::default());
+    let locked = truth_locked_doors(&layout);
+    let mut options = LogicOptions {
+        room_graph: Some(room_graph(&layout)),
+        ..LogicOptions::default()
+    };
+
+    // The first roll that works is used
+    let mut rolls = 0;
+    let ((roll, _), warning) = logic::roll_completable_doors(
+        &layout,
+        &options,
+        || {
+            rolls += 1;
+            Ok((rolls, if rolls < 3 { locked.clone() } else { HashMap::new() }))
+        },
+        |(_, doors)| doors.clone(),
+    ).unwrap();
+    assert_eq!((roll, warning), (3, None));
+
+    // If none do it's an error
+    let mut rolls = 0;
+    let err = logic::roll_completable_doors(
+        &layout,
+        &options,
+        || {
+            rolls += 1;
+            Ok((rolls, locked.clone()))
+        },
+        |(_, doors)| doors.clone(),
+    ).unwrap_err();
+    assert_eq!(rolls, logic::MAX_DOOR_ROLLS);
+    assert!(err.contains("100 tries"), "{}", err);
+    assert!(err.contains("Artifact of Truth"), "{}", err);
+
+    // A layout the doors can't fix isn't rolled for
+    options.door_types = locked.clone();
+    let mut rolls = 0;
+    let (roll, warning) = logic::roll_completable_doors(
+        &layout,
+        &options,
+        || {
+            rolls += 1;
+            Ok(rolls)
+        },
+        |_| HashMap::new(),
+    ).unwrap();
+    assert_eq!(roll, 1);
+    assert!(warning.unwrap().contains("weren't checked"));
+
+    // Errors from rolling are passed on
+    let res = logic::roll_completable_doors(
+        &layout,
+        &LogicOptions { door_types: HashMap::new(), ..options },
+        || Err::<(), _>("Unknown door type".to_string()),
+        |_| HashMap::new(),
+    );
+    assert_eq!(res.unwrap_err(), "Unknown door type");
+}
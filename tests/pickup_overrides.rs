@@ -0,0 +1,165 @@
+use randomprime::patches::{
+    resolve_pickup_overrides, AdditionalItem, PickupLocationOverride, ResolvedPickupOverride,
+};
+use randomprime::pickup_meta::PickupType;
+use randomprime::{custom_asset_ids, AssetIdAllocator};
+use reader_writer::FourCC;
+
+use std::collections::HashMap;
+
+fn location_overrides(json: &str) -> Vec<PickupLocationOverride>
+{
+    serde_json::from_str(json).unwrap()
+}
+
+fn additional_items(json: &str) -> Vec<AdditionalItem>
+{
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_resolve_location_overrides()
+{
+    let mut resources = HashMap::new();
+    let custom_models = HashMap::new();
+    let mut asset_ids = AssetIdAllocator::new(0x1000, 0x2000);
+    let (overrides, additional) = resolve_pickup_overrides(
+        &location_overrides(r#"[
+            {"room": "Tallon Overworld:Landing Site", "amount": 8},
+            {"room": "Chozo Ruins:Main Plaza", "index": 2, "scan_text": "A missile",
+             "hudmemo_text": "Missile acquired", "max_increase": 10, "model": "missile"}
+        ]"#),
+        &additional_items(r#"[
+            {"room": "Tallon Overworld:Landing Site", "item_type": "Missile",
+             "position": {"x": 0.0, "y": 0.0, "z": 0.0}, "curr_increase": 3}
+        ]"#),
+        false,
+        &mut resources,
+        &custom_models,
+        &mut asset_ids,
+    ).unwrap();
+    assert_eq!(overrides.len(), 2);
+    assert_eq!(additional.len(), 1);
+
+    // The scan and hudmemo text each get new assets
+    assert_eq!(asset_ids.peek(), 0x1003);
+    let mut keys: Vec<_> = resources.keys().cloned().collect();
+    keys.sort_by_key(|(id, _)| *id);
+    assert_eq!(keys, vec![
+        (0x1000, FourCC::from_bytes(b"SCAN")),
+        (0x1001, FourCC::from_bytes(b"STRG")),
+        (0x1002, FourCC::from_bytes(b"STRG")),
+    ]);
+
+    let mut landing_site: Vec<_> = overrides.iter()
+        .filter(|((_, index), _)| *index == 0)
+        .map(|(_, o)| o)
+        .collect();
+    assert_eq!(landing_site.len(), 1);
+    let mut pickup = PickupType::Missile.pickup_data().clone();
+    let (max, curr) = (pickup.max_increase, pickup.curr_increase);
+    landing_site.pop().unwrap().apply(&mut pickup);
+    // `amount` sets both the capacity and the current amount
+    assert_ne!((max, curr), (8, 8));
+    assert_eq!((pickup.max_increase, pickup.curr_increase), (8, 8));
+
+    let main_plaza = overrides.iter()
+        .find(|((_, index), _)| *index == 2)
+        .map(|(_, o)| o)
+        .unwrap();
+    let mut pickup = PickupType::Missile.pickup_data().clone();
+    let curr = pickup.curr_increase;
+    main_plaza.apply(&mut pickup);
+    assert_eq!((pickup.max_increase, pickup.curr_increase), (10, curr));
+    assert_eq!(pickup.actor_params.scan_params.scan, 0x1000);
+    let deps: Vec<_> = main_plaza.dependencies().map(|dep| dep.asset_id).collect();
+    assert_eq!(deps, vec![0x1000, 0x1001, 0x1002]);
+
+    let mut pickup = PickupType::Missile.pickup_data().clone();
+    let max = pickup.max_increase;
+    additional[0].apply(&mut pickup);
+    assert_eq!((pickup.max_increase, pickup.curr_increase), (max, 3));
+}
+
+#[test]
+fn test_resolve_foreign_item()
+{
+    let custom_models = HashMap::new();
+    let mut asset_ids = AssetIdAllocator::default();
+    let resolve = |json: &str, multiworld: bool, asset_ids: &mut AssetIdAllocator| {
+        resolve_pickup_overrides(
+            &location_overrides(json),
+            &[],
+            multiworld,
+            &mut HashMap::new(),
+            &custom_models,
+            asset_ids,
+        ).map(|(overrides, _)| overrides.values().next().cloned().unwrap())
+    };
+    let json = r#"[{"room": "Tallon Overworld:Landing Site", "foreign_item": 7, "amount": 2}]"#;
+
+    // Foreign items are only allowed in multiworld games
+    assert!(resolve(json, false, &mut asset_ids).is_err());
+
+    // The item isn't given to the player, its id is reported instead
+    let resolved: ResolvedPickupOverride = resolve(json, true, &mut asset_ids).unwrap();
+    let mut pickup = PickupType::Missile.pickup_data().clone();
+    resolved.apply(&mut pickup);
+    assert_eq!((pickup.max_increase, pickup.curr_increase), (0x40000007, 0));
+
+    let json = r#"[{"room": "Tallon Overworld:Landing Site", "foreign_item": 1073741824}]"#;
+    assert!(resolve(json, true, &mut asset_ids).is_err());
+}
+
+#[test]
+fn test_resolve_invalid_overrides()
+{
+    let resolve = |json: &str| {
+        resolve_pickup_overrides(
+            &location_overrides(json),
+            &[],
+            false,
+            &mut HashMap::new(),
+            &HashMap::new(),
+            &mut AssetIdAllocator::default(),
+        ).map(|_| ())
+    };
+
+    assert!(resolve(r#"[{"room": "Tallon Overworld:Landing Site", "index": 1}]"#).is_err());
+    assert!(resolve(r#"[
+        {"room": "Tallon Overworld:Landing Site", "amount": 1},
+        {"room": "Tallon Overworld:Landing Site", "amount": 2}
+    ]"#).is_err());
+    assert!(resolve(r#"[{"room": "Tallon Overworld:Landing Site", "model": "Bogus Model"}]"#)
+        .is_err());
+    assert!(resolve(r#"[{"room": "Tallon Overworld:Landing Site", "model": "Energy Tank"}]"#)
+        .is_ok());
+}
+
+#[test]
+fn test_asset_id_allocator()
+{
+    let mut asset_ids = AssetIdAllocator::default();
+    assert_eq!(asset_ids.next_id(), Ok(custom_asset_ids::PICKUP_OVERRIDE_ASSETS_START));
+    assert_eq!(asset_ids.next_id(), Ok(custom_asset_ids::PICKUP_OVERRIDE_ASSETS_START + 1));
+
+    // Running out is an error rather than running into ids reserved for something else
+    let mut asset_ids = AssetIdAllocator::new(0x1000, 0x1002);
+    assert_eq!(asset_ids.next_id(), Ok(0x1000));
+    assert_eq!(asset_ids.next_id(), Ok(0x1001));
+    assert!(asset_ids.next_id().is_err());
+    assert_eq!(asset_ids.peek(), 0x1002);
+
+    // Which is what resolving overrides that need new assets reports
+    let err = resolve_pickup_overrides(
+        &location_overrides(r#"[
+            {"room": "Chozo Ruins:Main Plaza", "index": 2, "scan_text": "A missile"}
+        ]"#),
+        &[],
+        false,
+        &mut HashMap::new(),
+        &HashMap::new(),
+        &mut AssetIdAllocator::new(0x1000, 0x1001),
+    ).map(|_| ()).unwrap_err();
+    assert!(err.contains("Ran out of asset ids"), "{}", err);
+}
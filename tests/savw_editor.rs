@@ -0,0 +1,153 @@
+use randomprime::savw_wrapper::SavwEditor;
+use reader_writer::{CStrConversionExtension, LazyArray, Readable, Reader, Writable};
+use structs::{LayerToggle, MemoryRelay, Relay, Savw, SclyObject, SclyProperty};
+
+use std::collections::HashMap;
+
+// An empty SAVW for a world with 4 areas
+fn empty_savw() -> Vec<u8>
+{
+    let mut bytes = vec![];
+    for word in &[0xC001D00Du32, 3, 4, 0, 0, 0, 0, 0] {
+        bytes.extend_from_slice(&word.to_be_bytes());
+    }
+    bytes
+}
+
+fn to_vec<'r, T>(array: &LazyArray<'r, T>) -> Vec<T>
+    where T: Readable<'r> + Clone,
+          T::Args: Clone,
+{
+    array.iter().map(|i| i.into_owned()).collect()
+}
+
+fn memory_relay<'r>(instance_id: u32) -> SclyObject<'r>
+{
+    SclyObject {
+        instance_id,
+        connections: vec![].into(),
+        property_data: SclyProperty::MemoryRelay(MemoryRelay {
+            name: b"relay\0".as_cstr(),
+            unknown: 0,
+            active: 1,
+        }),
+    }
+}
+
+#[test]
+fn test_savw_editor_apply()
+{
+    let bytes = empty_savw();
+    let mut savw: Savw = Reader::new(&bytes).read(());
+
+    let mut editor = SavwEditor::new();
+    assert!(editor.is_empty());
+    editor.add_memory_relay(0x00100001);
+    editor.add_memory_relay(0x00100001);
+    editor.add_layer_toggle(2, 3);
+    editor.add_door(0x00200005);
+    editor.add_scan(0x1234, 5);
+    editor.add_object_scan(0x5678);
+    editor.add_object_scan(0x9ABC);
+    // Invalid scans are ignored
+    editor.add_object_scan(0xFFFFFFFF);
+    assert!(!editor.is_empty());
+
+    let mut unresolved: Vec<_> = editor.unresolved_scans().collect();
+    unresolved.sort();
+    assert_eq!(unresolved, vec![0x5678, 0x9ABC]);
+
+    let scan_categories: HashMap<u32, u32> = vec![(0x5678, 2)].into_iter().collect();
+    editor.apply(&mut savw, &scan_categories);
+    // Applying twice doesn't add duplicates
+    editor.apply(&mut savw, &scan_categories);
+
+    assert_eq!(to_vec(&savw.memory_relay_array), vec![0x00100001]);
+    assert_eq!(to_vec(&savw.layer_toggle_array),
+               vec![LayerToggle { area_id: 2, layer_index: 3 }]);
+    assert_eq!(to_vec(&savw.door_array), vec![0x00200005]);
+    let scans: Vec<_> = savw.scan_array.iter()
+        .map(|scan| (scan.scan, scan.logbook_category))
+        .collect();
+    // Scans without a known category go in category 0
+    assert_eq!(scans, vec![(0x1234, 5), (0x5678, 2), (0x9ABC, 0)]);
+
+    // The counts are written along with the arrays
+    let mut written = vec![];
+    savw.write_to(&mut written).unwrap();
+    let reread: Savw = Reader::new(&written).read(());
+    assert_eq!(reread.scan_array.len(), 3);
+    assert_eq!(reread.door_array.len(), 1);
+}
+
+#[test]
+fn test_savw_editor_explicit_scan_category_wins()
+{
+    let mut editor = SavwEditor::new();
+    editor.add_object_scan(0x1234);
+    editor.add_scan(0x1234, 7);
+    assert_eq!(editor.unresolved_scans().count(), 0);
+
+    let bytes = empty_savw();
+    let mut savw: Savw = Reader::new(&bytes).read(());
+    let scan_categories: HashMap<u32, u32> = vec![(0x1234, 1)].into_iter().collect();
+    editor.apply(&mut savw, &scan_categories);
+    assert_eq!(savw.scan_array.iter().next().unwrap().logbook_category, 7);
+}
+
+#[test]
+fn test_savw_editor_scly_objects()
+{
+    let mut editor = SavwEditor::new();
+    editor.add_scly_object(&memory_relay(0x00100002));
+    // Objects that don't need saving aren't registered
+    editor.add_scly_object(&SclyObject {
+        instance_id: 0x00100003,
+        connections: vec![].into(),
+        property_data: SclyProperty::Relay(Relay { name: b"relay\0".as_cstr(), active: 1 }),
+    });
+
+    let bytes = empty_savw();
+    let mut savw: Savw = Reader::new(&bytes).read(());
+    editor.apply(&mut savw, &HashMap::new());
+    assert_eq!(to_vec(&savw.memory_relay_array), vec![0x00100002]);
+    assert_eq!(savw.door_array.len(), 0);
+}
+
+#[test]
+fn test_savw_editor_remove_object()
+{
+    // A SAVW that already saves a relay and a door
+    let bytes = empty_savw();
+    let mut savw: Savw = Reader::new(&bytes).read(());
+    let mut editor = SavwEditor::new();
+    editor.add_memory_relay(0x00100001);
+    editor.add_door(0x00200005);
+    editor.add_scan(0x1234, 5);
+    editor.apply(&mut savw, &HashMap::new());
+
+    let mut editor = SavwEditor::new();
+    editor.add_scly_object(&memory_relay(0x00100002));
+    editor.add_memory_relay(0x00100003);
+    // Removing objects the editor added drops them before they ever reach the SAVW
+    editor.remove_object(0x00100002);
+    // and removing ones already in the SAVW takes them out of it
+    editor.remove_object(0x00100001);
+    editor.remove_object(0x00200005);
+    assert!(!editor.is_empty());
+
+    // A removed instance ID can be handed out again, for an object of a different kind
+    editor.add_door(0x00100001);
+    editor.apply(&mut savw, &HashMap::new());
+
+    assert_eq!(to_vec(&savw.memory_relay_array), vec![0x00100003]);
+    assert_eq!(to_vec(&savw.door_array), vec![0x00100001]);
+    // The SAVW's own scans are shared, so they stay
+    assert_eq!(savw.scan_array.len(), 1);
+
+    // Removing something that was never saved only has to not fail
+    let mut editor = SavwEditor::new();
+    editor.remove_object(0x00300000);
+    editor.apply(&mut savw, &HashMap::new());
+    assert_eq!(to_vec(&savw.memory_relay_array), vec![0x00100003]);
+}
@@ -0,0 +1,100 @@
+use randomprime::spoiler::{
+    decrypt_spoiler, encrypt_spoiler, parse_spoiler_key, Spoiler, SpoilerDoor, SpoilerElevator,
+    SpoilerLocation,
+};
+
+use std::collections::BTreeMap;
+
+fn spoiler() -> Spoiler
+{
+    let mut starting_items = BTreeMap::new();
+    starting_items.insert("Missile".to_string(), 5);
+    starting_items.insert("Morph Ball".to_string(), 1);
+    Spoiler {
+        seed: 1234,
+        starting_items,
+        frigate_done_starting_items: None,
+        locations: vec![
+            SpoilerLocation {
+                world: "Chozo Ruins".to_string(),
+                room: "Main Plaza".to_string(),
+                item: "Progressive Beam".to_string(),
+            },
+        ],
+        elevators: vec![
+            SpoilerElevator {
+                elevator: "Chozo Ruins West (Main Plaza)".to_string(),
+                destination: "Tallon Overworld - Transport to Chozo Ruins West".to_string(),
+            },
+        ],
+        doors: vec![
+            SpoilerDoor {
+                world: "Chozo Ruins".to_string(),
+                room: "Main Plaza".to_string(),
+                dock: 2,
+                color: "Wave".to_string(),
+            },
+        ],
+    }
+}
+
+const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+#[test]
+fn test_spoiler_round_trip()
+{
+    let spoiler = spoiler();
+    let key = parse_spoiler_key(KEY).unwrap();
+    let encrypted = encrypt_spoiler(&spoiler, &key).unwrap();
+    assert_eq!(decrypt_spoiler(&encrypted, &key).unwrap(), spoiler.to_json().unwrap());
+
+    // The nonce after the header is random, so encrypting the same spoiler again gives a
+    // different file that decrypts to the same thing
+    let again = encrypt_spoiler(&spoiler, &key).unwrap();
+    assert_eq!(again[..8], encrypted[..8]);
+    assert_ne!(again[8..20], encrypted[8..20]);
+    assert_eq!(decrypt_spoiler(&again, &key).unwrap(), spoiler.to_json().unwrap());
+
+    let mut wrong_key = key;
+    wrong_key[0] ^= 1;
+    assert!(decrypt_spoiler(&encrypted, &wrong_key).is_err());
+
+    let mut tampered = encrypted.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    assert!(decrypt_spoiler(&tampered, &key).is_err());
+    assert!(decrypt_spoiler(&encrypted[..10], &key).is_err());
+
+    assert!(parse_spoiler_key("0011").is_err());
+    assert!(parse_spoiler_key(&KEY.replace('0', "g")).is_err());
+}
+
+#[test]
+fn test_spoiler_format()
+{
+    assert_eq!(spoiler().to_string(), "\
+Seed: 1234
+
+Starting items:
+    Missile: 5
+    Morph Ball: 1
+
+Items:
+    Chozo Ruins - Main Plaza: Progressive Beam
+
+Elevators:
+    Chozo Ruins West (Main Plaza): Tallon Overworld - Transport to Chozo Ruins West
+
+Doors:
+    Chozo Ruins - Main Plaza (dock 2): Wave
+");
+
+    let mut spoiler = spoiler();
+    spoiler.doors.clear();
+    let mut frigate_done_starting_items = BTreeMap::new();
+    frigate_done_starting_items.insert("Varia Suit".to_string(), 1);
+    spoiler.frigate_done_starting_items = Some(frigate_done_starting_items);
+    let formatted = spoiler.to_string();
+    assert!(formatted.contains("\n\nStarting items after the frigate:\n    Varia Suit: 1\n"));
+    assert!(!formatted.contains("Doors:"));
+}
@@ -0,0 +1,102 @@
+use randomprime::starting_items::StartingItems;
+
+use std::collections::HashMap;
+
+fn starting_items(json: &str) -> Result<StartingItems, String>
+{
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+// The u64 packs, from the lowest bit up: the Scan Visor, 8 bits of Missiles, 4 bits of Energy
+// Tanks, 4 bits of Power Bombs, then one bit each for the Wave, Ice and Plasma Beams, Charge
+// Beam, Morph Ball, Bombs, Spider Ball, Boost Ball, Varia, Gravity and Phazon Suits, Thermal
+// and X-Ray Visors, Space Jump Boots, Grapple Beam, Super Missile, Wavebuster, Ice Spreader and
+// Flamethrower
+const PACKED: u64 = 1 // Scan Visor
+    | 25 << 1 // Missiles
+    | 3 << 9 // Energy Tanks
+    | 4 << 13 // Power Bombs
+    | 1 << 17 // Wave Beam
+    | 1 << 18 // Ice Beam
+    | 1 << 20 // Charge Beam
+    | 1 << 21 // Morph Ball
+    | 1 << 22 // Morph Ball Bomb
+    | 1 << 24 // Boost Ball
+    | 1 << 25 // Varia Suit
+    | 1 << 28 // Thermal Visor
+    | 1 << 30 // Space Jump Boots
+    | 1 << 31 // Grapple Beam
+    | 1 << 35; // Flamethrower
+
+const NAMED: &str = r#"{
+    "Scan Visor": 1,
+    "Missile": 25,
+    "Energy Tank": 3,
+    "Power Bomb": 4,
+    "Wave Beam": 1,
+    "Ice Beam": 1,
+    "Charge Beam": 1,
+    "Morph Ball": 1,
+    "Morph Ball Bomb": 1,
+    "Boost Ball": 1,
+    "Varia Suit": 1,
+    "Thermal Visor": 1,
+    "Space Jump Boots": 1,
+    "Grapple Beam": 1,
+    "Flamethrower": 1
+}"#;
+
+#[test]
+fn test_packed_and_named_match()
+{
+    let packed = StartingItems::from_u64(PACKED);
+    let named = starting_items(NAMED).unwrap();
+    assert_eq!(starting_items(&PACKED.to_string()).unwrap(), packed);
+
+    // The u64 only sets the items it packs; the named form sets the same ones to the same
+    // amounts and everything it leaves out, bar the Power Beam and Combat Visor, to 0
+    let packed_amounts: HashMap<_, _> = packed.iter().collect();
+    assert_eq!(packed_amounts.len(), 23);
+    for (item, amount) in named.iter() {
+        assert_eq!(
+            packed_amounts.get(&item).cloned().unwrap_or(0),
+            amount,
+            "{}",
+            StartingItems::item_name(item),
+        );
+    }
+    for &item in packed_amounts.keys() {
+        assert!(named.amount(item).is_some(), "{}", StartingItems::item_name(item));
+    }
+    assert_eq!(named.amount(0), None);
+    assert_eq!(named.amount(17), None);
+    assert!(!packed.needs_rel());
+    assert!(!named.needs_rel());
+}
+
+#[test]
+fn test_starting_selection()
+{
+    let items = starting_items(r#"{
+        "items": {"Ice Beam": 1, "Scan Visor": 1, "Energy Tank": 2, "Artifact of Truth": 1},
+        "beam": "Ice Beam",
+        "visor": "scan visor",
+        "energy": 150
+    }"#).unwrap();
+    assert_eq!(items.beam(), Some(1));
+    assert_eq!(items.visor(), Some(2));
+    assert_eq!(items.energy(), Some(150));
+    assert_eq!(items.artifacts().collect::<Vec<_>>(), vec![29]);
+    assert!(items.needs_rel());
+
+    let err = starting_items(r#"{"items": {}, "beam": "Plasma Beam"}"#).unwrap_err();
+    assert!(err.contains("isn't a starting item"), "{}", err);
+    let err = starting_items(r#"{"items": {"Energy Tank": 1}, "energy": 200}"#).unwrap_err();
+    assert!(err.contains("between 1 and 199"), "{}", err);
+    assert!(starting_items(r#"{"items": {}, "visor": "Echo Visor"}"#).is_err());
+
+    // The Power Beam and Combat Visor are there unless they're taken away
+    let items = starting_items(r#"{"items": {}, "beam": "Power Beam", "visor": "Combat Visor"}"#)
+        .unwrap();
+    assert_eq!((items.beam(), items.visor()), (Some(0), Some(0)));
+}